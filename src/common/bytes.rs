@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+
+/// Reads little-endian primitives from on-disk structures,
+/// reporting truncated data as an error rather than panicking
+pub trait ReadBytes {
+    fn bytes_at(&self, offset: usize, len: usize) -> Result<&[u8]>;
+
+    fn u8_at(&self, offset: usize) -> Result<u8> {
+        self.bytes_at(offset, 1).map(|bytes| bytes[0])
+    }
+
+    fn u16_at(&self, offset: usize) -> Result<u16> {
+        self.array_at(offset).map(u16::from_le_bytes)
+    }
+
+    fn u32_at(&self, offset: usize) -> Result<u32> {
+        self.array_at(offset).map(u32::from_le_bytes)
+    }
+
+    fn u64_at(&self, offset: usize) -> Result<u64> {
+        self.array_at(offset).map(u64::from_le_bytes)
+    }
+
+    fn i16_at(&self, offset: usize) -> Result<i16> {
+        self.array_at(offset).map(i16::from_le_bytes)
+    }
+
+    fn i32_at(&self, offset: usize) -> Result<i32> {
+        self.array_at(offset).map(i32::from_le_bytes)
+    }
+
+    fn i64_at(&self, offset: usize) -> Result<i64> {
+        self.array_at(offset).map(i64::from_le_bytes)
+    }
+
+    fn f32_at(&self, offset: usize) -> Result<f32> {
+        self.array_at(offset).map(f32::from_le_bytes)
+    }
+
+    fn f64_at(&self, offset: usize) -> Result<f64> {
+        self.array_at(offset).map(f64::from_le_bytes)
+    }
+
+    fn array_at<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        self.bytes_at(offset, N)
+            .map(|bytes| bytes.try_into().expect("slice of N bytes"))
+    }
+}
+
+impl ReadBytes for [u8] {
+    fn bytes_at(&self, offset: usize, len: usize) -> Result<&[u8]> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.len() => Ok(&self[offset..end]),
+            _ => bail!(
+                "Unexpected end of data: {} bytes at offset {} requested, {} bytes available",
+                len,
+                offset,
+                self.len()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::ReadBytes;
+
+    #[test]
+    fn reads_little_endian_values() {
+        // given
+        let bytes: &[u8] = &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+        // then
+        assert_eq!(bytes.u8_at(1).unwrap(), 0x02);
+        assert_eq!(bytes.u16_at(0).unwrap(), 0x0201);
+        assert_eq!(bytes.u32_at(4).unwrap(), 0x08070605);
+        assert_eq!(bytes.u64_at(0).unwrap(), 0x0807060504030201);
+    }
+
+    #[test]
+    fn fails_on_truncated_data() {
+        // given
+        let bytes: &[u8] = &[0x01, 0x02, 0x03];
+
+        // when
+        let result = bytes.u32_at(1);
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some(
+                "Unexpected end of data: 4 bytes at offset 1 requested, 3 bytes available"
+                    .to_string()
+            )
+        );
+    }
+}
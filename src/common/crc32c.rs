@@ -0,0 +1,50 @@
+/// Reversed Castagnoli polynomial used by PostgreSQL's pg_crc32c
+const POLYNOMIAL: u32 = 0x82F63B78;
+
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 1 {
+                0 => crc >> 1,
+                _ => (crc >> 1) ^ POLYNOMIAL,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Computes CRC-32C the way INIT_CRC32C, COMP_CRC32C and FIN_CRC32C do
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::crc32c;
+
+    #[rstest]
+    #[case(b"", 0x00000000)]
+    #[case(b"a", 0xC1D04330)]
+    #[case(b"123456789", 0xE3069283)]
+    fn computes_crc32c(#[case] bytes: &[u8], #[case] expected: u32) {
+        // when
+        let crc = crc32c(bytes);
+
+        // then
+        assert_eq!(crc, expected);
+    }
+}
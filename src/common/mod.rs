@@ -1,14 +1,19 @@
-use std::ffi::OsStr;
+use std::{ffi::OsStr, fmt::Display};
 
 use anyhow::anyhow;
 
+pub mod bytes;
+pub mod crc32c;
 pub mod fs;
+pub mod pglz;
 pub mod result_option;
+pub mod size;
+pub mod time;
 
 #[cfg(test)]
 pub mod test_utils;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct PgOid(pub u32);
 
 impl PgOid {
@@ -30,6 +35,33 @@ impl From<u32> for PgOid {
     }
 }
 
+/// Log sequence number, a byte position in the write-ahead log
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+pub struct Lsn(pub u64);
+
+impl Lsn {
+    /// Composes an LSN of its high and low 32-bit halves as stored in pages
+    pub fn from_parts(hi: u32, lo: u32) -> Self {
+        Lsn(((hi as u64) << 32) | lo as u64)
+    }
+
+    /// Parses the `X/X` notation of an LSN, both halves in hex
+    pub fn try_parse(s: &str) -> Option<Self> {
+        let (hi, lo) = s.split_once('/')?;
+        Some(Lsn::from_parts(
+            u32::from_str_radix(hi, 16).ok()?,
+            u32::from_str_radix(lo, 16).ok()?,
+        ))
+    }
+}
+
+impl Display for Lsn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Lsn(lsn) = self;
+        write!(f, "{:X}/{:X}", lsn >> 32, lsn & 0xFFFFFFFF)
+    }
+}
+
 pub fn stringify(err: anyhow::Error) -> String {
     format!("{:?}", err)
 }
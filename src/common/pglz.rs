@@ -0,0 +1,95 @@
+use anyhow::{bail, Result};
+
+/// Decompresses data compressed with PostgreSQL's LZ-family algorithm the way pglz_decompress
+/// does, the output must have exactly `raw_size` bytes
+pub fn pglz_decompress(source: &[u8], raw_size: usize) -> Result<Vec<u8>> {
+    let mut dest = Vec::with_capacity(raw_size);
+    let mut sp = 0;
+    while sp < source.len() && dest.len() < raw_size {
+        // each control byte tells for the following 8 items whether they are a literal byte
+        // or a 2-3 bytes tag referring to the output produced so far
+        let mut ctrl = source[sp];
+        sp += 1;
+        for _ in 0..8 {
+            if sp >= source.len() || dest.len() >= raw_size {
+                break;
+            }
+            if ctrl & 1 == 0 {
+                dest.push(source[sp]);
+                sp += 1;
+            } else {
+                if sp + 1 >= source.len() {
+                    bail!("Compressed data is truncated in a tag at {}", sp);
+                }
+                let mut len = (source[sp] & 0x0F) as usize + 3;
+                let off = ((source[sp] & 0xF0) as usize) << 4 | source[sp + 1] as usize;
+                sp += 2;
+                if len == 18 {
+                    if sp >= source.len() {
+                        bail!("Compressed data is truncated in a tag at {}", sp);
+                    }
+                    len += source[sp] as usize;
+                    sp += 1;
+                }
+                if off == 0 || off > dest.len() {
+                    bail!(
+                        "Compressed data is corrupt, tag refers {} bytes back at output offset {}",
+                        off,
+                        dest.len()
+                    );
+                }
+                // the source and the target may overlap, which repeats the referred bytes
+                let start = dest.len() - off;
+                for i in 0..len.min(raw_size - dest.len()) {
+                    dest.push(dest[start + i]);
+                }
+            }
+            ctrl >>= 1;
+        }
+    }
+    if dest.len() != raw_size || sp != source.len() {
+        bail!(
+            "Compressed data is corrupt, {} of {} bytes decompressed from {} of {} bytes",
+            dest.len(),
+            raw_size,
+            sp,
+            source.len()
+        );
+    }
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::pglz_decompress;
+
+    #[rstest]
+    #[case(&[0x00, b'a', b'b', b'c'], 3, b"abc".to_vec())]
+    #[case(&[0x08, b'a', b'b', b'c', 0x06, 0x03], 12, b"abcabcabcabc".to_vec())]
+    #[case(&[0x02, 0, 0x0F, 0x01, 10], 29, vec![0; 29])]
+    fn decompresses_pglz(
+        #[case] source: &[u8],
+        #[case] raw_size: usize,
+        #[case] expected: Vec<u8>,
+    ) {
+        // when
+        let bytes = pglz_decompress(source, raw_size).unwrap();
+
+        // then
+        assert_eq!(bytes, expected);
+    }
+
+    #[rstest]
+    #[case(&[0x01, 0x00, 0x01], 3)]
+    #[case(&[0x08, b'a', b'b', b'c', 0x06, 0x03], 13)]
+    fn rejects_corrupt_pglz(#[case] source: &[u8], #[case] raw_size: usize) {
+        // when
+        let result = pglz_decompress(source, raw_size);
+
+        // then
+        assert!(result.is_err());
+    }
+}
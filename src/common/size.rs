@@ -0,0 +1,43 @@
+/// Formats the size in bytes the same way `pg_size_pretty` does
+pub fn pretty_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["bytes", "kB", "MB", "GB", "TB", "PB"];
+
+    if size < 10 * 1024 {
+        return format!("{} {}", size, UNITS[0]);
+    }
+
+    // the value is kept with one extra bit of precision to round it half up
+    let mut size = size >> 9;
+    for (i, unit) in UNITS.iter().enumerate().skip(1) {
+        if i == UNITS.len() - 1 || size < 20 * 1024 - 1 {
+            return format!("{} {}", size.div_ceil(2), unit);
+        }
+        size >>= 10;
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::pretty_size;
+
+    #[rstest]
+    #[case(0, "0 bytes")]
+    #[case(8192, "8192 bytes")]
+    #[case(10239, "10239 bytes")]
+    #[case(10240, "10 kB")]
+    #[case(24576, "24 kB")]
+    #[case(20 * 1024 * 1024, "20 MB")]
+    #[case(1024 * 1024 * 1024, "1024 MB")]
+    #[case(1024 * 1024 * 1024 * 1024 * 1024 * 1024, "1024 PB")]
+    fn formats_size(#[case] size: u64, #[case] expected: &str) {
+        // when
+        let formatted = pretty_size(size);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+}
@@ -36,7 +36,7 @@ fn is_pgdata_empty() -> bool {
         .map_or_else(expect(NotFound), ok_some())
         .expect("PGData dir read");
 
-    maybe_pgdata.map_or(true, |mut pgdata| pgdata.next().is_none())
+    maybe_pgdata.is_none_or(|mut pgdata| pgdata.next().is_none())
 }
 
 fn build_postgres_docker_image() {
@@ -101,12 +101,12 @@ fn fingerprint_path() -> PathBuf {
 }
 
 fn expect<T>(error_kind: ErrorKind) -> impl FnOnce(Error) -> Result<Option<T>, Error> {
-    return move |err| match err.kind() {
+    move |err| match err.kind() {
         kind if kind == error_kind => Ok(None),
         _ => Err(err),
-    };
+    }
 }
 
 fn ok_some<T>() -> impl FnOnce(T) -> Result<Option<T>, Error> {
-    return |v| Ok(Some(v));
+    |v| Ok(Some(v))
 }
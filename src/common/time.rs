@@ -0,0 +1,140 @@
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formats seconds since the Unix epoch the way strftime `%c` does in the C locale with `TZ=UTC`.
+/// There is no time zone database at hand to convert to local time, so unlike `%c` the output
+/// is always in UTC
+pub fn format_unix_time(secs: i64) -> String {
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{} {} {:>2} {:02}:{:02}:{:02} {}",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        MONTHS[month as usize - 1],
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        year
+    )
+}
+
+/// Seconds between the Unix epoch and the PostgreSQL epoch 2000-01-01
+const POSTGRES_EPOCH_UNIX_SECS: i64 = 946_684_800;
+
+/// Formats a `timestamptz` value, microseconds since 2000-01-01 UTC, as timestamptz_to_str does
+pub fn format_timestamptz(micros: i64) -> String {
+    let secs = micros.div_euclid(1_000_000) + POSTGRES_EPOCH_UNIX_SECS;
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06} UTC",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        micros.rem_euclid(1_000_000)
+    )
+}
+
+/// Formats a `timestamptz` value as ISO 8601 without spaces, e.g. `2026-10-17T17:29:45.123456Z`,
+/// for places where words are wrapped
+pub fn format_timestamptz_compact(micros: i64) -> String {
+    let secs = micros.div_euclid(1_000_000) + POSTGRES_EPOCH_UNIX_SECS;
+    let secs_of_day = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        micros.rem_euclid(1_000_000)
+    )
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian (year, month, day)
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // shifts the epoch to 0000-03-01 so that leap days end a 400-year era
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{
+        civil_from_days, format_timestamptz, format_timestamptz_compact, format_unix_time,
+    };
+
+    #[rstest]
+    #[case(0, "Thu Jan  1 00:00:00 1970")]
+    #[case(1_792_258_185, "Sat Oct 17 17:29:45 2026")]
+    #[case(951_782_400, "Tue Feb 29 00:00:00 2000")]
+    #[case(-1, "Wed Dec 31 23:59:59 1969")]
+    fn formats_unix_time(#[case] secs: i64, #[case] expected: &str) {
+        // when
+        let formatted = format_unix_time(secs);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[rstest]
+    #[case(0, "2000-01-01 00:00:00.000000 UTC")]
+    #[case(845_573_385_123_456, "2026-10-17 17:29:45.123456 UTC")]
+    #[case(-1, "1999-12-31 23:59:59.999999 UTC")]
+    fn formats_timestamptz(#[case] micros: i64, #[case] expected: &str) {
+        // when
+        let formatted = format_timestamptz(micros);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[rstest]
+    #[case(845_573_385_123_456, "2026-10-17T17:29:45.123456Z")]
+    #[case(-1, "1999-12-31T23:59:59.999999Z")]
+    fn formats_timestamptz_compact(#[case] micros: i64, #[case] expected: &str) {
+        // when
+        let formatted = format_timestamptz_compact(micros);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[rstest]
+    #[case(0, (1970, 1, 1))]
+    #[case(10_957, (2000, 1, 1))]
+    #[case(-719_468, (0, 3, 1))]
+    fn converts_days_to_date(#[case] days: i64, #[case] expected: (i64, u32, u32)) {
+        // when
+        let date = civil_from_days(days);
+
+        // then
+        assert_eq!(date, expected);
+    }
+}
@@ -12,8 +12,8 @@ fn main() -> Result<(), anyhow::Error> {
     let pgdata = pgdata::pgdata(current_dir.into());
     let root_viewer = Box::new(RootViewer { pgdata });
     let term_size = termsize::get()
-        .context("termsize not available")
-        .map(|size| TermSize::new(&size))?;
+        .map(|size| TermSize::new(&size))
+        .unwrap_or(TermSize::DEFAULT);
     let mut stdout = stdout();
     let viewer = find_viewer(root_viewer, &args[1..])?;
     viewer.handle(&term_size, Box::new(&mut stdout))?;
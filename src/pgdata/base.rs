@@ -4,6 +4,7 @@ use std::{borrow::Cow, ffi::OsStr, path::Path};
 
 use crate::common::fs::DirEntry;
 use crate::common::{self, PgOid};
+use crate::pgdata::global::{self, Database};
 use anyhow::bail;
 use anyhow::Context;
 
@@ -16,7 +17,7 @@ pub trait Base {
     fn items(
         &self,
     ) -> anyhow::Result<
-        impl IntoIterator<Item = BaseDirItem, IntoIter = impl Iterator<Item = BaseDirItem>>,
+        impl IntoIterator<Item = BaseDirItem<'_>, IntoIter = impl Iterator<Item = BaseDirItem<'_>>>,
     >;
     fn db_dir<'a>(&self, oid: PgOid) -> anyhow::Result<impl DbDir + 'a>;
 }
@@ -43,10 +44,17 @@ impl BaseDirItem<'_> {
         }
     }
 
-    pub fn database_dir(pg_oid: u32, db_name: &str) -> BaseDirItem {
+    pub fn database_dir(pg_oid: u32, db_name: &str) -> BaseDirItem<'_> {
         BaseDirItem::DatabaseDir(DatabaseDir {
             oid: PgOid(pg_oid),
-            db_name: db_name.into(),
+            db_name: Some(db_name.into()),
+        })
+    }
+
+    pub fn unnamed_database_dir(pg_oid: u32) -> BaseDirItem<'static> {
+        BaseDirItem::DatabaseDir(DatabaseDir {
+            oid: PgOid(pg_oid),
+            db_name: None,
         })
     }
 
@@ -75,11 +83,15 @@ impl PartialEq for BaseDirItem<'_> {
 #[derive(Debug, PartialEq)]
 pub struct DatabaseDir<'a> {
     oid: PgOid,
-    db_name: Cow<'a, str>,
+    /// Name from pg_database, absent when the catalog cannot be read or has no such database
+    db_name: Option<Cow<'a, str>>,
 }
 
 impl DatabaseDir<'_> {
-    pub fn from(dir_entry: &StdDirEntry) -> anyhow::Result<Option<DatabaseDir<'static>>> {
+    pub fn from(
+        dir_entry: &StdDirEntry,
+        databases: &[Database],
+    ) -> anyhow::Result<Option<DatabaseDir<'static>>> {
         let entry_name = dir_entry.file_name();
         let entry_type = dir_entry
             .file_type()
@@ -92,7 +104,10 @@ impl DatabaseDir<'_> {
         match PgOid::try_parse(&entry_name) {
             Some(oid) => Ok(Some(DatabaseDir {
                 oid,
-                db_name: "TODO: database name".into(),
+                db_name: databases
+                    .iter()
+                    .find(|database| database.oid == oid)
+                    .map(|database| database.name.clone().into()),
             })),
             None => Ok(None),
         }
@@ -105,8 +120,8 @@ impl DatabaseDir<'_> {
         format!("{}", oid)
     }
 
-    pub fn db_name(&self) -> &str {
-        &self.db_name
+    pub fn db_name(&self) -> Option<&str> {
+        self.db_name.as_deref()
     }
 }
 
@@ -116,31 +131,32 @@ pub fn dir_entry() -> common::fs::DirEntry<'static> {
 
 /// Instantiates a default implementation of [Base]
 pub fn base(pgdata_path: &Path) -> impl Base {
-    default_impl::Base::new(pgdata_path)
+    default_impl::Base::new(pgdata_path, global::global(pgdata_path))
 }
 
 mod default_impl {
     use std::fs::DirEntry as StdDirEntry;
-    use std::iter::empty;
     use std::{
         fs::read_dir,
         path::{Path, PathBuf},
         rc::Rc,
     };
 
-    use anyhow::{Context, Error};
+    use anyhow::{anyhow, Context, Error};
 
     use crate::common::fs::DirEntry;
     use crate::common::PgOid;
+    use crate::pgdata::global::{Database, Global};
 
-    use super::db_dir::DbDir;
+    use super::db_dir::{self, DbDir};
     use super::{BaseDirItem, DatabaseDir};
 
-    pub struct Base {
+    pub struct Base<G: Global> {
         path: Rc<PathBuf>,
+        global: G,
     }
 
-    impl super::Base for Base {
+    impl<G: Global> super::Base for Base<G> {
         fn path(&self) -> &Path {
             &self.path
         }
@@ -149,49 +165,51 @@ mod default_impl {
             &self,
         ) -> anyhow::Result<
             impl IntoIterator<
-                Item = super::BaseDirItem,
-                IntoIter = impl Iterator<Item = super::BaseDirItem>,
+                Item = super::BaseDirItem<'_>,
+                IntoIter = impl Iterator<Item = super::BaseDirItem<'_>>,
             >,
         > {
             let read_dir =
                 read_dir(self.path.as_ref()).with_context(|| format!("Reading {:?}", self.path))?;
-            let items = read_dir.map(|maybe_dir_entry| {
-                maybe_dir_entry
-                    .map_err(Error::new)
-                    .map_or_else(BaseDirItem::Error, |dir_entry| to_base_dir_item(&dir_entry))
-            });
+            // database names are optional, the reason they are unavailable goes last
+            let (databases, databases_error) = match self.global.databases() {
+                Ok(databases) => (databases, None),
+                Err(err) => (
+                    Vec::new(),
+                    Some(BaseDirItem::Error(anyhow!(
+                        "Database names are unknown, pg_database cannot be read: {:#}",
+                        err
+                    ))),
+                ),
+            };
+            let items = read_dir
+                .map(move |maybe_dir_entry| {
+                    maybe_dir_entry
+                        .map_err(Error::new)
+                        .map_or_else(BaseDirItem::Error, |dir_entry| {
+                            to_base_dir_item(&dir_entry, &databases)
+                        })
+                })
+                .chain(databases_error);
             Ok(items)
         }
 
-        fn db_dir<'a>(&self, _oid: PgOid) -> anyhow::Result<impl DbDir + 'a> {
-            Ok(StubBaseDir {})
-        }
-    }
-
-    struct StubBaseDir;
-    impl DbDir for StubBaseDir {
-        fn items(
-            &self,
-        ) -> anyhow::Result<
-            impl IntoIterator<
-                Item = super::db_dir::DbDirItem,
-                IntoIter = impl Iterator<Item = super::db_dir::DbDirItem>,
-            >,
-        > {
-            Ok(empty())
+        fn db_dir<'a>(&self, oid: PgOid) -> anyhow::Result<impl DbDir + 'a> {
+            db_dir::db_dir(&self.path, oid)
         }
     }
 
-    impl Base {
-        pub fn new(pgdata_path: &Path) -> Self {
+    impl<G: Global> Base<G> {
+        pub fn new(pgdata_path: &Path, global: G) -> Self {
             Base {
                 path: pgdata_path.join("base").into(),
+                global,
             }
         }
     }
 
-    fn to_base_dir_item<'a>(dir_entry: &StdDirEntry) -> BaseDirItem<'a> {
-        match DatabaseDir::from(dir_entry) {
+    fn to_base_dir_item<'a>(dir_entry: &StdDirEntry, databases: &[Database]) -> BaseDirItem<'a> {
+        match DatabaseDir::from(dir_entry, databases) {
             Ok(Some(database_dir)) => BaseDirItem::DatabaseDir(database_dir),
             Ok(None) => {
                 DirEntry::from(dir_entry).map_or_else(BaseDirItem::Error, BaseDirItem::UnknownEntry)
@@ -202,17 +220,21 @@ mod default_impl {
 
     #[cfg(test)]
     pub mod tests {
+        use std::fs::{create_dir_all, remove_dir_all, write};
         use std::path::PathBuf;
 
         use super::BaseDirItem;
-        use crate::{common::test_utils::fixture::*, pgdata::base::Base};
+        use crate::{
+            common::test_utils::fixture::*,
+            pgdata::{base::Base, global, global::test_stubs::StubGlobal},
+        };
         use pretty_assertions::assert_eq;
         use rstest::rstest;
 
         #[rstest]
         fn reads_base_dir(pgdata: PathBuf) {
             // given
-            let base = super::Base::new(&pgdata);
+            let base = super::Base::new(&pgdata, global::global(&pgdata));
 
             // when
             let items_iter = base.items().unwrap().into_iter();
@@ -225,9 +247,41 @@ mod default_impl {
             assert_eq!(
                 items,
                 vec![
-                    BaseDirItem::database_dir(1, "TODO: database name"),
-                    BaseDirItem::database_dir(4, "TODO: database name"),
-                    BaseDirItem::database_dir(5, "TODO: database name")
+                    BaseDirItem::database_dir(1, "template1"),
+                    BaseDirItem::database_dir(4, "template0"),
+                    BaseDirItem::database_dir(5, "postgres")
+                ]
+            )
+        }
+
+        #[test]
+        fn lists_database_dirs_unnamed_by_global() {
+            // given
+            let pgdata =
+                std::env::temp_dir().join(format!("pg-browser-base-{}", std::process::id()));
+            create_dir_all(pgdata.join("base/1")).unwrap();
+            create_dir_all(pgdata.join("base/pgsql_tmp")).unwrap();
+            write(pgdata.join("base/PG_VERSION"), "15").unwrap();
+            let base = super::Base::new(&pgdata, StubGlobal);
+
+            // when
+            let mut items = base
+                .items()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<BaseDirItem>>();
+            items.sort_by_key(|base_dir_item| {
+                base_dir_item.name().expect("BaseDirItem").into_owned()
+            });
+            remove_dir_all(&pgdata).unwrap();
+
+            // then
+            assert_eq!(
+                items,
+                vec![
+                    BaseDirItem::unnamed_database_dir(1),
+                    BaseDirItem::unknown_file("PG_VERSION"),
+                    BaseDirItem::unknown_dir("pgsql_tmp"),
                 ]
             )
         }
@@ -255,8 +309,8 @@ pub mod test_stubs {
             &self,
         ) -> anyhow::Result<
             impl IntoIterator<
-                Item = super::BaseDirItem,
-                IntoIter = impl Iterator<Item = super::BaseDirItem>,
+                Item = super::BaseDirItem<'_>,
+                IntoIter = impl Iterator<Item = super::BaseDirItem<'_>>,
             >,
         > {
             Ok(std::iter::empty())
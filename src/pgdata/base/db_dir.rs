@@ -5,22 +5,44 @@ use regex::Regex;
 use crate::common;
 use crate::common::PgOid;
 use std::fs::DirEntry as StdDirEntry;
+use std::path::Path;
 
 use anyhow::Result;
 
 use crate::common::{fs::DirEntry, result_option::ResultOption};
+use crate::pgdata::{catalog::Catalog, filenode_map::FileNodeMap};
+
+use self::relation::{Relation, RelationFiles};
+
+pub mod relation;
 
 pub trait DbDir {
+    /// Returns the actual path of the database directory
+    fn path(&self) -> &Path;
+
     fn items(
         &self,
     ) -> anyhow::Result<
-        impl IntoIterator<Item = DbDirItem, IntoIter = impl Iterator<Item = DbDirItem>>,
+        impl IntoIterator<Item = DbDirItem<'_>, IntoIter = impl Iterator<Item = DbDirItem<'_>>>,
     >;
+
+    /// Looks up the files of the relation stored under the given relfilenode
+    fn relation<'a>(&self, rel_file_node: RelFileNode) -> Result<impl RelationFiles + 'a>;
+
+    /// Reads the relation mapper file of the database local catalogs
+    fn filenode_map(&self) -> Result<FileNodeMap>;
+
+    /// Reads the major version of PostgreSQL the database was created by from PG_VERSION
+    fn pg_version(&self) -> Result<u32>;
+
+    /// Decodes the system catalogs of the database
+    fn catalog(&self) -> Result<Catalog>;
 }
 
 #[derive(Debug, PartialEq)]
 pub enum DbDirItem<'a> {
-    ForkSegmentFile(ForkSegmentFile),
+    /// All fork segment files of a relfilenode
+    Relation(Relation),
     FileNodeMapFile,
     PgVersionFile,
     UnknownEntry(DirEntry<'a>),
@@ -36,6 +58,7 @@ impl DbDirItem<'_> {
         Self::from_anyhow_error(anyhow!(error))
     }
 
+    /// Classifies a directory entry other than a fork segment file, see [ForkSegmentFile]
     pub fn from_dir_entry(dir_entry: &StdDirEntry) -> DbDirItem<'static> {
         DbDirItem::try_from_dir_entry(dir_entry).unwrap_or_else(DbDirItem::from_anyhow_error)
     }
@@ -45,16 +68,11 @@ impl DbDirItem<'_> {
         let dir_entry_name_lossy = dir_entry_name.to_string_lossy();
         let name_str = dir_entry_name_lossy.as_ref();
 
-        DbDirItem::fork_segment_file(name_str)
-            .or_if_empty(|| DbDirItem::file_node_map_file(name_str))
+        DbDirItem::file_node_map_file(name_str)
             .or_if_empty(|| DbDirItem::pg_version_file(name_str))
             .otherwise(|| DirEntry::from(std_dir_entry).map(DbDirItem::UnknownEntry))
     }
 
-    fn fork_segment_file(dir_entry_name: &str) -> Result<Option<DbDirItem<'static>>> {
-        Ok(ForkSegmentFile::try_parse(dir_entry_name).map(DbDirItem::ForkSegmentFile))
-    }
-
     fn file_node_map_file(dir_entry_name: &str) -> Result<Option<DbDirItem<'static>>> {
         match dir_entry_name {
             "pg_filenode.map" => Ok(Some(DbDirItem::FileNodeMapFile)),
@@ -73,56 +91,143 @@ impl DbDirItem<'_> {
 #[derive(Debug, PartialEq)]
 pub struct ForkSegmentFile {
     fork_type: ForkType,
-    oid: PgOid,
+    rel_file_node: RelFileNode,
     segment_id: u16,
 }
 
 static FORK_SEGMENT_FILE_REGEX: Lazy<Regex> =
-    regex_static::lazy_regex!(r"^([0-9]{1,10})(_(fsm|vm))?(\.([0-9]*))?$");
+    regex_static::lazy_regex!(r"^(t([0-9]{1,10})_)?([0-9]{1,10})(_(fsm|vm|init))?(\.([0-9]*))?$");
 
 impl ForkSegmentFile {
     pub fn try_parse(file_name: &str) -> Option<ForkSegmentFile> {
         match FORK_SEGMENT_FILE_REGEX.captures(file_name) {
             None => None,
             Some(captures) => {
+                let backend_id = captures.get(2).map(|m| m.as_str().parse::<u32>().ok());
                 let oid = captures
-                    .get(1)
+                    .get(3)
                     .map(|m| m.as_str())
                     .and_then(PgOid::try_parse);
-                let fork_type = ForkType::try_parse(captures.get(3).map(|m| m.as_str()));
+                let fork_type = ForkType::try_parse(captures.get(5).map(|m| m.as_str()));
                 let segment_id = captures
-                    .get(5)
+                    .get(7)
                     .map_or("0", |m| m.as_str())
                     .parse::<u16>()
                     .ok();
 
-                match (oid, fork_type, segment_id) {
-                    (Some(oid), Some(fork_type), Some(segment_id)) => {
+                match (backend_id, oid, fork_type, segment_id) {
+                    (None, Some(oid), Some(fork_type), Some(segment_id)) => {
                         Some(ForkSegmentFile::create(oid, fork_type, segment_id))
                     }
+                    (Some(Some(backend_id)), Some(oid), Some(fork_type), Some(segment_id)) => Some(
+                        ForkSegmentFile::temp(backend_id, oid, fork_type, segment_id),
+                    ),
                     _ => None,
                 }
             }
         }
     }
 
+    pub fn new(rel_file_node: RelFileNode, fork_type: ForkType, segment_id: u16) -> Self {
+        ForkSegmentFile {
+            fork_type,
+            rel_file_node,
+            segment_id,
+        }
+    }
+
     pub fn create<T>(oid: T, fork_type: ForkType, segment_id: u16) -> Self
     where
         T: Into<PgOid>,
     {
-        ForkSegmentFile {
+        ForkSegmentFile::new(RelFileNode::Permanent(oid.into()), fork_type, segment_id)
+    }
+
+    pub fn temp<T>(backend_id: u32, oid: T, fork_type: ForkType, segment_id: u16) -> Self
+    where
+        T: Into<PgOid>,
+    {
+        ForkSegmentFile::new(
+            RelFileNode::Temporary(backend_id, oid.into()),
             fork_type,
-            oid: oid.into(),
             segment_id,
+        )
+    }
+
+    pub fn fork_type(&self) -> &ForkType {
+        &self.fork_type
+    }
+
+    pub fn rel_file_node(&self) -> RelFileNode {
+        self.rel_file_node
+    }
+
+    pub fn oid(&self) -> PgOid {
+        self.rel_file_node.oid()
+    }
+
+    pub fn segment_id(&self) -> u16 {
+        self.segment_id
+    }
+
+    /// Restores the name of the file this fork segment is stored in
+    pub fn file_name(&self) -> String {
+        let fork_suffix = match self.fork_type {
+            ForkType::Main => "",
+            ForkType::FreeSpaceMap => "_fsm",
+            ForkType::VisibilityMap => "_vm",
+            ForkType::Init => "_init",
+        };
+        match self.segment_id {
+            0 => format!("{}{fork_suffix}", self.rel_file_node.file_name()),
+            segment_id => format!(
+                "{}{fork_suffix}.{segment_id}",
+                self.rel_file_node.file_name()
+            ),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// Identifies the files of a relation within a database directory
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum RelFileNode {
+    /// Files of a regular relation are named after its relfilenode
+    Permanent(PgOid),
+    /// Files of a temporary relation are prefixed with the id of the backend which owns it
+    Temporary(u32, PgOid),
+}
+
+impl RelFileNode {
+    pub fn oid(&self) -> PgOid {
+        match self {
+            RelFileNode::Permanent(oid) => *oid,
+            RelFileNode::Temporary(_, oid) => *oid,
+        }
+    }
+
+    pub fn backend_id(&self) -> Option<u32> {
+        match self {
+            RelFileNode::Permanent(_) => None,
+            RelFileNode::Temporary(backend_id, _) => Some(*backend_id),
+        }
+    }
+
+    /// Restores the file name of the main fork's first segment
+    pub fn file_name(&self) -> String {
+        match self {
+            RelFileNode::Permanent(PgOid(oid)) => format!("{oid}"),
+            RelFileNode::Temporary(backend_id, PgOid(oid)) => format!("t{backend_id}_{oid}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum ForkType {
     Main,
     FreeSpaceMap,
     VisibilityMap,
+    /// Initialization fork of an unlogged relation
+    Init,
 }
 
 impl ForkType {
@@ -131,34 +236,202 @@ impl ForkType {
             None => Some(ForkType::Main),
             Some("fsm") => Some(ForkType::FreeSpaceMap),
             Some("vm") => Some(ForkType::VisibilityMap),
+            Some("init") => Some(ForkType::Init),
             _ => None,
         }
     }
-}
 
-mod default_impl {
-    use anyhow::Context;
-    use std::{fs::read_dir, path::PathBuf};
+    /// Maps the fork number stored in WAL records to the fork
+    pub fn from_number(fork_number: u8) -> Option<ForkType> {
+        match fork_number {
+            0 => Some(ForkType::Main),
+            1 => Some(ForkType::FreeSpaceMap),
+            2 => Some(ForkType::VisibilityMap),
+            3 => Some(ForkType::Init),
+            _ => None,
+        }
+    }
+
+    /// Returns the fork name as PostgreSQL spells it in file names and messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForkType::Main => "main",
+            ForkType::FreeSpaceMap => "fsm",
+            ForkType::VisibilityMap => "vm",
+            ForkType::Init => "init",
+        }
+    }
+}
 
-    use super::DbDirItem;
+/// Instantiates a default implementation of [DbDir] for the database with the given oid
+pub fn db_dir(base_path: &Path, oid: PgOid) -> Result<impl DbDir> {
+    default_impl::DbDir::new(base_path, oid)
+}
 
-    #[allow(dead_code)]
-    struct DbDir {
+mod default_impl {
+    use anyhow::{bail, Context};
+    use std::{
+        fs::{metadata, read_dir, read_to_string},
+        path::{Path, PathBuf},
+    };
+
+    use crate::{
+        common::PgOid,
+        pgdata::{
+            catalog::Catalog,
+            filenode_map::{FileNodeMap, FILENODE_MAP_FILE_NAME},
+            global::{self, Global},
+            transactions,
+        },
+    };
+
+    use super::{
+        relation::{self, Relation, RelationFiles},
+        DbDirItem, ForkSegmentFile, RelFileNode,
+    };
+
+    pub struct DbDir {
         path: PathBuf,
     }
 
+    impl DbDir {
+        pub fn new(base_path: &Path, PgOid(oid): PgOid) -> anyhow::Result<Self> {
+            let path = base_path.join(oid.to_string());
+            let metadata = metadata(&path)
+                .with_context(|| format!("Database directory {:?} does not exist", path))?;
+            if !metadata.is_dir() {
+                bail!("{:?} is not a database directory", path);
+            }
+            Ok(DbDir { path })
+        }
+    }
+
     impl super::DbDir for DbDir {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
         fn items(
             &self,
         ) -> anyhow::Result<
-            impl IntoIterator<Item = DbDirItem, IntoIter = impl Iterator<Item = DbDirItem>>,
+            impl IntoIterator<Item = DbDirItem<'_>, IntoIter = impl Iterator<Item = DbDirItem<'_>>>,
         > {
             let dir = read_dir(&self.path).with_context(|| format!("Reading {:?}", self.path))?;
-            let db_dir_item = dir.map(|maybe_dir_entry| match maybe_dir_entry {
-                Ok(dir_entry) => DbDirItem::from_dir_entry(&dir_entry),
-                Err(err) => DbDirItem::from_io_error(err),
-            });
-            Ok(db_dir_item)
+
+            let mut segments = Vec::new();
+            let mut other_items = Vec::new();
+            for maybe_dir_entry in dir {
+                let dir_entry = match maybe_dir_entry {
+                    Ok(dir_entry) => dir_entry,
+                    Err(err) => {
+                        other_items.push(DbDirItem::from_io_error(err));
+                        continue;
+                    }
+                };
+                match ForkSegmentFile::try_parse(&dir_entry.file_name().to_string_lossy()) {
+                    Some(segment) => match dir_entry.metadata() {
+                        Ok(metadata) => segments.push((segment, metadata.len())),
+                        Err(err) => other_items.push(DbDirItem::from_io_error(err)),
+                    },
+                    None => other_items.push(DbDirItem::from_dir_entry(&dir_entry)),
+                }
+            }
+
+            let relations = Relation::group(segments)
+                .into_iter()
+                .map(DbDirItem::Relation);
+            Ok(relations.chain(other_items))
+        }
+
+        fn relation<'a>(
+            &self,
+            rel_file_node: RelFileNode,
+        ) -> anyhow::Result<impl RelationFiles + 'a> {
+            let relation = super::DbDir::items(self)?
+                .into_iter()
+                .find_map(|item| match item {
+                    DbDirItem::Relation(relation) if relation.rel_file_node() == rel_file_node => {
+                        Some(relation)
+                    }
+                    _ => None,
+                })
+                .with_context(|| {
+                    format!(
+                        "Relation {} not found in {:?}",
+                        rel_file_node.file_name(),
+                        self.path
+                    )
+                })?;
+            Ok(relation::relation_files(&self.path, relation))
+        }
+
+        fn filenode_map(&self) -> anyhow::Result<FileNodeMap> {
+            FileNodeMap::read(&self.path.join(FILENODE_MAP_FILE_NAME))
+        }
+
+        fn pg_version(&self) -> anyhow::Result<u32> {
+            let path = self.path.join("PG_VERSION");
+            let version = read_to_string(&path).with_context(|| format!("Reading {:?}", path))?;
+            version
+                .trim()
+                .parse()
+                .with_context(|| format!("Expected major version in {:?}, got {version:?}", path))
+        }
+
+        /// Owners are named after the roles of pg_authid in global when it is readable
+        fn catalog(&self) -> anyhow::Result<Catalog> {
+            let pgdata_path = self
+                .path
+                .parent()
+                .and_then(Path::parent)
+                .expect("pgdata path");
+            let mut catalog = Catalog::read(self, &transactions::transactions(pgdata_path))?;
+            if let Ok(roles) = global::global(pgdata_path).roles() {
+                catalog.add_roles(roles);
+            }
+            Ok(catalog)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::{Path, PathBuf};
+
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        use crate::{
+            common::{test_utils::fixture::*, PgOid},
+            pgdata::base::db_dir::{DbDir, DbDirItem},
+        };
+
+        #[rstest]
+        fn reads_db_dir(pgdata: PathBuf) {
+            // given
+            let db_dir = super::DbDir::new(&pgdata.join("base"), PgOid(5)).unwrap();
+
+            // when
+            let items = db_dir.items().unwrap().into_iter().collect::<Vec<_>>();
+
+            // then
+            assert_eq!(db_dir.path(), pgdata.join("base/5"));
+            assert!(items.contains(&DbDirItem::PgVersionFile));
+            assert!(items.contains(&DbDirItem::FileNodeMapFile));
+            assert!(items
+                .iter()
+                .any(|item| matches!(item, DbDirItem::Relation(_))));
+        }
+
+        #[test]
+        fn fails_when_db_dir_does_not_exist() {
+            // when
+            let result = super::DbDir::new(Path::new("/pgdata/base"), PgOid(5));
+
+            // then
+            assert_eq!(
+                result.err().map(|err| err.to_string()),
+                Some("Database directory \"/pgdata/base/5\" does not exist".to_string())
+            );
         }
     }
 }
@@ -213,6 +486,7 @@ mod fork_type_tests {
     #[case(None, Some(ForkType::Main))]
     #[case(Some("fsm"), Some(ForkType::FreeSpaceMap))]
     #[case(Some("vm"), Some(ForkType::VisibilityMap))]
+    #[case(Some("init"), Some(ForkType::Init))]
     #[case(Some("arb_string"), None)]
     fn parses_fork_type(#[case] s: Option<&str>, #[case] expected: Option<ForkType>) {
         // when
@@ -239,6 +513,7 @@ mod fork_segment_file_tests {
     #[case("12345_fsm.2", (12345, ForkType::FreeSpaceMap, 2))]
     #[case("12345_vm", (12345, ForkType::VisibilityMap, 0))]
     #[case("12345_vm.3", (12345, ForkType::VisibilityMap, 3))]
+    #[case("12345_init", (12345, ForkType::Init, 0))]
     fn parses_fork_segment_file(#[case] file_name: &str, #[case] expected: (u32, ForkType, u16)) {
         // given
         let (oid, fork_type, segment_id) = expected;
@@ -251,6 +526,56 @@ mod fork_segment_file_tests {
         assert_eq!(parsed, Some(expected));
     }
 
+    #[rstest]
+    #[case("t3_16400", (3, 16400, ForkType::Main, 0))]
+    #[case("t3_16400.2", (3, 16400, ForkType::Main, 2))]
+    #[case("t3_16400_fsm", (3, 16400, ForkType::FreeSpaceMap, 0))]
+    #[case("t12_16400_vm", (12, 16400, ForkType::VisibilityMap, 0))]
+    fn parses_temp_fork_segment_file(
+        #[case] file_name: &str,
+        #[case] expected: (u32, u32, ForkType, u16),
+    ) {
+        // given
+        let (backend_id, oid, fork_type, segment_id) = expected;
+        let expected = ForkSegmentFile::temp(backend_id, oid, fork_type, segment_id);
+
+        // when
+        let parsed = ForkSegmentFile::try_parse(file_name);
+
+        // then
+        assert_eq!(parsed, Some(expected));
+    }
+
+    #[rstest]
+    #[case((12345, ForkType::Main, 0), "12345")]
+    #[case((12345, ForkType::Main, 1), "12345.1")]
+    #[case((12345, ForkType::FreeSpaceMap, 0), "12345_fsm")]
+    #[case((12345, ForkType::VisibilityMap, 3), "12345_vm.3")]
+    #[case((12345, ForkType::Init, 0), "12345_init")]
+    fn restores_file_name(#[case] fork_segment_file: (u32, ForkType, u16), #[case] expected: &str) {
+        // given
+        let (oid, fork_type, segment_id) = fork_segment_file;
+        let fork_segment_file = ForkSegmentFile::create(oid, fork_type, segment_id);
+
+        // when
+        let file_name = fork_segment_file.file_name();
+
+        // then
+        assert_eq!(file_name, expected);
+    }
+
+    #[test]
+    fn restores_temp_file_name() {
+        // given
+        let fork_segment_file = ForkSegmentFile::temp(3, 16400, ForkType::FreeSpaceMap, 1);
+
+        // when
+        let file_name = fork_segment_file.file_name();
+
+        // then
+        assert_eq!(file_name, "t3_16400_fsm.1");
+    }
+
     #[rstest]
     #[case("pg_filenode.map")]
     #[case("PG_VERSION")]
@@ -258,6 +583,10 @@ mod fork_segment_file_tests {
     #[case("12341_qqq.1")]
     #[case("12342_fsm.q")]
     #[case("12343_vm.q")]
+    #[case("12344_init.q")]
+    #[case("t_12345")]
+    #[case("tq_12346")]
+    #[case("t3_")]
     fn does_not_parse_as_fork_segment_file(#[case] file_name: &str) {
         // when
         let parsed = ForkSegmentFile::try_parse(file_name);
@@ -269,18 +598,51 @@ mod fork_segment_file_tests {
 
 #[cfg(test)]
 pub mod test_stubs {
-    use std::iter::empty;
+    use std::{iter::empty, path::Path};
+
+    use anyhow::bail;
+
+    use crate::pgdata::{catalog::Catalog, filenode_map::FileNodeMap};
 
-    use super::DbDir;
+    use super::{
+        relation::{test_stubs::StubRelationFiles, Relation, RelationFiles},
+        DbDir, RelFileNode,
+    };
 
     pub struct StubDbDir;
     impl DbDir for StubDbDir {
+        fn relation<'a>(
+            &self,
+            rel_file_node: RelFileNode,
+        ) -> anyhow::Result<impl RelationFiles + 'a> {
+            Ok(StubRelationFiles {
+                relation: Relation::of(rel_file_node),
+                pages: vec![],
+            })
+        }
+
+        fn filenode_map(&self) -> anyhow::Result<FileNodeMap> {
+            bail!("pg_filenode.map is not stubbed")
+        }
+
+        fn pg_version(&self) -> anyhow::Result<u32> {
+            bail!("PG_VERSION is not stubbed")
+        }
+
+        fn catalog(&self) -> anyhow::Result<Catalog> {
+            bail!("Catalog is not stubbed")
+        }
+
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/base/5")
+        }
+
         fn items(
             &self,
         ) -> anyhow::Result<
             impl IntoIterator<
-                Item = super::DbDirItem,
-                IntoIter = impl Iterator<Item = super::DbDirItem>,
+                Item = super::DbDirItem<'_>,
+                IntoIter = impl Iterator<Item = super::DbDirItem<'_>>,
             >,
         > {
             Ok(empty())
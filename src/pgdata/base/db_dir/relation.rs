@@ -0,0 +1,351 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::PgOid,
+    pgdata::{page::Page, BLCKSZ},
+};
+
+use super::{ForkSegmentFile, ForkType, RelFileNode};
+
+/// Gives access to the files of a single relation within a database directory
+pub trait RelationFiles {
+    /// Returns the actual path of the first segment of the given fork
+    fn fork_path(&self, fork_type: ForkType) -> PathBuf;
+
+    /// Returns the forks and segments of the relation
+    fn relation(&self) -> &Relation;
+
+    /// Reads a block of the given fork
+    fn page(&self, fork_type: ForkType, block_number: u32) -> anyhow::Result<Page>;
+}
+
+/// Instantiates a default implementation of [RelationFiles]
+pub fn relation_files(db_dir_path: &Path, relation: Relation) -> impl RelationFiles {
+    default_impl::RelationFiles::new(db_dir_path, relation)
+}
+
+/// Represents all fork segment files stored for a single relfilenode
+#[derive(Debug, PartialEq)]
+pub struct Relation {
+    rel_file_node: RelFileNode,
+    forks: BTreeMap<ForkType, Fork>,
+}
+
+impl Relation {
+    pub fn new<T>(relfilenode: T) -> Self
+    where
+        T: Into<PgOid>,
+    {
+        Relation::of(RelFileNode::Permanent(relfilenode.into()))
+    }
+
+    pub fn of(rel_file_node: RelFileNode) -> Self {
+        Relation {
+            rel_file_node,
+            forks: BTreeMap::new(),
+        }
+    }
+
+    /// Groups fork segment files and their sizes into relations,
+    /// regular relations go first ordered by relfilenode followed by temporary ones
+    pub fn group<I>(segments: I) -> Vec<Relation>
+    where
+        I: IntoIterator<Item = (ForkSegmentFile, u64)>,
+    {
+        let mut relations = BTreeMap::<RelFileNode, Relation>::new();
+        segments.into_iter().for_each(|(segment, size)| {
+            relations
+                .entry(segment.rel_file_node())
+                .or_insert_with(|| Relation::of(segment.rel_file_node()))
+                .add_segment(&segment, size)
+        });
+        relations.into_values().collect()
+    }
+
+    pub fn add_segment(&mut self, segment: &ForkSegmentFile, size: u64) {
+        self.forks
+            .entry(*segment.fork_type())
+            .or_default()
+            .segments
+            .insert(segment.segment_id(), size);
+    }
+
+    pub fn relfilenode(&self) -> PgOid {
+        self.rel_file_node.oid()
+    }
+
+    pub fn rel_file_node(&self) -> RelFileNode {
+        self.rel_file_node
+    }
+
+    /// Unlogged relations have an initialization fork
+    pub fn is_unlogged(&self) -> bool {
+        self.forks.contains_key(&ForkType::Init)
+    }
+
+    pub fn fork(&self, fork_type: ForkType) -> Option<&Fork> {
+        self.forks.get(&fork_type)
+    }
+
+    pub fn forks(&self) -> impl Iterator<Item = (&ForkType, &Fork)> {
+        self.forks.iter()
+    }
+
+    /// Total size of all the segments of all the forks in bytes
+    pub fn size(&self) -> u64 {
+        self.forks.values().map(Fork::size).sum()
+    }
+
+    /// Lists segments absent from the segment sequence of each fork
+    pub fn missing_segments(&self) -> Vec<(ForkType, u16)> {
+        self.forks
+            .iter()
+            .flat_map(|(fork_type, fork)| {
+                fork.missing_segments()
+                    .into_iter()
+                    .map(|segment_id| (*fork_type, segment_id))
+            })
+            .collect()
+    }
+}
+
+/// Represents the segments of a single relation fork
+#[derive(Debug, PartialEq, Default)]
+pub struct Fork {
+    segments: BTreeMap<u16, u64>,
+}
+
+impl Fork {
+    /// Lists segment ids with their sizes in bytes
+    pub fn segments(&self) -> impl Iterator<Item = (u16, u64)> + '_ {
+        self.segments
+            .iter()
+            .map(|(segment_id, size)| (*segment_id, *size))
+    }
+
+    pub fn size(&self) -> u64 {
+        self.segments.values().sum()
+    }
+
+    pub fn blocks(&self) -> u64 {
+        self.size() / BLCKSZ
+    }
+
+    /// Lists segment ids between the first and the last segment which have no file
+    pub fn missing_segments(&self) -> Vec<u16> {
+        let last_segment_id = self.segments.keys().next_back().copied().unwrap_or(0);
+        (0..last_segment_id)
+            .filter(|segment_id| !self.segments.contains_key(segment_id))
+            .collect()
+    }
+}
+
+mod default_impl {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::bail;
+
+    use crate::pgdata::{
+        base::db_dir::{ForkSegmentFile, ForkType},
+        page::Page,
+    };
+
+    use super::Relation;
+
+    pub struct RelationFiles {
+        db_dir_path: PathBuf,
+        relation: Relation,
+    }
+
+    impl RelationFiles {
+        pub fn new(db_dir_path: &Path, relation: Relation) -> Self {
+            RelationFiles {
+                db_dir_path: db_dir_path.to_path_buf(),
+                relation,
+            }
+        }
+    }
+
+    impl super::RelationFiles for RelationFiles {
+        fn fork_path(&self, fork_type: ForkType) -> PathBuf {
+            let file_name =
+                ForkSegmentFile::new(self.relation.rel_file_node(), fork_type, 0).file_name();
+            self.db_dir_path.join(file_name)
+        }
+
+        fn relation(&self) -> &Relation {
+            &self.relation
+        }
+
+        fn page(&self, fork_type: ForkType, block_number: u32) -> anyhow::Result<Page> {
+            let blocks = self
+                .relation
+                .fork(fork_type)
+                .map_or(0, |fork| fork.blocks());
+            if block_number as u64 >= blocks {
+                bail!(
+                    "Block {} is out of range, {} has {} blocks",
+                    block_number,
+                    ForkSegmentFile::new(self.relation.rel_file_node(), fork_type, 0).file_name(),
+                    blocks
+                );
+            }
+            Page::read(&self.fork_path(fork_type), block_number)
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::PathBuf;
+
+    use anyhow::bail;
+
+    use crate::pgdata::{
+        base::db_dir::{ForkSegmentFile, ForkType},
+        page::Page,
+    };
+
+    use super::{Relation, RelationFiles};
+
+    /// Serves the blocks of the forks from memory
+    pub struct StubRelationFiles {
+        pub relation: Relation,
+        pub pages: Vec<(ForkType, u32, Vec<u8>)>,
+    }
+
+    impl RelationFiles for StubRelationFiles {
+        fn fork_path(&self, fork_type: ForkType) -> PathBuf {
+            let file_name =
+                ForkSegmentFile::new(self.relation.rel_file_node(), fork_type, 0).file_name();
+            PathBuf::from("/pgdata/base/5").join(file_name)
+        }
+
+        fn relation(&self) -> &Relation {
+            &self.relation
+        }
+
+        fn page(&self, fork_type: ForkType, block_number: u32) -> anyhow::Result<Page> {
+            match self
+                .pages
+                .iter()
+                .find(|(fork, block, _)| *fork == fork_type && *block == block_number)
+            {
+                Some((_, _, bytes)) => Page::new(bytes.clone()),
+                None => bail!("Block {block_number} is not stubbed"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::pgdata::base::db_dir::{ForkSegmentFile, ForkType};
+
+    use super::Relation;
+
+    const SEGMENT_SIZE: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn groups_segments_by_relfilenode() {
+        // given
+        let segments = vec![
+            (ForkSegmentFile::create(2, ForkType::Main, 1), 8192),
+            (ForkSegmentFile::create(1, ForkType::Main, 0), 16384),
+            (ForkSegmentFile::create(2, ForkType::Main, 0), SEGMENT_SIZE),
+            (ForkSegmentFile::create(1, ForkType::FreeSpaceMap, 0), 24576),
+            (ForkSegmentFile::create(1, ForkType::VisibilityMap, 0), 8192),
+        ];
+
+        // when
+        let relations = Relation::group(segments);
+
+        // then
+        let summary = relations
+            .iter()
+            .map(|relation| {
+                let forks = relation
+                    .forks()
+                    .map(|(fork_type, fork)| (*fork_type, fork.blocks()))
+                    .collect::<Vec<_>>();
+                (relation.relfilenode().0, relation.size(), forks)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    1,
+                    49152,
+                    vec![
+                        (ForkType::Main, 2),
+                        (ForkType::FreeSpaceMap, 3),
+                        (ForkType::VisibilityMap, 1)
+                    ]
+                ),
+                (2, SEGMENT_SIZE + 8192, vec![(ForkType::Main, 131073)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_temporary_segments_separately() {
+        // given
+        let segments = vec![
+            (ForkSegmentFile::temp(3, 16400, ForkType::Main, 0), 8192),
+            (ForkSegmentFile::create(16400, ForkType::Main, 0), 16384),
+            (ForkSegmentFile::temp(4, 16400, ForkType::Main, 0), 24576),
+        ];
+
+        // when
+        let relations = Relation::group(segments);
+
+        // then
+        assert_eq!(
+            relations
+                .iter()
+                .map(|relation| (relation.rel_file_node().file_name(), relation.size()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("16400".to_string(), 16384),
+                ("t3_16400".to_string(), 8192),
+                ("t4_16400".to_string(), 24576)
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(&[0], &[])]
+    #[case(&[0, 1, 2], &[])]
+    #[case(&[0, 1, 3], &[2])]
+    #[case(&[1, 3], &[0, 2])]
+    #[case(&[0, 4], &[1, 2, 3])]
+    fn reports_missing_segments(#[case] segment_ids: &[u16], #[case] expected: &[u16]) {
+        // given
+        let mut relation = Relation::new(1);
+        segment_ids.iter().for_each(|segment_id| {
+            relation.add_segment(
+                &ForkSegmentFile::create(1, ForkType::Main, *segment_id),
+                SEGMENT_SIZE,
+            )
+        });
+
+        // when
+        let missing_segments = relation.missing_segments();
+
+        // then
+        assert_eq!(
+            missing_segments,
+            expected
+                .iter()
+                .map(|segment_id| (ForkType::Main, *segment_id))
+                .collect::<Vec<_>>()
+        );
+    }
+}
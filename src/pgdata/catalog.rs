@@ -0,0 +1,1096 @@
+use std::{collections::BTreeMap, fmt::Display, ops::RangeInclusive};
+
+use anyhow::{bail, Context, Result};
+
+use crate::common::{bytes::ReadBytes, PgOid};
+
+use super::{
+    base::db_dir::{relation::RelationFiles, DbDir, ForkType, RelFileNode},
+    datum::{Array, Varlena},
+    filenode_map::FileNodeMap,
+    global::{read_name, Role, NAMEDATALEN},
+    page::heap::{Alignment, AttributeLayout, HeapTuple, VARLENA},
+    transactions::Transactions,
+};
+
+/// OID of pg_type, a mapped catalog
+pub const PG_TYPE_OID: PgOid = PgOid(1247);
+
+/// OID of pg_attribute, a mapped catalog
+pub const PG_ATTRIBUTE_OID: PgOid = PgOid(1249);
+
+/// OID of pg_class, a mapped catalog
+pub const PG_CLASS_OID: PgOid = PgOid(1259);
+
+/// OID of pg_index, its relfilenode is recorded in pg_class
+pub const PG_INDEX_OID: PgOid = PgOid(2610);
+
+/// OID of pg_namespace, its relfilenode is recorded in pg_class
+pub const PG_NAMESPACE_OID: PgOid = PgOid(2615);
+
+const OID: AttributeLayout = AttributeLayout::new(4, Alignment::Int);
+const NAME: AttributeLayout = AttributeLayout::new(NAMEDATALEN as i16, Alignment::Char);
+const INT2: AttributeLayout = AttributeLayout::new(2, Alignment::Short);
+const INT4: AttributeLayout = AttributeLayout::new(4, Alignment::Int);
+const FLOAT4: AttributeLayout = AttributeLayout::new(4, Alignment::Int);
+const BOOL: AttributeLayout = AttributeLayout::new(1, Alignment::Char);
+const CHAR: AttributeLayout = AttributeLayout::new(1, Alignment::Char);
+const REGPROC: AttributeLayout = OID;
+const INT_ARRAY: AttributeLayout = AttributeLayout::new(VARLENA, Alignment::Int);
+const ANYARRAY: AttributeLayout = AttributeLayout::new(VARLENA, Alignment::Double);
+
+/// Major versions of PostgreSQL laying the catalogs out as the descriptors below, PostgreSQL 16
+/// reorders the fixed part of pg_attribute and 17 moves attstattarget to its variable part
+const CATALOG_PG_VERSIONS: RangeInclusive<u32> = 14..=15;
+
+/// Bootstrap tuple descriptor of a catalog: the leading columns as PostgreSQL 14 and 15 lay
+/// them out, fixed length ones are never null. Later columns are not decoded
+type Descriptor = [(&'static str, AttributeLayout)];
+
+#[rustfmt::skip]
+const PG_CLASS: &Descriptor = &[
+    ("oid", OID), ("relname", NAME), ("relnamespace", OID), ("reltype", OID),
+    ("reloftype", OID), ("relowner", OID), ("relam", OID), ("relfilenode", OID),
+    ("reltablespace", OID), ("relpages", INT4), ("reltuples", FLOAT4), ("relallvisible", INT4),
+    ("reltoastrelid", OID), ("relhasindex", BOOL), ("relisshared", BOOL),
+    ("relpersistence", CHAR), ("relkind", CHAR), ("relnatts", INT2),
+];
+
+#[rustfmt::skip]
+const PG_ATTRIBUTE: &Descriptor = &[
+    ("attrelid", OID), ("attname", NAME), ("atttypid", OID), ("attstattarget", INT4),
+    ("attlen", INT2), ("attnum", INT2), ("attndims", INT4), ("attcacheoff", INT4),
+    ("atttypmod", INT4), ("attbyval", BOOL), ("attalign", CHAR), ("attstorage", CHAR),
+    ("attcompression", CHAR), ("attnotnull", BOOL), ("atthasdef", BOOL),
+    ("atthasmissing", BOOL), ("attidentity", CHAR), ("attgenerated", CHAR),
+    ("attisdropped", BOOL), ("attislocal", BOOL), ("attinhcount", INT4),
+    ("attcollation", OID), ("attacl", INT_ARRAY), ("attoptions", INT_ARRAY),
+    ("attfdwoptions", INT_ARRAY), ("attmissingval", ANYARRAY),
+];
+
+#[rustfmt::skip]
+const PG_TYPE: &Descriptor = &[
+    ("oid", OID), ("typname", NAME), ("typnamespace", OID), ("typowner", OID),
+    ("typlen", INT2), ("typbyval", BOOL), ("typtype", CHAR), ("typcategory", CHAR),
+    ("typispreferred", BOOL), ("typisdefined", BOOL), ("typdelim", CHAR), ("typrelid", OID),
+    ("typsubscript", REGPROC), ("typelem", OID), ("typarray", OID), ("typinput", REGPROC),
+    ("typoutput", REGPROC), ("typreceive", REGPROC), ("typsend", REGPROC),
+    ("typmodin", REGPROC), ("typmodout", REGPROC), ("typanalyze", REGPROC),
+    ("typalign", CHAR), ("typstorage", CHAR), ("typnotnull", BOOL), ("typbasetype", OID),
+];
+
+#[rustfmt::skip]
+const PG_NAMESPACE: &Descriptor = &[
+    ("oid", OID), ("nspname", NAME), ("nspowner", OID),
+];
+
+#[rustfmt::skip]
+const PG_INDEX: &Descriptor = &[
+    ("indexrelid", OID), ("indrelid", OID), ("indnatts", INT2), ("indnkeyatts", INT2),
+];
+
+/// System catalogs of a database decoded from its files, the relations, their columns and
+/// the types of the columns
+#[derive(Debug, PartialEq, Default)]
+pub struct Catalog {
+    namespaces: BTreeMap<PgOid, Namespace>,
+    classes: BTreeMap<PgOid, Class>,
+    types: BTreeMap<PgOid, Type>,
+    /// Names of the roles owning the relations, pg_authid is a shared catalog
+    roles: BTreeMap<PgOid, String>,
+    /// Maps relfilenodes to the OIDs of the relations stored in them
+    filenodes: BTreeMap<PgOid, PgOid>,
+}
+
+impl Catalog {
+    /// Locates pg_class, pg_attribute and pg_type through the relation mapper file of the
+    /// database, then pg_namespace and pg_index through pg_class, and decodes their live tuples
+    /// with the statuses of the transactions which are not hinted looked up. The
+    /// catalogs of the major versions with another layout are rejected rather than misread
+    pub fn read(db_dir: &impl DbDir, transactions: &impl Transactions) -> Result<Self> {
+        let pg_version = db_dir.pg_version()?;
+        if !CATALOG_PG_VERSIONS.contains(&pg_version) {
+            bail!("Unsupported catalog layout for PG {pg_version}");
+        }
+        let filenode_map = db_dir.filenode_map()?;
+        let mapped = |oid: PgOid, name: &str| {
+            filenode_map
+                .relfilenode(oid)
+                .with_context(|| format!("{name} is missing in the relation mapper file"))
+        };
+        let scan = |relfilenode: PgOid, name: &'static str, descriptor: &'static Descriptor| {
+            db_dir
+                .relation(RelFileNode::Permanent(relfilenode))
+                .and_then(|files| scan(&files, name, descriptor, transactions))
+                .with_context(|| format!("Reading {name}"))
+        };
+
+        let types = scan(mapped(PG_TYPE_OID, "pg_type")?, "pg_type", PG_TYPE)?
+            .iter()
+            .map(Type::from_row)
+            .collect::<Result<Vec<_>>>()?;
+        let classes = scan(mapped(PG_CLASS_OID, "pg_class")?, "pg_class", PG_CLASS)?
+            .iter()
+            .map(Class::from_row)
+            .collect::<Result<Vec<_>>>()?;
+        let columns = scan(
+            mapped(PG_ATTRIBUTE_OID, "pg_attribute")?,
+            "pg_attribute",
+            PG_ATTRIBUTE,
+        )?
+        .iter()
+        .map(|row| Ok((row.oid("attrelid")?, Column::from_row(row)?)))
+        .collect::<Result<Vec<_>>>()?;
+        let mut catalog = Catalog::new(types, classes, columns, &filenode_map);
+
+        let unmapped = |oid: PgOid, name: &str| {
+            catalog
+                .class(oid)
+                .map(|class| class.relfilenode)
+                .with_context(|| format!("{name} is missing in pg_class"))
+        };
+        let namespaces = scan(
+            unmapped(PG_NAMESPACE_OID, "pg_namespace")?,
+            "pg_namespace",
+            PG_NAMESPACE,
+        )?
+        .iter()
+        .map(Namespace::from_row)
+        .collect::<Result<Vec<_>>>()?;
+        let indexes = scan(unmapped(PG_INDEX_OID, "pg_index")?, "pg_index", PG_INDEX)?
+            .iter()
+            .map(|row| Ok((row.oid("indrelid")?, row.oid("indexrelid")?)))
+            .collect::<Result<Vec<_>>>()?;
+        catalog.add_namespaces(namespaces);
+        catalog.add_indexes(indexes);
+        Ok(catalog)
+    }
+
+    /// Assembles the catalog of the live versions of the catalog rows. A row is expected to have
+    /// a single live version, should several remain, e.g. on a corrupted page, the one found
+    /// last in physical order is kept. Mapped catalogs get their
+    /// relfilenodes from the relation mapper file
+    pub fn new(
+        types: Vec<Type>,
+        classes: Vec<Class>,
+        columns: Vec<(PgOid, Column)>,
+        filenode_map: &FileNodeMap,
+    ) -> Self {
+        let mut classes = classes
+            .into_iter()
+            .map(|mut class| {
+                if class.relfilenode == PgOid(0) {
+                    class.relfilenode = filenode_map.relfilenode(class.oid).unwrap_or(PgOid(0));
+                }
+                (class.oid, class)
+            })
+            .collect::<BTreeMap<_, _>>();
+        columns
+            .into_iter()
+            .filter(|(_, column)| column.attnum > 0)
+            .for_each(|(relid, column)| {
+                if let Some(class) = classes.get_mut(&relid) {
+                    class.columns.retain(|known| known.attnum != column.attnum);
+                    class.columns.push(column);
+                }
+            });
+        classes
+            .values_mut()
+            .for_each(|class| class.columns.sort_by_key(|column| column.attnum));
+        Catalog {
+            namespaces: BTreeMap::new(),
+            roles: BTreeMap::new(),
+            types: types.into_iter().map(|ty| (ty.oid, ty)).collect(),
+            filenodes: classes
+                .values()
+                .filter(|class| class.relfilenode != PgOid(0))
+                .map(|class| (class.relfilenode, class.oid))
+                .collect(),
+            classes,
+        }
+    }
+
+    pub fn add_namespaces(&mut self, namespaces: Vec<Namespace>) {
+        self.namespaces.extend(
+            namespaces
+                .into_iter()
+                .map(|namespace| (namespace.oid, namespace)),
+        );
+    }
+
+    pub fn add_roles(&mut self, roles: Vec<Role>) {
+        self.roles
+            .extend(roles.into_iter().map(|role| (role.oid, role.name)));
+    }
+
+    /// Attaches indexes given as pairs of the indexed relation and the index
+    pub fn add_indexes(&mut self, indexes: Vec<(PgOid, PgOid)>) {
+        indexes.into_iter().for_each(|(relid, index)| {
+            if let Some(class) = self.classes.get_mut(&relid) {
+                if !class.indexes.contains(&index) {
+                    class.indexes.push(index);
+                }
+            }
+        });
+        self.classes
+            .values_mut()
+            .for_each(|class| class.indexes.sort());
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &Class> {
+        self.classes.values()
+    }
+
+    pub fn class(&self, oid: PgOid) -> Option<&Class> {
+        self.classes.get(&oid)
+    }
+
+    /// Looks up the relation stored under the relfilenode
+    pub fn class_by_relfilenode(&self, relfilenode: PgOid) -> Option<&Class> {
+        self.filenodes
+            .get(&relfilenode)
+            .and_then(|oid| self.classes.get(oid))
+    }
+
+    pub fn data_type(&self, oid: PgOid) -> Option<&Type> {
+        self.types.get(&oid)
+    }
+
+    pub fn namespace(&self, oid: PgOid) -> Option<&Namespace> {
+        self.namespaces.get(&oid)
+    }
+
+    /// Finds the table an index or a TOAST table belongs to
+    pub fn parent(&self, class: &Class) -> Option<&Class> {
+        match class.kind {
+            RelKind::Index | RelKind::PartitionedIndex => self
+                .classes()
+                .find(|parent| parent.indexes.contains(&class.oid)),
+            RelKind::ToastTable => self
+                .classes()
+                .find(|parent| parent.toast_relid == class.oid),
+            _ => None,
+        }
+    }
+
+    pub fn role_name(&self, oid: PgOid) -> Option<&str> {
+        self.roles.get(&oid).map(String::as_str)
+    }
+
+    /// Names the role owning the relation, an unknown role is shown by its OID
+    pub fn owner_name(&self, class: &Class) -> String {
+        self.roles
+            .get(&class.owner)
+            .cloned()
+            .unwrap_or_else(|| class.owner.0.to_string())
+    }
+
+    /// Names the relation schema.relname, an unknown schema is shown by its OID
+    pub fn qualified_name(&self, class: &Class) -> String {
+        match self.namespace(class.namespace) {
+            Some(namespace) => format!("{}.{}", namespace.name, class.name),
+            None => format!("{}.{}", class.namespace.0, class.name),
+        }
+    }
+}
+
+/// A row of pg_namespace
+#[derive(Debug, PartialEq, Clone)]
+pub struct Namespace {
+    pub oid: PgOid,
+    pub name: String,
+}
+
+impl Namespace {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Namespace {
+            oid: row.oid("oid")?,
+            name: row.name("nspname")?,
+        })
+    }
+}
+
+/// A row of pg_class along with the columns and indexes of the relation
+#[derive(Debug, PartialEq, Clone)]
+pub struct Class {
+    pub oid: PgOid,
+    pub name: String,
+    pub namespace: PgOid,
+    pub owner: PgOid,
+    /// Relfilenode of the relation, mapped catalogs have it resolved through the relation
+    /// mapper file, 0 for relations without storage and mapped shared catalogs
+    pub relfilenode: PgOid,
+    pub kind: RelKind,
+    /// relpersistence: p for permanent, u for unlogged, t for temporary relations
+    pub persistence: char,
+    /// OID of the TOAST table, 0 when the relation has none
+    pub toast_relid: PgOid,
+    /// Columns with positive attnum ordered by it, dropped ones included
+    pub columns: Vec<Column>,
+    /// OIDs of the indexes of the relation
+    pub indexes: Vec<PgOid>,
+}
+
+impl Class {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Class {
+            oid: row.oid("oid")?,
+            name: row.name("relname")?,
+            namespace: row.oid("relnamespace")?,
+            owner: row.oid("relowner")?,
+            relfilenode: row.oid("relfilenode")?,
+            kind: RelKind::parse(row.char("relkind")?)?,
+            persistence: row.char("relpersistence")?,
+            toast_relid: row.oid("reltoastrelid")?,
+            columns: Vec::new(),
+            indexes: Vec::new(),
+        })
+    }
+
+    /// Splits a tuple of the relation into the values of its columns the way heap_deform_tuple
+    /// does, dropped columns included. Columns added after the tuple was written get their
+    /// fast default values, nulls when they have none
+    pub fn values<'a>(&'a self, tuple: &HeapTuple<'a>) -> Result<Vec<Option<&'a [u8]>>> {
+        if let Some(column) = (1..)
+            .zip(&self.columns)
+            .find_map(|(attnum, column)| (column.attnum != attnum).then_some(column))
+        {
+            bail!(
+                "Column {} of {} is out of order, attnum {}",
+                column.name,
+                self.name,
+                column.attnum
+            );
+        }
+        let layouts = self
+            .columns
+            .iter()
+            .map(|column| column.layout)
+            .collect::<Vec<_>>();
+        let natts = tuple.header.natts() as usize;
+        Ok(tuple
+            .attributes(&layouts)?
+            .into_iter()
+            .zip(&self.columns)
+            .enumerate()
+            .map(|(attnum, (value, column))| match attnum < natts {
+                true => value,
+                false => column.missing.as_deref(),
+            })
+            .collect())
+    }
+}
+
+/// relkind of pg_class
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RelKind {
+    Table,
+    Index,
+    Sequence,
+    ToastTable,
+    View,
+    MaterializedView,
+    CompositeType,
+    ForeignTable,
+    PartitionedTable,
+    PartitionedIndex,
+}
+
+impl RelKind {
+    pub fn parse(relkind: char) -> Result<Self> {
+        Ok(match relkind {
+            'r' => RelKind::Table,
+            'i' => RelKind::Index,
+            'S' => RelKind::Sequence,
+            't' => RelKind::ToastTable,
+            'v' => RelKind::View,
+            'm' => RelKind::MaterializedView,
+            'c' => RelKind::CompositeType,
+            'f' => RelKind::ForeignTable,
+            'p' => RelKind::PartitionedTable,
+            'I' => RelKind::PartitionedIndex,
+            relkind => bail!("Unknown relkind {:?}", relkind),
+        })
+    }
+}
+
+impl Display for RelKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RelKind::Table => "table",
+            RelKind::Index => "index",
+            RelKind::Sequence => "sequence",
+            RelKind::ToastTable => "TOAST table",
+            RelKind::View => "view",
+            RelKind::MaterializedView => "materialized view",
+            RelKind::CompositeType => "composite type",
+            RelKind::ForeignTable => "foreign table",
+            RelKind::PartitionedTable => "partitioned table",
+            RelKind::PartitionedIndex => "partitioned index",
+        })
+    }
+}
+
+/// A row of pg_attribute describing a column of a relation
+#[derive(Debug, PartialEq, Clone)]
+pub struct Column {
+    pub attnum: i16,
+    pub name: String,
+    pub type_oid: PgOid,
+    pub typmod: i32,
+    pub layout: AttributeLayout,
+    pub by_val: bool,
+    /// attstorage: p for plain, e for external, m for main, x for extended
+    pub storage: char,
+    pub not_null: bool,
+    pub dropped: bool,
+    /// Value of the column in the tuples written before the column was added with a constant
+    /// default, attmissingval, None when they read it as null
+    pub missing: Option<Vec<u8>>,
+}
+
+impl Column {
+    fn from_row(row: &Row) -> Result<Self> {
+        let name = row.name("attname")?;
+        let layout = AttributeLayout::new(
+            row.i16("attlen")?,
+            Alignment::parse(row.char("attalign")? as u8)?,
+        );
+        let missing = match (row.bool("atthasmissing")?, row.nullable("attmissingval")) {
+            (true, Some(array)) => missing_value(array, layout)
+                .with_context(|| format!("Decoding attmissingval of {name}"))?,
+            _ => None,
+        };
+        Ok(Column {
+            attnum: row.i16("attnum")?,
+            name,
+            type_oid: row.oid("atttypid")?,
+            typmod: row.i32("atttypmod")?,
+            layout,
+            by_val: row.bool("attbyval")?,
+            storage: row.char("attstorage")?,
+            not_null: row.bool("attnotnull")?,
+            dropped: row.bool("attisdropped")?,
+            missing,
+        })
+    }
+}
+
+/// Extracts the value of the single element array attmissingval holds
+fn missing_value(array: &[u8], layout: AttributeLayout) -> Result<Option<Vec<u8>>> {
+    let payload = Varlena::parse(array)?.payload()?;
+    let elements = Array::parse(&payload)?.elements(layout)?;
+    match elements.as_slice() {
+        [element] => Ok(element.map(|element| element.to_vec())),
+        elements => bail!("Expected a single element, got {}", elements.len()),
+    }
+}
+
+/// A row of pg_type
+#[derive(Debug, PartialEq, Clone)]
+pub struct Type {
+    pub oid: PgOid,
+    pub name: String,
+    pub namespace: PgOid,
+    pub len: i16,
+    pub by_val: bool,
+    /// typtype: b for base, c for composite, d for domain, e for enum, p for pseudo, r for
+    /// range and m for multirange types
+    pub kind: char,
+    /// Relation of a composite type
+    pub relid: PgOid,
+    /// Element type of an array type
+    pub elem: PgOid,
+    pub align: Alignment,
+    /// Underlying type of a domain
+    pub base_type: PgOid,
+}
+
+impl Type {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Type {
+            oid: row.oid("oid")?,
+            name: row.name("typname")?,
+            namespace: row.oid("typnamespace")?,
+            len: row.i16("typlen")?,
+            by_val: row.bool("typbyval")?,
+            kind: row.char("typtype")?,
+            relid: row.oid("typrelid")?,
+            elem: row.oid("typelem")?,
+            align: Alignment::parse(row.char("typalign")? as u8)?,
+            base_type: row.oid("typbasetype")?,
+        })
+    }
+}
+
+/// Values of a catalog tuple decoded with a bootstrap descriptor
+struct Row {
+    catalog: &'static str,
+    descriptor: &'static Descriptor,
+    values: Vec<Option<Vec<u8>>>,
+}
+
+impl Row {
+    fn nullable(&self, column: &str) -> Option<&[u8]> {
+        let attnum = self
+            .descriptor
+            .iter()
+            .position(|(name, _)| *name == column)
+            .expect("column of the descriptor");
+        self.values[attnum].as_deref()
+    }
+
+    fn value(&self, column: &str) -> Result<&[u8]> {
+        match self.nullable(column) {
+            Some(value) => Ok(value),
+            None => bail!("{}.{} is null", self.catalog, column),
+        }
+    }
+
+    fn oid(&self, column: &str) -> Result<PgOid> {
+        self.value(column)?.u32_at(0).map(PgOid)
+    }
+
+    fn name(&self, column: &str) -> Result<String> {
+        self.value(column).map(read_name)
+    }
+
+    fn i16(&self, column: &str) -> Result<i16> {
+        self.value(column)?.i16_at(0)
+    }
+
+    fn i32(&self, column: &str) -> Result<i32> {
+        self.value(column)?.i32_at(0)
+    }
+
+    fn bool(&self, column: &str) -> Result<bool> {
+        self.value(column)?.u8_at(0).map(|value| value != 0)
+    }
+
+    fn char(&self, column: &str) -> Result<char> {
+        self.value(column)?.u8_at(0).map(char::from)
+    }
+}
+
+/// Decodes the live tuples of the main fork in physical order, see
+/// [crate::pgdata::page::Page::live_tuples]
+fn scan(
+    files: &impl RelationFiles,
+    catalog: &'static str,
+    descriptor: &'static Descriptor,
+    transactions: &impl Transactions,
+) -> Result<Vec<Row>> {
+    let layouts = descriptor
+        .iter()
+        .map(|(_, layout)| *layout)
+        .collect::<Vec<_>>();
+    let blocks = files
+        .relation()
+        .fork(ForkType::Main)
+        .map_or(0, |fork| fork.blocks()) as u32;
+    let mut rows = Vec::new();
+    for block in 0..blocks {
+        let page = files.page(ForkType::Main, block)?;
+        for tuple in page.live_tuples(block, transactions)? {
+            let (ctid, tuple) = tuple?;
+            let values = tuple
+                .attributes(&layouts)
+                .with_context(|| format!("Decoding tuple {ctid}"))?
+                .into_iter()
+                .map(|value| value.map(<[u8]>::to_vec))
+                .collect();
+            rows.push(Row {
+                catalog,
+                descriptor,
+                values,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use crate::{
+        common::PgOid,
+        pgdata::{
+            filenode_map::{test_files::filenode_map, FileNodeMap},
+            global::Role,
+            page::heap::{Alignment, AttributeLayout},
+        },
+    };
+
+    use super::{Catalog, Class, Column, Namespace, RelKind, Type};
+
+    fn class(oid: u32, name: &str, namespace: u32, relfilenode: u32, kind: RelKind) -> Class {
+        Class {
+            oid: PgOid(oid),
+            name: name.to_string(),
+            namespace: PgOid(namespace),
+            owner: PgOid(if oid < 16384 { 10 } else { 16384 }),
+            relfilenode: PgOid(relfilenode),
+            kind,
+            persistence: 'p',
+            toast_relid: PgOid(0),
+            columns: Vec::new(),
+            indexes: Vec::new(),
+        }
+    }
+
+    fn column(attnum: i16, name: &str, type_oid: u32, len: i16) -> Column {
+        Column {
+            attnum,
+            name: name.to_string(),
+            type_oid: PgOid(type_oid),
+            typmod: -1,
+            layout: AttributeLayout::new(len, Alignment::Int),
+            by_val: len > 0,
+            storage: if len > 0 { 'p' } else { 'x' },
+            not_null: attnum == 1,
+            dropped: false,
+            missing: None,
+        }
+    }
+
+    fn data_type(oid: u32, name: &str, len: i16) -> Type {
+        Type {
+            oid: PgOid(oid),
+            name: name.to_string(),
+            namespace: PgOid(11),
+            len,
+            by_val: len > 0,
+            kind: 'b',
+            relid: PgOid(0),
+            elem: PgOid(0),
+            align: Alignment::Int,
+            base_type: PgOid(0),
+        }
+    }
+
+    /// Catalog of the database 5: the mapped pg_attribute, public.orders with its TOAST table
+    /// and primary key, the unlogged public.sessions and a temporary table of backend 3
+    pub fn catalog() -> Catalog {
+        let orders = Class {
+            toast_relid: PgOid(16389),
+            ..class(16385, "orders", 2200, 16385, RelKind::Table)
+        };
+        let mut catalog = Catalog::new(
+            vec![
+                data_type(23, "int4", 4),
+                data_type(25, "text", -1),
+                Type {
+                    elem: PgOid(25),
+                    ..data_type(1009, "_text", -1)
+                },
+            ],
+            vec![
+                class(1249, "pg_attribute", 11, 0, RelKind::Table),
+                orders,
+                class(16389, "pg_toast_16385", 99, 16389, RelKind::ToastTable),
+                class(16391, "orders_pkey", 2200, 16391, RelKind::Index),
+                Class {
+                    persistence: 'u',
+                    ..class(16406, "sessions", 2200, 16406, RelKind::Table)
+                },
+                Class {
+                    persistence: 't',
+                    ..class(16410, "scratch", 16400, 16410, RelKind::Table)
+                },
+            ],
+            vec![
+                (PgOid(16385), column(1, "id", 23, 4)),
+                (PgOid(16385), column(2, "note", 25, -1)),
+            ],
+            &FileNodeMap::parse(&filenode_map(&[(1249, 1249)])).expect("filenode map"),
+        );
+        catalog.add_namespaces(
+            [
+                (11, "pg_catalog"),
+                (99, "pg_toast"),
+                (2200, "public"),
+                (16400, "pg_temp_3"),
+            ]
+            .into_iter()
+            .map(|(oid, name)| Namespace {
+                oid: PgOid(oid),
+                name: name.to_string(),
+            })
+            .collect(),
+        );
+        catalog.add_roles(vec![
+            Role {
+                oid: PgOid(10),
+                name: "postgres".to_string(),
+            },
+            Role {
+                oid: PgOid(16384),
+                name: "alice".to_string(),
+            },
+        ]);
+        catalog.add_indexes(vec![(PgOid(16385), PgOid(16391))]);
+        catalog
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{iter::empty, path::Path};
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{
+        common::PgOid,
+        pgdata::{
+            base::db_dir::{
+                relation::{test_stubs::StubRelationFiles, Relation, RelationFiles},
+                DbDir, DbDirItem, ForkSegmentFile, ForkType, RelFileNode,
+            },
+            filenode_map::{test_files::filenode_map, FileNodeMap},
+            page::{
+                heap::{Alignment, AttributeLayout, HeapTuple, HEAP_XMAX_COMMITTED, VARLENA},
+                test_pages::{heap_tuple, with_nulls, PageBuilder},
+            },
+            transactions::test_stubs::StubTransactions,
+        },
+    };
+
+    use super::{
+        Catalog, Descriptor, RelKind, PG_ATTRIBUTE, PG_CLASS, PG_INDEX, PG_NAMESPACE, PG_TYPE,
+    };
+
+    /// Serves a single page of catalog tuples per relfilenode
+    struct CatalogDbDir {
+        pg_version: u32,
+        tables: Vec<(u32, Vec<Vec<u8>>)>,
+    }
+
+    impl DbDir for CatalogDbDir {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/base/5")
+        }
+
+        fn items(
+            &self,
+        ) -> anyhow::Result<
+            impl IntoIterator<Item = DbDirItem<'_>, IntoIter = impl Iterator<Item = DbDirItem<'_>>>,
+        > {
+            Ok(empty())
+        }
+
+        fn relation<'a>(
+            &self,
+            rel_file_node: RelFileNode,
+        ) -> anyhow::Result<impl RelationFiles + 'a> {
+            let tuples = self
+                .tables
+                .iter()
+                .find(|(relfilenode, _)| PgOid(*relfilenode) == rel_file_node.oid())
+                .map_or(&[][..], |(_, tuples)| tuples);
+            let page = tuples
+                .iter()
+                .fold(PageBuilder::empty(), |page, tuple| page.item(tuple))
+                .build();
+            Ok(StubRelationFiles {
+                relation: Relation::group([(
+                    ForkSegmentFile::new(rel_file_node, ForkType::Main, 0),
+                    8192,
+                )])
+                .remove(0),
+                pages: vec![(ForkType::Main, 0, page)],
+            })
+        }
+
+        fn filenode_map(&self) -> anyhow::Result<FileNodeMap> {
+            FileNodeMap::parse(&filenode_map(&[(1259, 1259), (1249, 1249), (1247, 1247)]))
+        }
+
+        fn pg_version(&self) -> anyhow::Result<u32> {
+            Ok(self.pg_version)
+        }
+
+        fn catalog(&self) -> anyhow::Result<Catalog> {
+            Catalog::read(self, &StubTransactions)
+        }
+    }
+
+    /// Lays out a live catalog tuple, the fixed length columns which are not given are zeroed
+    /// and the varlena ones are null
+    fn tuple(descriptor: &Descriptor, values: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut nulls = Vec::new();
+        let mut data = Vec::new();
+        descriptor
+            .iter()
+            .enumerate()
+            .for_each(|(attnum, (column, layout))| {
+                match values.iter().find(|(name, _)| name == column) {
+                    Some((_, value)) => {
+                        data.resize(layout.align.apply(data.len()), 0);
+                        data.extend_from_slice(value)
+                    }
+                    None if layout.len == VARLENA => nulls.push(attnum),
+                    None => {
+                        data.resize(layout.align.apply(data.len()), 0);
+                        data.resize(data.len() + layout.len as usize, 0)
+                    }
+                }
+            });
+        with_nulls(
+            heap_tuple(740, 0, 0x0800, descriptor.len() as u16, &data),
+            &nulls,
+        )
+    }
+
+    fn oid(oid: u32) -> [u8; 4] {
+        oid.to_le_bytes()
+    }
+
+    fn name(name: &str) -> Vec<u8> {
+        let mut bytes = name.as_bytes().to_vec();
+        bytes.resize(64, 0);
+        bytes
+    }
+
+    fn class(
+        relid: u32,
+        relname: &str,
+        relnamespace: u32,
+        relfilenode: u32,
+        relkind: u8,
+    ) -> Vec<u8> {
+        tuple(
+            PG_CLASS,
+            &[
+                ("oid", &oid(relid)),
+                ("relname", &name(relname)),
+                ("relnamespace", &oid(relnamespace)),
+                ("relfilenode", &oid(relfilenode)),
+                (
+                    "reltoastrelid",
+                    &oid(if relid == 16388 { 16391 } else { 0 }),
+                ),
+                ("relpersistence", b"p"),
+                ("relkind", &[relkind]),
+            ],
+        )
+    }
+
+    fn attribute(attrelid: u32, attname: &str, attnum: i16, atttypid: u32, attlen: i16) -> Vec<u8> {
+        tuple(
+            PG_ATTRIBUTE,
+            &[
+                ("attrelid", &oid(attrelid)),
+                ("attname", &name(attname)),
+                ("atttypid", &oid(atttypid)),
+                ("attlen", &attlen.to_le_bytes()),
+                ("attnum", &attnum.to_le_bytes()),
+                ("atttypmod", &(-1i32).to_le_bytes()),
+                ("attalign", b"i"),
+                ("attstorage", if attlen == -1 { b"x" } else { b"p" }),
+                ("attisdropped", &[attname.starts_with("....") as u8]),
+            ],
+        )
+    }
+
+    fn data_type(typid: u32, typname: &str, typlen: i16) -> Vec<u8> {
+        tuple(
+            PG_TYPE,
+            &[
+                ("oid", &oid(typid)),
+                ("typname", &name(typname)),
+                ("typnamespace", &oid(11)),
+                ("typlen", &typlen.to_le_bytes()),
+                ("typtype", b"b"),
+                ("typalign", b"i"),
+            ],
+        )
+    }
+
+    #[test]
+    fn reads_catalog() {
+        // given
+        let mut rewritten_fpt = class(16388, "fpt", 2200, 16388, b'r');
+        rewritten_fpt[4..8].copy_from_slice(&741u32.to_le_bytes());
+        rewritten_fpt[20..22].copy_from_slice(&HEAP_XMAX_COMMITTED.to_le_bytes());
+        // the update of xid 741 aborted without setting hint bits
+        let mut fpt = class(16388, "fpt", 2200, 16400, b'r');
+        fpt[4..8].copy_from_slice(&741u32.to_le_bytes());
+        fpt[20..22].copy_from_slice(&0u16.to_le_bytes());
+        let mut aborted_fpt = class(16388, "fpt", 2200, 16500, b'r');
+        aborted_fpt[0..4].copy_from_slice(&741u32.to_le_bytes());
+        let db_dir = CatalogDbDir {
+            pg_version: 15,
+            tables: vec![
+                (
+                    1259,
+                    vec![
+                        class(1259, "pg_class", 11, 0, b'r'),
+                        class(2610, "pg_index", 11, 2610, b'r'),
+                        class(2615, "pg_namespace", 11, 2615, b'r'),
+                        rewritten_fpt,
+                        fpt,
+                        aborted_fpt,
+                        class(16395, "fpt_pkey", 2200, 16395, b'i'),
+                    ],
+                ),
+                (
+                    1249,
+                    vec![
+                        attribute(16388, "ctid", -1, 27, 6),
+                        attribute(16388, "t", 2, 25, -1),
+                        attribute(16388, "id", 1, 23, 4),
+                        attribute(16388, "........pg.dropped.3........", 3, 0, 4),
+                    ],
+                ),
+                (
+                    1247,
+                    vec![data_type(23, "int4", 4), data_type(25, "text", -1)],
+                ),
+                (
+                    2615,
+                    vec![
+                        tuple(
+                            PG_NAMESPACE,
+                            &[("oid", &oid(11)), ("nspname", &name("pg_catalog"))],
+                        ),
+                        tuple(
+                            PG_NAMESPACE,
+                            &[("oid", &oid(2200)), ("nspname", &name("public"))],
+                        ),
+                    ],
+                ),
+                (
+                    2610,
+                    vec![tuple(
+                        PG_INDEX,
+                        &[("indexrelid", &oid(16395)), ("indrelid", &oid(16388))],
+                    )],
+                ),
+            ],
+        };
+
+        // when
+        let catalog = db_dir.catalog().unwrap();
+
+        // then
+        let fpt = catalog.class_by_relfilenode(PgOid(16400)).unwrap();
+        assert_eq!(catalog.qualified_name(fpt), "public.fpt");
+        assert_eq!(fpt.kind, RelKind::Table);
+        assert_eq!(fpt.toast_relid, PgOid(16391));
+        assert_eq!(fpt.indexes, vec![PgOid(16395)]);
+        assert_eq!(
+            fpt.columns
+                .iter()
+                .map(|column| (column.attnum, column.name.as_str(), column.dropped))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, "id", false),
+                (2, "t", false),
+                (3, "........pg.dropped.3........", true)
+            ]
+        );
+        assert_eq!(
+            fpt.columns[1].layout,
+            AttributeLayout::new(-1, Alignment::Int)
+        );
+        assert_eq!(
+            catalog
+                .data_type(fpt.columns[1].type_oid)
+                .map(|ty| ty.name.as_str()),
+            Some("text")
+        );
+        assert_eq!(catalog.class_by_relfilenode(PgOid(16388)), None);
+        assert_eq!(catalog.class_by_relfilenode(PgOid(16500)), None);
+        assert_eq!(
+            catalog
+                .class_by_relfilenode(PgOid(1259))
+                .map(|class| catalog.qualified_name(class)),
+            Some("pg_catalog.pg_class".to_string())
+        );
+        let index = catalog.class_by_relfilenode(PgOid(16395)).unwrap();
+        assert_eq!(index.kind.to_string(), "index");
+        assert_eq!(catalog.parent(index), Some(fpt));
+    }
+
+    #[test]
+    fn fills_in_missing_values() {
+        // given
+        let mut missing_value = Vec::new();
+        [28 << 2, 1, 0, 23, 1, 1, 5].iter().for_each(|word: &i32| {
+            missing_value.extend_from_slice(&word.to_le_bytes());
+        });
+        let db_dir = CatalogDbDir {
+            pg_version: 15,
+            tables: vec![
+                (
+                    1259,
+                    vec![
+                        class(2610, "pg_index", 11, 2610, b'r'),
+                        class(2615, "pg_namespace", 11, 2615, b'r'),
+                        class(16388, "fpt", 2200, 16388, b'r'),
+                    ],
+                ),
+                (
+                    1249,
+                    vec![
+                        attribute(16388, "id", 1, 23, 4),
+                        attribute(16388, "t", 2, 25, -1),
+                        attribute(16388, "........pg.dropped.3........", 3, 0, 4),
+                        tuple(
+                            PG_ATTRIBUTE,
+                            &[
+                                ("attrelid", &oid(16388)),
+                                ("attname", &name("qty")),
+                                ("atttypid", &oid(23)),
+                                ("attlen", &4i16.to_le_bytes()),
+                                ("attnum", &4i16.to_le_bytes()),
+                                ("attalign", b"i"),
+                                ("atthasmissing", &[1]),
+                                ("attmissingval", &missing_value),
+                            ],
+                        ),
+                    ],
+                ),
+            ],
+        };
+        let row = heap_tuple(740, 0, 0x0002, 2, &[7, 0, 0, 0, 0x07, b'a', b'b']);
+
+        // when
+        let catalog = db_dir.catalog().unwrap();
+        let fpt = catalog.class_by_relfilenode(PgOid(16388)).unwrap();
+        let values = fpt.values(&HeapTuple::parse(&row).unwrap()).unwrap();
+
+        // then
+        assert_eq!(fpt.columns[3].missing, Some(vec![5, 0, 0, 0]));
+        assert_eq!(
+            values,
+            vec![
+                Some(&[7, 0, 0, 0][..]),
+                Some(&[0x07, b'a', b'b'][..]),
+                None,
+                Some(&[5, 0, 0, 0][..]),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(13)]
+    #[case(16)]
+    #[case(17)]
+    fn rejects_unsupported_catalog_layouts(#[case] pg_version: u32) {
+        // given
+        let db_dir = CatalogDbDir {
+            pg_version,
+            tables: vec![],
+        };
+
+        // when
+        let catalog = db_dir.catalog();
+
+        // then
+        assert_eq!(
+            catalog.unwrap_err().to_string(),
+            format!("Unsupported catalog layout for PG {pg_version}")
+        );
+    }
+}
@@ -0,0 +1,922 @@
+use std::borrow::Cow;
+
+use anyhow::{bail, Context, Result};
+
+use crate::common::{bytes::ReadBytes, pglz::pglz_decompress, size::pretty_size, PgOid};
+
+use self::{
+    date_time::{format_date, format_interval, format_time, format_timestamp, format_timetz},
+    jsonb::{quote_json, Jsonb},
+    network::{format_inet, format_macaddr},
+    numeric::Numeric,
+};
+
+use super::{
+    catalog::{Catalog, Type},
+    global::read_name,
+    page::{
+        heap::{varsize_any, Alignment, AttributeLayout, VARLENA},
+        ItemPointer,
+    },
+};
+
+mod date_time;
+pub mod jsonb;
+mod network;
+mod numeric;
+
+// OIDs of the built-in types with decoders, see pg_type.dat
+const BOOL_OID: PgOid = PgOid(16);
+const BYTEA_OID: PgOid = PgOid(17);
+const CHAR_OID: PgOid = PgOid(18);
+const NAME_OID: PgOid = PgOid(19);
+const INT8_OID: PgOid = PgOid(20);
+const INT2_OID: PgOid = PgOid(21);
+const INT2VECTOR_OID: PgOid = PgOid(22);
+const INT4_OID: PgOid = PgOid(23);
+const REGPROC_OID: PgOid = PgOid(24);
+const TEXT_OID: PgOid = PgOid(25);
+const OID_OID: PgOid = PgOid(26);
+const TID_OID: PgOid = PgOid(27);
+const XID_OID: PgOid = PgOid(28);
+const CID_OID: PgOid = PgOid(29);
+const OIDVECTOR_OID: PgOid = PgOid(30);
+const JSON_OID: PgOid = PgOid(114);
+const CIDR_OID: PgOid = PgOid(650);
+const FLOAT4_OID: PgOid = PgOid(700);
+const FLOAT8_OID: PgOid = PgOid(701);
+const MACADDR8_OID: PgOid = PgOid(774);
+const MONEY_OID: PgOid = PgOid(790);
+const MACADDR_OID: PgOid = PgOid(829);
+const INET_OID: PgOid = PgOid(869);
+const BPCHAR_OID: PgOid = PgOid(1042);
+const VARCHAR_OID: PgOid = PgOid(1043);
+const DATE_OID: PgOid = PgOid(1082);
+const TIME_OID: PgOid = PgOid(1083);
+const TIMESTAMP_OID: PgOid = PgOid(1114);
+const TIMESTAMPTZ_OID: PgOid = PgOid(1184);
+const INTERVAL_OID: PgOid = PgOid(1186);
+const TIMETZ_OID: PgOid = PgOid(1266);
+const BIT_OID: PgOid = PgOid(1560);
+const VARBIT_OID: PgOid = PgOid(1562);
+const NUMERIC_OID: PgOid = PgOid(1700);
+const REGPROCEDURE_OID: PgOid = PgOid(2202);
+const REGOPER_OID: PgOid = PgOid(2203);
+const REGOPERATOR_OID: PgOid = PgOid(2204);
+const REGCLASS_OID: PgOid = PgOid(2205);
+const REGTYPE_OID: PgOid = PgOid(2206);
+const UUID_OID: PgOid = PgOid(2950);
+const REGCONFIG_OID: PgOid = PgOid(3734);
+const JSONB_OID: PgOid = PgOid(3802);
+const REGDICTIONARY_OID: PgOid = PgOid(3769);
+const REGNAMESPACE_OID: PgOid = PgOid(4089);
+const REGROLE_OID: PgOid = PgOid(4096);
+const REGCOLLATION_OID: PgOid = PgOid(4191);
+
+/// Schemas of the default search_path, output functions leave names in them unqualified
+const VISIBLE_NAMESPACES: [&str; 2] = ["pg_catalog", "public"];
+
+/// Size of the 4-byte varlena header
+const VARHDRSZ: usize = 4;
+
+/// Size of the header of an inline compressed varlena value, the 4-byte header and va_tcinfo
+const VARHDRSZ_COMPRESSED: usize = 8;
+
+/// Mask of the raw size in va_tcinfo and va_extinfo, the top 2 bits hold the method
+const VARLENA_EXTSIZE_MASK: u32 = 0x3FFF_FFFF;
+
+/// Method used to compress a varlena value, see ToastCompressionId
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CompressionMethod {
+    Pglz,
+    Lz4,
+}
+
+impl CompressionMethod {
+    fn parse(info: u32) -> Result<Self> {
+        Ok(match info >> 30 {
+            0 => CompressionMethod::Pglz,
+            1 => CompressionMethod::Lz4,
+            method => bail!("Unknown compression method {}", method),
+        })
+    }
+}
+
+/// Decoded varatt_external, the pointer a tuple holds to a value moved to the TOAST table
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ToastPointer {
+    /// Size of the original value including its header
+    pub raw_size: u32,
+    /// Size of the value as stored in the TOAST table
+    pub ext_size: u32,
+    /// Compression method when the stored value is compressed
+    pub compression: Option<CompressionMethod>,
+    /// chunk_id of the TOAST table rows holding the value
+    pub value_id: PgOid,
+    pub toast_relid: PgOid,
+}
+
+/// A varlena value of a tuple told apart by its header, see varatt.h
+#[derive(Debug, PartialEq)]
+pub enum Varlena<'a> {
+    /// Data following a 1-byte or a 4-byte header
+    Inline(&'a [u8]),
+    /// Data compressed in place, `raw_size` excludes the header
+    Compressed {
+        raw_size: usize,
+        method: CompressionMethod,
+        data: &'a [u8],
+    },
+    /// Pointer to a value stored out of line
+    External(ToastPointer),
+}
+
+impl<'a> Varlena<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        let size = varsize_any(bytes)?;
+        let bytes = bytes.bytes_at(0, size)?;
+        Ok(match bytes[0] {
+            0x01 => {
+                let pointer = &bytes[2..];
+                let ext_info = pointer.u32_at(4)?;
+                let raw_size = pointer.i32_at(0)?;
+                if raw_size < VARHDRSZ as i32 {
+                    bail!(
+                        "TOAST pointer raw size {} is below the varlena header size",
+                        raw_size
+                    );
+                }
+                let raw_size = raw_size as u32;
+                let ext_size = ext_info & VARLENA_EXTSIZE_MASK;
+                Varlena::External(ToastPointer {
+                    raw_size,
+                    ext_size,
+                    // VARATT_EXTERNAL_IS_COMPRESSED
+                    compression: match ext_size < raw_size - VARHDRSZ as u32 {
+                        true => Some(CompressionMethod::parse(ext_info)?),
+                        false => None,
+                    },
+                    value_id: PgOid(pointer.u32_at(8)?),
+                    toast_relid: PgOid(pointer.u32_at(12)?),
+                })
+            }
+            first if first & 0x01 != 0 => Varlena::Inline(&bytes[1..]),
+            first if first & 0x03 == 0x02 => {
+                let tc_info = bytes.u32_at(VARHDRSZ)?;
+                Varlena::Compressed {
+                    raw_size: (tc_info & VARLENA_EXTSIZE_MASK) as usize,
+                    method: CompressionMethod::parse(tc_info)?,
+                    data: bytes
+                        .get(VARHDRSZ_COMPRESSED..)
+                        .context("Compressed varlena value is truncated")?,
+                }
+            }
+            _ => Varlena::Inline(&bytes[VARHDRSZ..]),
+        })
+    }
+
+    /// Data of the value without the header, decompressed if needed. Values compressed with
+    /// lz4 and values stored out of line are not read
+    pub fn payload(&self) -> Result<Cow<'a, [u8]>> {
+        match self {
+            Varlena::Inline(data) => Ok(Cow::Borrowed(data)),
+            Varlena::Compressed {
+                raw_size,
+                method: CompressionMethod::Pglz,
+                data,
+            } => pglz_decompress(data, *raw_size).map(Cow::Owned),
+            Varlena::Compressed {
+                method: CompressionMethod::Lz4,
+                ..
+            } => bail!("Values compressed with lz4 are not decompressed"),
+            Varlena::External(pointer) => bail!(
+                "Value {} is stored in the TOAST table {}",
+                pointer.value_id.0,
+                pointer.toast_relid.0
+            ),
+        }
+    }
+}
+
+/// Decoded ArrayType, a varlena array of elements of the same type, see array.h
+#[derive(Debug, PartialEq)]
+pub struct Array<'a> {
+    pub element_type: PgOid,
+    /// Length and lower bound of each dimension
+    pub dimensions: Vec<(i32, i32)>,
+    null_bitmap: Option<&'a [u8]>,
+    data: &'a [u8],
+}
+
+impl<'a> Array<'a> {
+    /// Parses the payload of an array value, the offsets of the header count the 4-byte
+    /// varlena header preceding it
+    pub fn parse(payload: &'a [u8]) -> Result<Self> {
+        let ndim = payload.i32_at(0)?;
+        if !(0..=6).contains(&ndim) {
+            bail!("Invalid number of array dimensions {}", ndim);
+        }
+        let ndim = ndim as usize;
+        let data_offset = payload.i32_at(4)? as usize;
+        let dimensions = (0..ndim)
+            .map(|dim| {
+                Ok((
+                    payload.i32_at(12 + 4 * dim)?,
+                    payload.i32_at(12 + 4 * (ndim + dim))?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let items = dimensions
+            .iter()
+            .map(|(len, _)| *len as usize)
+            .product::<usize>();
+        let bitmap_offset = 12 + 8 * ndim;
+        let (null_bitmap, data_offset) = match data_offset {
+            // ARR_OVERHEAD_NONULLS
+            0 => (
+                None,
+                Alignment::Double.apply(VARHDRSZ + bitmap_offset) - VARHDRSZ,
+            ),
+            offset => (
+                Some(payload.bytes_at(bitmap_offset, items.div_ceil(8))?),
+                offset
+                    .checked_sub(VARHDRSZ)
+                    .context("Invalid array data offset")?,
+            ),
+        };
+        Ok(Array {
+            element_type: PgOid(payload.u32_at(8)?),
+            data: payload
+                .get(data_offset..)
+                .context("Array data offset is past its end")?,
+            null_bitmap,
+            dimensions,
+        })
+    }
+
+    /// Number of elements of all the dimensions
+    pub fn len(&self) -> usize {
+        match self.dimensions.is_empty() {
+            true => 0,
+            false => self
+                .dimensions
+                .iter()
+                .map(|(len, _)| *len as usize)
+                .product(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Splits the data into the elements in storage order, None for nulls. Varlena elements
+    /// keep their headers like tuple values do
+    pub fn elements(&self, layout: AttributeLayout) -> Result<Vec<Option<&'a [u8]>>> {
+        let mut offset = 0;
+        (0..self.len())
+            .map(|item| {
+                let is_null = self
+                    .null_bitmap
+                    .is_some_and(|bitmap| bitmap[item / 8] & (1 << (item % 8)) == 0);
+                if is_null {
+                    return Ok(None);
+                }
+                offset = layout.align.apply(offset);
+                let len = match layout.len {
+                    VARLENA => varsize_any(&self.data[offset.min(self.data.len())..])?,
+                    len if len > 0 => len as usize,
+                    len => bail!("Array elements of length {} are not decoded", len),
+                };
+                let element = self.data.bytes_at(offset, len)?;
+                offset += len;
+                Ok(Some(element))
+            })
+            .collect()
+    }
+}
+
+/// Renders the raw value of an attribute: fixed length values in hex, the way bytea is
+/// output, and varlena values by their decompressed data. Values stored out of line are
+/// described by their TOAST pointers
+pub fn format_raw(value: &[u8], layout: AttributeLayout) -> String {
+    if layout.len != VARLENA {
+        return format!("\\x{}", hex(value));
+    }
+    match Varlena::parse(value) {
+        Ok(Varlena::External(pointer)) => format!(
+            "TOAST value {} in {}, {}",
+            pointer.value_id.0,
+            pointer.toast_relid.0,
+            pretty_size((pointer.raw_size as u64).saturating_sub(VARHDRSZ as u64))
+        ),
+        Ok(varlena) => match varlena.payload() {
+            Ok(payload) => format!("\\x{}", hex(&payload)),
+            Err(err) => format!("{err:#}"),
+        },
+        Err(err) => format!("{err:#}"),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Renders a value of the type the way its output function does, assuming a UTF8 database,
+/// DateStyle ISO, IntervalStyle postgres, the UTC time zone and the C locale. Values of types
+/// without a decoder and values which fail to decode are dumped in hex annotated with the type
+/// name or the error, values stored out of line are described by their TOAST pointers
+pub fn format_datum(
+    catalog: &Catalog,
+    type_oid: PgOid,
+    layout: AttributeLayout,
+    value: &[u8],
+) -> String {
+    let bytes = match datum_bytes(value, layout) {
+        Ok(bytes) => bytes,
+        Err(text) => return text,
+    };
+    let type_name = || match catalog.data_type(type_oid) {
+        Some(data_type) => data_type.name.clone(),
+        None => format!("type {}", type_oid.0),
+    };
+    match decode(catalog, type_oid, &bytes) {
+        Ok(Some(text)) => text,
+        Ok(None) => format!("\\x{} ({})", hex(&bytes), type_name()),
+        Err(err) => format!("\\x{} ({}: {:#})", hex(&bytes), type_name(), err),
+    }
+}
+
+/// Renders a value of the type as a JSON value for structured output: jsonb values as the
+/// JSON they hold, booleans and finite numbers as JSON literals, all the other values as
+/// strings of what [format_datum] renders
+pub fn format_datum_json(
+    catalog: &Catalog,
+    type_oid: PgOid,
+    layout: AttributeLayout,
+    value: &[u8],
+) -> String {
+    let base_type = base_type(catalog, type_oid);
+    let json = datum_bytes(value, layout)
+        .ok()
+        .and_then(|bytes| match base_type {
+            JSONB_OID => Jsonb::parse(&bytes).ok().map(|jsonb| jsonb.to_string()),
+            BOOL_OID => fixed::<1>(&bytes)
+                .ok()
+                .map(|[byte]| (byte != 0).to_string()),
+            INT2_OID | INT4_OID | INT8_OID | OID_OID | XID_OID | CID_OID | FLOAT4_OID
+            | FLOAT8_OID | NUMERIC_OID => decode(catalog, base_type, &bytes)
+                .ok()
+                .flatten()
+                .filter(|number| number.parse::<f64>().is_ok_and(f64::is_finite)),
+            _ => None,
+        });
+    json.unwrap_or_else(|| quote_json(&format_datum(catalog, type_oid, layout, value)))
+}
+
+/// Takes a fixed length value as is and the payload of a varlena one. Values stored out of
+/// line and values which cannot be read are rendered as text instead
+fn datum_bytes(
+    value: &[u8],
+    layout: AttributeLayout,
+) -> std::result::Result<Cow<'_, [u8]>, String> {
+    match layout.len {
+        VARLENA => match Varlena::parse(value) {
+            Ok(Varlena::External(_)) => Err(format_raw(value, layout)),
+            Ok(varlena) => varlena.payload().map_err(|err| format!("{err:#}")),
+            Err(err) => Err(format!("{err:#}")),
+        },
+        _ => Ok(Cow::Borrowed(value)),
+    }
+}
+
+/// Follows domains down to the type they are based on
+fn base_type(catalog: &Catalog, type_oid: PgOid) -> PgOid {
+    match catalog.data_type(type_oid) {
+        Some(domain) if domain.kind == 'd' => base_type(catalog, domain.base_type),
+        _ => type_oid,
+    }
+}
+
+/// Decodes a fixed length value or the payload of a varlena one, None for types without a
+/// decoder. Domains are decoded as their base types
+fn decode(catalog: &Catalog, type_oid: PgOid, bytes: &[u8]) -> Result<Option<String>> {
+    let type_oid = base_type(catalog, type_oid);
+    let data_type = catalog.data_type(type_oid);
+    Ok(Some(match type_oid {
+        BOOL_OID => match fixed::<1>(bytes)? {
+            [0] => "f".to_string(),
+            _ => "t".to_string(),
+        },
+        BYTEA_OID => format!("\\x{}", hex(bytes)),
+        CHAR_OID => match fixed::<1>(bytes)? {
+            [0] => String::new(),
+            [byte] if byte >= 0x80 => format!("\\{byte:03o}"),
+            [byte] => char::from(byte).to_string(),
+        },
+        NAME_OID => read_name(bytes),
+        INT2_OID => i16::from_le_bytes(fixed(bytes)?).to_string(),
+        INT4_OID => i32::from_le_bytes(fixed(bytes)?).to_string(),
+        INT8_OID => i64::from_le_bytes(fixed(bytes)?).to_string(),
+        OID_OID | XID_OID | CID_OID | REGPROC_OID | REGPROCEDURE_OID | REGOPER_OID
+        | REGOPERATOR_OID | REGCONFIG_OID | REGDICTIONARY_OID | REGCOLLATION_OID => {
+            u32::from_le_bytes(fixed(bytes)?).to_string()
+        }
+        REGCLASS_OID => {
+            let oid = PgOid(u32::from_le_bytes(fixed(bytes)?));
+            match catalog.class(oid) {
+                Some(class) => qualify(catalog, class.namespace, &class.name),
+                None => oid.0.to_string(),
+            }
+        }
+        REGTYPE_OID => {
+            let oid = PgOid(u32::from_le_bytes(fixed(bytes)?));
+            match catalog.data_type(oid) {
+                Some(data_type) => format_type(catalog, data_type),
+                None => oid.0.to_string(),
+            }
+        }
+        REGNAMESPACE_OID => {
+            let oid = PgOid(u32::from_le_bytes(fixed(bytes)?));
+            match catalog.namespace(oid) {
+                Some(namespace) => quote_identifier(&namespace.name),
+                None => oid.0.to_string(),
+            }
+        }
+        REGROLE_OID => {
+            let oid = PgOid(u32::from_le_bytes(fixed(bytes)?));
+            match catalog.role_name(oid) {
+                Some(name) => quote_identifier(name),
+                None => oid.0.to_string(),
+            }
+        }
+        TID_OID => ItemPointer::parse(&fixed::<6>(bytes)?)?.to_string(),
+        TEXT_OID | VARCHAR_OID | BPCHAR_OID | JSON_OID => {
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+        JSONB_OID => Jsonb::parse(bytes)?.to_string(),
+        FLOAT4_OID => {
+            let value = f32::from_le_bytes(fixed(bytes)?);
+            match value {
+                value if value.is_nan() => "NaN".to_string(),
+                value if value.is_infinite() => infinity(value.is_sign_negative()),
+                value => format_float(format!("{value}"), format!("{value:e}"), 6),
+            }
+        }
+        FLOAT8_OID => {
+            let value = f64::from_le_bytes(fixed(bytes)?);
+            match value {
+                value if value.is_nan() => "NaN".to_string(),
+                value if value.is_infinite() => infinity(value.is_sign_negative()),
+                value => format_float(format!("{value}"), format!("{value:e}"), 15),
+            }
+        }
+        NUMERIC_OID => Numeric::parse(bytes)?.to_string(),
+        MONEY_OID => format_money(i64::from_le_bytes(fixed(bytes)?)),
+        DATE_OID => format_date(i32::from_le_bytes(fixed(bytes)?)),
+        TIME_OID => format_time(i64::from_le_bytes(fixed(bytes)?)),
+        TIMETZ_OID => {
+            let bytes = fixed::<12>(bytes)?;
+            format_timetz(bytes.i64_at(0)?, bytes.i32_at(8)?)
+        }
+        TIMESTAMP_OID => format_timestamp(i64::from_le_bytes(fixed(bytes)?), false),
+        TIMESTAMPTZ_OID => format_timestamp(i64::from_le_bytes(fixed(bytes)?), true),
+        INTERVAL_OID => {
+            let bytes = fixed::<16>(bytes)?;
+            format_interval(bytes.i64_at(0)?, bytes.i32_at(8)?, bytes.i32_at(12)?)
+        }
+        UUID_OID => {
+            let hex = hex(&fixed::<16>(bytes)?);
+            format!(
+                "{}-{}-{}-{}-{}",
+                &hex[0..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..32]
+            )
+        }
+        INET_OID => format_inet(bytes, false)?,
+        CIDR_OID => format_inet(bytes, true)?,
+        MACADDR_OID => format_macaddr(&fixed::<6>(bytes)?),
+        MACADDR8_OID => format_macaddr(&fixed::<8>(bytes)?),
+        BIT_OID | VARBIT_OID => {
+            let bit_len = bytes.i32_at(0)?.max(0) as usize;
+            let bits = bytes.bytes_at(4, bit_len.div_ceil(8))?;
+            (0..bit_len)
+                .map(|bit| match bits[bit / 8] & (0x80 >> (bit % 8)) {
+                    0 => '0',
+                    _ => '1',
+                })
+                .collect()
+        }
+        INT2VECTOR_OID | OIDVECTOR_OID => {
+            let array = Array::parse(bytes)?;
+            let layout = match type_oid {
+                INT2VECTOR_OID => AttributeLayout::new(2, Alignment::Short),
+                _ => AttributeLayout::new(4, Alignment::Int),
+            };
+            array
+                .elements(layout)?
+                .into_iter()
+                .map(|element| match element {
+                    Some(element) if type_oid == INT2VECTOR_OID => {
+                        Ok(element.i16_at(0)?.to_string())
+                    }
+                    Some(element) => Ok(element.u32_at(0)?.to_string()),
+                    None => bail!("Vector elements are never null"),
+                })
+                .collect::<Result<Vec<_>>>()?
+                .join(" ")
+        }
+        _ => match data_type {
+            Some(data_type) if data_type.elem != PgOid(0) && data_type.len == VARLENA => {
+                match format_array(catalog, data_type.elem, bytes)? {
+                    Some(array) => array,
+                    None => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        },
+    }))
+}
+
+/// Takes a fixed length value checking its length
+fn fixed<const N: usize>(bytes: &[u8]) -> Result<[u8; N]> {
+    match bytes.try_into() {
+        Ok(bytes) => Ok(bytes),
+        Err(_) => bail!("Expected {} bytes, got {}", N, bytes.len()),
+    }
+}
+
+fn infinity(negative: bool) -> String {
+    match negative {
+        true => "-Infinity".to_string(),
+        false => "Infinity".to_string(),
+    }
+}
+
+/// Chooses between the fixed and the scientific notation of the shortest representation of a
+/// float like float8out does: the fixed one is used for decimal exponents from -4 up to
+/// `max_exponent` exclusive, the scientific one gets a signed exponent of at least 2 digits
+fn format_float(fixed: String, scientific: String, max_exponent: i32) -> String {
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("exponent of the scientific notation");
+    let exponent = exponent
+        .parse::<i32>()
+        .expect("exponent of the scientific notation");
+    match (-4..max_exponent).contains(&exponent) {
+        true => fixed,
+        false => format!(
+            "{}e{}{:02}",
+            mantissa,
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        ),
+    }
+}
+
+/// Formats an amount of cents the way cash_out does in the C locale, e.g. `-$1,234.56`
+fn format_money(cents: i64) -> String {
+    let amount = cents.unsigned_abs();
+    let units = (amount / 100).to_string();
+    let grouped = units
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "{}${}.{:02}",
+        if cents < 0 { "-" } else { "" },
+        grouped,
+        amount % 100
+    )
+}
+
+/// Renders an array as array_out does, e.g. `{{1,2},{3,NULL}}`, prefixed with the bounds of
+/// the dimensions when some of them do not start at 1. None when the element type is unknown
+fn format_array(catalog: &Catalog, elem_oid: PgOid, payload: &[u8]) -> Result<Option<String>> {
+    let Some(elem_type) = catalog.data_type(elem_oid) else {
+        return Ok(None);
+    };
+    let array = Array::parse(payload)?;
+    if array.is_empty() {
+        return Ok(Some("{}".to_string()));
+    }
+    let layout = AttributeLayout::new(elem_type.len, elem_type.align);
+    let mut elements = array
+        .elements(layout)?
+        .into_iter()
+        .map(|element| match element {
+            Some(element) => quote_array_element(format_datum(catalog, elem_oid, layout, element)),
+            None => "NULL".to_string(),
+        });
+    let bounds = match array.dimensions.iter().all(|(_, lower)| *lower == 1) {
+        true => String::new(),
+        false => {
+            let bounds = array
+                .dimensions
+                .iter()
+                .map(|(len, lower)| format!("[{}:{}]", lower, lower + len - 1))
+                .collect::<String>();
+            format!("{bounds}=")
+        }
+    };
+    Ok(Some(format!(
+        "{}{}",
+        bounds,
+        nest_elements(&array.dimensions, &mut elements)
+    )))
+}
+
+fn nest_elements(dimensions: &[(i32, i32)], elements: &mut impl Iterator<Item = String>) -> String {
+    let items = match dimensions {
+        [] => Vec::new(),
+        [(len, _)] => elements.take(*len as usize).collect(),
+        [(len, _), inner @ ..] => (0..*len).map(|_| nest_elements(inner, elements)).collect(),
+    };
+    format!("{{{}}}", items.join(","))
+}
+
+/// Double-quotes an array element which would not read back as itself otherwise
+fn quote_array_element(element: String) -> String {
+    let needs_quotes = element.is_empty()
+        || element.eq_ignore_ascii_case("NULL")
+        || element.chars().any(|char| {
+            matches!(
+                char,
+                '"' | '\\' | '{' | '}' | ',' | ' ' | '\t' | '\n' | '\r' | '\x0b' | '\x0c'
+            )
+        });
+    match needs_quotes {
+        true => format!("\"{}\"", element.replace('\\', "\\\\").replace('"', "\\\"")),
+        false => element,
+    }
+}
+
+/// Names a type as format_type does: SQL names for the standard types, `[]` for arrays and
+/// the schema for types outside of the default search_path
+fn format_type(catalog: &Catalog, data_type: &Type) -> String {
+    if data_type.elem != PgOid(0) && data_type.len == VARLENA {
+        if let Some(elem_type) = catalog.data_type(data_type.elem) {
+            return format!("{}[]", format_type(catalog, elem_type));
+        }
+    }
+    let sql_name = match data_type.oid {
+        BOOL_OID => "boolean",
+        INT2_OID => "smallint",
+        INT4_OID => "integer",
+        INT8_OID => "bigint",
+        FLOAT4_OID => "real",
+        FLOAT8_OID => "double precision",
+        BPCHAR_OID => "character",
+        VARCHAR_OID => "character varying",
+        VARBIT_OID => "bit varying",
+        TIME_OID => "time without time zone",
+        TIMETZ_OID => "time with time zone",
+        TIMESTAMP_OID => "timestamp without time zone",
+        TIMESTAMPTZ_OID => "timestamp with time zone",
+        _ => return qualify(catalog, data_type.namespace, &data_type.name),
+    };
+    sql_name.to_string()
+}
+
+/// Quotes the name and prefixes it with the schema unless the schema is in the default
+/// search_path
+fn qualify(catalog: &Catalog, namespace: PgOid, name: &str) -> String {
+    match catalog.namespace(namespace) {
+        Some(namespace) if VISIBLE_NAMESPACES.contains(&namespace.name.as_str()) => {
+            quote_identifier(name)
+        }
+        Some(namespace) => format!(
+            "{}.{}",
+            quote_identifier(&namespace.name),
+            quote_identifier(name)
+        ),
+        None => quote_identifier(name),
+    }
+}
+
+/// Double-quotes an identifier unless it consists of lower case letters, digits and
+/// underscores only and does not start with a digit. Keywords are not recognized
+fn quote_identifier(name: &str) -> String {
+    let is_plain = name
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+        && name
+            .chars()
+            .all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '_');
+    match is_plain {
+        true => name.to_string(),
+        false => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{
+        common::PgOid,
+        pgdata::{
+            catalog::test_stubs::catalog,
+            page::heap::{Alignment, AttributeLayout},
+        },
+    };
+
+    use super::{
+        format_datum, format_datum_json, format_raw, Array, CompressionMethod, ToastPointer,
+        Varlena,
+    };
+
+    /// Prefixes the payload with a 1-byte varlena header
+    fn varlena(payload: &[u8]) -> Vec<u8> {
+        let mut value = vec![(payload.len() as u8 + 1) << 1 | 1];
+        value.extend_from_slice(payload);
+        value
+    }
+
+    fn words(words: &[i32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn text_array() -> Vec<u8> {
+        let mut payload = words(&[1, 32, 25, 3, 1]);
+        payload.extend_from_slice(&[0b101, 0, 0, 0, 0, 0, 0, 0]);
+        payload.extend_from_slice(&[7 << 2, 0, 0, 0, b'a', b' ', b'b', 0]);
+        payload.extend_from_slice(&[5 << 2, 0, 0, 0, b'"']);
+        varlena(&payload)
+    }
+
+    fn name(name: &str) -> Vec<u8> {
+        let mut bytes = name.as_bytes().to_vec();
+        bytes.resize(64, 0);
+        bytes
+    }
+
+    #[rstest]
+    #[case(&[0x09, b'a', b'b', b'c'], Varlena::Inline(b"abc"))]
+    #[case(&[0x18, 0, 0, 0, b'h', b'i'], Varlena::Inline(b"hi"))]
+    #[case(
+        &[0x26, 0, 0, 0, 0x0a, 0, 0, 0x40, 0xff],
+        Varlena::Compressed { raw_size: 10, method: CompressionMethod::Lz4, data: &[0xff] },
+    )]
+    #[case(
+        &[0x01, 0x12, 0x10, 0x27, 0, 0, 0x64, 0, 0, 0, 0x10, 0x40, 0, 0, 0x15, 0x40, 0, 0],
+        Varlena::External(ToastPointer {
+            raw_size: 10000,
+            ext_size: 100,
+            compression: Some(CompressionMethod::Pglz),
+            value_id: PgOid(16400),
+            toast_relid: PgOid(16405),
+        }),
+    )]
+    fn parses_varlena_headers(#[case] bytes: &[u8], #[case] expected: Varlena) {
+        // when
+        let varlena = Varlena::parse(bytes).unwrap();
+
+        // then
+        assert_eq!(varlena, expected);
+    }
+
+    #[test]
+    fn rejects_toast_pointer_below_header_size() {
+        // when
+        let varlena = Varlena::parse(&[
+            0x01, 0x12, 0x02, 0, 0, 0, 0, 0, 0, 0, 0x10, 0x40, 0, 0, 0x15, 0x40, 0, 0,
+        ]);
+
+        // then
+        assert_eq!(
+            varlena.unwrap_err().to_string(),
+            "TOAST pointer raw size 2 is below the varlena header size"
+        );
+    }
+
+    #[test]
+    fn splits_array_elements() {
+        // given
+        let mut payload = Vec::new();
+        [1i32, 32, 23, 3, 1].iter().for_each(|word| {
+            payload.extend_from_slice(&word.to_le_bytes());
+        });
+        payload.extend_from_slice(&[0b101, 0, 0, 0, 0, 0, 0, 0]);
+        [7i32, 9].iter().for_each(|word| {
+            payload.extend_from_slice(&word.to_le_bytes());
+        });
+
+        // when
+        let array = Array::parse(&payload).unwrap();
+        let elements = array
+            .elements(AttributeLayout::new(4, Alignment::Int))
+            .unwrap();
+
+        // then
+        assert_eq!(array.element_type, PgOid(23));
+        assert_eq!(array.dimensions, vec![(3, 1)]);
+        assert_eq!(
+            elements,
+            vec![Some(&[7, 0, 0, 0][..]), None, Some(&[9, 0, 0, 0][..])]
+        );
+    }
+
+    #[rstest]
+    #[case(&[0x2a, 0, 0, 0], 4, "\\x2a000000")]
+    #[case(&[0x09, b'a', b'b', b'c'], -1, "\\x616263")]
+    #[case(
+        &[0x01, 0x12, 0x10, 0x27, 0, 0, 0x64, 0, 0, 0, 0x10, 0x40, 0, 0, 0x15, 0x40, 0, 0],
+        -1,
+        "TOAST value 16400 in 16405, 9996 bytes",
+    )]
+    fn formats_raw_values(#[case] value: &[u8], #[case] len: i16, #[case] expected: &str) {
+        // when
+        let formatted = format_raw(value, AttributeLayout::new(len, Alignment::Int));
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[rstest]
+    #[case(16, 1, &[1], "t")]
+    #[case(18, 1, &[0xe9], "\\351")]
+    #[case(19, 64, &name("orders"), "orders")]
+    #[case(21, 2, &[0xff, 0xff], "-1")]
+    #[case(20, 8, &9_000_000_000i64.to_le_bytes(), "9000000000")]
+    #[case(26, 4, &[0xff, 0xff, 0xff, 0xff], "4294967295")]
+    #[case(27, 6, &[0, 0, 2, 0, 7, 0], "(2,7)")]
+    #[case(700, 4, &1.5f32.to_le_bytes(), "1.5")]
+    #[case(700, 4, &1e7f32.to_le_bytes(), "1e+07")]
+    #[case(701, 8, &0.1f64.to_le_bytes(), "0.1")]
+    #[case(701, 8, &1e20f64.to_le_bytes(), "1e+20")]
+    #[case(701, 8, &1.5e-5f64.to_le_bytes(), "1.5e-05")]
+    #[case(701, 8, &f64::NEG_INFINITY.to_le_bytes(), "-Infinity")]
+    #[case(701, 8, &f64::NAN.to_le_bytes(), "NaN")]
+    #[case(25, -1, &varlena("héllo".as_bytes()), "héllo")]
+    #[case(1042, -1, &varlena(b"ab  "), "ab  ")]
+    #[case(17, -1, &varlena(&[0xde, 0xad]), "\\xdead")]
+    #[case(790, 8, &(-123_456i64).to_le_bytes(), "-$1,234.56")]
+    #[case(790, 8, &5i64.to_le_bytes(), "$0.05")]
+    #[case(1082, 4, &8780i32.to_le_bytes(), "2024-01-15")]
+    #[case(1184, 8, &758_629_800_000_000i64.to_le_bytes(), "2024-01-15 10:30:00+00")]
+    #[case(1186, 16, &[words(&[1_000_000, 0, 3, 14])].concat(), "1 year 2 mons 3 days 00:00:01")]
+    #[case(2950, 16, &(0..16).collect::<Vec<u8>>(), "00010203-0405-0607-0809-0a0b0c0d0e0f")]
+    #[case(1700, -1, &varlena(&[0x01, 0x81, 12, 0, 0x80, 0x0d, 0x78, 0x1e]), "123456.78")]
+    #[case(869, -1, &varlena(&[2, 24, 10, 0, 0, 1]), "10.0.0.1/24")]
+    #[case(829, 6, &[8, 0, 0x2b, 1, 2, 3], "08:00:2b:01:02:03")]
+    #[case(1562, -1, &varlena(&[5, 0, 0, 0, 0b1011_0000]), "10110")]
+    #[case(30, -1, &varlena(&[words(&[1, 0, 26, 2, 0]), words(&[23, 25])].concat()), "23 25")]
+    #[case(2205, 4, &16389u32.to_le_bytes(), "pg_toast.pg_toast_16385")]
+    #[case(2205, 4, &16385u32.to_le_bytes(), "orders")]
+    #[case(2205, 4, &99999u32.to_le_bytes(), "99999")]
+    #[case(2206, 4, &1009u32.to_le_bytes(), "text[]")]
+    #[case(4096, 4, &16384u32.to_le_bytes(), "alice")]
+    #[case(1009, -1, &text_array(), "{\"a b\",NULL,\"\\\"\"}")]
+    #[case(3802, -1, &varlena(&[words(&[0x2000_0001, 1, 0x4000_0000]), b"a".to_vec()].concat()), "{\"a\": null}")]
+    #[case(3614, -1, &varlena(&[1, 2]), "\\x0102 (type 3614)")]
+    #[case(23, 4, &[1, 2], "\\x0102 (int4: Expected 4 bytes, got 2)")]
+    fn formats_datums(
+        #[case] type_oid: u32,
+        #[case] len: i16,
+        #[case] value: &[u8],
+        #[case] expected: &str,
+    ) {
+        // given
+        let catalog = catalog();
+        let layout = AttributeLayout::new(len, Alignment::Int);
+
+        // when
+        let formatted = format_datum(&catalog, PgOid(type_oid), layout, value);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[rstest]
+    #[case(23, 4, &7i32.to_le_bytes(), "7")]
+    #[case(16, 1, &[1], "true")]
+    #[case(701, 8, &f64::NAN.to_le_bytes(), "\"NaN\"")]
+    #[case(25, -1, &varlena(b"a\"b"), "\"a\\\"b\"")]
+    #[case(3802, -1, &varlena(&words(&[0x4000_0002, 0x3000_0000, 0x4000_0000])), "[true, null]")]
+    #[case(23, 4, &[1, 2], "\"\\\\x0102 (int4: Expected 4 bytes, got 2)\"")]
+    fn formats_datums_as_json(
+        #[case] type_oid: u32,
+        #[case] len: i16,
+        #[case] value: &[u8],
+        #[case] expected: &str,
+    ) {
+        // given
+        let catalog = catalog();
+        let layout = AttributeLayout::new(len, Alignment::Int);
+
+        // when
+        let formatted = format_datum_json(&catalog, PgOid(type_oid), layout, value);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::common::time::civil_from_days;
+
+/// Days between the Unix epoch and the PostgreSQL epoch 2000-01-01
+const POSTGRES_EPOCH_UNIX_DAYS: i64 = 10_957;
+
+const USECS_PER_SEC: i64 = 1_000_000;
+const USECS_PER_MINUTE: i64 = 60 * USECS_PER_SEC;
+const USECS_PER_HOUR: i64 = 60 * USECS_PER_MINUTE;
+const USECS_PER_DAY: i64 = 24 * USECS_PER_HOUR;
+
+/// Formats a date, days since 2000-01-01, as date_out does with DateStyle ISO
+pub fn format_date(days: i32) -> String {
+    match days {
+        i32::MIN => "-infinity".to_string(),
+        i32::MAX => "infinity".to_string(),
+        days => {
+            let (date, bc) = format_days(days as i64);
+            format!("{date}{bc}")
+        }
+    }
+}
+
+/// Formats a time of day in microseconds as time_out does
+pub fn format_time(micros: i64) -> String {
+    let (hour, min, sec, fsec) = split_time(micros);
+    format!("{:02}:{:02}:{}", hour, min, format_seconds(sec, fsec))
+}
+
+/// Formats a time of day with the time zone offset timetz stores in seconds west of UTC
+pub fn format_timetz(micros: i64, zone: i32) -> String {
+    format!("{}{}", format_time(micros), format_zone(zone))
+}
+
+/// Formats microseconds since 2000-01-01 as timestamp_out does with DateStyle ISO, and as
+/// timestamptz_out does in the UTC time zone when `with_zone` is set
+pub fn format_timestamp(micros: i64, with_zone: bool) -> String {
+    match micros {
+        i64::MIN => "-infinity".to_string(),
+        i64::MAX => "infinity".to_string(),
+        micros => {
+            let (date, bc) = format_days(micros.div_euclid(USECS_PER_DAY));
+            let zone = if with_zone {
+                format_zone(0)
+            } else {
+                String::new()
+            };
+            format!(
+                "{} {}{}{}",
+                date,
+                format_time(micros.rem_euclid(USECS_PER_DAY)),
+                zone,
+                bc
+            )
+        }
+    }
+}
+
+/// Formats an interval as interval_out does with IntervalStyle postgres, e.g.
+/// `1 year 2 mons -3 days +04:05:06.5`
+pub fn format_interval(micros: i64, days: i32, months: i32) -> String {
+    let mut parts = Vec::new();
+    let mut is_before = false;
+    [(months / 12, "year"), (months % 12, "mon"), (days, "day")]
+        .into_iter()
+        .filter(|(value, _)| *value != 0)
+        .for_each(|(value, unit)| {
+            parts.push(format!(
+                "{}{} {}{}",
+                if is_before && value > 0 { "+" } else { "" },
+                value,
+                unit,
+                if value != 1 { "s" } else { "" }
+            ));
+            is_before = value < 0;
+        });
+    if parts.is_empty() || micros != 0 {
+        let (hour, min, sec, fsec) = split_time(micros);
+        let minus = micros < 0;
+        parts.push(format!(
+            "{}{:02}:{:02}:{}",
+            if minus {
+                "-"
+            } else if is_before {
+                "+"
+            } else {
+                ""
+            },
+            hour.abs(),
+            min.abs(),
+            format_seconds(sec.abs(), fsec.abs())
+        ));
+    }
+    parts.join(" ")
+}
+
+/// Formats days since 2000-01-01 as yyyy-mm-dd and the BC suffix for years before 1 AD
+fn format_days(days: i64) -> (String, &'static str) {
+    let (year, month, day) = civil_from_days(days + POSTGRES_EPOCH_UNIX_DAYS);
+    match year {
+        year if year > 0 => (format!("{:04}-{:02}-{:02}", year, month, day), ""),
+        year => (format!("{:04}-{:02}-{:02}", 1 - year, month, day), " BC"),
+    }
+}
+
+/// Splits microseconds into hours, minutes, seconds and microseconds, all of the same sign
+fn split_time(micros: i64) -> (i64, i64, i64, i64) {
+    (
+        micros / USECS_PER_HOUR,
+        micros % USECS_PER_HOUR / USECS_PER_MINUTE,
+        micros % USECS_PER_MINUTE / USECS_PER_SEC,
+        micros % USECS_PER_SEC,
+    )
+}
+
+/// Formats seconds with the fraction trimmed of trailing zeros, see AppendSeconds
+fn format_seconds(sec: i64, fsec: i64) -> String {
+    match fsec {
+        0 => format!("{sec:02}"),
+        fsec => format!("{sec:02}.{fsec:06}")
+            .trim_end_matches('0')
+            .to_string(),
+    }
+}
+
+/// Formats a time zone offset given in seconds west of UTC, see EncodeTimezone
+fn format_zone(zone: i32) -> String {
+    let offset = -zone;
+    let sign = if offset < 0 { '-' } else { '+' };
+    let (hour, min, sec) = (
+        offset.abs() / 3600,
+        offset.abs() % 3600 / 60,
+        offset.abs() % 60,
+    );
+    match (min, sec) {
+        (0, 0) => format!("{sign}{hour:02}"),
+        (min, 0) => format!("{sign}{hour:02}:{min:02}"),
+        (min, sec) => format!("{sign}{hour:02}:{min:02}:{sec:02}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{format_date, format_interval, format_time, format_timestamp, format_timetz};
+
+    #[rstest]
+    #[case(0, "2000-01-01")]
+    #[case(8780, "2024-01-15")]
+    #[case(-730_485, "0001-01-01 BC")]
+    #[case(i32::MAX, "infinity")]
+    fn formats_dates(#[case] days: i32, #[case] expected: &str) {
+        // when
+        let formatted = format_date(days);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[rstest]
+    #[case(0, false, "2000-01-01 00:00:00")]
+    #[case(758_629_800_500_000, false, "2024-01-15 10:30:00.5")]
+    #[case(758_629_800_000_001, true, "2024-01-15 10:30:00.000001+00")]
+    #[case(-63_113_904_000_000_000, true, "0001-01-01 00:00:00+00 BC")]
+    #[case(i64::MIN, true, "-infinity")]
+    fn formats_timestamps(#[case] micros: i64, #[case] with_zone: bool, #[case] expected: &str) {
+        // when
+        let formatted = format_timestamp(micros, with_zone);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn formats_times() {
+        // when
+        let formatted = [
+            format_time(86_399_250_000),
+            format_timetz(37_800_000_000, -19_800),
+            format_timetz(0, 3600),
+        ];
+
+        // then
+        assert_eq!(formatted, ["23:59:59.25", "10:30:00+05:30", "00:00:00-01"]);
+    }
+
+    #[rstest]
+    #[case(0, 0, 0, "00:00:00")]
+    #[case(14_706_500_000, 3, 14, "1 year 2 mons 3 days 04:05:06.5")]
+    #[case(7_200_000_000, -1, 0, "-1 days +02:00:00")]
+    #[case(-60_000_000, 0, -1, "-1 mons -00:01:00")]
+    #[case(0, 1, 12, "1 year 1 day")]
+    fn formats_intervals(
+        #[case] micros: i64,
+        #[case] days: i32,
+        #[case] months: i32,
+        #[case] expected: &str,
+    ) {
+        // when
+        let formatted = format_interval(micros, days, months);
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+}
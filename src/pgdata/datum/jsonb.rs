@@ -0,0 +1,238 @@
+use anyhow::{bail, Context, Result};
+
+use crate::common::bytes::ReadBytes;
+
+use super::{numeric::Numeric, Varlena};
+
+/// Mask of the number of elements or pairs in the header of a JsonbContainer
+const JB_CMASK: u32 = 0x0FFF_FFFF;
+/// Flag of the pseudo array holding a single scalar at the top level
+const JB_FSCALAR: u32 = 0x1000_0000;
+const JB_FOBJECT: u32 = 0x2000_0000;
+const JB_FARRAY: u32 = 0x4000_0000;
+
+/// Mask of the offset or the length of the value a JEntry points to
+const JENTRY_OFFLENMASK: u32 = 0x0FFF_FFFF;
+const JENTRY_TYPEMASK: u32 = 0x7000_0000;
+/// Flag of a JEntry holding the end offset of its value instead of the length
+const JENTRY_HAS_OFF: u32 = 0x8000_0000;
+
+const JENTRY_ISSTRING: u32 = 0x0000_0000;
+const JENTRY_ISNUMERIC: u32 = 0x1000_0000;
+const JENTRY_ISBOOL_FALSE: u32 = 0x2000_0000;
+const JENTRY_ISBOOL_TRUE: u32 = 0x3000_0000;
+const JENTRY_ISNULL: u32 = 0x4000_0000;
+const JENTRY_ISCONTAINER: u32 = 0x5000_0000;
+
+/// Size of a JEntry and of the header of a JsonbContainer
+const JENTRY_SIZE: usize = 4;
+
+/// Decoded jsonb value. Object pairs keep the order they are stored in, keys sorted by length
+/// first and bytes second, which is also the order jsonb_out prints them in
+#[derive(Debug, PartialEq)]
+pub enum Jsonb {
+    Null,
+    Bool(bool),
+    Number(Numeric),
+    String(String),
+    Array(Vec<Jsonb>),
+    Object(Vec<(String, Jsonb)>),
+}
+
+impl Jsonb {
+    /// Parses the payload of a jsonb varlena value, the root JsonbContainer. A scalar at the
+    /// top level is stored as a single element array flagged as a scalar
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        match parse_container(payload)? {
+            (Jsonb::Array(mut elements), true) if elements.len() == 1 => Ok(elements.remove(0)),
+            (_, true) => bail!("Scalar jsonb container does not hold a single element"),
+            (jsonb, false) => Ok(jsonb),
+        }
+    }
+}
+
+/// Parses a JsonbContainer: the header with the flags and the count, the JEntries of the
+/// elements or of the keys followed by the values, then their data. Also tells whether the
+/// container is the pseudo array of a scalar
+fn parse_container(bytes: &[u8]) -> Result<(Jsonb, bool)> {
+    let header = bytes.u32_at(0)?;
+    let count = (header & JB_CMASK) as usize;
+    let is_object = match header & (JB_FOBJECT | JB_FARRAY) {
+        JB_FOBJECT => true,
+        JB_FARRAY => false,
+        _ => bail!("Unknown jsonb container flags {:#010x}", header),
+    };
+    let entries = if is_object { 2 * count } else { count };
+    let data_offset = JENTRY_SIZE * (1 + entries);
+    let mut end = 0;
+    let mut values = (0..entries)
+        .map(|index| {
+            let entry = bytes.u32_at(JENTRY_SIZE * (1 + index))?;
+            let start = end;
+            end = match entry & JENTRY_HAS_OFF {
+                0 => start + (entry & JENTRY_OFFLENMASK) as usize,
+                _ => (entry & JENTRY_OFFLENMASK) as usize,
+            };
+            if end < start {
+                bail!(
+                    "Jsonb entry {} ends at {} before it starts at {}",
+                    index,
+                    end,
+                    start
+                );
+            }
+            parse_entry(entry, bytes, data_offset, start, end)
+                .with_context(|| format!("Decoding jsonb entry {index}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let jsonb = match is_object {
+        true => {
+            let pairs = values.split_off(count);
+            Jsonb::Object(
+                values
+                    .into_iter()
+                    .zip(pairs)
+                    .map(|(key, value)| match key {
+                        Jsonb::String(key) => Ok((key, value)),
+                        key => bail!("Expected string jsonb key, got {:?}", key),
+                    })
+                    .collect::<Result<_>>()?,
+            )
+        }
+        false => Jsonb::Array(values),
+    };
+    Ok((jsonb, header & JB_FSCALAR != 0))
+}
+
+/// Decodes the value of a JEntry spanning `start..end` of the data of the container. Numeric
+/// values and nested containers are aligned to 4 bytes, the padding counts in their span
+fn parse_entry(
+    entry: u32,
+    bytes: &[u8],
+    data_offset: usize,
+    start: usize,
+    end: usize,
+) -> Result<Jsonb> {
+    let data = |aligned: bool| {
+        let start = match aligned {
+            true => start.next_multiple_of(4).min(end),
+            false => start,
+        };
+        bytes.bytes_at(data_offset + start, end - start)
+    };
+    Ok(match entry & JENTRY_TYPEMASK {
+        JENTRY_ISSTRING => Jsonb::String(String::from_utf8_lossy(data(false)?).into_owned()),
+        JENTRY_ISNUMERIC => {
+            Jsonb::Number(Numeric::parse(&Varlena::parse(data(true)?)?.payload()?)?)
+        }
+        JENTRY_ISBOOL_FALSE => Jsonb::Bool(false),
+        JENTRY_ISBOOL_TRUE => Jsonb::Bool(true),
+        JENTRY_ISNULL => Jsonb::Null,
+        JENTRY_ISCONTAINER => parse_container(data(true)?)?.0,
+        entry_type => bail!("Unknown jsonb entry type {:#010x}", entry_type),
+    })
+}
+
+impl std::fmt::Display for Jsonb {
+    /// Renders the value as jsonb_out does, e.g. `{"a": [1, true], "b": null}`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Jsonb::Null => f.write_str("null"),
+            Jsonb::Bool(value) => write!(f, "{value}"),
+            Jsonb::Number(number) => write!(f, "{number}"),
+            Jsonb::String(value) => f.write_str(&quote_json(value)),
+            Jsonb::Array(elements) => {
+                f.write_str("[")?;
+                elements
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(index, element)| match index {
+                        0 => write!(f, "{element}"),
+                        _ => write!(f, ", {element}"),
+                    })?;
+                f.write_str("]")
+            }
+            Jsonb::Object(pairs) => {
+                f.write_str("{")?;
+                pairs
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(index, (key, value))| match index {
+                        0 => write!(f, "{}: {}", quote_json(key), value),
+                        _ => write!(f, ", {}: {}", quote_json(key), value),
+                    })?;
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Quotes a string as a JSON string literal the way escape_json does
+pub fn quote_json(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    value.chars().for_each(|char| match char {
+        '"' => quoted.push_str("\\\""),
+        '\\' => quoted.push_str("\\\\"),
+        '\u{8}' => quoted.push_str("\\b"),
+        '\u{c}' => quoted.push_str("\\f"),
+        '\n' => quoted.push_str("\\n"),
+        '\r' => quoted.push_str("\\r"),
+        '\t' => quoted.push_str("\\t"),
+        char if char < ' ' => quoted.push_str(&format!("\\u{:04x}", char as u32)),
+        char => quoted.push(char),
+    });
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{quote_json, Jsonb};
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    #[rstest]
+    // scalar string "ab"
+    #[case(&[words(&[0x5000_0001, 2]), b"ab".to_vec()].concat(), "\"ab\"")]
+    // scalar number 12.5, a short numeric of weight 0 and dscale 1 with a 4-byte header
+    #[case(&[words(&[0x5000_0001, 0x1000_000a, 40]), vec![0x80, 0x80, 12, 0, 0x88, 0x13]].concat(), "12.5")]
+    // [true, null, "x\ny"]
+    #[case(&[words(&[0x4000_0003, 0x3000_0000, 0x4000_0000, 3]), b"x\ny".to_vec()].concat(), "[true, null, \"x\\ny\"]")]
+    // {"a": false, "bb": []} with the end offset of "bb" stored instead of its length
+    #[case(&[words(&[0x2000_0002, 1, 0x8000_0003, 0x2000_0000, 0x5000_0005]), b"abb".to_vec(), vec![0], words(&[0x4000_0000])].concat(), "{\"a\": false, \"bb\": []}")]
+    #[case(&words(&[0x2000_0000]), "{}")]
+    fn parses_jsonb(#[case] payload: &[u8], #[case] expected: &str) {
+        // when
+        let jsonb = Jsonb::parse(payload).map(|jsonb| jsonb.to_string());
+
+        // then
+        assert_eq!(jsonb.unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_unknown_entry_types() {
+        // when
+        let jsonb = Jsonb::parse(&words(&[0x4000_0001, 0x6000_0000]));
+
+        // then
+        assert_eq!(
+            format!("{:#}", jsonb.unwrap_err()),
+            "Decoding jsonb entry 0: Unknown jsonb entry type 0x60000000"
+        );
+    }
+
+    #[test]
+    fn quotes_json_strings() {
+        // when
+        let quoted = quote_json("a\"b\\c\u{1}é");
+
+        // then
+        assert_eq!(quoted, "\"a\\\"b\\\\c\\u0001é\"");
+    }
+}
@@ -0,0 +1,95 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use anyhow::{bail, Result};
+
+use crate::common::bytes::ReadBytes;
+
+/// Address family of inet values, PGSQL_AF_INET
+const PGSQL_AF_INET: u8 = 2;
+
+/// Address family of inet values, PGSQL_AF_INET6
+const PGSQL_AF_INET6: u8 = 3;
+
+/// Formats the payload of an inet or cidr value, the family and the netmask bits followed by
+/// the address bytes, as inet_out and cidr_out do. Inet values omit the netmask of a
+/// single host
+pub fn format_inet(payload: &[u8], is_cidr: bool) -> Result<String> {
+    let family = payload.u8_at(0)?;
+    let bits = payload.u8_at(1)?;
+    let (address, max_bits) = match family {
+        PGSQL_AF_INET => {
+            let octets: [u8; 4] = payload.array_at(2)?;
+            (Ipv4Addr::from(octets).to_string(), 32)
+        }
+        PGSQL_AF_INET6 => {
+            let octets: [u8; 16] = payload.array_at(2)?;
+            (format_ipv6(Ipv6Addr::from(octets)), 128)
+        }
+        family => bail!("Unknown inet address family {}", family),
+    };
+    match is_cidr || bits != max_bits {
+        true => Ok(format!("{address}/{bits}")),
+        false => Ok(address),
+    }
+}
+
+/// Compresses the longest run of zero groups like Ipv6Addr does, also writing IPv4-compatible
+/// addresses with a dotted quad like inet_net_ntop does
+fn format_ipv6(address: Ipv6Addr) -> String {
+    match address.segments() {
+        [0, 0, 0, 0, 0, 0, high, low] if high != 0 => {
+            let [a, b] = high.to_be_bytes();
+            let [c, d] = low.to_be_bytes();
+            format!("::{}", Ipv4Addr::new(a, b, c, d))
+        }
+        _ => address.to_string(),
+    }
+}
+
+/// Formats the bytes of a macaddr or macaddr8 value as colon separated hex pairs
+pub fn format_macaddr(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{format_inet, format_macaddr};
+
+    fn inet(family: u8, bits: u8, address: &[u8]) -> Vec<u8> {
+        let mut payload = vec![family, bits];
+        payload.extend_from_slice(address);
+        payload
+    }
+
+    #[rstest]
+    #[case(inet(2, 32, &[192, 168, 0, 1]), false, "192.168.0.1")]
+    #[case(inet(2, 24, &[192, 168, 0, 1]), false, "192.168.0.1/24")]
+    #[case(inet(2, 16, &[10, 1, 0, 0]), true, "10.1.0.0/16")]
+    #[case(inet(3, 128, &[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]), false, "2001:db8::1")]
+    #[case(inet(3, 64, &[0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), true, "fe80::/64")]
+    #[case(inet(3, 128, &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4]), false, "::1.2.3.4")]
+    #[case(inet(3, 128, &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 1, 2, 3, 4]), false, "::ffff:1.2.3.4")]
+    fn formats_inet(#[case] payload: Vec<u8>, #[case] is_cidr: bool, #[case] expected: &str) {
+        // when
+        let formatted = format_inet(&payload, is_cidr).unwrap();
+
+        // then
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn formats_macaddr() {
+        // when
+        let formatted = format_macaddr(&[0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]);
+
+        // then
+        assert_eq!(formatted, "08:00:2b:01:02:03");
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{bail, Result};
+
+use crate::common::bytes::ReadBytes;
+
+const NUMERIC_SIGN_MASK: u16 = 0xC000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_SHORT: u16 = 0x8000;
+const NUMERIC_SPECIAL: u16 = 0xC000;
+
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+const NUMERIC_DSCALE_MASK: u16 = 0x3FFF;
+
+const NUMERIC_SHORT_SIGN_MASK: u16 = 0x2000;
+const NUMERIC_SHORT_DSCALE_MASK: u16 = 0x1F80;
+const NUMERIC_SHORT_DSCALE_SHIFT: u16 = 7;
+const NUMERIC_SHORT_WEIGHT_SIGN_MASK: u16 = 0x0040;
+const NUMERIC_SHORT_WEIGHT_MASK: u16 = 0x003F;
+
+/// Decimal digits a base-10000 digit holds
+const DEC_DIGITS: usize = 4;
+
+/// Decoded NumericData: digits in base 10000, the weight of the first one, the number of
+/// decimal digits after the point to display and the sign
+#[derive(Debug, PartialEq)]
+pub enum Numeric {
+    NaN,
+    Infinity,
+    NegativeInfinity,
+    Finite {
+        negative: bool,
+        weight: i16,
+        dscale: u16,
+        digits: Vec<i16>,
+    },
+}
+
+impl Numeric {
+    /// Parses the payload of a numeric varlena value, either the 2-byte header of the short
+    /// format or the 4-byte one of the long format followed by the digits, see numeric.c
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let header = payload.u16_at(0)?;
+        let (negative, weight, dscale, digits_offset) = match header & NUMERIC_SIGN_MASK {
+            NUMERIC_SPECIAL => {
+                return Ok(match header {
+                    NUMERIC_NAN => Numeric::NaN,
+                    NUMERIC_PINF => Numeric::Infinity,
+                    NUMERIC_NINF => Numeric::NegativeInfinity,
+                    header => bail!("Unknown special numeric value {:#06x}", header),
+                })
+            }
+            NUMERIC_SHORT => {
+                let weight = header & NUMERIC_SHORT_WEIGHT_MASK;
+                let weight = match header & NUMERIC_SHORT_WEIGHT_SIGN_MASK {
+                    0 => weight as i16,
+                    _ => (weight | !NUMERIC_SHORT_WEIGHT_MASK) as i16,
+                };
+                (
+                    header & NUMERIC_SHORT_SIGN_MASK != 0,
+                    weight,
+                    (header & NUMERIC_SHORT_DSCALE_MASK) >> NUMERIC_SHORT_DSCALE_SHIFT,
+                    2,
+                )
+            }
+            sign => (
+                sign == NUMERIC_NEG,
+                payload.i16_at(2)?,
+                header & NUMERIC_DSCALE_MASK,
+                4,
+            ),
+        };
+        if !(payload.len() - digits_offset).is_multiple_of(2) {
+            bail!("Numeric digits take an odd number of bytes");
+        }
+        let digits = (digits_offset..payload.len())
+            .step_by(2)
+            .map(|offset| payload.i16_at(offset))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Numeric::Finite {
+            negative,
+            weight,
+            dscale,
+            digits,
+        })
+    }
+}
+
+impl std::fmt::Display for Numeric {
+    /// Renders the value the way numeric_out does, with dscale digits after the point
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (negative, weight, dscale, digits) = match self {
+            Numeric::NaN => return f.write_str("NaN"),
+            Numeric::Infinity => return f.write_str("Infinity"),
+            Numeric::NegativeInfinity => return f.write_str("-Infinity"),
+            Numeric::Finite {
+                negative,
+                weight,
+                dscale,
+                digits,
+            } => (*negative, *weight as isize, *dscale as usize, digits),
+        };
+        let digit = |index: isize| match usize::try_from(index) {
+            Ok(index) => digits.get(index).copied().unwrap_or(0),
+            Err(_) => 0,
+        };
+        if negative {
+            f.write_str("-")?;
+        }
+        match weight {
+            weight if weight < 0 => f.write_str("0")?,
+            weight => {
+                write!(f, "{}", digit(0))?;
+                (1..=weight).try_for_each(|index| write!(f, "{:04}", digit(index)))?;
+            }
+        }
+        if dscale > 0 {
+            let fraction = (0..dscale.div_ceil(DEC_DIGITS) as isize)
+                .map(|group| format!("{:04}", digit(weight + 1 + group)))
+                .collect::<String>();
+            write!(f, ".{}", &fraction[..dscale])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::Numeric;
+
+    fn long(sign_dscale: u16, weight: i16, digits: &[i16]) -> Vec<u8> {
+        let mut payload = sign_dscale.to_le_bytes().to_vec();
+        payload.extend_from_slice(&weight.to_le_bytes());
+        digits
+            .iter()
+            .for_each(|digit| payload.extend_from_slice(&digit.to_le_bytes()));
+        payload
+    }
+
+    fn short(header: u16, digits: &[i16]) -> Vec<u8> {
+        let mut payload = header.to_le_bytes().to_vec();
+        digits
+            .iter()
+            .for_each(|digit| payload.extend_from_slice(&digit.to_le_bytes()));
+        payload
+    }
+
+    #[rstest]
+    #[case(short(0x8000, &[]), "0")]
+    #[case(short(0x8000 | 2 << 7, &[]), "0.00")]
+    #[case(short(0x8000 | 2 << 7 | 1, &[12, 3456, 7800]), "123456.78")]
+    #[case(short(0x8000 | 0x2000 | 3 << 7 | 0x007F, &[10]), "-0.001")]
+    #[case(short(0x8000 | 6 << 7 | 0x007E, &[5]), "0.000000")]
+    #[case(long(0x4000 | 1, 2, &[1, 0, 0, 5000]), "-100000000.5")]
+    #[case(long(0, 3, &[9]), "9000000000000")]
+    #[case(short(0xC000, &[]), "NaN")]
+    #[case(short(0xD000, &[]), "Infinity")]
+    #[case(short(0xF000, &[]), "-Infinity")]
+    fn formats_numeric(#[case] payload: Vec<u8>, #[case] expected: &str) {
+        // when
+        let numeric = Numeric::parse(&payload).unwrap();
+
+        // then
+        assert_eq!(numeric.to_string(), expected);
+    }
+}
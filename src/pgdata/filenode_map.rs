@@ -0,0 +1,297 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::common::{bytes::ReadBytes, crc32c::crc32c, PgOid};
+
+/// Name of the relation mapper file stored in global and in each database directory
+pub const FILENODE_MAP_FILE_NAME: &str = "pg_filenode.map";
+
+/// Identifies the relation mapper file format
+pub const RELMAPPER_FILEMAGIC: u32 = 0x592717;
+
+/// Size of the file up to PG15 which holds 62 mapping slots followed by the CRC and a padding
+pub const FILE_SIZE_PG15: usize = 512;
+
+/// Size of the file since PG16 which holds 64 mapping slots followed by the CRC
+pub const FILE_SIZE_PG16: usize = 524;
+
+/// Returns the number of mapping slots of a file, the layout changed in PG16 without a change
+/// of the magic number so the file size tells them apart
+pub fn max_mappings(file_size: usize) -> Result<usize> {
+    match file_size {
+        FILE_SIZE_PG15 => Ok(62),
+        FILE_SIZE_PG16 => Ok(64),
+        _ => bail!(
+            "Invalid file size {file_size}, expected {FILE_SIZE_PG15} or {FILE_SIZE_PG16} bytes"
+        ),
+    }
+}
+
+/// Returns the offset of the CRC which covers all the preceding bytes
+fn crc_offset(max_mappings: usize) -> usize {
+    8 + max_mappings * 8
+}
+
+/// Maps the OIDs of mapped catalogs, whose pg_class.relfilenode is 0, to their relfilenodes
+#[derive(Debug, PartialEq)]
+pub struct FileNodeMap {
+    pub mappings: Vec<FileNodeMapping>,
+    /// CRC stored in the file
+    pub crc: u32,
+    /// CRC computed over the file contents
+    pub computed_crc: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FileNodeMapping {
+    pub oid: PgOid,
+    pub relfilenode: PgOid,
+}
+
+impl FileNodeMap {
+    pub fn read(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("Reading {:?}", path))?;
+        FileNodeMap::parse(&bytes).with_context(|| format!("Decoding {:?}", path))
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let magic = bytes.u32_at(0)?;
+        if magic != RELMAPPER_FILEMAGIC {
+            bail!(
+                "Invalid magic number {:#x}, expected {:#x}",
+                magic,
+                RELMAPPER_FILEMAGIC
+            );
+        }
+        let max_mappings = max_mappings(bytes.len())?;
+        let num_mappings = bytes.i32_at(4)?;
+        if num_mappings < 0 || num_mappings as usize > max_mappings {
+            bail!(
+                "Invalid number of mappings {}, expected at most {}",
+                num_mappings,
+                max_mappings
+            );
+        }
+        let mappings = (0..num_mappings as usize)
+            .map(|i| {
+                Ok(FileNodeMapping {
+                    oid: PgOid(bytes.u32_at(8 + i * 8)?),
+                    relfilenode: PgOid(bytes.u32_at(12 + i * 8)?),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let crc_offset = crc_offset(max_mappings);
+        Ok(FileNodeMap {
+            mappings,
+            crc: bytes.u32_at(crc_offset)?,
+            computed_crc: crc32c(bytes.bytes_at(0, crc_offset)?),
+        })
+    }
+
+    pub fn is_crc_valid(&self) -> bool {
+        self.crc == self.computed_crc
+    }
+
+    /// Looks up the relfilenode of the mapped catalog with the given OID
+    pub fn relfilenode(&self, oid: PgOid) -> Option<PgOid> {
+        self.mappings
+            .iter()
+            .find(|mapping| mapping.oid == oid)
+            .map(|mapping| mapping.relfilenode)
+    }
+}
+
+/// Returns the name of a system catalog, its TOAST table or index which may be mapped
+#[rustfmt::skip]
+pub fn mapped_catalog_name(PgOid(oid): PgOid) -> Option<&'static str> {
+    Some(match oid {
+        // shared catalogs stored in global
+        1213 => "pg_tablespace",
+        1214 => "pg_shdepend",
+        1232 => "pg_shdepend_depender_index",
+        1233 => "pg_shdepend_reference_index",
+        1260 => "pg_authid",
+        1261 => "pg_auth_members",
+        1262 => "pg_database",
+        2396 => "pg_shdescription",
+        2397 => "pg_shdescription_o_c_index",
+        2671 => "pg_database_datname_index",
+        2672 => "pg_database_oid_index",
+        2676 => "pg_authid_rolname_index",
+        2677 => "pg_authid_oid_index",
+        2694 => "pg_auth_members_role_member_index",
+        2695 => "pg_auth_members_member_role_index",
+        2697 => "pg_tablespace_oid_index",
+        2698 => "pg_tablespace_spcname_index",
+        2846 => "pg_toast_2396",
+        2847 => "pg_toast_2396_index",
+        2964 => "pg_db_role_setting",
+        2965 => "pg_db_role_setting_databaseid_rol_index",
+        2966 => "pg_toast_2964",
+        2967 => "pg_toast_2964_index",
+        3592 => "pg_shseclabel",
+        3593 => "pg_shseclabel_object_index",
+        4060 => "pg_toast_3592",
+        4061 => "pg_toast_3592_index",
+        4175 => "pg_toast_1260",
+        4176 => "pg_toast_1260_index",
+        4177 => "pg_toast_1262",
+        4178 => "pg_toast_1262_index",
+        4181 => "pg_toast_6000",
+        4182 => "pg_toast_6000_index",
+        4183 => "pg_toast_6100",
+        4184 => "pg_toast_6100_index",
+        4185 => "pg_toast_1213",
+        4186 => "pg_toast_1213_index",
+        6000 => "pg_replication_origin",
+        6001 => "pg_replication_origin_roiident_index",
+        6002 => "pg_replication_origin_roname_index",
+        6100 => "pg_subscription",
+        6114 => "pg_subscription_oid_index",
+        6115 => "pg_subscription_subname_index",
+        6243 => "pg_parameter_acl",
+        6244 => "pg_toast_6243",
+        6245 => "pg_toast_6243_index",
+        6246 => "pg_parameter_acl_parname_index",
+        6247 => "pg_parameter_acl_oid_index",
+        // catalogs stored in each database directory
+        1247 => "pg_type",
+        1249 => "pg_attribute",
+        1255 => "pg_proc",
+        1259 => "pg_class",
+        2658 => "pg_attribute_relid_attnam_index",
+        2659 => "pg_attribute_relid_attnum_index",
+        2662 => "pg_class_oid_index",
+        2663 => "pg_class_relname_nsp_index",
+        2690 => "pg_proc_oid_index",
+        2691 => "pg_proc_proname_args_nsp_index",
+        2703 => "pg_type_oid_index",
+        2704 => "pg_type_typname_nsp_index",
+        2836 => "pg_toast_1255",
+        2837 => "pg_toast_1255_index",
+        3455 => "pg_class_tblspc_relfilenode_index",
+        4171 => "pg_toast_1247",
+        4172 => "pg_toast_1247_index",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+pub mod test_files {
+    use crate::common::crc32c::crc32c;
+
+    use super::{crc_offset, max_mappings, FILE_SIZE_PG15, RELMAPPER_FILEMAGIC};
+
+    /// Lays out a relation mapper file of PG15 with the given mappings and a valid CRC
+    pub fn filenode_map(mappings: &[(u32, u32)]) -> Vec<u8> {
+        sized_filenode_map(FILE_SIZE_PG15, mappings)
+    }
+
+    /// Lays out a relation mapper file of the given size with the mappings and a valid CRC
+    pub fn sized_filenode_map(file_size: usize, mappings: &[(u32, u32)]) -> Vec<u8> {
+        let max_mappings = max_mappings(file_size).expect("relation mapper file size");
+        let mut bytes = vec![0; file_size];
+        bytes[0..4].copy_from_slice(&RELMAPPER_FILEMAGIC.to_le_bytes());
+        bytes[4..8].copy_from_slice(&(mappings.len() as i32).to_le_bytes());
+        mappings
+            .iter()
+            .take(max_mappings)
+            .enumerate()
+            .for_each(|(i, (oid, relfilenode))| {
+                bytes[8 + i * 8..12 + i * 8].copy_from_slice(&oid.to_le_bytes());
+                bytes[12 + i * 8..16 + i * 8].copy_from_slice(&relfilenode.to_le_bytes());
+            });
+        let crc_offset = crc_offset(max_mappings);
+        let crc = crc32c(&bytes[..crc_offset]);
+        bytes[crc_offset..crc_offset + 4].copy_from_slice(&crc.to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::common::PgOid;
+
+    use super::{
+        test_files::{filenode_map, sized_filenode_map},
+        FileNodeMap, FILE_SIZE_PG16,
+    };
+
+    #[test]
+    fn decodes_filenode_map() {
+        // given
+        let bytes = filenode_map(&[(1262, 1262), (1260, 16390)]);
+
+        // when
+        let map = FileNodeMap::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(map.mappings.len(), 2);
+        assert_eq!(map.relfilenode(PgOid(1260)), Some(PgOid(16390)));
+        assert_eq!(map.relfilenode(PgOid(1259)), None);
+        assert!(map.is_crc_valid());
+    }
+
+    #[test]
+    fn decodes_pg16_filenode_map() {
+        // given
+        let mappings = (0..64).map(|i| (1000 + i, 2000 + i)).collect::<Vec<_>>();
+        let bytes = sized_filenode_map(FILE_SIZE_PG16, &mappings);
+
+        // when
+        let map = FileNodeMap::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(map.mappings.len(), 64);
+        assert_eq!(map.relfilenode(PgOid(1063)), Some(PgOid(2063)));
+        assert!(map.is_crc_valid());
+    }
+
+    #[test]
+    fn rejects_unknown_file_size() {
+        // given
+        let mut bytes = filenode_map(&[]);
+        bytes.truncate(500);
+
+        // when
+        let result = FileNodeMap::parse(&bytes);
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some("Invalid file size 500, expected 512 or 524 bytes".to_string())
+        );
+    }
+
+    #[test]
+    fn detects_crc_mismatch() {
+        // given
+        let mut bytes = filenode_map(&[(1262, 1262)]);
+        bytes[12] = 0x11;
+
+        // when
+        let map = FileNodeMap::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(map.relfilenode(PgOid(1262)), Some(PgOid(0x0411)));
+        assert!(!map.is_crc_valid());
+    }
+
+    #[test]
+    fn rejects_invalid_magic() {
+        // given
+        let bytes = vec![0; 512];
+
+        // when
+        let result = FileNodeMap::parse(&bytes);
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some("Invalid magic number 0x0, expected 0x592717".to_string())
+        );
+    }
+}
@@ -1,8 +1,354 @@
-use crate::common::fs::DirEntry;
+use std::path::Path;
 
-#[allow(dead_code)]
-pub trait Global {}
+use anyhow::{bail, Result};
+
+use crate::common::{bytes::ReadBytes, fs::DirEntry, PgOid};
+
+use self::pg_control::ControlFile;
+
+use super::{filenode_map::FileNodeMap, page::heap::HeapTuple};
+
+pub mod pg_control;
+
+/// OID of the shared catalog pg_database
+pub const PG_DATABASE_OID: PgOid = PgOid(1262);
+
+/// OID of the shared catalog pg_authid
+pub const PG_AUTHID_OID: PgOid = PgOid(1260);
+
+/// Length of the `name` type including the terminating zero byte
+pub const NAMEDATALEN: usize = 64;
+
+/// Represents PG_DATA/global directory
+pub trait Global {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Reads the relation mapper file of the shared catalogs
+    fn filenode_map(&self) -> Result<FileNodeMap>;
+
+    /// Reads the cluster control file pg_control
+    fn control_file(&self) -> Result<ControlFile>;
+
+    /// Reads the databases of the cluster from the shared catalog pg_database
+    fn databases(&self) -> Result<Vec<Database>>;
+
+    /// Reads the roles of the cluster from the shared catalog pg_authid
+    fn roles(&self) -> Result<Vec<Role>>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("global")
 }
+
+/// Instantiates a default implementation of [Global]
+pub fn global(pgdata_path: &Path) -> impl Global {
+    default_impl::Global::new(pgdata_path)
+}
+
+/// A row of pg_database reduced to the columns identifying the database
+#[derive(Debug, PartialEq, Clone)]
+pub struct Database {
+    pub oid: PgOid,
+    pub name: String,
+}
+
+impl Database {
+    /// Decodes the leading `oid` and `datname` columns of a pg_database tuple,
+    /// before PostgreSQL 12 the oid is a system column stored in the tuple header
+    pub fn from_tuple(tuple: &HeapTuple) -> Result<Self> {
+        // 0-based attribute number and offset of datname
+        let (oid, datname_attnum, datname_offset) = match tuple.header.oid {
+            Some(oid) => (oid, 0, 0),
+            None => (tuple.data.u32_at(0)?, 1, 4),
+        };
+        if tuple.header.is_null(datname_attnum) {
+            bail!("pg_database tuple of database {} has no datname", oid);
+        }
+        Ok(Database {
+            oid: PgOid(oid),
+            name: read_name(tuple.data.bytes_at(datname_offset, NAMEDATALEN)?),
+        })
+    }
+}
+
+/// A row of pg_authid reduced to the columns identifying the role
+#[derive(Debug, PartialEq, Clone)]
+pub struct Role {
+    pub oid: PgOid,
+    pub name: String,
+}
+
+impl Role {
+    /// Decodes the leading `oid` and `rolname` columns of a pg_authid tuple,
+    /// before PostgreSQL 12 the oid is a system column stored in the tuple header
+    pub fn from_tuple(tuple: &HeapTuple) -> Result<Self> {
+        let (oid, rolname_attnum, rolname_offset) = match tuple.header.oid {
+            Some(oid) => (oid, 0, 0),
+            None => (tuple.data.u32_at(0)?, 1, 4),
+        };
+        if tuple.header.is_null(rolname_attnum) {
+            bail!("pg_authid tuple of role {} has no rolname", oid);
+        }
+        Ok(Role {
+            oid: PgOid(oid),
+            name: read_name(tuple.data.bytes_at(rolname_offset, NAMEDATALEN)?),
+        })
+    }
+}
+
+/// Decodes a zero-terminated value of the `name` type
+pub fn read_name(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+mod default_impl {
+    use std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+    };
+
+    use anyhow::Context;
+
+    use crate::{
+        common::PgOid,
+        pgdata::{
+            filenode_map::{FileNodeMap, FILENODE_MAP_FILE_NAME},
+            page::{heap::HeapTuple, read_fork},
+            transactions,
+        },
+    };
+
+    use super::{
+        pg_control::{ControlFile, PG_CONTROL_FILE_NAME},
+        Database, Role, PG_AUTHID_OID, PG_DATABASE_OID,
+    };
+
+    pub struct Global {
+        path: PathBuf,
+    }
+
+    impl Global {
+        pub fn new(pgdata_path: &Path) -> Self {
+            Global {
+                path: pgdata_path.join("global"),
+            }
+        }
+    }
+
+    impl super::Global for Global {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn filenode_map(&self) -> anyhow::Result<FileNodeMap> {
+            FileNodeMap::read(&self.path.join(FILENODE_MAP_FILE_NAME))
+        }
+
+        fn control_file(&self) -> anyhow::Result<ControlFile> {
+            ControlFile::read(&self.path.join(PG_CONTROL_FILE_NAME))
+        }
+
+        fn databases(&self) -> anyhow::Result<Vec<Database>> {
+            self.read_shared_catalog(PG_DATABASE_OID, "pg_database", |tuple| {
+                Database::from_tuple(tuple).map(|database| (database.oid, database))
+            })
+        }
+
+        fn roles(&self) -> anyhow::Result<Vec<Role>> {
+            self.read_shared_catalog(PG_AUTHID_OID, "pg_authid", |tuple| {
+                Role::from_tuple(tuple).map(|role| (role.oid, role))
+            })
+        }
+    }
+
+    impl Global {
+        /// Decodes the live tuples of a mapped shared catalog ordered by OID
+        fn read_shared_catalog<T>(
+            &self,
+            oid: PgOid,
+            name: &str,
+            decode: impl Fn(&HeapTuple) -> anyhow::Result<(PgOid, T)>,
+        ) -> anyhow::Result<Vec<T>> {
+            let relfilenode = super::Global::filenode_map(self)?
+                .relfilenode(oid)
+                .with_context(|| format!("{name} is missing in the global relation mapper file"))?;
+            let pages = read_fork(&self.path.join(relfilenode.0.to_string()))
+                .with_context(|| format!("Reading {name}"))?;
+
+            // a row has a single live version unless the page is corrupted, then the one found
+            // last in physical order is kept
+            let mut rows = BTreeMap::new();
+            let transactions = transactions::transactions(self.path.parent().expect("pgdata path"));
+            for (block, page) in pages.iter().enumerate() {
+                for tuple in page.live_tuples(block as u32, &transactions)? {
+                    let (_, tuple) = tuple?;
+                    let (oid, row) = decode(&tuple)?;
+                    rows.insert(oid, row);
+                }
+            }
+            Ok(rows.into_values().collect())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::PathBuf;
+
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        use crate::{
+            common::{test_utils::fixture::*, PgOid},
+            pgdata::global::{Database, Global},
+        };
+
+        #[rstest]
+        fn reads_databases(pgdata: PathBuf) {
+            // given
+            let global = super::Global::new(&pgdata);
+
+            // when
+            let databases = global.databases().unwrap();
+
+            // then
+            assert_eq!(
+                databases,
+                vec![
+                    Database {
+                        oid: PgOid(1),
+                        name: "template1".to_string()
+                    },
+                    Database {
+                        oid: PgOid(4),
+                        name: "template0".to_string()
+                    },
+                    Database {
+                        oid: PgOid(5),
+                        name: "postgres".to_string()
+                    },
+                ]
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::Path;
+
+    use anyhow::bail;
+
+    use crate::pgdata::filenode_map::FileNodeMap;
+
+    use super::{
+        pg_control::{test_files::pg_control, ControlFile},
+        Database, Global, Role,
+    };
+
+    pub struct StubGlobal;
+    impl Global for StubGlobal {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/global")
+        }
+
+        fn filenode_map(&self) -> anyhow::Result<FileNodeMap> {
+            bail!("pg_filenode.map is not stubbed")
+        }
+
+        fn control_file(&self) -> anyhow::Result<ControlFile> {
+            ControlFile::parse(&pg_control())
+        }
+
+        fn databases(&self) -> anyhow::Result<Vec<Database>> {
+            Ok(Vec::new())
+        }
+
+        fn roles(&self) -> anyhow::Result<Vec<Role>> {
+            Ok(Vec::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        common::PgOid,
+        pgdata::page::{heap::HeapTuple, test_pages::heap_tuple},
+    };
+
+    use super::{Database, Role};
+
+    fn datname(name: &str) -> Vec<u8> {
+        let mut bytes = name.as_bytes().to_vec();
+        bytes.resize(64, 0);
+        bytes
+    }
+
+    #[test]
+    fn decodes_database_from_tuple() {
+        // given
+        let mut data = 16384u32.to_le_bytes().to_vec();
+        data.extend(datname("shop"));
+        data.extend([10, 0, 0, 0]);
+        let bytes = heap_tuple(740, 0, 0x0800, 17, &data);
+
+        // when
+        let database = Database::from_tuple(&HeapTuple::parse(&bytes).unwrap()).unwrap();
+
+        // then
+        assert_eq!(
+            database,
+            Database {
+                oid: PgOid(16384),
+                name: "shop".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_database_with_oid_in_header() {
+        // given
+        let mut bytes = heap_tuple(740, 0, 0x0008 | 0x0800, 13, &[]);
+        bytes.extend([0; 4]);
+        bytes[22] = 28;
+        bytes[24..28].copy_from_slice(&13000u32.to_le_bytes());
+        bytes.extend(datname("legacy"));
+
+        // when
+        let database = Database::from_tuple(&HeapTuple::parse(&bytes).unwrap()).unwrap();
+
+        // then
+        assert_eq!(
+            database,
+            Database {
+                oid: PgOid(13000),
+                name: "legacy".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_role_from_tuple() {
+        // given
+        let mut data = 10u32.to_le_bytes().to_vec();
+        data.extend(datname("postgres"));
+        data.extend([1, 1, 1, 1]);
+        let bytes = heap_tuple(1, 0, 0x0800, 12, &data);
+
+        // when
+        let role = Role::from_tuple(&HeapTuple::parse(&bytes).unwrap()).unwrap();
+
+        // then
+        assert_eq!(
+            role,
+            Role {
+                oid: PgOid(10),
+                name: "postgres".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,388 @@
+use std::{fmt::Display, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::common::{bytes::ReadBytes, crc32c::crc32c, Lsn};
+
+pub const PG_CONTROL_FILE_NAME: &str = "pg_control";
+
+/// Value of floatFormat which tells the file was written with the same float byte order
+pub const FLOATFORMAT_VALUE: f64 = 1234567.0;
+
+/// Offset of the CRC which covers all the preceding bytes
+const CRC_OFFSET: usize = 288;
+
+/// Decoded ControlFileData of PostgreSQL 13 and later, pg_control versions 1300 and 1700
+#[derive(Debug, PartialEq)]
+pub struct ControlFile {
+    pub system_identifier: u64,
+    pub pg_control_version: u32,
+    pub catalog_version_no: u32,
+    pub state: DbState,
+    /// Time of the last pg_control update in seconds since the Unix epoch
+    pub time: i64,
+    /// Location of the last checkpoint record
+    pub checkpoint: Lsn,
+    /// Copy of the last checkpoint record
+    pub checkpoint_copy: CheckPoint,
+    pub unlogged_lsn: Lsn,
+    pub min_recovery_point: Lsn,
+    pub min_recovery_point_tli: u32,
+    pub backup_start_point: Lsn,
+    pub backup_end_point: Lsn,
+    pub backup_end_required: bool,
+    pub wal_level: WalLevel,
+    pub wal_log_hints: bool,
+    pub max_connections: i32,
+    pub max_worker_processes: i32,
+    pub max_wal_senders: i32,
+    pub max_prepared_xacts: i32,
+    pub max_locks_per_xact: i32,
+    pub track_commit_timestamp: bool,
+    pub max_align: u32,
+    pub float_format: f64,
+    pub blcksz: u32,
+    pub relseg_size: u32,
+    pub xlog_blcksz: u32,
+    pub xlog_seg_size: u32,
+    pub name_data_len: u32,
+    pub index_max_keys: u32,
+    pub toast_max_chunk_size: u32,
+    pub loblksize: u32,
+    pub float8_by_val: bool,
+    pub data_checksum_version: u32,
+    pub mock_authentication_nonce: [u8; 32],
+    /// CRC stored in the file
+    pub crc: u32,
+    /// CRC computed over the file contents
+    pub computed_crc: u32,
+}
+
+/// Decoded CheckPoint record
+#[derive(Debug, PartialEq)]
+pub struct CheckPoint {
+    pub redo: Lsn,
+    pub this_timeline_id: u32,
+    pub prev_timeline_id: u32,
+    pub full_page_writes: bool,
+    /// Full transaction id, the epoch in the high half and the xid in the low one
+    pub next_xid: u64,
+    pub next_oid: u32,
+    pub next_multi: u32,
+    pub next_multi_offset: u32,
+    pub oldest_xid: u32,
+    pub oldest_xid_db: u32,
+    pub oldest_multi: u32,
+    pub oldest_multi_db: u32,
+    /// Time of the checkpoint in seconds since the Unix epoch
+    pub time: i64,
+    pub oldest_commit_ts_xid: u32,
+    pub newest_commit_ts_xid: u32,
+    pub oldest_active_xid: u32,
+}
+
+impl ControlFile {
+    pub fn read(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("Reading {:?}", path))?;
+        ControlFile::parse(&bytes).with_context(|| format!("Decoding {:?}", path))
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let pg_control_version = bytes.u32_at(8)?;
+        if pg_control_version != 1300 && pg_control_version != 1700 {
+            bail!(
+                "Unsupported pg_control version {}, versions 1300 and 1700 are supported",
+                pg_control_version
+            );
+        }
+
+        Ok(ControlFile {
+            system_identifier: bytes.u64_at(0)?,
+            pg_control_version,
+            catalog_version_no: bytes.u32_at(12)?,
+            state: DbState::from(bytes.i32_at(16)?),
+            time: bytes.i64_at(24)?,
+            checkpoint: Lsn(bytes.u64_at(32)?),
+            checkpoint_copy: CheckPoint::parse(bytes.bytes_at(40, 88)?)?,
+            unlogged_lsn: Lsn(bytes.u64_at(128)?),
+            min_recovery_point: Lsn(bytes.u64_at(136)?),
+            min_recovery_point_tli: bytes.u32_at(144)?,
+            backup_start_point: Lsn(bytes.u64_at(152)?),
+            backup_end_point: Lsn(bytes.u64_at(160)?),
+            backup_end_required: bytes.u8_at(168)? != 0,
+            wal_level: WalLevel::from(bytes.i32_at(172)?),
+            wal_log_hints: bytes.u8_at(176)? != 0,
+            max_connections: bytes.i32_at(180)?,
+            max_worker_processes: bytes.i32_at(184)?,
+            max_wal_senders: bytes.i32_at(188)?,
+            max_prepared_xacts: bytes.i32_at(192)?,
+            max_locks_per_xact: bytes.i32_at(196)?,
+            track_commit_timestamp: bytes.u8_at(200)? != 0,
+            max_align: bytes.u32_at(204)?,
+            float_format: bytes.f64_at(208)?,
+            blcksz: bytes.u32_at(216)?,
+            relseg_size: bytes.u32_at(220)?,
+            xlog_blcksz: bytes.u32_at(224)?,
+            xlog_seg_size: bytes.u32_at(228)?,
+            name_data_len: bytes.u32_at(232)?,
+            index_max_keys: bytes.u32_at(236)?,
+            toast_max_chunk_size: bytes.u32_at(240)?,
+            loblksize: bytes.u32_at(244)?,
+            float8_by_val: bytes.u8_at(248)? != 0,
+            data_checksum_version: bytes.u32_at(252)?,
+            mock_authentication_nonce: bytes.array_at(256)?,
+            crc: bytes.u32_at(CRC_OFFSET)?,
+            computed_crc: crc32c(bytes.bytes_at(0, CRC_OFFSET)?),
+        })
+    }
+
+    pub fn is_crc_valid(&self) -> bool {
+        self.crc == self.computed_crc
+    }
+}
+
+impl CheckPoint {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        Ok(CheckPoint {
+            redo: Lsn(bytes.u64_at(0)?),
+            this_timeline_id: bytes.u32_at(8)?,
+            prev_timeline_id: bytes.u32_at(12)?,
+            full_page_writes: bytes.u8_at(16)? != 0,
+            next_xid: bytes.u64_at(24)?,
+            next_oid: bytes.u32_at(32)?,
+            next_multi: bytes.u32_at(36)?,
+            next_multi_offset: bytes.u32_at(40)?,
+            oldest_xid: bytes.u32_at(44)?,
+            oldest_xid_db: bytes.u32_at(48)?,
+            oldest_multi: bytes.u32_at(52)?,
+            oldest_multi_db: bytes.u32_at(56)?,
+            time: bytes.i64_at(64)?,
+            oldest_commit_ts_xid: bytes.u32_at(72)?,
+            newest_commit_ts_xid: bytes.u32_at(76)?,
+            oldest_active_xid: bytes.u32_at(80)?,
+        })
+    }
+
+    /// Epoch of the next full transaction id
+    pub fn next_xid_epoch(&self) -> u32 {
+        (self.next_xid >> 32) as u32
+    }
+
+    /// Next transaction id within its epoch
+    pub fn next_xid_xid(&self) -> u32 {
+        self.next_xid as u32
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DbState {
+    StartingUp,
+    Shutdowned,
+    ShutdownedInRecovery,
+    Shutdowning,
+    InCrashRecovery,
+    InArchiveRecovery,
+    InProduction,
+    Unknown(i32),
+}
+
+impl From<i32> for DbState {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => DbState::StartingUp,
+            1 => DbState::Shutdowned,
+            2 => DbState::ShutdownedInRecovery,
+            3 => DbState::Shutdowning,
+            4 => DbState::InCrashRecovery,
+            5 => DbState::InArchiveRecovery,
+            6 => DbState::InProduction,
+            value => DbState::Unknown(value),
+        }
+    }
+}
+
+impl Display for DbState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbState::StartingUp => write!(f, "starting up"),
+            DbState::Shutdowned => write!(f, "shut down"),
+            DbState::ShutdownedInRecovery => write!(f, "shut down in recovery"),
+            DbState::Shutdowning => write!(f, "shutting down"),
+            DbState::InCrashRecovery => write!(f, "in crash recovery"),
+            DbState::InArchiveRecovery => write!(f, "in archive recovery"),
+            DbState::InProduction => write!(f, "in production"),
+            DbState::Unknown(value) => write!(f, "unrecognized status code {value}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WalLevel {
+    Minimal,
+    Replica,
+    Logical,
+    Unknown(i32),
+}
+
+impl From<i32> for WalLevel {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => WalLevel::Minimal,
+            1 => WalLevel::Replica,
+            2 => WalLevel::Logical,
+            value => WalLevel::Unknown(value),
+        }
+    }
+}
+
+impl Display for WalLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalLevel::Minimal => write!(f, "minimal"),
+            WalLevel::Replica => write!(f, "replica"),
+            WalLevel::Logical => write!(f, "logical"),
+            WalLevel::Unknown(_) => write!(f, "unrecognized wal_level"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_files {
+    use crate::common::crc32c::crc32c;
+
+    use super::CRC_OFFSET;
+
+    /// Lays out pg_control of a freshly shut down cluster with a valid CRC
+    pub fn pg_control() -> Vec<u8> {
+        let mut bytes = vec![0; 8192];
+        let mut put = |offset: usize, value: &[u8]| {
+            bytes[offset..offset + value.len()].copy_from_slice(value)
+        };
+        put(0, &7697690184480792812u64.to_le_bytes());
+        put(8, &1300u32.to_le_bytes());
+        put(12, &202209061u32.to_le_bytes());
+        put(16, &1i32.to_le_bytes());
+        put(24, &1792258185i64.to_le_bytes());
+        put(32, &0x1B7B110u64.to_le_bytes());
+        // checkpoint copy
+        put(40, &0x1B7B110u64.to_le_bytes());
+        put(48, &1u32.to_le_bytes());
+        put(52, &1u32.to_le_bytes());
+        put(56, &[1]);
+        put(64, &740u64.to_le_bytes());
+        put(72, &16420u32.to_le_bytes());
+        put(76, &1u32.to_le_bytes());
+        put(84, &716u32.to_le_bytes());
+        put(88, &1u32.to_le_bytes());
+        put(92, &1u32.to_le_bytes());
+        put(96, &1u32.to_le_bytes());
+        put(104, &1792258185i64.to_le_bytes());
+        put(112, &724u32.to_le_bytes());
+        put(116, &739u32.to_le_bytes());
+        // the rest of control file
+        put(128, &1000u64.to_le_bytes());
+        put(172, &1i32.to_le_bytes());
+        put(180, &100i32.to_le_bytes());
+        put(184, &8i32.to_le_bytes());
+        put(188, &10i32.to_le_bytes());
+        put(196, &64i32.to_le_bytes());
+        put(200, &[1]);
+        put(204, &8u32.to_le_bytes());
+        put(208, &1234567.0f64.to_le_bytes());
+        put(216, &8192u32.to_le_bytes());
+        put(220, &131072u32.to_le_bytes());
+        put(224, &8192u32.to_le_bytes());
+        put(228, &(16 * 1024 * 1024u32).to_le_bytes());
+        put(232, &64u32.to_le_bytes());
+        put(236, &32u32.to_le_bytes());
+        put(240, &1996u32.to_le_bytes());
+        put(244, &2048u32.to_le_bytes());
+        put(248, &[1]);
+        put(252, &1u32.to_le_bytes());
+        put(256, &[0xAB; 32]);
+        let crc = crc32c(&bytes[..CRC_OFFSET]);
+        bytes[CRC_OFFSET..CRC_OFFSET + 4].copy_from_slice(&crc.to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::common::Lsn;
+
+    use super::{test_files::pg_control, CheckPoint, ControlFile, DbState, WalLevel};
+
+    #[test]
+    fn decodes_control_file() {
+        // given
+        let bytes = pg_control();
+
+        // when
+        let control_file = ControlFile::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(control_file.system_identifier, 7697690184480792812);
+        assert_eq!(control_file.state, DbState::Shutdowned);
+        assert_eq!(control_file.checkpoint, Lsn(0x1B7B110));
+        assert_eq!(
+            control_file.checkpoint_copy,
+            CheckPoint {
+                redo: Lsn(0x1B7B110),
+                this_timeline_id: 1,
+                prev_timeline_id: 1,
+                full_page_writes: true,
+                next_xid: 740,
+                next_oid: 16420,
+                next_multi: 1,
+                next_multi_offset: 0,
+                oldest_xid: 716,
+                oldest_xid_db: 1,
+                oldest_multi: 1,
+                oldest_multi_db: 1,
+                time: 1792258185,
+                oldest_commit_ts_xid: 724,
+                newest_commit_ts_xid: 739,
+                oldest_active_xid: 0,
+            }
+        );
+        assert_eq!(control_file.wal_level, WalLevel::Replica);
+        assert_eq!(control_file.xlog_seg_size, 16 * 1024 * 1024);
+        assert_eq!(control_file.data_checksum_version, 1);
+        assert!(control_file.is_crc_valid());
+    }
+
+    #[test]
+    fn detects_crc_mismatch() {
+        // given
+        let mut bytes = pg_control();
+        bytes[16] = 6;
+
+        // when
+        let control_file = ControlFile::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(control_file.state, DbState::InProduction);
+        assert!(!control_file.is_crc_valid());
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        // given
+        let mut bytes = pg_control();
+        bytes[8..12].copy_from_slice(&1201u32.to_le_bytes());
+
+        // when
+        let result = ControlFile::parse(&bytes);
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some(
+                "Unsupported pg_control version 1201, versions 1300 and 1700 are supported"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -1,28 +1,35 @@
 pub mod base;
+pub mod catalog;
 mod current_logfiles;
-mod global;
-mod pg_commit_ts;
+pub mod datum;
+pub mod filenode_map;
+pub mod global;
+pub mod page;
+pub mod pg_commit_ts;
 mod pg_dynshmem;
 mod pg_hba_conf;
 mod pg_ident_conf;
 mod pg_logical;
-mod pg_multiexact;
-mod pg_notify;
+pub mod pg_multiexact;
+pub mod pg_notify;
 mod pg_replslot;
-mod pg_serial;
+pub mod pg_serial;
 mod pg_snapshots;
 mod pg_stat;
 mod pg_stat_tmp;
-mod pg_subtrans;
+pub mod pg_subtrans;
 mod pg_tblspc;
 mod pg_twophase;
 mod pg_version;
-mod pg_wal;
-mod pg_xact;
+pub mod pg_wal;
+pub mod pg_xact;
 mod postgresql_auto_conf;
 mod postgresql_conf;
 mod postmaster_opts;
 mod postmaster_pid;
+pub mod rows;
+pub mod slru;
+pub mod transactions;
 
 use std::{fmt::Debug, path::Path, rc::Rc};
 
@@ -30,7 +37,13 @@ use strum_macros::EnumIter;
 
 use crate::common::fs::DirEntry;
 
-use self::base::Base;
+use self::{
+    base::Base, global::Global, pg_commit_ts::PGCommitTS, pg_wal::PGWal, pg_xact::PGXact,
+    transactions::Transactions,
+};
+
+/// Size of a disk block (page) used by a default PostgreSQL build
+pub const BLCKSZ: u64 = 8192;
 
 /// Represents the PG_DATA directory
 pub trait PGData {
@@ -41,7 +54,7 @@ pub trait PGData {
     fn list_items(
         &self,
     ) -> anyhow::Result<
-        impl IntoIterator<Item = PGDataItem, IntoIter = impl Iterator<Item = PGDataItem>>,
+        impl IntoIterator<Item = PGDataItem<'_>, IntoIter = impl Iterator<Item = PGDataItem<'_>>>,
     >;
 
     /// Represents PG_DATA/base directory
@@ -51,6 +64,21 @@ pub trait PGData {
 pub trait PGDataItems {
     /// Represents PG_DATA/base directory
     fn base<'a>(&self) -> impl Base + 'a;
+
+    /// Represents PG_DATA/global directory
+    fn global<'a>(&self) -> impl Global + 'a;
+
+    /// Represents PG_DATA/pg_wal directory
+    fn pg_wal<'a>(&self) -> impl PGWal + 'a;
+
+    /// Represents PG_DATA/pg_xact directory
+    fn pg_xact<'a>(&self) -> impl PGXact + 'a;
+
+    /// Represents PG_DATA/pg_commit_ts directory
+    fn pg_commit_ts<'a>(&self) -> impl PGCommitTS + 'a;
+
+    /// Represents the transaction metadata of PG_DATA
+    fn transactions<'a>(&self) -> impl Transactions + 'a;
 }
 
 /// Represents the item in the root of PG_DATA directory
@@ -79,7 +107,7 @@ impl PGDataItem<'_> {
     pub fn known_present_dir(
         dir_name: &'static str,
         known_pgdata_item: KnownPGDataItem,
-    ) -> PGDataItem {
+    ) -> PGDataItem<'static> {
         PGDataItem::Known(
             DirEntry::dir(dir_name),
             known_pgdata_item,
@@ -90,7 +118,7 @@ impl PGDataItem<'_> {
     pub fn known_present_file(
         file_name: &'static str,
         known_pgdata_item: KnownPGDataItem,
-    ) -> PGDataItem {
+    ) -> PGDataItem<'static> {
         PGDataItem::Known(
             DirEntry::file(file_name),
             known_pgdata_item,
@@ -154,10 +182,12 @@ mod default_impl {
     use anyhow::anyhow;
 
     use super::{
-        base::Base, current_logfiles, global, pg_commit_ts, pg_dynshmem, pg_hba_conf, pg_logical,
-        pg_multiexact, pg_notify, pg_replslot, pg_serial, pg_snapshots, pg_stat, pg_stat_tmp,
-        pg_subtrans, pg_tblspc, pg_twophase, pg_wal, pg_xact, postgresql_auto_conf,
-        postmaster_opts, postmaster_pid, KnownPGDataItem, PGDataItem, PGDataItemState,
+        base::Base, current_logfiles, global, global::Global, pg_commit_ts,
+        pg_commit_ts::PGCommitTS, pg_dynshmem, pg_hba_conf, pg_logical, pg_multiexact, pg_notify,
+        pg_replslot, pg_serial, pg_snapshots, pg_stat, pg_stat_tmp, pg_subtrans, pg_tblspc,
+        pg_twophase, pg_wal, pg_wal::PGWal, pg_xact, pg_xact::PGXact, postgresql_auto_conf,
+        postmaster_opts, postmaster_pid, transactions, transactions::Transactions, KnownPGDataItem,
+        PGDataItem, PGDataItemState,
     };
 
     // allows to query set of PGDataItems by DirEntry
@@ -182,6 +212,26 @@ mod default_impl {
         fn base<'a>(&self) -> impl Base + 'a {
             base::base(&self.pgdata.path)
         }
+
+        fn global<'a>(&self) -> impl Global + 'a {
+            global::global(&self.pgdata.path)
+        }
+
+        fn pg_wal<'a>(&self) -> impl PGWal + 'a {
+            pg_wal::pg_wal(&self.pgdata.path)
+        }
+
+        fn pg_xact<'a>(&self) -> impl PGXact + 'a {
+            pg_xact::pg_xact(&self.pgdata.path)
+        }
+
+        fn pg_commit_ts<'a>(&self) -> impl PGCommitTS + 'a {
+            pg_commit_ts::pg_commit_ts(&self.pgdata.path)
+        }
+
+        fn transactions<'a>(&self) -> impl Transactions + 'a {
+            transactions::transactions(&self.pgdata.path)
+        }
     }
 
     impl super::PGData for PGData {
@@ -192,7 +242,7 @@ mod default_impl {
         fn list_items(
             &self,
         ) -> anyhow::Result<
-            impl IntoIterator<Item = PGDataItem, IntoIter = impl Iterator<Item = PGDataItem>>,
+            impl IntoIterator<Item = PGDataItem<'_>, IntoIter = impl Iterator<Item = PGDataItem<'_>>>,
         > {
             let pgdata_items = read(self.path.as_ref())?;
             Ok(pgdata_items.into_iter())
@@ -209,7 +259,7 @@ mod default_impl {
         }
     }
 
-    fn read(pgdata: &Path) -> anyhow::Result<BTreeSet<PGDataItem>> {
+    fn read(pgdata: &Path) -> anyhow::Result<BTreeSet<PGDataItem<'_>>> {
         let known_items = known_items();
         let actual_items = actual_items(pgdata)?;
 
@@ -343,13 +393,38 @@ mod default_impl {
 pub mod test_stubs {
     use super::{
         base::{test_stubs::StubBase, Base},
+        global::{test_stubs::StubGlobal, Global},
+        pg_commit_ts::{test_stubs::StubPGCommitTS, PGCommitTS},
+        pg_wal::{test_stubs::StubPGWal, PGWal},
+        pg_xact::{test_stubs::StubPGXact, PGXact},
+        transactions::{test_stubs::StubTransactions, Transactions},
         PGDataItems,
     };
 
     pub struct StubPGDataItems;
     impl PGDataItems for StubPGDataItems {
-        fn base<'a, 'b>(&'a self) -> impl Base + 'b {
+        fn base<'b>(&self) -> impl Base + 'b {
             StubBase {}
         }
+
+        fn global<'b>(&self) -> impl Global + 'b {
+            StubGlobal {}
+        }
+
+        fn pg_wal<'b>(&self) -> impl PGWal + 'b {
+            StubPGWal {}
+        }
+
+        fn pg_xact<'b>(&self) -> impl PGXact + 'b {
+            StubPGXact {}
+        }
+
+        fn pg_commit_ts<'b>(&self) -> impl PGCommitTS + 'b {
+            StubPGCommitTS {}
+        }
+
+        fn transactions<'b>(&self) -> impl Transactions + 'b {
+            StubTransactions {}
+        }
     }
 }
@@ -0,0 +1,625 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::common::{bytes::ReadBytes, Lsn};
+
+use self::heap::HeapTuple;
+
+use super::{transactions::Transactions, BLCKSZ};
+
+pub mod free_space_map;
+pub mod heap;
+pub mod visibility_map;
+
+/// Number of blocks in a single segment file of a relation fork
+pub const RELSEG_SIZE: u32 = 131072;
+
+/// Size of PageHeaderData
+pub const PAGE_HEADER_SIZE: usize = 24;
+
+/// Size of ItemIdData
+const LINE_POINTER_SIZE: usize = 4;
+
+/// Layout version of pages written by PostgreSQL 8.3 and later
+pub const PG_PAGE_LAYOUT_VERSION: u8 = 4;
+
+const PD_HAS_FREE_LINES: u16 = 0x0001;
+const PD_PAGE_FULL: u16 = 0x0002;
+const PD_ALL_VISIBLE: u16 = 0x0004;
+
+/// Represents a single block of a relation fork
+pub struct Page {
+    bytes: Vec<u8>,
+}
+
+impl Page {
+    pub fn new(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() != BLCKSZ as usize {
+            bail!(
+                "Page size is expected to be {} bytes, got {} bytes",
+                BLCKSZ,
+                bytes.len()
+            );
+        }
+        Ok(Page { bytes })
+    }
+
+    /// Reads the block with the given number of the relation fork whose first segment file is at `path`
+    pub fn read(path: &Path, block_number: u32) -> Result<Self> {
+        let segment_path = segment_path(path, block_number / RELSEG_SIZE);
+        let offset = (block_number % RELSEG_SIZE) as u64 * BLCKSZ;
+
+        let mut file =
+            File::open(&segment_path).with_context(|| format!("Opening {:?}", segment_path))?;
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| {
+                let mut bytes = vec![0; BLCKSZ as usize];
+                file.read_exact(&mut bytes).map(|_| bytes)
+            })
+            .with_context(|| format!("Reading block {} from {:?}", block_number, segment_path))
+            .and_then(Page::new)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// A page which has never been initialized is filled with zeroes
+    pub fn is_new(&self) -> bool {
+        self.bytes.u16_at(14).is_ok_and(|pd_upper| pd_upper == 0)
+    }
+
+    pub fn header(&self) -> Result<PageHeader> {
+        PageHeader::parse(&self.bytes)
+    }
+
+    /// Decodes the line pointer array which follows the page header
+    pub fn line_pointers(&self) -> Result<Vec<LinePointer>> {
+        let header = self.header()?;
+        let count = header.line_pointer_count();
+        (0..count)
+            .map(|i| {
+                let offset = PAGE_HEADER_SIZE + i * LINE_POINTER_SIZE;
+                self.bytes
+                    .u32_at(offset)
+                    .map(|raw| LinePointer::from_raw((i + 1) as u16, raw))
+            })
+            .collect()
+    }
+
+    /// Returns the bytes of the item the line pointer points to
+    pub fn item(&self, line_pointer: &LinePointer) -> Result<&[u8]> {
+        if line_pointer.state != LinePointerState::Normal {
+            bail!(
+                "Line pointer {} has no storage, its state is {}",
+                line_pointer.number,
+                line_pointer.state
+            );
+        }
+        self.bytes
+            .bytes_at(line_pointer.offset as usize, line_pointer.length as usize)
+            .with_context(|| {
+                format!(
+                    "Line pointer {} points outside the page",
+                    line_pointer.number
+                )
+            })
+    }
+
+    /// Decodes the heap tuple the line pointer points to
+    pub fn heap_tuple(&self, line_pointer: &LinePointer) -> Result<HeapTuple<'_>> {
+        self.item(line_pointer).and_then(HeapTuple::parse)
+    }
+
+    /// Decodes the heap tuples of the normal line pointers which are live, see
+    /// [heap::HeapTupleHeader::is_live]. Tuples which cannot be decoded or whose liveness cannot
+    /// be judged are reported in their place, a new page holds none
+    pub fn live_tuples(
+        &self,
+        block_number: u32,
+        transactions: &impl Transactions,
+    ) -> Result<Vec<Result<(ItemPointer, HeapTuple<'_>)>>> {
+        if self.is_new() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .line_pointers()?
+            .iter()
+            .filter(|line_pointer| line_pointer.state == LinePointerState::Normal)
+            .filter_map(|line_pointer| {
+                let ctid = ItemPointer {
+                    block_number,
+                    offset_number: line_pointer.number,
+                };
+                match self.heap_tuple(line_pointer) {
+                    Ok(tuple) => match tuple.header.is_live(transactions) {
+                        Ok(true) => Some(Ok((ctid, tuple))),
+                        Ok(false) => None,
+                        Err(err) => Some(Err(
+                            err.context(format!("Checking visibility of tuple {ctid}"))
+                        )),
+                    },
+                    Err(err) => Some(Err(err.context(format!("Decoding tuple {ctid}")))),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Returns the path of a relation fork segment file given the path of its first segment
+pub fn segment_path(path: &Path, segment_id: u32) -> PathBuf {
+    match segment_id {
+        0 => path.to_path_buf(),
+        segment_id => {
+            let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+            file_name.push(format!(".{segment_id}"));
+            path.with_file_name(file_name)
+        }
+    }
+}
+
+/// Groups consecutive blocks with equal values into runs
+pub fn group_runs<T: PartialEq>(
+    blocks: impl Iterator<Item = (u32, T)>,
+) -> Vec<(RangeInclusive<u32>, T)> {
+    let mut runs: Vec<(RangeInclusive<u32>, T)> = Vec::new();
+    blocks.for_each(|(block, value)| match runs.last_mut() {
+        Some((range, last)) if *last == value && *range.end() + 1 == block => {
+            *range = *range.start()..=block
+        }
+        _ => runs.push((block..=block, value)),
+    });
+    runs
+}
+
+/// Reads all blocks of the relation fork whose first segment file is at `path`
+pub fn read_fork(path: &Path) -> Result<Vec<Page>> {
+    let mut pages = Vec::new();
+    for segment_id in 0.. {
+        let segment_path = segment_path(path, segment_id);
+        if segment_id > 0 && !segment_path.exists() {
+            break;
+        }
+        let bytes =
+            std::fs::read(&segment_path).with_context(|| format!("Reading {:?}", segment_path))?;
+        if !(bytes.len() as u64).is_multiple_of(BLCKSZ) {
+            bail!(
+                "Size of {:?} is not a multiple of the block size: {} bytes",
+                segment_path,
+                bytes.len()
+            );
+        }
+        bytes
+            .chunks(BLCKSZ as usize)
+            .try_for_each(|block| Page::new(block.to_vec()).map(|page| pages.push(page)))?;
+    }
+    Ok(pages)
+}
+
+/// Decoded PageHeaderData
+#[derive(Debug, PartialEq)]
+pub struct PageHeader {
+    pub lsn: Lsn,
+    pub checksum: u16,
+    pub flags: u16,
+    pub lower: u16,
+    pub upper: u16,
+    pub special: u16,
+    pub page_size: u16,
+    pub layout_version: u8,
+    pub prune_xid: u32,
+}
+
+impl PageHeader {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let pagesize_version = bytes.u16_at(18)?;
+        let header = PageHeader {
+            lsn: Lsn::from_parts(bytes.u32_at(0)?, bytes.u32_at(4)?),
+            checksum: bytes.u16_at(8)?,
+            flags: bytes.u16_at(10)?,
+            lower: bytes.u16_at(12)?,
+            upper: bytes.u16_at(14)?,
+            special: bytes.u16_at(16)?,
+            page_size: pagesize_version & 0xFF00,
+            layout_version: (pagesize_version & 0x00FF) as u8,
+            prune_xid: bytes.u32_at(20)?,
+        };
+        header.validate().map(|_| header)
+    }
+
+    fn validate(&self) -> Result<()> {
+        // mirrors the sanity checks of PageIsVerifiedExtended
+        let is_valid = (self.lower as usize) >= PAGE_HEADER_SIZE
+            && self.lower <= self.upper
+            && self.upper <= self.special
+            && (self.special as u64) <= BLCKSZ
+            && self.special.is_multiple_of(8)
+            && self.flags & !(PD_HAS_FREE_LINES | PD_PAGE_FULL | PD_ALL_VISIBLE) == 0;
+        if !is_valid {
+            bail!(
+                "Invalid page header: pd_lower = {}, pd_upper = {}, pd_special = {}, pd_flags = {:#06x}",
+                self.lower,
+                self.upper,
+                self.special,
+                self.flags
+            );
+        }
+        Ok(())
+    }
+
+    pub fn line_pointer_count(&self) -> usize {
+        (self.lower as usize).saturating_sub(PAGE_HEADER_SIZE) / LINE_POINTER_SIZE
+    }
+
+    /// Amount of unallocated space between the line pointers and the tuples
+    pub fn free_space(&self) -> u16 {
+        self.upper - self.lower
+    }
+
+    pub fn is_all_visible(&self) -> bool {
+        self.flags & PD_ALL_VISIBLE != 0
+    }
+
+    pub fn flag_names(&self) -> Vec<&'static str> {
+        [
+            (PD_HAS_FREE_LINES, "PD_HAS_FREE_LINES"),
+            (PD_PAGE_FULL, "PD_PAGE_FULL"),
+            (PD_ALL_VISIBLE, "PD_ALL_VISIBLE"),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| self.flags & flag != 0)
+        .map(|(_, name)| name)
+        .collect()
+    }
+}
+
+/// Decoded ItemIdData
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LinePointer {
+    /// 1-based offset number of the line pointer within the page
+    pub number: u16,
+    /// Offset of the item, or the offset number of the line pointer a redirect points to
+    pub offset: u16,
+    pub state: LinePointerState,
+    pub length: u16,
+}
+
+impl LinePointer {
+    fn from_raw(number: u16, raw: u32) -> Self {
+        LinePointer {
+            number,
+            offset: (raw & 0x7FFF) as u16,
+            state: LinePointerState::from_raw(((raw >> 15) & 0x03) as u8),
+            length: (raw >> 17) as u16,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LinePointerState {
+    /// LP_UNUSED, the line pointer is available for reuse
+    Unused,
+    /// LP_NORMAL, the line pointer points to a tuple
+    Normal,
+    /// LP_REDIRECT, HOT redirect to another line pointer
+    Redirect,
+    /// LP_DEAD, the tuple is dead, storage may or may not be reclaimed
+    Dead,
+}
+
+impl LinePointerState {
+    fn from_raw(lp_flags: u8) -> Self {
+        match lp_flags {
+            0 => LinePointerState::Unused,
+            1 => LinePointerState::Normal,
+            2 => LinePointerState::Redirect,
+            _ => LinePointerState::Dead,
+        }
+    }
+}
+
+impl Display for LinePointerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LinePointerState::Unused => "LP_UNUSED",
+            LinePointerState::Normal => "LP_NORMAL",
+            LinePointerState::Redirect => "LP_REDIRECT",
+            LinePointerState::Dead => "LP_DEAD",
+        })
+    }
+}
+
+/// Decoded ItemPointerData, a physical location of a tuple
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct ItemPointer {
+    pub block_number: u32,
+    pub offset_number: u16,
+}
+
+impl ItemPointer {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        Ok(ItemPointer {
+            block_number: ((bytes.u16_at(0)? as u32) << 16) | bytes.u16_at(2)? as u32,
+            offset_number: bytes.u16_at(4)?,
+        })
+    }
+}
+
+impl Display for ItemPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{})", self.block_number, self.offset_number)
+    }
+}
+
+#[cfg(test)]
+pub mod test_pages {
+    use crate::pgdata::BLCKSZ;
+
+    use super::PAGE_HEADER_SIZE;
+
+    /// Builds heap pages the way PageAddItem does
+    pub struct PageBuilder {
+        bytes: Vec<u8>,
+        lower: usize,
+        upper: usize,
+    }
+
+    impl PageBuilder {
+        pub fn empty() -> Self {
+            let mut builder = PageBuilder {
+                bytes: vec![0; BLCKSZ as usize],
+                lower: PAGE_HEADER_SIZE,
+                upper: BLCKSZ as usize,
+            };
+            builder.bytes[0..8].copy_from_slice(&[0, 0, 0, 0, 0x28, 0xB0, 0x7B, 0x01]);
+            builder.bytes[18..20].copy_from_slice(&(0x2000u16 | 4).to_le_bytes());
+            builder.bytes[16..18].copy_from_slice(&(BLCKSZ as u16).to_le_bytes());
+            builder.update_bounds()
+        }
+
+        /// Reserves the special space at the end of the page, to be called before adding items
+        pub fn special(mut self, size: usize) -> Self {
+            self.upper = BLCKSZ as usize - size;
+            self.bytes[16..18].copy_from_slice(&(self.upper as u16).to_le_bytes());
+            self.update_bounds()
+        }
+
+        pub fn flags(mut self, flags: u16) -> Self {
+            self.bytes[10..12].copy_from_slice(&flags.to_le_bytes());
+            self
+        }
+
+        pub fn item(mut self, item: &[u8]) -> Self {
+            self.upper = (self.upper - item.len()) & !7;
+            self.bytes[self.upper..self.upper + item.len()].copy_from_slice(item);
+            let upper = self.upper as u32;
+            self.line_pointer(upper, 1, item.len() as u32)
+        }
+
+        pub fn line_pointer(mut self, offset: u32, flags: u32, length: u32) -> Self {
+            let raw = offset | (flags << 15) | (length << 17);
+            self.bytes[self.lower..self.lower + 4].copy_from_slice(&raw.to_le_bytes());
+            self.lower += 4;
+            self.update_bounds()
+        }
+
+        fn update_bounds(mut self) -> Self {
+            self.bytes[12..14].copy_from_slice(&(self.lower as u16).to_le_bytes());
+            self.bytes[14..16].copy_from_slice(&(self.upper as u16).to_le_bytes());
+            self
+        }
+
+        pub fn build(self) -> Vec<u8> {
+            self.bytes
+        }
+    }
+
+    /// Builds a heap tuple header followed by the given data
+    pub fn heap_tuple(xmin: u32, xmax: u32, infomask: u16, natts: u16, data: &[u8]) -> Vec<u8> {
+        let mut tuple = vec![0; 24];
+        tuple[0..4].copy_from_slice(&xmin.to_le_bytes());
+        tuple[4..8].copy_from_slice(&xmax.to_le_bytes());
+        tuple[14..16].copy_from_slice(&1u16.to_le_bytes());
+        tuple[16..18].copy_from_slice(&1u16.to_le_bytes());
+        tuple[18..20].copy_from_slice(&natts.to_le_bytes());
+        tuple[20..22].copy_from_slice(&infomask.to_le_bytes());
+        tuple[22] = 24;
+        tuple.extend_from_slice(data);
+        tuple
+    }
+
+    /// Adds a null bitmap marking the given 0-based attributes null to a tuple built by
+    /// [heap_tuple], the data is expected to leave them out
+    pub fn with_nulls(mut tuple: Vec<u8>, nulls: &[usize]) -> Vec<u8> {
+        let natts = u16::from_le_bytes([tuple[18], tuple[19]]) as usize & 0x07FF;
+        let mut bitmap = (0..natts.div_ceil(8) * 8)
+            .collect::<Vec<_>>()
+            .chunks(8)
+            .map(|attnums| {
+                attnums
+                    .iter()
+                    .filter(|attnum| **attnum < natts && !nulls.contains(attnum))
+                    .fold(0u8, |byte, attnum| byte | 1 << (attnum % 8))
+            })
+            .collect::<Vec<_>>();
+        let hoff = (23 + bitmap.len()).next_multiple_of(8);
+        bitmap.resize(hoff - 23, 0);
+        tuple.splice(23..24, bitmap);
+        tuple[20] |= 0x01;
+        tuple[22] = hoff as u8;
+        tuple
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{common::Lsn, pgdata::transactions::test_stubs::StubTransactions};
+
+    use super::{
+        segment_path,
+        test_pages::{heap_tuple, PageBuilder},
+        ItemPointer, LinePointer, LinePointerState, Page, PageHeader,
+    };
+
+    #[test]
+    fn decodes_page_header() {
+        // given
+        let page = Page::new(PageBuilder::empty().flags(0x0004).build()).unwrap();
+
+        // when
+        let header = page.header().unwrap();
+
+        // then
+        assert_eq!(
+            header,
+            PageHeader {
+                lsn: Lsn(0x17BB028),
+                checksum: 0,
+                flags: 0x0004,
+                lower: 24,
+                upper: 8192,
+                special: 8192,
+                page_size: 8192,
+                layout_version: 4,
+                prune_xid: 0,
+            }
+        );
+        assert_eq!(header.flag_names(), vec!["PD_ALL_VISIBLE"]);
+        assert_eq!(format!("{}", header.lsn), "0/17BB028");
+    }
+
+    #[test]
+    fn rejects_corrupted_page_header() {
+        // given
+        let mut bytes = PageBuilder::empty().build();
+        bytes[12..14].copy_from_slice(&9000u16.to_le_bytes());
+        let page = Page::new(bytes).unwrap();
+
+        // when
+        let result = page.header();
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some(
+                "Invalid page header: pd_lower = 9000, pd_upper = 8192, pd_special = 8192, pd_flags = 0x0000"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn decodes_line_pointers() {
+        // given
+        let page = Page::new(
+            PageBuilder::empty()
+                .item(&heap_tuple(100, 0, 0x0802, 2, &[1, 2, 3, 4]))
+                .line_pointer(1, 2, 0)
+                .line_pointer(0, 3, 0)
+                .line_pointer(0, 0, 0)
+                .build(),
+        )
+        .unwrap();
+
+        // when
+        let line_pointers = page.line_pointers().unwrap();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            line_pointers,
+            vec![
+                LinePointer { number: 1, offset: 8160, state: LinePointerState::Normal, length: 28 },
+                LinePointer { number: 2, offset: 1, state: LinePointerState::Redirect, length: 0 },
+                LinePointer { number: 3, offset: 0, state: LinePointerState::Dead, length: 0 },
+                LinePointer { number: 4, offset: 0, state: LinePointerState::Unused, length: 0 },
+            ]
+        );
+        assert_eq!(page.item(&line_pointers[0]).unwrap().len(), 28);
+        assert!(page.item(&line_pointers[1]).is_err());
+    }
+
+    #[test]
+    fn lists_live_tuples() {
+        // given
+        let page = Page::new(
+            PageBuilder::empty()
+                .item(&heap_tuple(100, 0, 0x0902, 1, &[1, 0, 0, 0]))
+                .item(&heap_tuple(100, 0, 0x0a00, 1, &[2, 0, 0, 0]))
+                .line_pointer(0, 3, 0)
+                .item(&heap_tuple(100, 101, 0x0500, 1, &[4, 0, 0, 0]))
+                .line_pointer(8150, 1, 100)
+                .item(&heap_tuple(100, 0, 0, 1, &[6, 0, 0, 0]))
+                .build(),
+        )
+        .unwrap();
+
+        // when
+        let tuples = page
+            .live_tuples(3, &StubTransactions)
+            .unwrap()
+            .into_iter()
+            .map(|tuple| {
+                tuple
+                    .map(|(ctid, tuple)| (ctid.to_string(), tuple.header.xmin))
+                    .map_err(|err| format!("{err:#}"))
+            })
+            .collect::<Vec<_>>();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            tuples,
+            vec![
+                Ok(("(3,1)".to_string(), 100)),
+                Err("Decoding tuple (3,5): Line pointer 5 points outside the page: Unexpected end of data: 100 bytes at offset 8150 requested, 8192 bytes available".to_string()),
+                Err("Checking visibility of tuple (3,6): Status of xid 100 is missing".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn recognizes_new_page() {
+        // given
+        let page = Page::new(vec![0; 8192]).unwrap();
+
+        // then
+        assert!(page.is_new());
+    }
+
+    #[test]
+    fn decodes_item_pointer() {
+        // given
+        let bytes = [0x01, 0x00, 0x02, 0x00, 0x03, 0x00];
+
+        // when
+        let item_pointer = ItemPointer::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(format!("{item_pointer}"), "(65538,3)");
+    }
+
+    #[rstest]
+    #[case(0, "/pgdata/base/5/16384")]
+    #[case(1, "/pgdata/base/5/16384.1")]
+    #[case(12, "/pgdata/base/5/16384.12")]
+    fn builds_segment_path(#[case] segment_id: u32, #[case] expected: &str) {
+        // when
+        let path = segment_path(Path::new("/pgdata/base/5/16384"), segment_id);
+
+        // then
+        assert_eq!(path, Path::new(expected));
+    }
+}
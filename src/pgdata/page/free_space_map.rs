@@ -0,0 +1,363 @@
+use std::ops::RangeInclusive;
+
+use anyhow::{bail, Result};
+
+use crate::{common::bytes::ReadBytes, pgdata::BLCKSZ};
+
+use super::{
+    group_runs, heap::HEAP_TUPLE_HEADER_SIZE, LinePointerState, Page, LINE_POINTER_SIZE,
+    PAGE_HEADER_SIZE, PD_HAS_FREE_LINES,
+};
+
+/// Bytes of free space a category step stands for, categories are 0..=255
+pub const FSM_CAT_STEP: u32 = BLCKSZ as u32 / 256;
+
+/// Largest request the map serves, MaxHeapTupleSize, the top category stands for it
+pub const MAX_FSM_REQUEST_SIZE: u32 =
+    BLCKSZ as u32 - (PAGE_HEADER_SIZE + LINE_POINTER_SIZE + 4) as u32;
+
+/// Line pointers a heap page holds at most, MaxHeapTuplesPerPage
+pub const MAX_HEAP_TUPLES_PER_PAGE: usize = (BLCKSZ as usize - PAGE_HEADER_SIZE)
+    / (HEAP_TUPLE_HEADER_SIZE.next_multiple_of(8) + LINE_POINTER_SIZE);
+
+/// Nodes of the binary tree an FSM page holds after the page header and fp_next_slot
+pub const NODES_PER_PAGE: usize = BLCKSZ as usize - PAGE_HEADER_SIZE - 4;
+
+/// Inner nodes of the tree, the leaves follow them
+pub const NON_LEAF_NODES_PER_PAGE: usize = BLCKSZ as usize / 2 - 1;
+
+/// Leaves of the tree, each holds the category of a heap block or of a lower level FSM page
+pub const SLOTS_PER_FSM_PAGE: u32 = (NODES_PER_PAGE - NON_LEAF_NODES_PER_PAGE) as u32;
+
+/// Levels of FSM pages, 3 are enough to address 2^32 heap blocks with 8 kB pages
+pub const FSM_TREE_DEPTH: u32 = 3;
+
+/// Converts free space in bytes to the category the map records, see fsm_space_avail_to_cat
+pub fn avail_to_category(avail: u32) -> u8 {
+    match avail {
+        avail if avail >= MAX_FSM_REQUEST_SIZE => 255,
+        avail => (avail / FSM_CAT_STEP).min(254) as u8,
+    }
+}
+
+/// Converts a category to the least free space it stands for, see fsm_space_cat_to_avail
+pub fn category_to_avail(category: u8) -> u32 {
+    match category {
+        255 => MAX_FSM_REQUEST_SIZE,
+        category => category as u32 * FSM_CAT_STEP,
+    }
+}
+
+/// Logical position of an FSM page: the level, 0 for the leaves which hold heap blocks, and the
+/// number of the page within the level
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FsmAddress {
+    pub level: u32,
+    pub logical_page: u32,
+}
+
+impl FsmAddress {
+    /// Pages are laid out depth-first: the root, the first page of level 1, its leaf pages, the
+    /// second page of level 1 and so on, see fsm_logical_to_physical
+    pub fn physical_block(&self) -> u64 {
+        let mut leaf = self.logical_page as u64;
+        (0..self.level).for_each(|_| leaf *= SLOTS_PER_FSM_PAGE as u64);
+        let pages = (0..FSM_TREE_DEPTH)
+            .map(|_| {
+                let pages = leaf + 1;
+                leaf /= SLOTS_PER_FSM_PAGE as u64;
+                pages
+            })
+            .sum::<u64>();
+        pages - self.level as u64 - 1
+    }
+
+    /// Inverse of [FsmAddress::physical_block]
+    pub fn of_physical_block(block: u32) -> FsmAddress {
+        if block == 0 {
+            return FsmAddress {
+                level: FSM_TREE_DEPTH - 1,
+                logical_page: 0,
+            };
+        }
+        let subtree_pages = SLOTS_PER_FSM_PAGE + 1;
+        let (subtree, offset) = ((block - 1) / subtree_pages, (block - 1) % subtree_pages);
+        match offset {
+            0 => FsmAddress {
+                level: 1,
+                logical_page: subtree,
+            },
+            offset => FsmAddress {
+                level: 0,
+                logical_page: subtree * SLOTS_PER_FSM_PAGE + offset - 1,
+            },
+        }
+    }
+}
+
+/// Decoded FSMPageData, a page which has never been initialized has all the categories 0
+pub struct FsmPage {
+    /// Slot the next search starts at, spreads concurrent inserts over the heap
+    pub next_slot: i32,
+    nodes: Vec<u8>,
+}
+
+impl FsmPage {
+    pub fn parse(page: &Page) -> Result<Self> {
+        let bytes = page.bytes();
+        if bytes.len() < PAGE_HEADER_SIZE + 4 + NODES_PER_PAGE {
+            bail!(
+                "FSM page is expected to be {} bytes, got {}",
+                BLCKSZ,
+                bytes.len()
+            );
+        }
+        Ok(FsmPage {
+            next_slot: bytes.i32_at(PAGE_HEADER_SIZE)?,
+            nodes: bytes[PAGE_HEADER_SIZE + 4..PAGE_HEADER_SIZE + 4 + NODES_PER_PAGE].to_vec(),
+        })
+    }
+
+    /// Category of the root node, the largest one of the page as far as the tree is up to date
+    pub fn root(&self) -> u8 {
+        self.nodes[0]
+    }
+
+    /// Category the leaf of the slot holds
+    pub fn slot(&self, slot: u32) -> u8 {
+        self.nodes[NON_LEAF_NODES_PER_PAGE + slot as usize]
+    }
+}
+
+/// Decoded free space map fork, heap blocks beyond its leaf pages are recorded as full
+pub struct FreeSpaceMap {
+    pages: Vec<FsmPage>,
+}
+
+impl FreeSpaceMap {
+    /// Decodes the pages of the fork in block order
+    pub fn new(pages: &[Page]) -> Result<Self> {
+        Ok(FreeSpaceMap {
+            pages: pages.iter().map(FsmPage::parse).collect::<Result<_>>()?,
+        })
+    }
+
+    /// Pages of the fork with their logical positions
+    pub fn pages(&self) -> impl Iterator<Item = (FsmAddress, &FsmPage)> {
+        self.pages
+            .iter()
+            .enumerate()
+            .map(|(block, page)| (FsmAddress::of_physical_block(block as u32), page))
+    }
+
+    /// Category the map records for the heap block, see fsm_get_location
+    pub fn category(&self, heap_block: u32) -> u8 {
+        let address = FsmAddress {
+            level: 0,
+            logical_page: heap_block / SLOTS_PER_FSM_PAGE,
+        };
+        self.pages
+            .get(address.physical_block() as usize)
+            .map_or(0, |page| page.slot(heap_block % SLOTS_PER_FSM_PAGE))
+    }
+
+    /// Compares the recorded category of each heap block with the one of its actual free
+    /// space, None for pages which could not be read, and groups the blocks into runs of the
+    /// same recorded and actual categories
+    pub fn compare(&self, actual_avail: &[Option<u32>]) -> Vec<FreeSpaceRun> {
+        let categories = actual_avail.iter().enumerate().map(|(block, avail)| {
+            (
+                block as u32,
+                (self.category(block as u32), avail.map(avail_to_category)),
+            )
+        });
+        group_runs(categories)
+            .into_iter()
+            .map(|(blocks, (recorded, actual))| FreeSpaceRun {
+                blocks,
+                recorded,
+                actual,
+            })
+            .collect()
+    }
+}
+
+/// Heap blocks with the same recorded and actual free space categories
+#[derive(Debug, PartialEq)]
+pub struct FreeSpaceRun {
+    pub blocks: RangeInclusive<u32>,
+    pub recorded: u8,
+    /// Category of the free space of the pages, None when they could not be read
+    pub actual: Option<u8>,
+}
+
+impl FreeSpaceRun {
+    /// Tells whether the map records a different category than the pages have
+    pub fn is_stale(&self) -> bool {
+        self.actual.is_some_and(|actual| actual != self.recorded)
+    }
+}
+
+/// Free space of a heap page as vacuum records it, PageGetHeapFreeSpace: the gap between
+/// pd_lower and pd_upper less a line pointer for the next tuple. A page holding the most line
+/// pointers it may has no room for another tuple unless one of them is unused. A page which has
+/// never been initialized is recorded as empty
+pub fn heap_page_avail(page: &Page) -> Result<u32> {
+    if page.is_new() {
+        return Ok(BLCKSZ as u32 - PAGE_HEADER_SIZE as u32);
+    }
+    let header = page.header()?;
+    let free_space = (header.free_space() as u32).saturating_sub(LINE_POINTER_SIZE as u32);
+    if free_space > 0 {
+        let line_pointers = page.line_pointers()?;
+        if line_pointers.len() >= MAX_HEAP_TUPLES_PER_PAGE
+            && (header.flags & PD_HAS_FREE_LINES == 0
+                || line_pointers
+                    .iter()
+                    .all(|line_pointer| line_pointer.state != LinePointerState::Unused))
+        {
+            return Ok(0);
+        }
+    }
+    Ok(free_space)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::pgdata::page::{
+        test_pages::{heap_tuple, PageBuilder},
+        Page, PAGE_HEADER_SIZE, PD_HAS_FREE_LINES,
+    };
+
+    use super::{
+        avail_to_category, category_to_avail, heap_page_avail, FreeSpaceMap, FreeSpaceRun,
+        FsmAddress, MAX_HEAP_TUPLES_PER_PAGE, NON_LEAF_NODES_PER_PAGE,
+    };
+
+    fn address(level: u32, logical_page: u32) -> FsmAddress {
+        FsmAddress {
+            level,
+            logical_page,
+        }
+    }
+
+    #[rstest]
+    #[case(address(2, 0), 0)]
+    #[case(address(1, 0), 1)]
+    #[case(address(0, 0), 2)]
+    #[case(address(0, 1), 3)]
+    #[case(address(0, 4068), 4070)]
+    #[case(address(1, 1), 4071)]
+    #[case(address(0, 4069), 4072)]
+    fn maps_logical_to_physical_blocks(#[case] address: FsmAddress, #[case] block: u64) {
+        // when
+        let physical_block = address.physical_block();
+        let logical_address = FsmAddress::of_physical_block(block as u32);
+
+        // then
+        assert_eq!(physical_block, block);
+        assert_eq!(logical_address, address);
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(31, 0)]
+    #[case(32, 1)]
+    #[case(8127, 253)]
+    #[case(8159, 254)]
+    #[case(8160, 255)]
+    fn converts_free_space_to_category(#[case] avail: u32, #[case] category: u8) {
+        // when
+        let converted = avail_to_category(avail);
+
+        // then
+        assert_eq!(converted, category);
+        assert!(category_to_avail(category) <= avail);
+    }
+
+    #[test]
+    fn compares_recorded_and_actual_categories() {
+        // given
+        let leaf_page = |categories: &[u8]| {
+            let mut page = vec![0; 8192];
+            page[PAGE_HEADER_SIZE..PAGE_HEADER_SIZE + 4].copy_from_slice(&2i32.to_le_bytes());
+            let leaves = PAGE_HEADER_SIZE + 4 + NON_LEAF_NODES_PER_PAGE;
+            page[leaves..leaves + categories.len()].copy_from_slice(categories);
+            Page::new(page).unwrap()
+        };
+        let map = FreeSpaceMap::new(&[
+            Page::new(vec![0; 8192]).unwrap(),
+            Page::new(vec![0; 8192]).unwrap(),
+            leaf_page(&[254, 254, 3, 0]),
+        ])
+        .unwrap();
+        let actual_avail = [Some(8130), Some(8140), Some(100), None, Some(0)];
+
+        // when
+        let runs = map.compare(&actual_avail);
+
+        // then
+        assert_eq!(map.pages().nth(2).map(|(_, page)| page.next_slot), Some(2));
+        assert_eq!(
+            runs,
+            vec![
+                FreeSpaceRun {
+                    blocks: 0..=1,
+                    recorded: 254,
+                    actual: Some(254)
+                },
+                FreeSpaceRun {
+                    blocks: 2..=2,
+                    recorded: 3,
+                    actual: Some(3)
+                },
+                FreeSpaceRun {
+                    blocks: 3..=3,
+                    recorded: 0,
+                    actual: None
+                },
+                FreeSpaceRun {
+                    blocks: 4..=4,
+                    recorded: 0,
+                    actual: Some(0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn computes_heap_page_free_space() {
+        // given
+        let page = Page::new(
+            PageBuilder::empty()
+                .item(&heap_tuple(740, 0, 0, 0, &[]))
+                .build(),
+        )
+        .unwrap();
+        let new_page = Page::new(vec![0; 8192]).unwrap();
+        let full_page = |flags: u16, unused: usize| {
+            let page = (0..MAX_HEAP_TUPLES_PER_PAGE)
+                .fold(PageBuilder::empty().flags(flags), |page, number| {
+                    page.line_pointer(0, if number == unused { 0 } else { 3 }, 0)
+                });
+            Page::new(page.build()).unwrap()
+        };
+
+        // when
+        let avail = [
+            &page,
+            &new_page,
+            &full_page(0, 0),
+            &full_page(PD_HAS_FREE_LINES, MAX_HEAP_TUPLES_PER_PAGE),
+            &full_page(PD_HAS_FREE_LINES, 7),
+        ]
+        .map(heap_page_avail)
+        .map(Result::unwrap);
+
+        // then
+        assert_eq!(avail, [8168 - 4 - 24 - 4, 8168, 0, 0, 8168 - 291 * 4 - 4]);
+    }
+}
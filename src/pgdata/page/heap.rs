@@ -0,0 +1,536 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    common::bytes::ReadBytes,
+    pgdata::{
+        pg_multiexact::{MultiXactStatus, PGMultixact},
+        pg_xact::{PGXact, XactStatus},
+        transactions::Transactions,
+    },
+};
+
+use super::ItemPointer;
+
+/// Size of the fixed part of HeapTupleHeaderData
+pub const HEAP_TUPLE_HEADER_SIZE: usize = 23;
+
+pub const HEAP_HASNULL: u16 = 0x0001;
+pub const HEAP_HASVARWIDTH: u16 = 0x0002;
+pub const HEAP_HASEXTERNAL: u16 = 0x0004;
+pub const HEAP_HASOID_OLD: u16 = 0x0008;
+pub const HEAP_XMAX_KEYSHR_LOCK: u16 = 0x0010;
+pub const HEAP_COMBOCID: u16 = 0x0020;
+pub const HEAP_XMAX_EXCL_LOCK: u16 = 0x0040;
+pub const HEAP_XMAX_LOCK_ONLY: u16 = 0x0080;
+pub const HEAP_XMIN_COMMITTED: u16 = 0x0100;
+pub const HEAP_XMIN_INVALID: u16 = 0x0200;
+pub const HEAP_XMAX_COMMITTED: u16 = 0x0400;
+pub const HEAP_XMAX_INVALID: u16 = 0x0800;
+pub const HEAP_XMAX_IS_MULTI: u16 = 0x1000;
+pub const HEAP_UPDATED: u16 = 0x2000;
+pub const HEAP_MOVED_OFF: u16 = 0x4000;
+pub const HEAP_MOVED_IN: u16 = 0x8000;
+
+/// Both xmin hint bits set mark a frozen tuple rather than an invalid xmin
+pub const HEAP_XMIN_FROZEN: u16 = HEAP_XMIN_COMMITTED | HEAP_XMIN_INVALID;
+/// Lock strength bits of xmax, both of them make a share lock
+pub const HEAP_LOCK_MASK: u16 = HEAP_XMAX_EXCL_LOCK | HEAP_XMAX_KEYSHR_LOCK;
+
+pub const HEAP_NATTS_MASK: u16 = 0x07FF;
+pub const HEAP_KEYS_UPDATED: u16 = 0x2000;
+pub const HEAP_HOT_UPDATED: u16 = 0x4000;
+pub const HEAP_ONLY_TUPLE: u16 = 0x8000;
+
+const INFOMASK_FLAGS: [(u16, &str); 16] = [
+    (HEAP_HASNULL, "HEAP_HASNULL"),
+    (HEAP_HASVARWIDTH, "HEAP_HASVARWIDTH"),
+    (HEAP_HASEXTERNAL, "HEAP_HASEXTERNAL"),
+    (HEAP_HASOID_OLD, "HEAP_HASOID_OLD"),
+    (HEAP_XMAX_KEYSHR_LOCK, "HEAP_XMAX_KEYSHR_LOCK"),
+    (HEAP_COMBOCID, "HEAP_COMBOCID"),
+    (HEAP_XMAX_EXCL_LOCK, "HEAP_XMAX_EXCL_LOCK"),
+    (HEAP_XMAX_LOCK_ONLY, "HEAP_XMAX_LOCK_ONLY"),
+    (HEAP_XMIN_COMMITTED, "HEAP_XMIN_COMMITTED"),
+    (HEAP_XMIN_INVALID, "HEAP_XMIN_INVALID"),
+    (HEAP_XMAX_COMMITTED, "HEAP_XMAX_COMMITTED"),
+    (HEAP_XMAX_INVALID, "HEAP_XMAX_INVALID"),
+    (HEAP_XMAX_IS_MULTI, "HEAP_XMAX_IS_MULTI"),
+    (HEAP_UPDATED, "HEAP_UPDATED"),
+    (HEAP_MOVED_OFF, "HEAP_MOVED_OFF"),
+    (HEAP_MOVED_IN, "HEAP_MOVED_IN"),
+];
+
+const INFOMASK2_FLAGS: [(u16, &str); 3] = [
+    (HEAP_KEYS_UPDATED, "HEAP_KEYS_UPDATED"),
+    (HEAP_HOT_UPDATED, "HEAP_HOT_UPDATED"),
+    (HEAP_ONLY_TUPLE, "HEAP_ONLY_TUPLE"),
+];
+
+/// Decoded HeapTupleHeaderData
+#[derive(Debug, PartialEq)]
+pub struct HeapTupleHeader {
+    pub xmin: u32,
+    pub xmax: u32,
+    /// Either t_cid or t_xvac depending on HEAP_MOVED flags
+    pub field3: u32,
+    pub ctid: ItemPointer,
+    pub infomask2: u16,
+    pub infomask: u16,
+    pub hoff: u8,
+    pub null_bitmap: Option<Vec<u8>>,
+    /// Object id stored in the header by tables created WITH OIDS before PostgreSQL 12
+    pub oid: Option<u32>,
+}
+
+impl HeapTupleHeader {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let infomask2 = bytes.u16_at(18)?;
+        let infomask = bytes.u16_at(20)?;
+        let hoff = bytes.u8_at(22)?;
+        if (hoff as usize) < HEAP_TUPLE_HEADER_SIZE || hoff as usize > bytes.len() {
+            bail!(
+                "Invalid heap tuple header: t_hoff = {}, tuple length = {}",
+                hoff,
+                bytes.len()
+            );
+        }
+
+        let natts = (infomask2 & HEAP_NATTS_MASK) as usize;
+        let null_bitmap = match infomask & HEAP_HASNULL {
+            0 => None,
+            _ => Some(
+                bytes
+                    .bytes_at(HEAP_TUPLE_HEADER_SIZE, natts.div_ceil(8))?
+                    .to_vec(),
+            ),
+        };
+        let oid = match infomask & HEAP_HASOID_OLD {
+            0 => None,
+            _ => Some(bytes.u32_at(hoff as usize - 4)?),
+        };
+
+        Ok(HeapTupleHeader {
+            xmin: bytes.u32_at(0)?,
+            xmax: bytes.u32_at(4)?,
+            field3: bytes.u32_at(8)?,
+            ctid: ItemPointer::parse(bytes.bytes_at(12, 6)?)?,
+            infomask2,
+            infomask,
+            hoff,
+            null_bitmap,
+            oid,
+        })
+    }
+
+    /// Number of attributes stored in the tuple
+    pub fn natts(&self) -> u16 {
+        self.infomask2 & HEAP_NATTS_MASK
+    }
+
+    /// Command id of the inserting and/or deleting transaction
+    pub fn cid(&self) -> Option<u32> {
+        match self.infomask & (HEAP_MOVED_OFF | HEAP_MOVED_IN) {
+            0 => Some(self.field3),
+            _ => None,
+        }
+    }
+
+    /// Transaction id of a pre-9.0 VACUUM FULL which moved the tuple
+    pub fn xvac(&self) -> Option<u32> {
+        match self.infomask & (HEAP_MOVED_OFF | HEAP_MOVED_IN) {
+            0 => None,
+            _ => Some(self.field3),
+        }
+    }
+
+    /// Tells whether the attribute with the given 0-based number is null
+    pub fn is_null(&self, attnum: usize) -> bool {
+        match &self.null_bitmap {
+            None => attnum >= self.natts() as usize,
+            Some(bitmap) => bitmap
+                .get(attnum / 8)
+                .is_none_or(|byte| byte & (1 << (attnum % 8)) == 0),
+        }
+    }
+
+    /// Judges whether the tuple version is live by hint bits, looking the transactions whose
+    /// status is not hinted up in pg_xact and the updater of a multixact xmax up in
+    /// pg_multixact. Inserts and deletes of transactions in progress do not count yet, an xmax
+    /// which only locks the tuple does not delete it. A status which cannot be looked up is an
+    /// error rather than a guess
+    pub fn is_live(&self, transactions: &impl Transactions) -> Result<bool> {
+        let committed = |xid: u32| {
+            Ok::<_, anyhow::Error>(transactions.pg_xact().status(xid)? == XactStatus::Committed)
+        };
+        let inserted = match self.infomask & HEAP_XMIN_FROZEN {
+            HEAP_XMIN_INVALID => false,
+            0 => committed(self.xmin)?,
+            _ => true,
+        };
+        if !inserted
+            || self.xmax == 0
+            || self.has_infomask(HEAP_XMAX_INVALID)
+            || self.is_locked_only()
+        {
+            return Ok(inserted);
+        }
+        let deleted = match self.has_infomask(HEAP_XMAX_IS_MULTI) {
+            true => match transactions
+                .pg_multixact()
+                .members(self.xmax)?
+                .into_iter()
+                .find(|member| {
+                    matches!(
+                        member.status,
+                        MultiXactStatus::NoKeyUpdate | MultiXactStatus::Update
+                    )
+                }) {
+                Some(updater) => committed(updater.xid)?,
+                None => false,
+            },
+            false => self.has_infomask(HEAP_XMAX_COMMITTED) || committed(self.xmax)?,
+        };
+        Ok(!deleted)
+    }
+
+    /// Tells whether xmax only locks the tuple as HEAP_XMAX_IS_LOCKED_ONLY does, a tuple locked
+    /// exclusively before 9.3 carries the exclusive lock bit alone
+    pub fn is_locked_only(&self) -> bool {
+        self.has_infomask(HEAP_XMAX_LOCK_ONLY)
+            || self.infomask & (HEAP_XMAX_IS_MULTI | HEAP_LOCK_MASK) == HEAP_XMAX_EXCL_LOCK
+    }
+
+    pub fn has_infomask(&self, flag: u16) -> bool {
+        self.infomask & flag != 0
+    }
+
+    pub fn infomask_flags(&self) -> Vec<&'static str> {
+        flag_names(self.infomask, &INFOMASK_FLAGS)
+    }
+
+    pub fn infomask2_flags(&self) -> Vec<&'static str> {
+        flag_names(self.infomask2, &INFOMASK2_FLAGS)
+    }
+
+    /// Renders the null bitmap the way pageinspect does, one char per attribute
+    pub fn null_bitmap_string(&self) -> Option<String> {
+        self.null_bitmap.as_ref().map(|_| {
+            (0..self.natts() as usize)
+                .map(|attnum| if self.is_null(attnum) { '0' } else { '1' })
+                .collect()
+        })
+    }
+}
+
+fn flag_names(mask: u16, flags: &[(u16, &'static str)]) -> Vec<&'static str> {
+    flags
+        .iter()
+        .filter(|(flag, _)| mask & flag != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Heap tuple header and the attribute data following it
+#[derive(Debug, PartialEq)]
+pub struct HeapTuple<'a> {
+    pub header: HeapTupleHeader,
+    pub data: &'a [u8],
+}
+
+impl<'a> HeapTuple<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        let header = HeapTupleHeader::parse(bytes)?;
+        let data = &bytes[header.hoff as usize..];
+        Ok(HeapTuple { header, data })
+    }
+
+    /// Splits the data into the raw values of the attributes the way heap_deform_tuple does,
+    /// None for nulls and for attributes added after the tuple was written. Varlena values keep
+    /// their headers, attributes past the given layouts are not decoded
+    pub fn attributes(&self, layouts: &[AttributeLayout]) -> Result<Vec<Option<&'a [u8]>>> {
+        let mut offset = 0;
+        layouts
+            .iter()
+            .enumerate()
+            .map(|(attnum, layout)| {
+                if attnum >= self.header.natts() as usize || self.header.is_null(attnum) {
+                    return Ok(None);
+                }
+                // short varlena values are not aligned, a pad byte is always zero
+                if layout.len != VARLENA || self.data.u8_at(offset)? == 0 {
+                    offset = layout.align.apply(offset);
+                }
+                let len = match layout.len {
+                    VARLENA => varsize_any(&self.data[offset.min(self.data.len())..])?,
+                    CSTRING => self.data[offset.min(self.data.len())..]
+                        .iter()
+                        .position(|byte| *byte == 0)
+                        .map_or(self.data.len(), |len| len + 1),
+                    len => len as usize,
+                };
+                let value = self.data.bytes_at(offset, len)?;
+                offset += len;
+                Ok(Some(value))
+            })
+            .collect()
+    }
+}
+
+/// attlen of variable length types
+pub const VARLENA: i16 = -1;
+
+/// attlen of zero-terminated strings
+pub const CSTRING: i16 = -2;
+
+/// Size of varattrib_1b_e header preceding a TOAST pointer
+const VARHDRSZ_EXTERNAL: usize = 2;
+
+/// vartag of a TOAST pointer to an on-disk value
+pub const VARTAG_ONDISK: u8 = 18;
+
+/// Size of varatt_external which a VARTAG_ONDISK TOAST pointer holds
+const TOAST_POINTER_SIZE: usize = 16;
+
+/// Total size of a varlena value including its header, see VARSIZE_ANY
+pub fn varsize_any(bytes: &[u8]) -> Result<usize> {
+    let first = bytes.u8_at(0)?;
+    let size = match first {
+        0x01 => match bytes.u8_at(1)? {
+            VARTAG_ONDISK => VARHDRSZ_EXTERNAL + TOAST_POINTER_SIZE,
+            // in-memory pointers never reach the disk
+            tag => bail!("Unexpected vartag {} of an external varlena value", tag),
+        },
+        first if first & 0x01 != 0 => (first >> 1) as usize,
+        _ => (bytes.u32_at(0)? >> 2) as usize,
+    };
+    if size == 0 {
+        bail!("Invalid varlena header {:#04x}", first);
+    }
+    Ok(size)
+}
+
+/// Storage of an attribute as pg_attribute describes it by attlen and attalign
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AttributeLayout {
+    /// Length of fixed length values, [VARLENA] or [CSTRING]
+    pub len: i16,
+    pub align: Alignment,
+}
+
+impl AttributeLayout {
+    pub const fn new(len: i16, align: Alignment) -> Self {
+        AttributeLayout { len, align }
+    }
+}
+
+/// Alignment of attribute values within tuple data, attalign of pg_attribute
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Alignment {
+    Char,
+    Short,
+    Int,
+    Double,
+}
+
+impl Alignment {
+    pub fn parse(typalign: u8) -> Result<Self> {
+        Ok(match typalign {
+            b'c' => Alignment::Char,
+            b's' => Alignment::Short,
+            b'i' => Alignment::Int,
+            b'd' => Alignment::Double,
+            typalign => bail!("Unknown alignment {:?}", typalign as char),
+        })
+    }
+
+    pub fn bytes(&self) -> usize {
+        match self {
+            Alignment::Char => 1,
+            Alignment::Short => 2,
+            Alignment::Int => 4,
+            Alignment::Double => 8,
+        }
+    }
+
+    /// Rounds the offset up to the alignment
+    pub fn apply(&self, offset: usize) -> usize {
+        offset.next_multiple_of(self.bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::pgdata::{
+        page::{test_pages::heap_tuple, ItemPointer},
+        transactions::test_stubs::StubTransactions,
+    };
+
+    use super::{
+        Alignment, AttributeLayout, HeapTuple, HeapTupleHeader, CSTRING, HEAP_XMAX_COMMITTED,
+        HEAP_XMAX_EXCL_LOCK, HEAP_XMAX_INVALID, HEAP_XMAX_IS_MULTI, HEAP_XMAX_KEYSHR_LOCK,
+        HEAP_XMAX_LOCK_ONLY, HEAP_XMIN_FROZEN, HEAP_XMIN_INVALID, VARLENA,
+    };
+
+    #[test]
+    fn decodes_heap_tuple_header() {
+        // given
+        let mut bytes = heap_tuple(740, 741, 0x0102 | 0x0400, 3, &[7, 0, 0, 0]);
+        bytes[8..12].copy_from_slice(&2u32.to_le_bytes());
+        bytes[18..20].copy_from_slice(&(0x4000u16 | 3).to_le_bytes());
+
+        // when
+        let tuple = HeapTuple::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(
+            tuple.header,
+            HeapTupleHeader {
+                xmin: 740,
+                xmax: 741,
+                field3: 2,
+                ctid: ItemPointer {
+                    block_number: 1,
+                    offset_number: 1
+                },
+                infomask2: 0x4003,
+                infomask: 0x0502,
+                hoff: 24,
+                null_bitmap: None,
+                oid: None,
+            }
+        );
+        assert_eq!(tuple.data, &[7, 0, 0, 0]);
+        assert_eq!(tuple.header.natts(), 3);
+        assert_eq!(tuple.header.cid(), Some(2));
+        assert_eq!(tuple.header.xvac(), None);
+        assert_eq!(
+            tuple.header.infomask_flags(),
+            vec![
+                "HEAP_HASVARWIDTH",
+                "HEAP_XMIN_COMMITTED",
+                "HEAP_XMAX_COMMITTED"
+            ]
+        );
+        assert_eq!(tuple.header.infomask2_flags(), vec!["HEAP_HOT_UPDATED"]);
+    }
+
+    #[test]
+    fn decodes_null_bitmap() {
+        // given
+        let mut bytes = heap_tuple(740, 0, 0x0001, 10, &[]);
+        bytes[23] = 0b1111_0101;
+        bytes.extend_from_slice(&[0b0000_0010, 0, 0, 0, 0, 0, 0, 0]);
+        bytes[22] = 32;
+
+        // when
+        let header = HeapTupleHeader::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(header.null_bitmap, Some(vec![0b1111_0101, 0b0000_0010]));
+        assert_eq!(header.null_bitmap_string(), Some("1010111101".to_string()));
+        assert!(header.is_null(1));
+        assert!(!header.is_null(9));
+    }
+
+    #[rstest]
+    #[case(740, 0, 0, Ok(true))]
+    #[case(740, 0, HEAP_XMIN_INVALID, Ok(false))]
+    #[case(741, 0, HEAP_XMIN_FROZEN, Ok(true))]
+    #[case(741, 0, 0, Ok(false))]
+    #[case(742, 0, 0, Ok(false))]
+    #[case(900, 0, 0, Err("Status of xid 900 is missing"))]
+    #[case(741, 900, 0, Ok(false))]
+    #[case(740, 740, 0, Ok(false))]
+    #[case(740, 741, 0, Ok(true))]
+    #[case(740, 742, 0, Ok(true))]
+    #[case(740, 900, 0, Err("Status of xid 900 is missing"))]
+    #[case(740, 741, HEAP_XMAX_COMMITTED, Ok(false))]
+    #[case(740, 740, HEAP_XMAX_INVALID, Ok(true))]
+    #[case(740, 740, HEAP_XMAX_LOCK_ONLY, Ok(true))]
+    #[case(740, 740, HEAP_XMAX_EXCL_LOCK, Ok(true))]
+    #[case(740, 740, HEAP_XMAX_EXCL_LOCK | HEAP_XMAX_KEYSHR_LOCK, Ok(false))]
+    #[case(740, 7, HEAP_XMAX_IS_MULTI, Ok(true))]
+    #[case(740, 9, HEAP_XMAX_IS_MULTI, Ok(true))]
+    #[case(740, 10, HEAP_XMAX_IS_MULTI, Ok(false))]
+    #[case(740, 11, HEAP_XMAX_IS_MULTI | HEAP_XMAX_EXCL_LOCK, Ok(true))]
+    #[case(740, 5, HEAP_XMAX_IS_MULTI, Err("Members of multixact 5 are missing"))]
+    fn tells_live_tuples(
+        #[case] xmin: u32,
+        #[case] xmax: u32,
+        #[case] infomask: u16,
+        #[case] expected: Result<bool, &str>,
+    ) {
+        // given
+        let bytes = heap_tuple(xmin, xmax, infomask, 1, &[]);
+
+        // when
+        let header = HeapTupleHeader::parse(&bytes).unwrap();
+
+        // then
+        assert_eq!(
+            header
+                .is_live(&StubTransactions)
+                .map_err(|err| err.to_string()),
+            expected.map_err(String::from)
+        );
+    }
+
+    #[test]
+    fn deforms_attributes() {
+        // given
+        // int2, null int4, short varlena 'ab', int4 aligned after it, 4-byte header varlena,
+        // cstring, attribute missing from the tuple
+        let layouts = [
+            AttributeLayout::new(2, Alignment::Short),
+            AttributeLayout::new(4, Alignment::Int),
+            AttributeLayout::new(VARLENA, Alignment::Int),
+            AttributeLayout::new(4, Alignment::Int),
+            AttributeLayout::new(VARLENA, Alignment::Int),
+            AttributeLayout::new(CSTRING, Alignment::Char),
+            AttributeLayout::new(8, Alignment::Double),
+        ];
+        let mut data = vec![7, 0, 0x07, b'a', b'b', 0, 0, 0, 9, 0, 0, 0];
+        data.extend([(6 << 2) as u8, 0, 0, 0, b'x', b'y', b'z', 0]);
+        let mut bytes = heap_tuple(740, 0, 0x0001, 6, &data);
+        bytes[23] = 0b0011_1101;
+        bytes.splice(24..24, [0; 8]);
+        bytes[22] = 32;
+
+        // when
+        let tuple = HeapTuple::parse(&bytes).unwrap();
+        let attributes = tuple.attributes(&layouts).unwrap();
+
+        // then
+        assert_eq!(
+            attributes,
+            vec![
+                Some(&[7, 0][..]),
+                None,
+                Some(&[0x07, b'a', b'b'][..]),
+                Some(&[9, 0, 0, 0][..]),
+                Some(&[(6 << 2) as u8, 0, 0, 0, b'x', b'y'][..]),
+                Some(&[b'z', 0][..]),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_header_offset() {
+        // given
+        let mut bytes = heap_tuple(740, 0, 0, 1, &[]);
+        bytes[22] = 200;
+
+        // when
+        let result = HeapTupleHeader::parse(&bytes);
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some("Invalid heap tuple header: t_hoff = 200, tuple length = 24".to_string())
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::pgdata::BLCKSZ;
+
+use super::{group_runs, Page, PAGE_HEADER_SIZE};
+
+/// All tuples of the heap block are visible to all transactions
+pub const VISIBILITYMAP_ALL_VISIBLE: u8 = 0x01;
+
+/// All tuples of the heap block are frozen
+pub const VISIBILITYMAP_ALL_FROZEN: u8 = 0x02;
+
+/// Bits the visibility map keeps per heap block
+pub const BITS_PER_HEAPBLOCK: u32 = 2;
+
+/// Bytes of a visibility map page following the page header
+const MAPSIZE: u32 = BLCKSZ as u32 - PAGE_HEADER_SIZE as u32;
+
+/// Heap blocks a single visibility map page covers
+pub const HEAPBLOCKS_PER_PAGE: u32 = MAPSIZE * 8 / BITS_PER_HEAPBLOCK;
+
+/// Decoded visibility map fork, heap blocks beyond the last page have both bits clear
+pub struct VisibilityMap {
+    /// Bits of each heap block the pages cover
+    bits: Vec<u8>,
+}
+
+impl VisibilityMap {
+    /// Decodes the pages of the fork in block order, a page which has never been initialized
+    /// has all the bits clear
+    pub fn new(pages: &[Page]) -> Self {
+        VisibilityMap {
+            bits: pages
+                .iter()
+                .flat_map(|page| &page.bytes()[PAGE_HEADER_SIZE..])
+                .flat_map(|byte| {
+                    (0..8 / BITS_PER_HEAPBLOCK)
+                        .map(move |i| (byte >> (i * BITS_PER_HEAPBLOCK)) & 0x03)
+                })
+                .collect(),
+        }
+    }
+
+    /// Number of heap blocks the pages of the map cover
+    pub fn heap_blocks(&self) -> u64 {
+        self.bits.len() as u64
+    }
+
+    /// Bits of the heap block, a combination of [VISIBILITYMAP_ALL_VISIBLE] and
+    /// [VISIBILITYMAP_ALL_FROZEN]
+    pub fn bits(&self, heap_block: u32) -> u8 {
+        self.bits.get(heap_block as usize).copied().unwrap_or(0)
+    }
+
+    /// Counts the all-visible and all-frozen blocks among the first `heap_blocks` blocks
+    pub fn counts(&self, heap_blocks: u32) -> VisibilityCounts {
+        let count = |flag: u8| {
+            (0..heap_blocks)
+                .filter(|block| self.bits(*block) & flag != 0)
+                .count() as u32
+        };
+        VisibilityCounts {
+            all_visible: count(VISIBILITYMAP_ALL_VISIBLE),
+            all_frozen: count(VISIBILITYMAP_ALL_FROZEN),
+        }
+    }
+
+    /// Groups the first `heap_blocks` blocks into runs of blocks with the same bits
+    pub fn runs(&self, heap_blocks: u32) -> Vec<(RangeInclusive<u32>, u8)> {
+        group_runs((0..heap_blocks).map(|block| (block, self.bits(block))))
+    }
+
+    /// Compares the bits of each heap block with PD_ALL_VISIBLE of its page, None for pages
+    /// which could not be read. Bits set for blocks past the end of the main fork are reported
+    /// too, the map is truncated along with the relation
+    pub fn check(&self, pages_all_visible: &[Option<bool>]) -> Vec<VisibilityMismatch> {
+        let kinds =
+            (0..self.heap_blocks().max(pages_all_visible.len() as u64)).filter_map(|block| {
+                let bits = self.bits(block as u32);
+                let kind = match pages_all_visible.get(block as usize) {
+                    None if bits != 0 => Some(MismatchKind::BeyondMainFork),
+                    None | Some(None) => None,
+                    Some(Some(_)) if bits == VISIBILITYMAP_ALL_FROZEN => {
+                        Some(MismatchKind::FrozenNotVisible)
+                    }
+                    Some(Some(false)) if bits & VISIBILITYMAP_ALL_VISIBLE != 0 => {
+                        Some(MismatchKind::PageNotAllVisible)
+                    }
+                    Some(Some(true)) if bits & VISIBILITYMAP_ALL_VISIBLE == 0 => {
+                        Some(MismatchKind::BitNotSet)
+                    }
+                    Some(Some(_)) => None,
+                };
+                kind.map(|kind| (block as u32, kind))
+            });
+        group_runs(kinds)
+            .into_iter()
+            .map(|(blocks, kind)| VisibilityMismatch { blocks, kind })
+            .collect()
+    }
+}
+
+/// Numbers of all-visible and all-frozen heap blocks
+#[derive(Debug, PartialEq)]
+pub struct VisibilityCounts {
+    pub all_visible: u32,
+    pub all_frozen: u32,
+}
+
+/// Heap blocks whose visibility map bits disagree with their pages
+#[derive(Debug, PartialEq)]
+pub struct VisibilityMismatch {
+    pub blocks: RangeInclusive<u32>,
+    pub kind: MismatchKind,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MismatchKind {
+    /// The map marks the block all-visible but its page has no PD_ALL_VISIBLE, vacuum warns about
+    /// this one as the map makes index-only scans skip the heap
+    PageNotAllVisible,
+    /// The page has PD_ALL_VISIBLE but the map does not mark the block all-visible
+    BitNotSet,
+    /// The map marks the block all-frozen but not all-visible, which vacuum never does
+    FrozenNotVisible,
+    /// The map has bits set for a block past the end of the main fork
+    BeyondMainFork,
+}
+
+impl Display for VisibilityMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.blocks.start() == self.blocks.end() {
+            true => write!(f, "block {}: ", self.blocks.start())?,
+            false => write!(f, "blocks {}..{}: ", self.blocks.start(), self.blocks.end())?,
+        }
+        f.write_str(match self.kind {
+            MismatchKind::PageNotAllVisible => {
+                "visibility map bit is set but PD_ALL_VISIBLE is not"
+            }
+            MismatchKind::BitNotSet => "PD_ALL_VISIBLE is set but visibility map bit is not",
+            MismatchKind::FrozenNotVisible => "all-frozen but not all-visible",
+            MismatchKind::BeyondMainFork => {
+                "visibility map bits are set past the end of the main fork"
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::pgdata::page::{Page, PAGE_HEADER_SIZE};
+
+    use super::{
+        MismatchKind, VisibilityCounts, VisibilityMap, VisibilityMismatch, HEAPBLOCKS_PER_PAGE,
+    };
+
+    fn visibility_map(bytes: &[(u32, u8)]) -> VisibilityMap {
+        let pages = (0..2)
+            .map(|page_number| {
+                let mut page = vec![0; 8192];
+                bytes
+                    .iter()
+                    .filter(|(block, _)| block / HEAPBLOCKS_PER_PAGE == page_number)
+                    .for_each(|(block, byte)| {
+                        page[PAGE_HEADER_SIZE + (block % HEAPBLOCKS_PER_PAGE / 4) as usize] = *byte
+                    });
+                Page::new(page).unwrap()
+            })
+            .collect::<Vec<_>>();
+        VisibilityMap::new(&pages)
+    }
+
+    #[test]
+    fn decodes_bits_per_heap_block() {
+        // given
+        // blocks 0..=3 in the first byte from the least significant bits, block 32672 starts
+        // the second page
+        let map = visibility_map(&[(0, 0b01_00_11_11), (32672, 0b11)]);
+
+        // when
+        let bits = [0, 1, 2, 3, 4, 32672, 32673, 70000].map(|block| map.bits(block));
+
+        // then
+        assert_eq!(map.heap_blocks(), 2 * 32672);
+        assert_eq!(bits, [3, 3, 0, 1, 0, 3, 0, 0]);
+        assert_eq!(
+            map.counts(32673),
+            VisibilityCounts {
+                all_visible: 4,
+                all_frozen: 3
+            }
+        );
+        assert_eq!(
+            map.runs(6),
+            vec![(0..=1, 3), (2..=2, 0), (3..=3, 1), (4..=5, 0)]
+        );
+    }
+
+    #[test]
+    fn cross_checks_page_flags() {
+        // given
+        let map = visibility_map(&[(0, 0b01_01_11_11), (4, 0b10_00_01_01), (8, 0b01)]);
+        let pages_all_visible = [
+            Some(true),
+            Some(true),
+            Some(false),
+            None,
+            Some(true),
+            Some(false),
+            Some(true),
+            Some(true),
+        ];
+
+        // when
+        let mismatches = map.check(&pages_all_visible);
+
+        // then
+        assert_eq!(
+            mismatches,
+            vec![
+                VisibilityMismatch {
+                    blocks: 2..=2,
+                    kind: MismatchKind::PageNotAllVisible
+                },
+                VisibilityMismatch {
+                    blocks: 5..=5,
+                    kind: MismatchKind::PageNotAllVisible
+                },
+                VisibilityMismatch {
+                    blocks: 6..=6,
+                    kind: MismatchKind::BitNotSet
+                },
+                VisibilityMismatch {
+                    blocks: 7..=7,
+                    kind: MismatchKind::FrozenNotVisible
+                },
+                VisibilityMismatch {
+                    blocks: 8..=8,
+                    kind: MismatchKind::BeyondMainFork
+                },
+            ]
+        );
+        assert_eq!(
+            mismatches
+                .iter()
+                .map(|mismatch| mismatch.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "block 2: visibility map bit is set but PD_ALL_VISIBLE is not",
+                "block 5: visibility map bit is set but PD_ALL_VISIBLE is not",
+                "block 6: PD_ALL_VISIBLE is set but visibility map bit is not",
+                "block 7: all-frozen but not all-visible",
+                "block 8: visibility map bits are set past the end of the main fork",
+            ]
+        );
+    }
+}
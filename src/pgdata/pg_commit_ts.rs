@@ -1,8 +1,181 @@
+use std::path::Path;
+
+use anyhow::Result;
+
 use crate::common::fs::DirEntry;
 
-#[allow(dead_code)]
-pub trait PGCommitTS {}
+use super::slru::{self, EntryLayout, SlruSegment};
+
+/// Commit timestamp (TimestampTz) followed by the replication origin (RepOriginId) of a
+/// transaction take 10 bytes of a pg_commit_ts page
+pub const COMMIT_TS_LAYOUT: EntryLayout = EntryLayout::bytes(8 + 2);
+
+/// Represents PG_DATA/pg_commit_ts directory, filled only while track_commit_timestamp is on
+pub trait PGCommitTS {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Lists the segment files of pg_commit_ts ordered by name
+    fn segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Looks the commit timestamp of the transaction up, None when none is recorded as for
+    /// transactions which are in progress, aborted, or committed while tracking was off
+    fn commit_ts(&self, xid: u32) -> Result<Option<CommitTimestamp>>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("pg_commit_ts")
 }
+
+/// Instantiates a default implementation of [PGCommitTS]
+pub fn pg_commit_ts(pgdata_path: &Path) -> impl PGCommitTS {
+    default_impl::PGCommitTS::new(slru::slru(&pgdata_path.join("pg_commit_ts")))
+}
+
+/// Commit timestamp entry of a transaction, see CommitTimestampEntry
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CommitTimestamp {
+    /// Microseconds since 2000-01-01 UTC
+    pub time: i64,
+    /// Replication origin the transaction was replayed from, 0 for local transactions
+    pub origin: u16,
+}
+
+mod default_impl {
+    use std::path::Path;
+
+    use anyhow::Context;
+
+    use crate::{
+        common::bytes::ReadBytes,
+        pgdata::{
+            pg_xact::FIRST_NORMAL_TRANSACTION_ID,
+            slru::{Slru, SlruSegment},
+        },
+    };
+
+    use super::{CommitTimestamp, COMMIT_TS_LAYOUT};
+
+    pub struct PGCommitTS<S: Slru> {
+        slru: S,
+    }
+
+    impl<S: Slru> PGCommitTS<S> {
+        pub fn new(slru: S) -> Self {
+            PGCommitTS { slru }
+        }
+    }
+
+    impl<S: Slru> super::PGCommitTS for PGCommitTS<S> {
+        fn path(&self) -> &Path {
+            self.slru.path()
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            self.slru.segments()
+        }
+
+        fn commit_ts(&self, xid: u32) -> anyhow::Result<Option<CommitTimestamp>> {
+            // permanent xids have no commit timestamp, as in TransactionIdGetCommitTsData
+            if xid < FIRST_NORMAL_TRANSACTION_ID {
+                return Ok(None);
+            }
+            let bytes = self
+                .slru
+                .read(&COMMIT_TS_LAYOUT.locate(xid), 10)
+                .with_context(|| format!("Looking up commit timestamp of xid {xid}"))?;
+            let entry = CommitTimestamp {
+                time: bytes.i64_at(0)?,
+                origin: bytes.u16_at(8)?,
+            };
+            Ok(Some(entry).filter(|entry| entry.time != 0))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        use crate::pgdata::{
+            pg_commit_ts::{CommitTimestamp, PGCommitTS as _},
+            slru::test_stubs::StubSlru,
+        };
+
+        use super::PGCommitTS;
+
+        #[rstest]
+        #[case(740, Ok(Some(CommitTimestamp { time: 845_573_385_123_456, origin: 0 })))]
+        #[case(741, Ok(None))]
+        #[case(742, Ok(Some(CommitTimestamp { time: 845_573_386_000_001, origin: 3 })))]
+        #[case(2, Ok(None))]
+        #[case(5000, Err("Looking up commit timestamp of xid 5000: Segment \"/pgdata/pg_commit_ts/0000\" holds 1 pages, page 6 is missing"))]
+        fn looks_up_commit_ts(
+            #[case] xid: u32,
+            #[case] expected: Result<Option<CommitTimestamp>, &str>,
+        ) {
+            // given
+            let mut page = vec![0; 8192];
+            page[7400..7408].copy_from_slice(&845_573_385_123_456i64.to_le_bytes());
+            page[7420..7428].copy_from_slice(&845_573_386_000_001i64.to_le_bytes());
+            page[7428..7430].copy_from_slice(&3u16.to_le_bytes());
+            let pg_commit_ts = PGCommitTS::new(StubSlru {
+                path: Path::new("/pgdata/pg_commit_ts").to_path_buf(),
+                segments: vec![("0000".to_string(), page)],
+            });
+
+            // when
+            let commit_ts = pg_commit_ts.commit_ts(xid);
+
+            // then
+            assert_eq!(
+                commit_ts.map_err(|err| format!("{err:#}")),
+                expected.map_err(String::from)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::Path;
+
+    use anyhow::bail;
+
+    use crate::pgdata::slru::SlruSegment;
+
+    use super::{CommitTimestamp, PGCommitTS};
+
+    /// 740 and 743 committed at 2026-10-17 17:29:45.123456 UTC, the latter replayed from origin 1,
+    /// 741 and 742 have no commit timestamp, timestamps of xids after 743 are missing
+    pub struct StubPGCommitTS;
+    impl PGCommitTS for StubPGCommitTS {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/pg_commit_ts")
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            Ok(vec![SlruSegment {
+                file_name: "0000".to_string(),
+                size: 8192,
+            }])
+        }
+
+        fn commit_ts(&self, xid: u32) -> anyhow::Result<Option<CommitTimestamp>> {
+            match xid {
+                740 => Ok(Some(CommitTimestamp {
+                    time: 845_573_385_123_456,
+                    origin: 0,
+                })),
+                743 => Ok(Some(CommitTimestamp {
+                    time: 845_573_385_123_456,
+                    origin: 1,
+                })),
+                ..=743 => Ok(None),
+                _ => bail!("Commit timestamp of xid {xid} is missing"),
+            }
+        }
+    }
+}
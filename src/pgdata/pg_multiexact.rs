@@ -1,8 +1,402 @@
+use std::{fmt::Display, path::Path};
+
+use anyhow::{bail, Result};
+
 use crate::common::fs::DirEntry;
 
-#[allow(dead_code)]
-pub trait PGMultixact {}
+use super::slru::{self, EntryLayout, EntryLocation, SlruSegment};
+
+/// Subdirectory keeping the offset of the first member of each multixact
+pub const OFFSETS_DIR_NAME: &str = "offsets";
+
+/// Subdirectory keeping the members of multixacts
+pub const MEMBERS_DIR_NAME: &str = "members";
+
+/// Offset of the first member of a multixact takes 4 bytes of a pg_multixact/offsets page
+pub const OFFSETS_LAYOUT: EntryLayout = EntryLayout::bytes(4);
+
+/// Members are kept in groups: the flag bytes holding the lock modes of the members
+/// followed by their xids
+pub const MEMBERS_PER_GROUP: u32 = 4;
+pub const MEMBER_GROUP_LAYOUT: EntryLayout = EntryLayout::bytes(MEMBERS_PER_GROUP * (1 + 4));
+
+/// The first valid multixact id, 0 is InvalidMultiXactId and is skipped when the ids wrap around
+pub const FIRST_MULTIXACT_ID: u32 = 1;
+
+/// Represents PG_DATA/pg_multixact directory keeping the transactions which lock a tuple
+/// together, a tuple refers to them by the multixact id in its xmax
+pub trait PGMultixact {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Lists the segment files of pg_multixact/offsets ordered by name
+    fn offsets_segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Lists the segment files of pg_multixact/members ordered by name
+    fn members_segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Reads the members of the multixact as GetMultiXactIdMembers does. The number of members
+    /// follows from the offset of the next multixact, the members of the newest one are read up
+    /// to the first empty slot since its following offset may not be written yet
+    fn members(&self, multi: u32) -> Result<Vec<MultiXactMember>>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("pg_multixact")
 }
+
+/// Instantiates a default implementation of [PGMultixact]
+pub fn pg_multixact(pgdata_path: &Path) -> impl PGMultixact {
+    let path = pgdata_path.join("pg_multixact");
+    default_impl::PGMultixact::new(
+        &path,
+        slru::slru(&path.join(OFFSETS_DIR_NAME)),
+        slru::slru(&path.join(MEMBERS_DIR_NAME)),
+    )
+}
+
+/// Follows the multixact ids as GetMultiXactIdMembers does, skipping the invalid one on wraparound
+pub fn next_multixact_id(multi: u32) -> u32 {
+    multi.wrapping_add(1).max(FIRST_MULTIXACT_ID)
+}
+
+/// Locates the group of the member at the given offset and the slot of the member in the group
+pub fn locate_member(offset: u32) -> (EntryLocation, u32) {
+    (
+        MEMBER_GROUP_LAYOUT.locate(offset / MEMBERS_PER_GROUP),
+        offset % MEMBERS_PER_GROUP,
+    )
+}
+
+/// A transaction locking or updating a tuple as a member of a multixact
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MultiXactMember {
+    /// Offset of the member in pg_multixact/members
+    pub offset: u32,
+    pub xid: u32,
+    pub status: MultiXactStatus,
+}
+
+/// Lock mode of a multixact member kept in its flag byte, see MultiXactStatus
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MultiXactStatus {
+    ForKeyShare,
+    ForShare,
+    ForNoKeyUpdate,
+    ForUpdate,
+    /// The member updated the tuple without changing its key columns
+    NoKeyUpdate,
+    /// The member updated or deleted the tuple
+    Update,
+}
+
+impl MultiXactStatus {
+    /// Decodes the flag byte of a member
+    pub fn from_flags(flags: u8) -> Result<MultiXactStatus> {
+        Ok(match flags {
+            0x00 => MultiXactStatus::ForKeyShare,
+            0x01 => MultiXactStatus::ForShare,
+            0x02 => MultiXactStatus::ForNoKeyUpdate,
+            0x03 => MultiXactStatus::ForUpdate,
+            0x04 => MultiXactStatus::NoKeyUpdate,
+            0x05 => MultiXactStatus::Update,
+            _ => bail!("Unknown multixact member status {flags:#04x}"),
+        })
+    }
+}
+
+impl Display for MultiXactStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MultiXactStatus::ForKeyShare => "ForKeyShare",
+            MultiXactStatus::ForShare => "ForShare",
+            MultiXactStatus::ForNoKeyUpdate => "ForNoKeyUpdate",
+            MultiXactStatus::ForUpdate => "ForUpdate",
+            MultiXactStatus::NoKeyUpdate => "NoKeyUpdate",
+            MultiXactStatus::Update => "Update",
+        })
+    }
+}
+
+mod default_impl {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::{bail, Context};
+
+    use crate::{
+        common::bytes::ReadBytes,
+        pgdata::slru::{Slru, SlruSegment},
+    };
+
+    use super::{
+        locate_member, next_multixact_id, MultiXactMember, MultiXactStatus, MEMBERS_PER_GROUP,
+        OFFSETS_LAYOUT,
+    };
+
+    pub struct PGMultixact<O: Slru, M: Slru> {
+        path: PathBuf,
+        offsets: O,
+        members: M,
+    }
+
+    impl<O: Slru, M: Slru> PGMultixact<O, M> {
+        pub fn new(path: &Path, offsets: O, members: M) -> Self {
+            PGMultixact {
+                path: path.to_path_buf(),
+                offsets,
+                members,
+            }
+        }
+
+        fn offset(&self, multi: u32) -> anyhow::Result<u32> {
+            self.offsets
+                .read(&OFFSETS_LAYOUT.locate(multi), 4)
+                .and_then(|bytes| bytes.u32_at(0))
+        }
+
+        /// Reads the member at the offset, None when its slot is empty
+        fn member(&self, offset: u32) -> anyhow::Result<Option<MultiXactMember>> {
+            let (location, slot) = locate_member(offset);
+            let group = self
+                .members
+                .read(&location, (MEMBERS_PER_GROUP * (1 + 4)) as usize)?;
+            let xid = group.u32_at((MEMBERS_PER_GROUP + slot * 4) as usize)?;
+            if xid == 0 {
+                return Ok(None);
+            }
+            Ok(Some(MultiXactMember {
+                offset,
+                xid,
+                status: MultiXactStatus::from_flags(group[slot as usize])?,
+            }))
+        }
+    }
+
+    impl<O: Slru, M: Slru> super::PGMultixact for PGMultixact<O, M> {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn offsets_segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            self.offsets.segments()
+        }
+
+        fn members_segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            self.members.segments()
+        }
+
+        fn members(&self, multi: u32) -> anyhow::Result<Vec<MultiXactMember>> {
+            let context = || format!("Looking up members of multixact {multi}");
+            if multi == 0 {
+                bail!("Multixact id 0 is invalid");
+            }
+            let offset = self.offset(multi).with_context(context)?;
+            if offset == 0 {
+                bail!("Multixact {multi} is not recorded");
+            }
+            // the offsets page of the next multixact may not exist yet for the newest one
+            let next_offset = self.offset(next_multixact_id(multi)).unwrap_or(0);
+
+            if next_offset != 0 {
+                // empty slots are left by backends which crashed while recording a multixact
+                return (0..next_offset.wrapping_sub(offset))
+                    .map(|i| self.member(offset.wrapping_add(i)))
+                    .filter_map(Result::transpose)
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .with_context(context);
+            }
+            let mut members = Vec::new();
+            let mut member_offset = offset;
+            // the first member must be present, the following ones may be on a page which is
+            // not created yet
+            while let Some(member) = match self.member(member_offset) {
+                Ok(member) => member,
+                Err(err) if members.is_empty() => return Err(err).with_context(context),
+                Err(_) => None,
+            } {
+                members.push(member);
+                member_offset = member_offset.wrapping_add(1);
+            }
+            // the offset of the next multixact is written ahead of its members
+            if members.is_empty() {
+                bail!("Multixact {multi} is not recorded");
+            }
+            Ok(members)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        use crate::pgdata::{
+            pg_multiexact::{MultiXactMember, MultiXactStatus, PGMultixact as _},
+            slru::test_stubs::StubSlru,
+        };
+
+        use super::PGMultixact;
+
+        fn member(offset: u32, xid: u32, status: MultiXactStatus) -> MultiXactMember {
+            MultiXactMember {
+                offset,
+                xid,
+                status,
+            }
+        }
+
+        #[rstest]
+        #[case(1, Ok(vec![
+            member(1, 740, MultiXactStatus::ForKeyShare),
+            member(2, 741, MultiXactStatus::ForShare),
+        ]))]
+        #[case(2, Ok(vec![
+            member(3, 742, MultiXactStatus::ForUpdate),
+            member(5, 743, MultiXactStatus::Update),
+        ]))]
+        #[case(3, Ok(vec![member(6, 744, MultiXactStatus::NoKeyUpdate)]))]
+        #[case(4, Err("Multixact 4 is not recorded"))]
+        #[case(0, Err("Multixact id 0 is invalid"))]
+        #[case(3000, Err("Looking up members of multixact 3000: Segment \"/pgdata/pg_multixact/offsets/0000\" holds 1 pages, page 1 is missing"))]
+        fn reads_members(#[case] multi: u32, #[case] expected: Result<Vec<MultiXactMember>, &str>) {
+            // given
+            let mut offsets = vec![0; 8192];
+            [1u32, 3, 6].iter().enumerate().for_each(|(i, offset)| {
+                offsets[(i + 1) * 4..(i + 2) * 4].copy_from_slice(&offset.to_le_bytes())
+            });
+            // offset 0 is never used, the slot of offset 4 is left empty by a crashed backend
+            let mut members = vec![0; 8192];
+            members[1..4].copy_from_slice(&[0x00, 0x01, 0x03]);
+            members[8..20].copy_from_slice(
+                &[740u32, 741, 742]
+                    .iter()
+                    .flat_map(|xid| xid.to_le_bytes())
+                    .collect::<Vec<_>>(),
+            );
+            members[21..23].copy_from_slice(&[0x05, 0x04]);
+            members[28..36].copy_from_slice(
+                &[743u32, 744]
+                    .iter()
+                    .flat_map(|xid| xid.to_le_bytes())
+                    .collect::<Vec<_>>(),
+            );
+            let pg_multixact = PGMultixact::new(
+                Path::new("/pgdata/pg_multixact"),
+                StubSlru {
+                    path: Path::new("/pgdata/pg_multixact/offsets").to_path_buf(),
+                    segments: vec![("0000".to_string(), offsets)],
+                },
+                StubSlru {
+                    path: Path::new("/pgdata/pg_multixact/members").to_path_buf(),
+                    segments: vec![("0000".to_string(), members)],
+                },
+            );
+
+            // when
+            let members = pg_multixact.members(multi);
+
+            // then
+            assert_eq!(
+                members.map_err(|err| format!("{err:#}")),
+                expected.map_err(String::from)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::Path;
+
+    use anyhow::bail;
+
+    use crate::pgdata::slru::SlruSegment;
+
+    use super::{MultiXactMember, MultiXactStatus, PGMultixact};
+
+    /// Multixact 7 is shared by 740 and 742 locking a tuple which 742 then updated, 741 updated
+    /// the tuple of multixact 9 and aborted, 740 the one of 10 and committed, the tuple of 11 is
+    /// locked only. The members of the others are missing
+    pub struct StubPGMultixact;
+    impl PGMultixact for StubPGMultixact {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/pg_multixact")
+        }
+
+        fn offsets_segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            Ok(vec![SlruSegment {
+                file_name: "0000".to_string(),
+                size: 8192,
+            }])
+        }
+
+        fn members_segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            Ok(vec![
+                SlruSegment {
+                    file_name: "0000".to_string(),
+                    size: 8192,
+                },
+                SlruSegment {
+                    file_name: "0001".to_string(),
+                    size: 8192,
+                },
+            ])
+        }
+
+        fn members(&self, multi: u32) -> anyhow::Result<Vec<MultiXactMember>> {
+            match multi {
+                7 => Ok(vec![
+                    MultiXactMember {
+                        offset: 12,
+                        xid: 740,
+                        status: MultiXactStatus::ForKeyShare,
+                    },
+                    MultiXactMember {
+                        offset: 13,
+                        xid: 742,
+                        status: MultiXactStatus::NoKeyUpdate,
+                    },
+                ]),
+                9 => Ok(vec![
+                    MultiXactMember {
+                        offset: 14,
+                        xid: 740,
+                        status: MultiXactStatus::ForKeyShare,
+                    },
+                    MultiXactMember {
+                        offset: 15,
+                        xid: 741,
+                        status: MultiXactStatus::Update,
+                    },
+                ]),
+                10 => Ok(vec![
+                    MultiXactMember {
+                        offset: 16,
+                        xid: 742,
+                        status: MultiXactStatus::ForShare,
+                    },
+                    MultiXactMember {
+                        offset: 17,
+                        xid: 740,
+                        status: MultiXactStatus::NoKeyUpdate,
+                    },
+                ]),
+                11 => Ok(vec![
+                    MultiXactMember {
+                        offset: 18,
+                        xid: 740,
+                        status: MultiXactStatus::ForShare,
+                    },
+                    MultiXactMember {
+                        offset: 19,
+                        xid: 742,
+                        status: MultiXactStatus::ForShare,
+                    },
+                ]),
+                _ => bail!("Members of multixact {multi} are missing"),
+            }
+        }
+    }
+}
@@ -1,8 +1,181 @@
+use std::path::Path;
+
+use anyhow::Result;
+
 use crate::common::fs::DirEntry;
 
-#[allow(dead_code)]
-pub trait PGNotify {}
+use super::slru::{self, SlruSegment};
+
+/// Size of the AsyncQueueEntry fields preceding the channel name and the payload, entries are
+/// of variable length and aligned to 4 bytes, an entry never spans two pages
+pub const QUEUE_ENTRY_HEADER_SIZE: u32 = 16;
+
+/// Notification of the LISTEN/NOTIFY queue, an AsyncQueueEntry
+#[derive(Debug, PartialEq)]
+pub struct QueueEntry {
+    /// Length of the entry with its header and its alignment padding
+    pub length: u32,
+    pub db_oid: u32,
+    pub xid: u32,
+    pub src_pid: i32,
+    pub channel: String,
+    pub payload: String,
+}
+
+/// Represents PG_DATA/pg_notify directory. It holds the queue of the notifications not yet
+/// read by every listening backend and is cleared on startup
+pub trait PGNotify {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Lists the segment files of pg_notify ordered by name
+    fn segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Reads the queue entry at a QueuePosition. The queue is addressed by pages and offsets
+    /// rather than by entry numbers as the entries are of variable length
+    fn entry(&self, page_number: u64, offset: u32) -> Result<QueueEntry>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("pg_notify")
 }
+
+/// Instantiates a default implementation of [PGNotify]
+pub fn pg_notify(pgdata_path: &Path) -> impl PGNotify {
+    default_impl::PGNotify::new(slru::slru(&pgdata_path.join("pg_notify")))
+}
+
+mod default_impl {
+    use std::path::Path;
+
+    use anyhow::{bail, Context};
+
+    use crate::{
+        common::bytes::ReadBytes,
+        pgdata::{
+            slru::{EntryLocation, Slru, SlruSegment},
+            BLCKSZ,
+        },
+    };
+
+    use super::{QueueEntry, QUEUE_ENTRY_HEADER_SIZE};
+
+    pub struct PGNotify<S: Slru> {
+        slru: S,
+    }
+
+    impl<S: Slru> PGNotify<S> {
+        pub fn new(slru: S) -> Self {
+            PGNotify { slru }
+        }
+
+        fn read_entry(&self, page_number: u64, offset: u32) -> anyhow::Result<QueueEntry> {
+            let location = EntryLocation {
+                page_number,
+                byte: offset,
+                shift: 0,
+            };
+            if offset as u64 + QUEUE_ENTRY_HEADER_SIZE as u64 > BLCKSZ {
+                bail!("Entry header spans past the end of the page");
+            }
+            let header = self
+                .slru
+                .read(&location, QUEUE_ENTRY_HEADER_SIZE as usize)?;
+            let length = header.u32_at(0)?;
+            if length < QUEUE_ENTRY_HEADER_SIZE {
+                bail!("Entry length {length} is below the entry header size");
+            }
+            if offset as u64 + length as u64 > BLCKSZ {
+                bail!("Entry of length {length} spans past the end of the page");
+            }
+            let entry = self.slru.read(&location, length as usize)?;
+            let mut strings = entry[QUEUE_ENTRY_HEADER_SIZE as usize..]
+                .split(|byte| *byte == 0)
+                .map(|string| String::from_utf8_lossy(string).into_owned());
+            Ok(QueueEntry {
+                length,
+                db_oid: header.u32_at(4)?,
+                xid: header.u32_at(8)?,
+                src_pid: header.i32_at(12)?,
+                channel: strings.next().unwrap_or_default(),
+                payload: strings.next().unwrap_or_default(),
+            })
+        }
+    }
+
+    impl<S: Slru> super::PGNotify for PGNotify<S> {
+        fn path(&self) -> &Path {
+            self.slru.path()
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            self.slru.segments()
+        }
+
+        fn entry(&self, page_number: u64, offset: u32) -> anyhow::Result<QueueEntry> {
+            self.read_entry(page_number, offset).with_context(|| {
+                format!("Reading queue entry at page {page_number} offset {offset}")
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+
+        use pretty_assertions::assert_eq;
+
+        use crate::pgdata::{
+            pg_notify::{PGNotify as _, QueueEntry},
+            slru::test_stubs::StubSlru,
+        };
+
+        use super::PGNotify;
+
+        #[test]
+        fn reads_queue_entries() {
+            // given
+            let mut page = vec![0; 8192];
+            let entry = [
+                &28u32.to_le_bytes()[..],
+                &5u32.to_le_bytes(),
+                &740u32.to_le_bytes(),
+                &4242i32.to_le_bytes(),
+                b"jobs\0run\0\0\0\0",
+            ]
+            .concat();
+            page[..28].copy_from_slice(&entry);
+            page[28..32].copy_from_slice(&8u32.to_le_bytes());
+            page[32..36].copy_from_slice(&8192u32.to_le_bytes());
+            page[36..40].copy_from_slice(&u32::MAX.to_le_bytes());
+            let pg_notify = PGNotify::new(StubSlru {
+                path: Path::new("/pgdata/pg_notify").to_path_buf(),
+                segments: vec![("0000".to_string(), page)],
+            });
+
+            // when
+            let entries = [0, 28, 32, 36, 8188, u32::MAX]
+                .map(|offset| pg_notify.entry(0, offset).map_err(|err| format!("{err:#}")));
+
+            // then
+            assert_eq!(
+                entries,
+                [
+                    Ok(QueueEntry {
+                        length: 28,
+                        db_oid: 5,
+                        xid: 740,
+                        src_pid: 4242,
+                        channel: "jobs".to_string(),
+                        payload: "run".to_string(),
+                    }),
+                    Err("Reading queue entry at page 0 offset 28: Entry length 8 is below the entry header size".to_string()),
+                    Err("Reading queue entry at page 0 offset 32: Entry of length 8192 spans past the end of the page".to_string()),
+                    Err("Reading queue entry at page 0 offset 36: Entry of length 4294967295 spans past the end of the page".to_string()),
+                    Err("Reading queue entry at page 0 offset 8188: Entry header spans past the end of the page".to_string()),
+                    Err("Reading queue entry at page 0 offset 4294967295: Entry header spans past the end of the page".to_string()),
+                ]
+            );
+        }
+    }
+}
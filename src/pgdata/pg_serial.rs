@@ -1,8 +1,115 @@
+use std::path::Path;
+
+use anyhow::Result;
+
 use crate::common::fs::DirEntry;
 
-#[allow(dead_code)]
-pub trait PGSerial {}
+use super::slru::{self, EntryLayout, SlruSegment};
+
+/// Commit sequence number (SerCommitSeqNo) of the earliest conflicting serializable transaction
+/// takes 8 bytes of a pg_serial page
+pub const SERIAL_LAYOUT: EntryLayout = EntryLayout::bytes(8);
+
+/// Represents PG_DATA/pg_serial directory. It keeps the out-conflicts of committed serializable
+/// transactions summarized once they no longer fit in shared memory
+pub trait PGSerial {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Lists the segment files of pg_serial ordered by name
+    fn segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Returns the commit sequence number of the earliest transaction the serializable
+    /// transaction has a conflict out to, None if it has none
+    fn conflict_commit_seq_no(&self, xid: u32) -> Result<Option<u64>>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("pg_serial")
 }
+
+/// Instantiates a default implementation of [PGSerial]
+pub fn pg_serial(pgdata_path: &Path) -> impl PGSerial {
+    default_impl::PGSerial::new(slru::slru(&pgdata_path.join("pg_serial")))
+}
+
+mod default_impl {
+    use std::path::Path;
+
+    use anyhow::Context;
+
+    use crate::{
+        common::bytes::ReadBytes,
+        pgdata::slru::{Slru, SlruSegment},
+    };
+
+    use super::SERIAL_LAYOUT;
+
+    pub struct PGSerial<S: Slru> {
+        slru: S,
+    }
+
+    impl<S: Slru> PGSerial<S> {
+        pub fn new(slru: S) -> Self {
+            PGSerial { slru }
+        }
+    }
+
+    impl<S: Slru> super::PGSerial for PGSerial<S> {
+        fn path(&self) -> &Path {
+            self.slru.path()
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            self.slru.segments()
+        }
+
+        fn conflict_commit_seq_no(&self, xid: u32) -> anyhow::Result<Option<u64>> {
+            let seq_no = self
+                .slru
+                .read(&SERIAL_LAYOUT.locate(xid), 8)
+                .and_then(|bytes| bytes.u64_at(0))
+                .with_context(|| format!("Looking up conflict of xid {xid}"))?;
+            Ok(Some(seq_no).filter(|&seq_no| seq_no != 0))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+
+        use pretty_assertions::assert_eq;
+
+        use crate::pgdata::{pg_serial::PGSerial as _, slru::test_stubs::StubSlru};
+
+        use super::PGSerial;
+
+        #[test]
+        fn reads_conflict_commit_seq_no() {
+            // given
+            let mut page = vec![0; 8192];
+            page[743 * 8..744 * 8].copy_from_slice(&12u64.to_le_bytes());
+            let pg_serial = PGSerial::new(StubSlru {
+                path: Path::new("/pgdata/pg_serial").to_path_buf(),
+                segments: vec![("0000".to_string(), page)],
+            });
+
+            // when
+            let seq_nos = [743, 741, 1024].map(|xid| {
+                pg_serial
+                    .conflict_commit_seq_no(xid)
+                    .map_err(|err| format!("{err:#}"))
+            });
+
+            // then
+            assert_eq!(
+                seq_nos,
+                [
+                    Ok(Some(12)),
+                    Ok(None),
+                    Err("Looking up conflict of xid 1024: Segment \"/pgdata/pg_serial/0000\" holds 1 pages, page 1 is missing".to_string())
+                ]
+            );
+        }
+    }
+}
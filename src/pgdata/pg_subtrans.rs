@@ -1,8 +1,190 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
 use crate::common::fs::DirEntry;
 
-#[allow(dead_code)]
-pub trait PGSubtrans {}
+use super::{
+    pg_xact::xid_precedes,
+    slru::{self, EntryLayout, SlruSegment},
+};
+
+/// Parent xid of a subtransaction takes 4 bytes of a pg_subtrans page, 0 for top-level ones
+pub const SUBTRANS_LAYOUT: EntryLayout = EntryLayout::bytes(4);
+
+/// Represents PG_DATA/pg_subtrans directory. It is zeroed on startup and keeps the parents of
+/// the transactions started since then only, older ones look like top-level transactions
+pub trait PGSubtrans {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Lists the segment files of pg_subtrans ordered by name
+    fn segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Returns the parent xid of the subtransaction, None for a top-level transaction
+    fn parent(&self, xid: u32) -> Result<Option<u32>>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("pg_subtrans")
 }
+
+/// Instantiates a default implementation of [PGSubtrans]
+pub fn pg_subtrans(pgdata_path: &Path) -> impl PGSubtrans {
+    default_impl::PGSubtrans::new(slru::slru(&pgdata_path.join("pg_subtrans")))
+}
+
+/// Walks the parents of the subtransaction up to its top-level transaction, the chain starts with
+/// the direct parent and is empty for a top-level transaction. A parent always precedes its
+/// child, otherwise pg_subtrans is corrupted and the walk stops
+pub fn parent_chain(pg_subtrans: &impl PGSubtrans, xid: u32) -> Result<Vec<u32>> {
+    let mut chain = Vec::new();
+    let mut child = xid;
+    while let Some(parent) = pg_subtrans.parent(child)? {
+        if !xid_precedes(parent, child) {
+            bail!("Parent {parent} of subtransaction {child} does not precede it");
+        }
+        chain.push(parent);
+        child = parent;
+    }
+    Ok(chain)
+}
+
+mod default_impl {
+    use std::path::Path;
+
+    use anyhow::Context;
+
+    use crate::{
+        common::bytes::ReadBytes,
+        pgdata::slru::{Slru, SlruSegment},
+    };
+
+    use super::SUBTRANS_LAYOUT;
+
+    pub struct PGSubtrans<S: Slru> {
+        slru: S,
+    }
+
+    impl<S: Slru> PGSubtrans<S> {
+        pub fn new(slru: S) -> Self {
+            PGSubtrans { slru }
+        }
+    }
+
+    impl<S: Slru> super::PGSubtrans for PGSubtrans<S> {
+        fn path(&self) -> &Path {
+            self.slru.path()
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            self.slru.segments()
+        }
+
+        fn parent(&self, xid: u32) -> anyhow::Result<Option<u32>> {
+            let parent = self
+                .slru
+                .read(&SUBTRANS_LAYOUT.locate(xid), 4)
+                .and_then(|bytes| bytes.u32_at(0))
+                .with_context(|| format!("Looking up parent of xid {xid}"))?;
+            Ok(Some(parent).filter(|&parent| parent != 0))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+
+        use pretty_assertions::assert_eq;
+
+        use crate::pgdata::{pg_subtrans::PGSubtrans as _, slru::test_stubs::StubSlru};
+
+        use super::PGSubtrans;
+
+        #[test]
+        fn reads_parent_xid() {
+            // given
+            let mut page = vec![0; 8192];
+            page[743 * 4..744 * 4].copy_from_slice(&741u32.to_le_bytes());
+            let pg_subtrans = PGSubtrans::new(StubSlru {
+                path: Path::new("/pgdata/pg_subtrans").to_path_buf(),
+                segments: vec![("0000".to_string(), page)],
+            });
+
+            // when
+            let parents = [743, 741, 5000]
+                .map(|xid| pg_subtrans.parent(xid).map_err(|err| format!("{err:#}")));
+
+            // then
+            assert_eq!(
+                parents,
+                [
+                    Ok(Some(741)),
+                    Ok(None),
+                    Err("Looking up parent of xid 5000: Segment \"/pgdata/pg_subtrans/0000\" holds 1 pages, page 2 is missing".to_string())
+                ]
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::Path;
+
+    use anyhow::bail;
+
+    use crate::pgdata::slru::SlruSegment;
+
+    use super::PGSubtrans;
+
+    /// 743 is a subtransaction of 741 which is a subtransaction of 740, 745 claims to be a
+    /// subtransaction of a following xid, parents of xids after 745 are missing
+    pub struct StubPGSubtrans;
+    impl PGSubtrans for StubPGSubtrans {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/pg_subtrans")
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            Ok(vec![SlruSegment {
+                file_name: "0000".to_string(),
+                size: 8192,
+            }])
+        }
+
+        fn parent(&self, xid: u32) -> anyhow::Result<Option<u32>> {
+            match xid {
+                743 => Ok(Some(741)),
+                741 => Ok(Some(740)),
+                745 => Ok(Some(746)),
+                ..=745 => Ok(None),
+                _ => bail!("Parent of xid {xid} is missing"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{parent_chain, test_stubs::StubPGSubtrans};
+
+    #[rstest]
+    #[case(743, Ok(vec![741, 740]))]
+    #[case(740, Ok(vec![]))]
+    #[case(745, Err("Parent 746 of subtransaction 745 does not precede it"))]
+    #[case(5000, Err("Parent of xid 5000 is missing"))]
+    fn walks_parent_chain(#[case] xid: u32, #[case] expected: Result<Vec<u32>, &str>) {
+        // when
+        let chain = parent_chain(&StubPGSubtrans, xid);
+
+        // then
+        assert_eq!(
+            chain.map_err(|err| err.to_string()),
+            expected.map_err(String::from)
+        );
+    }
+}
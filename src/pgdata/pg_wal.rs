@@ -1,8 +1,590 @@
-use crate::common::fs::DirEntry;
+use std::path::Path;
 
-#[allow(dead_code)]
-pub trait PGWal {}
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::common::{
+    fs::{DirEntry, FileType},
+    Lsn,
+};
+
+use self::reader::WalRecords;
+
+pub mod block_history;
+pub mod reader;
+pub mod record;
+pub mod rmgr;
+pub mod stats;
+
+/// Name of the subdirectory holding the archiver's `.ready` and `.done` markers
+pub const ARCHIVE_STATUS_DIR_NAME: &str = "archive_status";
+
+/// Represents PG_DATA/pg_wal directory
+pub trait PGWal {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Returns the WAL segment size recorded in pg_control, or the size of a segment file
+    /// if pg_control cannot be read or the directory is an archive outside PG_DATA
+    fn wal_seg_size(&self) -> Result<u32>;
+
+    /// Lists the WAL files with their archive status and the gaps in the segment sequence
+    fn items(&self) -> Result<Vec<WalItem>>;
+
+    /// Decodes the records starting in the given segment, following segments are read
+    /// to complete the last record
+    fn records(&self, segment: &WalSegmentName) -> Result<WalRecords>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("pg_wal")
 }
+
+/// Instantiates a default implementation of [PGWal]
+pub fn pg_wal(pgdata_path: &Path) -> impl PGWal {
+    default_impl::PGWal::new(pgdata_path)
+}
+
+/// Instantiates a default implementation of [PGWal] reading a directory of archived segments
+/// rather than pg_wal of a cluster
+pub fn archive(path: &Path) -> impl PGWal {
+    default_impl::PGWal::archive(path)
+}
+
+/// Composes the name of the WAL segment file holding the given LSN, as XLogFileName does
+pub fn wal_file_name(timeline_id: u32, Lsn(lsn): Lsn, wal_seg_size: u32) -> String {
+    let segment_no = lsn / wal_seg_size as u64;
+    let segments_per_xlog_id = 0x1_0000_0000 / wal_seg_size as u64;
+    format!(
+        "{:08X}{:08X}{:08X}",
+        timeline_id,
+        segment_no / segments_per_xlog_id,
+        segment_no % segments_per_xlog_id
+    )
+}
+
+/// Represents an item of PG_DATA/pg_wal
+#[derive(Debug, PartialEq)]
+pub enum WalItem {
+    /// A WAL file recognized by its name
+    File(WalFile),
+
+    /// Segments missing between two present segments of the same timeline, inclusive
+    Gap {
+        first: WalSegmentName,
+        last: WalSegmentName,
+    },
+
+    /// An archive status marker of a WAL file which is not present anymore
+    OrphanArchiveStatus(String, ArchiveStatus),
+
+    /// An item not following any of the WAL file naming conventions
+    Unknown(DirEntry<'static>),
+}
+
+/// A WAL file with its archive status
+#[derive(Debug, PartialEq)]
+pub struct WalFile {
+    pub file_name: String,
+    pub kind: WalFileKind,
+    pub archive_status: Option<ArchiveStatus>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WalFileKind {
+    /// A WAL segment, e.g. `000000010000000000000001`
+    Segment(WalSegmentName),
+
+    /// The last, incomplete segment of a timeline left behind on promotion or by pg_receivewal
+    Partial(WalSegmentName),
+
+    /// The timeline history file, e.g. `00000002.history`
+    History(u32),
+
+    /// The backup history file created by pg_backup_stop, e.g.
+    /// `000000010000000000000002.00000028.backup`, with the offset of the backup start in the segment
+    Backup(WalSegmentName, u32),
+}
+
+/// State of a WAL file in the archiver, as marked in pg_wal/archive_status
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArchiveStatus {
+    /// `.ready`, the file is complete and waits for archive_command
+    Ready,
+    /// `.done`, the file has been archived
+    Done,
+}
+
+impl ArchiveStatus {
+    /// Splits the archive status marker name into the WAL file name and the status
+    pub fn try_parse(file_name: &str) -> Option<(&str, ArchiveStatus)> {
+        if let Some(wal_file_name) = file_name.strip_suffix(".ready") {
+            Some((wal_file_name, ArchiveStatus::Ready))
+        } else {
+            file_name
+                .strip_suffix(".done")
+                .map(|wal_file_name| (wal_file_name, ArchiveStatus::Done))
+        }
+    }
+}
+
+/// Timeline, log and segment numbers a WAL segment file name is composed of
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct WalSegmentName {
+    pub timeline_id: u32,
+    pub log: u32,
+    pub seg: u32,
+}
+
+impl WalSegmentName {
+    /// Returns the segment number counted from the beginning of the WAL, as XLogFromFileName does
+    pub fn segment_no(&self, wal_seg_size: u32) -> u64 {
+        self.log as u64 * (0x1_0000_0000 / wal_seg_size as u64) + self.seg as u64
+    }
+
+    /// Returns the name of the segment of the given number on this segment's timeline
+    pub fn with_segment_no(&self, segment_no: u64, wal_seg_size: u32) -> WalSegmentName {
+        let segments_per_xlog_id = 0x1_0000_0000 / wal_seg_size as u64;
+        WalSegmentName {
+            timeline_id: self.timeline_id,
+            log: (segment_no / segments_per_xlog_id) as u32,
+            seg: (segment_no % segments_per_xlog_id) as u32,
+        }
+    }
+
+    /// Returns the LSN of the first byte of the segment
+    pub fn start_lsn(&self, wal_seg_size: u32) -> Lsn {
+        Lsn(self.segment_no(wal_seg_size) * wal_seg_size as u64)
+    }
+
+    /// Returns the LSN following the last byte of the segment
+    pub fn end_lsn(&self, wal_seg_size: u32) -> Lsn {
+        Lsn((self.segment_no(wal_seg_size) + 1) * wal_seg_size as u64)
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{:08X}{:08X}{:08X}", self.timeline_id, self.log, self.seg)
+    }
+}
+
+static WAL_FILE_REGEX: Lazy<Regex> = regex_static::lazy_regex!(
+    r"^([0-9A-F]{8})(([0-9A-F]{8})([0-9A-F]{8})(\.partial|\.([0-9A-F]{8})\.backup)?|\.history)$"
+);
+
+impl WalFileKind {
+    /// Recognizes the WAL file by its name, segment names are checked against the segment size
+    pub fn try_parse(file_name: &str, wal_seg_size: u32) -> Option<WalFileKind> {
+        let captures = WAL_FILE_REGEX.captures(file_name)?;
+        let hex = |i: usize| {
+            captures
+                .get(i)
+                .and_then(|m| u32::from_str_radix(m.as_str(), 16).ok())
+        };
+        let timeline_id = hex(1)?;
+        if captures.get(3).is_none() {
+            return Some(WalFileKind::History(timeline_id));
+        }
+
+        let name = WalSegmentName {
+            timeline_id,
+            log: hex(3)?,
+            seg: hex(4)?,
+        };
+        if name.seg as u64 >= 0x1_0000_0000 / wal_seg_size as u64 {
+            return None;
+        }
+        match (captures.get(5).map(|m| m.as_str()), hex(6)) {
+            (None, _) => Some(WalFileKind::Segment(name)),
+            (Some(".partial"), _) => Some(WalFileKind::Partial(name)),
+            (Some(_), Some(offset)) => Some(WalFileKind::Backup(name, offset)),
+            _ => None,
+        }
+    }
+}
+
+/// Classifies the entries of pg_wal, attaches the archive status markers to their files
+/// and reports the segments missing within each timeline
+pub fn wal_items(
+    entries: Vec<DirEntry<'static>>,
+    archive_statuses: Vec<String>,
+    wal_seg_size: u32,
+) -> Vec<WalItem> {
+    let mut statuses = archive_statuses
+        .iter()
+        .filter_map(|file_name| ArchiveStatus::try_parse(file_name))
+        .collect::<Vec<_>>();
+
+    let mut files = Vec::new();
+    let mut unknown = Vec::new();
+    for entry in entries {
+        let file_name = entry.name.to_string_lossy().into_owned();
+        match WalFileKind::try_parse(&file_name, wal_seg_size) {
+            Some(kind) if entry.entry_type == FileType::File => {
+                let archive_status = statuses
+                    .iter()
+                    .position(|(name, _)| *name == file_name)
+                    .map(|i| statuses.remove(i).1);
+                files.push(WalFile {
+                    file_name,
+                    kind,
+                    archive_status,
+                });
+            }
+            _ if file_name == ARCHIVE_STATUS_DIR_NAME => (),
+            _ => unknown.push(entry),
+        }
+    }
+    files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+    let mut items = Vec::new();
+    let mut previous: Option<WalSegmentName> = None;
+    for file in files {
+        if let WalFileKind::Segment(name) | WalFileKind::Partial(name) = file.kind {
+            if let Some(previous) = previous.filter(|p| p.timeline_id == name.timeline_id) {
+                let previous_no = previous.segment_no(wal_seg_size);
+                let segment_no = name.segment_no(wal_seg_size);
+                if segment_no > previous_no + 1 {
+                    items.push(WalItem::Gap {
+                        first: name.with_segment_no(previous_no + 1, wal_seg_size),
+                        last: name.with_segment_no(segment_no - 1, wal_seg_size),
+                    });
+                }
+            }
+            previous = Some(name);
+        }
+        items.push(WalItem::File(file));
+    }
+
+    statuses.sort_by(|a, b| a.0.cmp(b.0));
+    items.extend(
+        statuses
+            .into_iter()
+            .map(|(file_name, status)| WalItem::OrphanArchiveStatus(file_name.to_string(), status)),
+    );
+    items.extend(unknown.into_iter().map(WalItem::Unknown));
+    items
+}
+
+mod default_impl {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::{anyhow, bail, Context};
+
+    use crate::{
+        common::fs::DirEntry,
+        pgdata::global::{self, Global as _},
+    };
+
+    use super::{
+        reader::{WalReader, WalRecords},
+        wal_items, WalFileKind, WalItem, WalSegmentName, ARCHIVE_STATUS_DIR_NAME,
+    };
+
+    pub struct PGWal {
+        /// None for an archive, there is no pg_control to read the segment size from
+        pgdata_path: Option<PathBuf>,
+        path: PathBuf,
+    }
+
+    impl PGWal {
+        pub fn new(pgdata_path: &Path) -> Self {
+            PGWal {
+                pgdata_path: Some(pgdata_path.to_path_buf()),
+                path: pgdata_path.join("pg_wal"),
+            }
+        }
+
+        pub fn archive(path: &Path) -> Self {
+            PGWal {
+                pgdata_path: None,
+                path: path.to_path_buf(),
+            }
+        }
+
+        fn entries(&self) -> anyhow::Result<Vec<DirEntry<'static>>> {
+            std::fs::read_dir(&self.path)
+                .with_context(|| format!("Reading {:?}", self.path))?
+                .map(|entry| entry.map_err(|err| anyhow!(err)))
+                .map(|entry| entry.and_then(|fs_dir_entry| DirEntry::from(&fs_dir_entry)))
+                .collect()
+        }
+
+        fn archive_statuses(&self) -> anyhow::Result<Vec<String>> {
+            let path = self.path.join(ARCHIVE_STATUS_DIR_NAME);
+            if !path.exists() {
+                return Ok(Vec::new());
+            }
+            std::fs::read_dir(&path)
+                .with_context(|| format!("Reading {:?}", path))?
+                .map(|entry| {
+                    entry
+                        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                        .map_err(|err| anyhow!(err))
+                })
+                .collect()
+        }
+    }
+
+    impl super::PGWal for PGWal {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn wal_seg_size(&self) -> anyhow::Result<u32> {
+            let control_file = self
+                .pgdata_path
+                .as_ref()
+                .and_then(|pgdata_path| global::global(pgdata_path).control_file().ok());
+            if let Some(control_file) = control_file {
+                return Ok(control_file.xlog_seg_size);
+            }
+            // pg_control is unreadable or missing, a complete segment file tells its size as well
+            for entry in self.entries()? {
+                let file_name = entry.name.to_string_lossy();
+                if let Some(WalFileKind::Segment(_)) = WalFileKind::try_parse(&file_name, 1) {
+                    let size = std::fs::metadata(self.path.join(file_name.as_ref()))?.len();
+                    if size.is_power_of_two() && size <= 1 << 30 {
+                        return Ok(size as u32);
+                    }
+                }
+            }
+            bail!("WAL segment size is unknown, neither pg_control nor a segment can be read")
+        }
+
+        fn items(&self) -> anyhow::Result<Vec<WalItem>> {
+            Ok(wal_items(
+                self.entries()?,
+                self.archive_statuses()?,
+                self.wal_seg_size()?,
+            ))
+        }
+
+        fn records(&self, segment: &WalSegmentName) -> anyhow::Result<WalRecords> {
+            WalReader::new(segment.timeline_id, self.wal_seg_size()?, |name| {
+                let path = self.path.join(name.file_name());
+                std::fs::read(&path).with_context(|| format!("Reading {:?}", path))
+            })
+            .records(segment)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::fs::{create_dir_all, remove_dir_all, write};
+
+        use pretty_assertions::assert_eq;
+
+        use crate::pgdata::pg_wal::{
+            reader::test_files::WalBuilder, record::test_records::wal_record, PGWal as _,
+            WalSegmentName,
+        };
+
+        use super::PGWal;
+
+        #[test]
+        fn reads_archived_segments() {
+            // given
+            let path =
+                std::env::temp_dir().join(format!("pg-browser-archive-{}", std::process::id()));
+            create_dir_all(&path).unwrap();
+            let mut wal = WalBuilder::new(1 << 20, 1, 1);
+            wal.record(&wal_record(1, 0x00, 740, 0, &[], &0i64.to_le_bytes()));
+            write(path.join("000000010000000000000001"), wal.segment(0)).unwrap();
+            let archive = PGWal::archive(&path);
+
+            // when
+            let wal_seg_size = archive.wal_seg_size().map_err(|err| err.to_string());
+            let records = archive.records(&WalSegmentName {
+                timeline_id: 1,
+                log: 0,
+                seg: 1,
+            });
+            remove_dir_all(&path).unwrap();
+
+            // then
+            assert_eq!(wal_seg_size, Ok(1 << 20));
+            assert_eq!(records.unwrap().records.len(), 1);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::Path;
+
+    use anyhow::bail;
+
+    use crate::common::fs::DirEntry;
+
+    use super::{
+        reader::{test_files::WalBuilder, WalReader, WalRecords},
+        record::test_records::wal_record,
+        wal_items, PGWal, WalItem, WalSegmentName,
+    };
+
+    pub const WAL_SEG_SIZE: u32 = 16 * 1024 * 1024;
+
+    pub struct StubPGWal;
+    impl PGWal for StubPGWal {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/pg_wal")
+        }
+
+        fn wal_seg_size(&self) -> anyhow::Result<u32> {
+            Ok(WAL_SEG_SIZE)
+        }
+
+        fn items(&self) -> anyhow::Result<Vec<WalItem>> {
+            Ok(wal_items(
+                vec![
+                    DirEntry::file("000000010000000000000001"),
+                    DirEntry::file("000000010000000000000002.00000028.backup"),
+                    DirEntry::file("000000010000000000000002"),
+                    DirEntry::file("000000010000000000000005.partial"),
+                    DirEntry::file("00000002.history"),
+                    DirEntry::file("000000020000000000000005"),
+                    DirEntry::dir("archive_status"),
+                    DirEntry::file("xlogtemp.42"),
+                ],
+                vec![
+                    "000000010000000000000001.done".to_string(),
+                    "000000010000000000000002.ready".to_string(),
+                    "00000002.history.ready".to_string(),
+                    "000000010000000000000000.done".to_string(),
+                ],
+                WAL_SEG_SIZE,
+            ))
+        }
+
+        fn records(&self, segment: &WalSegmentName) -> anyhow::Result<WalRecords> {
+            let mut wal = WalBuilder::new(WAL_SEG_SIZE, 1, 1);
+            wal.record(&wal_record(0, 0x30, 0, 0, &[], &[0x00, 0x60, 0, 0]));
+            let mut block = vec![0, 0x20, 3, 0, 0x7F, 6, 0, 0, 5, 0, 0, 0, 0, 0x40, 0, 0];
+            block.extend(7u32.to_le_bytes());
+            wal.record(&wal_record(
+                10,
+                0x00,
+                740,
+                0,
+                &[(&block, &[1, 2, 3])],
+                &[3, 0, 0x08],
+            ));
+            wal.record(&wal_record(1, 0x00, 740, 0, &[], &0i64.to_le_bytes()));
+            WalReader::new(segment.timeline_id, WAL_SEG_SIZE, |name| match name.seg {
+                1 => Ok(wal.segment(0)),
+                _ => bail!("{} is missing", name.file_name()),
+            })
+            .records(segment)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::common::{fs::DirEntry, Lsn};
+
+    use super::{
+        wal_file_name, wal_items, ArchiveStatus, WalFile, WalFileKind, WalItem, WalSegmentName,
+    };
+
+    const WAL_SEG_SIZE: u32 = 16 * 1024 * 1024;
+
+    fn segment(timeline_id: u32, log: u32, seg: u32) -> WalSegmentName {
+        WalSegmentName {
+            timeline_id,
+            log,
+            seg,
+        }
+    }
+
+    #[rstest]
+    #[case(1, 0x1B7B110, 16 * 1024 * 1024, "000000010000000000000001")]
+    #[case(2, 0x3_FF00_0000, 16 * 1024 * 1024, "0000000200000003000000FF")]
+    #[case(1, 0x1_4000_0000, 1024 * 1024 * 1024, "000000010000000100000001")]
+    fn composes_wal_file_name(
+        #[case] timeline_id: u32,
+        #[case] lsn: u64,
+        #[case] wal_seg_size: u32,
+        #[case] expected: &str,
+    ) {
+        // when
+        let file_name = wal_file_name(timeline_id, Lsn(lsn), wal_seg_size);
+
+        // then
+        assert_eq!(file_name, expected);
+    }
+
+    #[rstest]
+    #[case(
+        "0000000200000003000000FF",
+        Some(WalFileKind::Segment(segment(2, 3, 0xFF)))
+    )]
+    #[case(
+        "000000010000000000000005.partial",
+        Some(WalFileKind::Partial(segment(1, 0, 5)))
+    )]
+    #[case("0000000A.history", Some(WalFileKind::History(10)))]
+    #[case(
+        "000000010000000000000002.00000028.backup",
+        Some(WalFileKind::Backup(segment(1, 0, 2), 0x28))
+    )]
+    #[case("000000010000000000000100", None)]
+    #[case("00000001000000000000000a", None)]
+    #[case("xlogtemp.42", None)]
+    fn recognizes_wal_file(#[case] file_name: &str, #[case] expected: Option<WalFileKind>) {
+        // when
+        let kind = WalFileKind::try_parse(file_name, WAL_SEG_SIZE);
+
+        // then
+        assert_eq!(kind, expected);
+    }
+
+    #[test]
+    fn computes_segment_lsn_range() {
+        // given
+        let name = segment(1, 3, 0xFF);
+
+        // when
+        let range = (name.start_lsn(WAL_SEG_SIZE), name.end_lsn(WAL_SEG_SIZE));
+
+        // then
+        assert_eq!(range, (Lsn(0x3_FF00_0000), Lsn(0x4_0000_0000)));
+    }
+
+    #[test]
+    fn lists_wal_items_with_gaps_and_archive_statuses() {
+        // given
+        let entries = vec![
+            DirEntry::file("000000010000000100000001"),
+            DirEntry::file("0000000100000000000000FE"),
+            DirEntry::file("000000020000000100000001"),
+            DirEntry::dir("archive_status"),
+            DirEntry::file("xlogtemp.42"),
+        ];
+        let statuses = vec![
+            "0000000100000000000000FE.done".to_string(),
+            "0000000100000000000000FD.done".to_string(),
+        ];
+
+        // when
+        let items = wal_items(entries, statuses, WAL_SEG_SIZE);
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            items,
+            vec![
+                WalItem::File(WalFile { file_name: "0000000100000000000000FE".to_string(), kind: WalFileKind::Segment(segment(1, 0, 0xFE)), archive_status: Some(ArchiveStatus::Done) }),
+                WalItem::Gap { first: segment(1, 0, 0xFF), last: segment(1, 1, 0) },
+                WalItem::File(WalFile { file_name: "000000010000000100000001".to_string(), kind: WalFileKind::Segment(segment(1, 1, 1)), archive_status: None }),
+                WalItem::File(WalFile { file_name: "000000020000000100000001".to_string(), kind: WalFileKind::Segment(segment(2, 1, 1)), archive_status: None }),
+                WalItem::OrphanArchiveStatus("0000000100000000000000FD".to_string(), ArchiveStatus::Done),
+                WalItem::Unknown(DirEntry::file("xlogtemp.42")),
+            ]
+        );
+    }
+}
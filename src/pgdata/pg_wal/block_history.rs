@@ -0,0 +1,194 @@
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    common::PgOid,
+    pgdata::base::db_dir::{ForkSegmentFile, ForkType, RelFileNode},
+};
+
+use super::{
+    record::{BlockImage, BlockRef, RelFileLocator, WalRecord},
+    rmgr::{DEFAULTTABLESPACE_OID, GLOBALTABLESPACE_OID},
+    PGWal, WalFileKind, WalItem,
+};
+
+/// A fork of a relation as WAL records refer to it
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RelationFork {
+    pub locator: RelFileLocator,
+    pub fork: ForkType,
+}
+
+impl RelationFork {
+    /// Parses a path of a fork relative to PG_DATA, e.g. `base/5/16384`, `global/1262_vm` or
+    /// `pg_tblspc/16390/PG_15_202209061/5/16391`, segments and temporary relations are rejected
+    pub fn try_parse(relpath: &str) -> Result<RelationFork> {
+        let parts = relpath.trim_matches('/').split('/').collect::<Vec<_>>();
+        let oid = |part: &str| {
+            PgOid::try_parse(part)
+                .map(|PgOid(oid)| oid)
+                .with_context(|| format!("Expected oid, got {part}"))
+        };
+        let (spc_oid, db_oid, file_name) = match parts.as_slice() {
+            ["base", db_oid, file_name] => (DEFAULTTABLESPACE_OID, oid(db_oid)?, *file_name),
+            ["global", file_name] => (GLOBALTABLESPACE_OID, 0, *file_name),
+            ["pg_tblspc", spc_oid, _, db_oid, file_name] => {
+                (oid(spc_oid)?, oid(db_oid)?, *file_name)
+            }
+            _ => bail!("Expected relation path, e.g. base/5/16384 or global/1262, got {relpath}"),
+        };
+        let fork_segment_file = ForkSegmentFile::try_parse(file_name).with_context(|| {
+            format!("Expected relfilenode, e.g. 16384 or 16384_vm, got {file_name}")
+        })?;
+        if fork_segment_file.segment_id() != 0 {
+            bail!(
+                "Segments are not searched separately, pass the block number of the fork instead"
+            );
+        }
+        let rel_number = match fork_segment_file.rel_file_node() {
+            RelFileNode::Permanent(PgOid(oid)) => oid,
+            RelFileNode::Temporary(..) => bail!("Temporary relations are not WAL-logged"),
+        };
+        Ok(RelationFork {
+            locator: RelFileLocator {
+                spc_oid,
+                db_oid,
+                rel_number,
+            },
+            fork: *fork_segment_file.fork_type(),
+        })
+    }
+}
+
+/// A record referencing the searched block
+#[derive(Debug, PartialEq)]
+pub struct BlockChange {
+    /// Major version of PostgreSQL which wrote the record
+    pub pg_version: u32,
+    pub record: WalRecord,
+}
+
+impl BlockChange {
+    /// Returns the full-page image of the searched block if the record carries one
+    pub fn image(&self, fork: &RelationFork, block_number: u32) -> Option<&BlockImage> {
+        self.record
+            .blocks
+            .iter()
+            .filter(|block| matches(fork, block_number, block))
+            .find_map(|block| block.image.as_ref())
+    }
+}
+
+fn matches(fork: &RelationFork, block_number: u32, block: &BlockRef) -> bool {
+    block.locator == fork.locator && block.fork == fork.fork && block.block_number == block_number
+}
+
+/// Scans all segments in pg_wal, timeline by timeline, for records referencing the given block.
+/// The reasons the decoding of the segments stopped early are returned along
+pub fn block_history(
+    pg_wal: &impl PGWal,
+    fork: &RelationFork,
+    block_number: u32,
+) -> Result<(Vec<BlockChange>, Vec<anyhow::Error>)> {
+    let mut segments = pg_wal
+        .items()?
+        .into_iter()
+        .filter_map(|item| match item {
+            WalItem::File(file) => match file.kind {
+                WalFileKind::Segment(segment) => Some(segment),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    segments.sort();
+
+    let mut changes = Vec::new();
+    let mut errors = Vec::new();
+    for segment in segments {
+        match pg_wal.records(&segment) {
+            Ok(records) => {
+                changes.extend(
+                    records
+                        .records
+                        .into_iter()
+                        .filter(|record| {
+                            record
+                                .blocks
+                                .iter()
+                                .any(|block| matches(fork, block_number, block))
+                        })
+                        .map(|record| BlockChange {
+                            pg_version: records.pg_version,
+                            record,
+                        }),
+                );
+                errors.extend(records.error);
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+    Ok((changes, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::pgdata::{
+        base::db_dir::ForkType,
+        pg_wal::{record::RelFileLocator, test_stubs::StubPGWal},
+    };
+
+    use super::{block_history, RelationFork};
+
+    fn fork(spc_oid: u32, db_oid: u32, rel_number: u32, fork: ForkType) -> RelationFork {
+        RelationFork {
+            locator: RelFileLocator {
+                spc_oid,
+                db_oid,
+                rel_number,
+            },
+            fork,
+        }
+    }
+
+    #[rstest]
+    #[case("base/5/16384", Some(fork(1663, 5, 16384, ForkType::Main)))]
+    #[case(
+        "/base/5/16384_vm",
+        Some(fork(1663, 5, 16384, ForkType::VisibilityMap))
+    )]
+    #[case("global/1262_fsm", Some(fork(1664, 0, 1262, ForkType::FreeSpaceMap)))]
+    #[case(
+        "pg_tblspc/16390/PG_15_202209061/5/16391",
+        Some(fork(16390, 5, 16391, ForkType::Main))
+    )]
+    #[case("base/5/16384.1", None)]
+    #[case("base/5/t3_16384", None)]
+    #[case("base/16384", None)]
+    fn parses_relation_path(#[case] relpath: &str, #[case] expected: Option<RelationFork>) {
+        // when
+        let fork = RelationFork::try_parse(relpath).ok();
+
+        // then
+        assert_eq!(fork, expected);
+    }
+
+    #[test]
+    fn finds_records_referencing_block() {
+        // when
+        let (changes, errors) =
+            block_history(&StubPGWal, &fork(1663, 5, 16384, ForkType::Main), 7).unwrap();
+
+        // then
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.record.lsn.to_string())
+                .collect::<Vec<_>>(),
+            vec!["0/1000048"]
+        );
+        assert!(!errors.is_empty());
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+
+use crate::common::{bytes::ReadBytes, Lsn};
+
+use super::{
+    record::{WalRecord, XLogRecordHeader, SIZE_OF_XLOG_RECORD},
+    rmgr::{RmgrId, XLOG_SWITCH},
+    WalSegmentName,
+};
+
+/// Size of a WAL page used by a default PostgreSQL build
+pub const XLOG_BLCKSZ: u64 = 8192;
+
+const XLP_FIRST_IS_CONTRECORD: u16 = 0x0001;
+const XLP_LONG_HEADER: u16 = 0x0002;
+
+const SIZE_OF_XLOG_SHORT_PHD: u64 = 24;
+const SIZE_OF_XLOG_LONG_PHD: u64 = 40;
+
+/// Header of a WAL page, XLogPageHeaderData, the first page of a segment has the long variant
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct XLogPageHeader {
+    pub magic: u16,
+    pub info: u16,
+    pub timeline_id: u32,
+    pub page_addr: Lsn,
+    /// Bytes of the record continued from the previous page
+    pub rem_len: u32,
+}
+
+impl XLogPageHeader {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        Ok(XLogPageHeader {
+            magic: bytes.u16_at(0)?,
+            info: bytes.u16_at(2)?,
+            timeline_id: bytes.u32_at(4)?,
+            page_addr: Lsn(bytes.u64_at(8)?),
+            rem_len: bytes.u32_at(16)?,
+        })
+    }
+
+    pub fn is_contrecord(&self) -> bool {
+        self.info & XLP_FIRST_IS_CONTRECORD != 0
+    }
+
+    pub fn size(&self) -> u64 {
+        match self.info & XLP_LONG_HEADER {
+            0 => SIZE_OF_XLOG_SHORT_PHD,
+            _ => SIZE_OF_XLOG_LONG_PHD,
+        }
+    }
+}
+
+/// Maps XLOG_PAGE_MAGIC to the major version of PostgreSQL which wrote the WAL
+pub fn pg_version(magic: u16) -> Option<u32> {
+    match magic {
+        0xD106 => Some(13),
+        0xD10D => Some(14),
+        0xD110 => Some(15),
+        0xD113 => Some(16),
+        0xD116 => Some(17),
+        _ => None,
+    }
+}
+
+/// Records starting in a WAL segment
+#[derive(Debug)]
+pub struct WalRecords {
+    /// Major version of PostgreSQL which wrote the segment
+    pub pg_version: u32,
+    pub records: Vec<WalRecord>,
+    /// Reason the decoding stopped before the end of the segment, usually the end of WAL
+    pub error: Option<anyhow::Error>,
+}
+
+/// Reassembles records from WAL pages, possibly spanning several segments,
+/// the way xlogreader does
+pub struct WalReader<F: FnMut(&WalSegmentName) -> Result<Vec<u8>>> {
+    timeline_id: u32,
+    wal_seg_size: u32,
+    read_segment: F,
+    segments: HashMap<u64, Vec<u8>>,
+    pg_version: Option<u32>,
+}
+
+impl<F: FnMut(&WalSegmentName) -> Result<Vec<u8>>> WalReader<F> {
+    pub fn new(timeline_id: u32, wal_seg_size: u32, read_segment: F) -> Self {
+        WalReader {
+            timeline_id,
+            wal_seg_size,
+            read_segment,
+            segments: HashMap::new(),
+            pg_version: None,
+        }
+    }
+
+    /// Decodes the records starting in the given segment, a record continued from the previous
+    /// segment is skipped and the one continued in the next segment is read to its end
+    pub fn records(mut self, segment: &WalSegmentName) -> Result<WalRecords> {
+        let seg_size = self.wal_seg_size as u64;
+        let seg_start = segment.start_lsn(self.wal_seg_size).0;
+        let seg_end = seg_start + seg_size;
+
+        let first_page = self.page_header(seg_start)?;
+        let mut lsn = seg_start + first_page.size();
+        if first_page.is_contrecord() {
+            lsn = self.read(lsn, first_page.rem_len as usize)?.1;
+        }
+        lsn = self.next_record_lsn(lsn);
+
+        let mut records = Vec::new();
+        let mut error = None;
+        let mut prev = None;
+        while lsn < seg_end {
+            match self.read_record(lsn, prev) {
+                Ok((record, end)) => {
+                    prev = Some(record.lsn);
+                    lsn = match (RmgrId::from(record.header.rmid), record.rmgr_info()) {
+                        // the rest of the segment is left unused after a segment switch
+                        (RmgrId::Xlog, XLOG_SWITCH) => end.div_ceil(seg_size) * seg_size,
+                        _ => self.next_record_lsn(end),
+                    };
+                    records.push(record);
+                }
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+        }
+
+        Ok(WalRecords {
+            pg_version: self.pg_version.expect("version of a read page"),
+            records,
+            error,
+        })
+    }
+
+    fn read_record(&mut self, lsn: u64, prev: Option<Lsn>) -> Result<(WalRecord, u64)> {
+        let (header_bytes, _) = self.read(lsn, SIZE_OF_XLOG_RECORD)?;
+        let header = XLogRecordHeader::parse(&header_bytes)?;
+        if (header.tot_len as usize) < SIZE_OF_XLOG_RECORD {
+            bail!(
+                "invalid record length at {}: expected at least {}, got {}",
+                Lsn(lsn),
+                SIZE_OF_XLOG_RECORD,
+                header.tot_len
+            );
+        }
+        if let RmgrId::Unknown(rmid) = RmgrId::from(header.rmid) {
+            bail!("invalid resource manager ID {} at {}", rmid, Lsn(lsn));
+        }
+        if prev.is_some_and(|prev| prev != header.prev) {
+            bail!(
+                "record with incorrect prev-link {} at {}",
+                header.prev,
+                Lsn(lsn)
+            );
+        }
+
+        let (bytes, end) = self.read(lsn, header.tot_len as usize)?;
+        let pg_version = self.pg_version.expect("version of a read page");
+        Ok((WalRecord::parse(Lsn(lsn), &bytes, pg_version)?, end))
+    }
+
+    /// Reads bytes of a record skipping the page headers, returns them and the position past them
+    fn read(&mut self, lsn: u64, len: usize) -> Result<(Vec<u8>, u64)> {
+        let mut bytes = Vec::with_capacity(len);
+        let mut pos = lsn;
+        while bytes.len() < len {
+            let page_start = pos - pos % XLOG_BLCKSZ;
+            if pos == page_start {
+                let header = self.page_header(page_start)?;
+                if !header.is_contrecord() {
+                    bail!("there is no contrecord flag at {}", Lsn(pos));
+                }
+                if (header.rem_len as usize) < len - bytes.len() {
+                    bail!(
+                        "invalid contrecord length {} at {}, expected at least {}",
+                        header.rem_len,
+                        Lsn(pos),
+                        len - bytes.len()
+                    );
+                }
+                pos += header.size();
+            }
+            let chunk_len = ((page_start + XLOG_BLCKSZ - pos) as usize).min(len - bytes.len());
+            let (segment, offset) = self.segment(pos)?;
+            bytes.extend(segment.bytes_at(offset, chunk_len)?);
+            pos += chunk_len as u64;
+        }
+        Ok((bytes, pos))
+    }
+
+    /// Reads and validates the header of the page starting at the given LSN
+    fn page_header(&mut self, page_start: u64) -> Result<XLogPageHeader> {
+        let (segment, offset) = self.segment(page_start)?;
+        let header = XLogPageHeader::parse(segment.bytes_at(offset, XLOG_BLCKSZ as usize)?)?;
+
+        let pg_version = pg_version(header.magic).with_context(|| {
+            format!(
+                "invalid magic number {:04X} in WAL segment {}, offset {}",
+                header.magic,
+                self.segment_name(page_start).file_name(),
+                offset
+            )
+        })?;
+        match self.pg_version {
+            Some(version) if version != pg_version => bail!(
+                "WAL page at {} is written by PostgreSQL {}, previous pages by PostgreSQL {}",
+                Lsn(page_start),
+                pg_version,
+                version
+            ),
+            _ => self.pg_version = Some(pg_version),
+        }
+        if header.page_addr != Lsn(page_start) {
+            bail!(
+                "unexpected pageaddr {} in WAL segment {}, offset {}",
+                header.page_addr,
+                self.segment_name(page_start).file_name(),
+                offset
+            );
+        }
+        if offset == 0 && header.size() != SIZE_OF_XLOG_LONG_PHD {
+            bail!("WAL page at {} lacks the long header", Lsn(page_start));
+        }
+        Ok(header)
+    }
+
+    /// Returns the segment holding the given LSN and the offset of the LSN in it
+    fn segment(&mut self, lsn: u64) -> Result<(&[u8], usize)> {
+        let segment_no = lsn / self.wal_seg_size as u64;
+        if !self.segments.contains_key(&segment_no) {
+            let name = self.segment_name(lsn);
+            let bytes = (self.read_segment)(&name)
+                .with_context(|| format!("Reading WAL segment {}", name.file_name()))?;
+            self.segments.insert(segment_no, bytes);
+        }
+        Ok((
+            &self.segments[&segment_no],
+            (lsn % self.wal_seg_size as u64) as usize,
+        ))
+    }
+
+    fn segment_name(&self, lsn: u64) -> WalSegmentName {
+        WalSegmentName {
+            timeline_id: self.timeline_id,
+            log: 0,
+            seg: 0,
+        }
+        .with_segment_no(lsn / self.wal_seg_size as u64, self.wal_seg_size)
+    }
+
+    /// Aligns the end of a record to MAXALIGN and skips the page header if a page starts there
+    fn next_record_lsn(&self, end: u64) -> u64 {
+        let lsn = end.next_multiple_of(8);
+        match lsn {
+            _ if lsn.is_multiple_of(self.wal_seg_size as u64) => lsn + SIZE_OF_XLOG_LONG_PHD,
+            _ if lsn.is_multiple_of(XLOG_BLCKSZ) => lsn + SIZE_OF_XLOG_SHORT_PHD,
+            _ => lsn,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_files {
+    use super::{
+        SIZE_OF_XLOG_LONG_PHD, SIZE_OF_XLOG_SHORT_PHD, XLOG_BLCKSZ, XLP_FIRST_IS_CONTRECORD,
+        XLP_LONG_HEADER,
+    };
+
+    /// Lays out records in WAL segments splitting them between pages as XLogInsert does
+    pub struct WalBuilder {
+        wal_seg_size: u64,
+        start_lsn: u64,
+        pub bytes: Vec<u8>,
+        pos: u64,
+        pub prev: u64,
+    }
+
+    impl WalBuilder {
+        /// Starts WAL at the beginning of the given segment, the records are numbered from
+        /// there, `prev` of the first record is 0
+        pub fn new(wal_seg_size: u32, segment_no: u64, segments: usize) -> Self {
+            let start_lsn = segment_no * wal_seg_size as u64;
+            let mut builder = WalBuilder {
+                wal_seg_size: wal_seg_size as u64,
+                start_lsn,
+                bytes: vec![0; wal_seg_size as usize * segments],
+                pos: start_lsn,
+                prev: 0,
+            };
+            builder.page_header(start_lsn, 0);
+            builder.pos += SIZE_OF_XLOG_LONG_PHD;
+            builder
+        }
+
+        /// Places the record at the next MAXALIGNed position and returns its LSN,
+        /// the record's xl_prev is overwritten with the previous record's LSN
+        pub fn record(&mut self, record: &[u8]) -> u64 {
+            let mut record = record.to_vec();
+            record[8..16].copy_from_slice(&self.prev.to_le_bytes());
+            let crc = crate::common::crc32c::crc32c(&[&record[24..], &record[..20]].concat());
+            record[20..24].copy_from_slice(&crc.to_le_bytes());
+
+            let lsn = self.pos;
+            let mut written = 0;
+            while written < record.len() {
+                if self.pos.is_multiple_of(XLOG_BLCKSZ) {
+                    let rem_len = (record.len() - written) as u32;
+                    self.page_header(self.pos, rem_len);
+                    self.pos += self.header_size(self.pos);
+                }
+                let chunk =
+                    ((XLOG_BLCKSZ - self.pos % XLOG_BLCKSZ) as usize).min(record.len() - written);
+                let offset = (self.pos - self.start_lsn) as usize;
+                self.bytes[offset..offset + chunk]
+                    .copy_from_slice(&record[written..written + chunk]);
+                written += chunk;
+                self.pos += chunk as u64;
+            }
+            self.pos = self.pos.next_multiple_of(8);
+            if self.pos.is_multiple_of(XLOG_BLCKSZ) {
+                self.page_header(self.pos, 0);
+                self.pos += self.header_size(self.pos);
+            }
+            self.prev = lsn;
+            lsn
+        }
+
+        /// Returns the bytes of the n-th segment
+        pub fn segment(&self, n: usize) -> Vec<u8> {
+            let size = self.wal_seg_size as usize;
+            self.bytes[n * size..(n + 1) * size].to_vec()
+        }
+
+        fn header_size(&self, lsn: u64) -> u64 {
+            match lsn % self.wal_seg_size {
+                0 => SIZE_OF_XLOG_LONG_PHD,
+                _ => SIZE_OF_XLOG_SHORT_PHD,
+            }
+        }
+
+        fn page_header(&mut self, lsn: u64, rem_len: u32) {
+            let offset = (lsn - self.start_lsn) as usize;
+            if offset >= self.bytes.len() {
+                return;
+            }
+            let mut info = match rem_len {
+                0 => 0,
+                _ => XLP_FIRST_IS_CONTRECORD,
+            };
+            if lsn.is_multiple_of(self.wal_seg_size) {
+                info |= XLP_LONG_HEADER;
+            }
+            let header = &mut self.bytes[offset..offset + 40];
+            header[0..2].copy_from_slice(&0xD110u16.to_le_bytes());
+            header[2..4].copy_from_slice(&info.to_le_bytes());
+            header[4..8].copy_from_slice(&1u32.to_le_bytes());
+            header[8..16].copy_from_slice(&lsn.to_le_bytes());
+            header[16..20].copy_from_slice(&rem_len.to_le_bytes());
+            if info & XLP_LONG_HEADER != 0 {
+                header[32..36].copy_from_slice(&(self.wal_seg_size as u32).to_le_bytes());
+                header[36..40].copy_from_slice(&(XLOG_BLCKSZ as u32).to_le_bytes());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        common::Lsn,
+        pgdata::pg_wal::{record::test_records::wal_record, WalSegmentName},
+    };
+
+    use super::{test_files::WalBuilder, WalReader};
+
+    const WAL_SEG_SIZE: u32 = 64 * 1024;
+
+    fn segment_name(seg: u32) -> WalSegmentName {
+        WalSegmentName {
+            timeline_id: 1,
+            log: 0,
+            seg,
+        }
+    }
+
+    #[test]
+    fn reads_records_spanning_pages_and_segments() {
+        // given
+        let mut wal = WalBuilder::new(WAL_SEG_SIZE, 1, 2);
+        let small = wal_record(0, 0x30, 0, 0, &[], &[1, 0, 0, 0]);
+        let large = wal_record(0, 0x20, 0, 0, &[], &vec![7; 20000]);
+        let mut lsns = vec![wal.record(&small)];
+        while wal.prev + 21000 < 0x0002_0000 {
+            lsns.push(wal.record(&large));
+        }
+        let following = wal.record(&small);
+        let segments = [wal.segment(0), wal.segment(1)];
+
+        // when
+        let records = WalReader::new(1, WAL_SEG_SIZE, |name: &WalSegmentName| {
+            Ok(segments[name.seg as usize - 1].clone())
+        })
+        .records(&segment_name(1))
+        .unwrap();
+
+        // then
+        assert_eq!(
+            records
+                .records
+                .iter()
+                .map(|record| record.lsn)
+                .collect::<Vec<_>>(),
+            lsns.iter().map(|lsn| Lsn(*lsn)).collect::<Vec<_>>()
+        );
+        assert_eq!(records.records.last().unwrap().main_data, vec![7; 20000]);
+        assert_eq!(records.pg_version, 15);
+        assert!(records.error.is_none());
+
+        // when
+        let next_records = WalReader::new(1, WAL_SEG_SIZE, |name: &WalSegmentName| {
+            Ok(segments[name.seg as usize - 1].clone())
+        })
+        .records(&segment_name(2))
+        .unwrap();
+
+        // then
+        assert_eq!(next_records.records[0].lsn, Lsn(following));
+        assert_eq!(
+            next_records.error.unwrap().to_string(),
+            format!(
+                "invalid record length at {}: expected at least 24, got 0",
+                Lsn(following + 32)
+            )
+        );
+    }
+}
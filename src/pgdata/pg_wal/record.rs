@@ -0,0 +1,560 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    common::{bytes::ReadBytes, crc32c::crc32c, pglz::pglz_decompress, Lsn},
+    pgdata::{base::db_dir::ForkType, BLCKSZ},
+};
+
+/// Size of the fixed part of a record, XLogRecord
+pub const SIZE_OF_XLOG_RECORD: usize = 24;
+
+/// Offset of xl_crc in XLogRecord, the CRC covers the header bytes preceding it
+const XL_CRC_OFFSET: usize = 20;
+
+/// Bits of xl_info used by the WAL machinery itself, the rest belongs to the resource manager
+pub const XLR_INFO_MASK: u8 = 0x0F;
+
+const XLR_MAX_BLOCK_ID: u8 = 32;
+const XLR_BLOCK_ID_DATA_SHORT: u8 = 255;
+const XLR_BLOCK_ID_DATA_LONG: u8 = 254;
+const XLR_BLOCK_ID_ORIGIN: u8 = 253;
+const XLR_BLOCK_ID_TOPLEVEL_XID: u8 = 252;
+
+const BKPBLOCK_FORK_MASK: u8 = 0x0F;
+const BKPBLOCK_HAS_IMAGE: u8 = 0x10;
+const BKPBLOCK_HAS_DATA: u8 = 0x20;
+const BKPBLOCK_WILL_INIT: u8 = 0x40;
+const BKPBLOCK_SAME_REL: u8 = 0x80;
+
+const BKPIMAGE_HAS_HOLE: u8 = 0x01;
+
+/// Fixed part of a WAL record, XLogRecord
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct XLogRecordHeader {
+    pub tot_len: u32,
+    pub xid: u32,
+    pub prev: Lsn,
+    pub info: u8,
+    pub rmid: u8,
+    pub crc: u32,
+}
+
+impl XLogRecordHeader {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        Ok(XLogRecordHeader {
+            tot_len: bytes.u32_at(0)?,
+            xid: bytes.u32_at(4)?,
+            prev: Lsn(bytes.u64_at(8)?),
+            info: bytes.u8_at(16)?,
+            rmid: bytes.u8_at(17)?,
+            crc: bytes.u32_at(XL_CRC_OFFSET)?,
+        })
+    }
+}
+
+/// Identifies a relation's storage in WAL records: tablespace, database and relfilenode
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RelFileLocator {
+    pub spc_oid: u32,
+    pub db_oid: u32,
+    pub rel_number: u32,
+}
+
+impl RelFileLocator {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        Ok(RelFileLocator {
+            spc_oid: bytes.u32_at(0)?,
+            db_oid: bytes.u32_at(4)?,
+            rel_number: bytes.u32_at(8)?,
+        })
+    }
+}
+
+impl Display for RelFileLocator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.spc_oid, self.db_oid, self.rel_number)
+    }
+}
+
+/// Compression method of a full-page image
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ImageCompression {
+    None,
+    Pglz,
+    Lz4,
+    Zstd,
+}
+
+/// Full-page image of a referenced block as stored in the record, without its hole
+#[derive(Debug, PartialEq)]
+pub struct BlockImage {
+    pub bytes: Vec<u8>,
+    pub hole_offset: u16,
+    pub hole_length: u16,
+    /// The image is restored on replay rather than kept for wal_consistency_checking only
+    pub apply: bool,
+    pub compression: ImageCompression,
+}
+
+impl BlockImage {
+    /// Reconstructs the page decompressing the image and filling its hole with zeros
+    /// as RestoreBlockImage does
+    pub fn restore(&self) -> Result<Vec<u8>> {
+        let hole_offset = self.hole_offset as usize;
+        let hole_length = self.hole_length as usize;
+        let image_len = (BLCKSZ as usize)
+            .checked_sub(hole_length)
+            .with_context(|| format!("Invalid image hole length {}", hole_length))?;
+        let image = match self.compression {
+            ImageCompression::None => self.bytes.clone(),
+            ImageCompression::Pglz => {
+                pglz_decompress(&self.bytes, image_len).context("Decompressing the page image")?
+            }
+            ImageCompression::Lz4 => bail!("LZ4 compressed page images are not supported"),
+            ImageCompression::Zstd => bail!("zstd compressed page images are not supported"),
+        };
+        if image.len() != image_len || hole_offset > image_len {
+            bail!(
+                "Page image of {} bytes does not fit its hole of {} bytes at offset {}",
+                image.len(),
+                hole_length,
+                hole_offset
+            );
+        }
+
+        let mut page = Vec::with_capacity(BLCKSZ as usize);
+        page.extend_from_slice(&image[..hole_offset]);
+        page.resize(hole_offset + hole_length, 0);
+        page.extend_from_slice(&image[hole_offset..]);
+        Ok(page)
+    }
+}
+
+/// A block modified by the record, XLogRecordBlockHeader and its payload
+#[derive(Debug, PartialEq)]
+pub struct BlockRef {
+    pub id: u8,
+    pub locator: RelFileLocator,
+    pub fork: ForkType,
+    pub block_number: u32,
+    /// Replay initializes the page from scratch instead of reading it
+    pub will_init: bool,
+    pub image: Option<BlockImage>,
+    pub data: Vec<u8>,
+}
+
+/// A decoded WAL record, the equivalent of DecodedXLogRecord
+#[derive(Debug, PartialEq)]
+pub struct WalRecord {
+    pub lsn: Lsn,
+    pub header: XLogRecordHeader,
+    pub blocks: Vec<BlockRef>,
+    pub main_data: Vec<u8>,
+    pub origin: Option<u16>,
+    pub toplevel_xid: Option<u32>,
+}
+
+impl WalRecord {
+    /// Verifies the CRC of the xl_tot_len bytes of a record and decodes its block references
+    /// and main data as DecodeXLogRecord does
+    pub fn parse(lsn: Lsn, bytes: &[u8], pg_version: u32) -> Result<Self> {
+        let header = XLogRecordHeader::parse(bytes)?;
+        if bytes.len() != header.tot_len as usize {
+            bail!(
+                "Record at {} is {} bytes long, {} bytes given",
+                lsn,
+                header.tot_len,
+                bytes.len()
+            );
+        }
+        let computed_crc =
+            crc32c(&[&bytes[SIZE_OF_XLOG_RECORD..], &bytes[..XL_CRC_OFFSET]].concat());
+        if computed_crc != header.crc {
+            bail!(
+                "incorrect resource manager data checksum in record at {}",
+                lsn
+            );
+        }
+        WalRecord::decode(lsn, header, bytes, pg_version)
+            .with_context(|| format!("Decoding record at {}", lsn))
+    }
+
+    fn decode(lsn: Lsn, header: XLogRecordHeader, bytes: &[u8], pg_version: u32) -> Result<Self> {
+        struct BlockHeader {
+            id: u8,
+            locator: RelFileLocator,
+            fork: ForkType,
+            block_number: u32,
+            will_init: bool,
+            data_len: usize,
+            image: Option<(usize, u16, u16, bool, ImageCompression)>,
+        }
+
+        let mut offset = SIZE_OF_XLOG_RECORD;
+        let mut block_headers = Vec::<BlockHeader>::new();
+        let mut main_data_len = 0;
+        let mut origin = None;
+        let mut toplevel_xid = None;
+        let mut payload_len = 0;
+
+        // block headers come first, the main data header is always the last one if present,
+        // the headers end where only the payloads they announce remain
+        while bytes.len().saturating_sub(offset) > payload_len {
+            let block_id = bytes.u8_at(offset)?;
+            offset += 1;
+            match block_id {
+                XLR_BLOCK_ID_DATA_SHORT => {
+                    main_data_len = bytes.u8_at(offset)? as usize;
+                    offset += 1;
+                    break;
+                }
+                XLR_BLOCK_ID_DATA_LONG => {
+                    main_data_len = bytes.u32_at(offset)? as usize;
+                    offset += 4;
+                    break;
+                }
+                XLR_BLOCK_ID_ORIGIN => {
+                    origin = Some(bytes.u16_at(offset)?);
+                    offset += 2;
+                }
+                XLR_BLOCK_ID_TOPLEVEL_XID => {
+                    toplevel_xid = Some(bytes.u32_at(offset)?);
+                    offset += 4;
+                }
+                id if id <= XLR_MAX_BLOCK_ID => {
+                    let fork_flags = bytes.u8_at(offset)?;
+                    let data_len = bytes.u16_at(offset + 1)? as usize;
+                    offset += 3;
+                    if fork_flags & BKPBLOCK_HAS_DATA == 0 && data_len != 0 {
+                        bail!("BKPBLOCK_HAS_DATA not set, but data length is {}", data_len);
+                    }
+
+                    let image = match fork_flags & BKPBLOCK_HAS_IMAGE {
+                        0 => None,
+                        _ => {
+                            let image_len = bytes.u16_at(offset)? as usize;
+                            let hole_offset = bytes.u16_at(offset + 2)?;
+                            let bimg_info = bytes.u8_at(offset + 4)?;
+                            offset += 5;
+                            let (apply, compression) = image_flags(bimg_info, pg_version);
+                            let hole_length =
+                                match (bimg_info & BKPIMAGE_HAS_HOLE != 0, compression) {
+                                    (false, _) => 0,
+                                    (true, ImageCompression::None) => {
+                                        (BLCKSZ as usize).saturating_sub(image_len) as u16
+                                    }
+                                    (true, _) => {
+                                        offset += 2;
+                                        bytes.u16_at(offset - 2)?
+                                    }
+                                };
+                            Some((image_len, hole_offset, hole_length, apply, compression))
+                        }
+                    };
+
+                    let locator = match fork_flags & BKPBLOCK_SAME_REL {
+                        0 => {
+                            offset += 12;
+                            RelFileLocator::parse(bytes.bytes_at(offset - 12, 12)?)?
+                        }
+                        _ => match block_headers.last() {
+                            Some(previous) => previous.locator,
+                            None => bail!("BKPBLOCK_SAME_REL set but no previous rel"),
+                        },
+                    };
+                    let block_number = bytes.u32_at(offset)?;
+                    offset += 4;
+
+                    payload_len += data_len + image.map_or(0, |(image_len, ..)| image_len);
+                    let fork_number = fork_flags & BKPBLOCK_FORK_MASK;
+                    block_headers.push(BlockHeader {
+                        id,
+                        locator,
+                        fork: ForkType::from_number(fork_number)
+                            .with_context(|| format!("Invalid fork number {}", fork_number))?,
+                        block_number,
+                        will_init: fork_flags & BKPBLOCK_WILL_INIT != 0,
+                        data_len,
+                        image,
+                    });
+                }
+                id => bail!("invalid block_id {} at {}", id, lsn),
+            }
+        }
+
+        // payloads follow the headers in the same order, each image precedes the block's data
+        let mut blocks = Vec::with_capacity(block_headers.len());
+        for block_header in block_headers {
+            let image = match block_header.image {
+                None => None,
+                Some((image_len, hole_offset, hole_length, apply, compression)) => {
+                    offset += image_len;
+                    Some(BlockImage {
+                        bytes: bytes.bytes_at(offset - image_len, image_len)?.to_vec(),
+                        hole_offset,
+                        hole_length,
+                        apply,
+                        compression,
+                    })
+                }
+            };
+            offset += block_header.data_len;
+            blocks.push(BlockRef {
+                id: block_header.id,
+                locator: block_header.locator,
+                fork: block_header.fork,
+                block_number: block_header.block_number,
+                will_init: block_header.will_init,
+                image,
+                data: bytes
+                    .bytes_at(offset - block_header.data_len, block_header.data_len)?
+                    .to_vec(),
+            });
+        }
+        let main_data = bytes.bytes_at(offset, main_data_len)?.to_vec();
+        if offset + main_data_len != bytes.len() {
+            bail!(
+                "record length {} does not match its contents, {} bytes decoded",
+                bytes.len(),
+                offset + main_data_len
+            );
+        }
+
+        Ok(WalRecord {
+            lsn,
+            header,
+            blocks,
+            main_data,
+            origin,
+            toplevel_xid,
+        })
+    }
+
+    /// The part of xl_info owned by the resource manager
+    pub fn rmgr_info(&self) -> u8 {
+        self.header.info & !XLR_INFO_MASK
+    }
+
+    /// Looks up the block reference with the given block id, as XLogRecGetBlock does
+    pub fn block(&self, id: u8) -> Option<&BlockRef> {
+        self.blocks.iter().find(|block| block.id == id)
+    }
+
+    /// Total length of the full-page images, pg_waldump reports the rest as the record length
+    pub fn fpi_len(&self) -> usize {
+        self.blocks
+            .iter()
+            .filter_map(|block| block.image.as_ref())
+            .map(|image| image.bytes.len())
+            .sum()
+    }
+}
+
+/// Decodes bimg_info flags, which were reshuffled in PostgreSQL 15 to add LZ4 and zstd
+fn image_flags(bimg_info: u8, pg_version: u32) -> (bool, ImageCompression) {
+    match pg_version {
+        ..=14 => (
+            bimg_info & 0x04 != 0,
+            match bimg_info & 0x02 {
+                0 => ImageCompression::None,
+                _ => ImageCompression::Pglz,
+            },
+        ),
+        _ => (
+            bimg_info & 0x02 != 0,
+            match bimg_info {
+                info if info & 0x04 != 0 => ImageCompression::Pglz,
+                info if info & 0x08 != 0 => ImageCompression::Lz4,
+                info if info & 0x10 != 0 => ImageCompression::Zstd,
+                _ => ImageCompression::None,
+            },
+        ),
+    }
+}
+
+#[cfg(test)]
+pub mod test_records {
+    use crate::common::crc32c::crc32c;
+
+    use super::{SIZE_OF_XLOG_RECORD, XL_CRC_OFFSET};
+
+    /// Composes a record of the header, the block headers, the main data header, the block
+    /// payloads and the main data
+    pub fn wal_record(
+        rmid: u8,
+        info: u8,
+        xid: u32,
+        prev: u64,
+        blocks: &[(&[u8], &[u8])],
+        main_data: &[u8],
+    ) -> Vec<u8> {
+        let mut body = blocks
+            .iter()
+            .flat_map(|(header, _)| header.to_vec())
+            .collect::<Vec<_>>();
+        match main_data.len() {
+            0 => (),
+            len if len < 256 => body.extend([255, len as u8]),
+            len => {
+                body.push(254);
+                body.extend((len as u32).to_le_bytes());
+            }
+        }
+        body.extend(blocks.iter().flat_map(|(_, payload)| payload.to_vec()));
+        body.extend(main_data);
+
+        let mut bytes = Vec::new();
+        bytes.extend(((SIZE_OF_XLOG_RECORD + body.len()) as u32).to_le_bytes());
+        bytes.extend(xid.to_le_bytes());
+        bytes.extend(prev.to_le_bytes());
+        bytes.extend([info, rmid, 0, 0]);
+        bytes.extend([0; 4]);
+        bytes.extend(body);
+        let crc = crc32c(&[&bytes[SIZE_OF_XLOG_RECORD..], &bytes[..XL_CRC_OFFSET]].concat());
+        bytes[XL_CRC_OFFSET..SIZE_OF_XLOG_RECORD].copy_from_slice(&crc.to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{common::Lsn, pgdata::base::db_dir::ForkType};
+
+    use super::{
+        test_records::wal_record, BlockImage, BlockRef, ImageCompression, RelFileLocator, WalRecord,
+    };
+
+    #[test]
+    fn decodes_record_with_block_references() {
+        // given
+        let mut block_0 = vec![0, 0x20 | 0x10, 3, 0];
+        block_0.extend([4, 0, 0x10, 0, 0x04 | 0x02 | 0x01]); // 4 bytes image with hole, pglz
+        block_0.extend([0x10, 0]); // hole length
+        block_0.extend([0x7F, 6, 0, 0, 5, 0, 0, 0, 0, 0x40, 0, 0]);
+        block_0.extend(7u32.to_le_bytes());
+        let mut block_1 = vec![1, 0x80, 0, 0];
+        block_1.extend(9u32.to_le_bytes());
+        block_1.extend([252, 0xE8, 3, 0, 0]);
+        let bytes = wal_record(
+            10,
+            0x00,
+            1000,
+            0x0100_0028,
+            &[
+                (&block_0, &[0xAA, 0xBB, 0xCC, 0xDD, 1, 2, 3]),
+                (&block_1, &[]),
+            ],
+            &[5, 0, 0],
+        );
+
+        // when
+        let record = WalRecord::parse(Lsn(0x0100_0060), &bytes, 15).unwrap();
+
+        // then
+        let locator = RelFileLocator {
+            spc_oid: 1663,
+            db_oid: 5,
+            rel_number: 16384,
+        };
+        assert_eq!(record.header.xid, 1000);
+        assert_eq!(record.header.prev, Lsn(0x0100_0028));
+        assert_eq!(record.toplevel_xid, Some(1000));
+        assert_eq!(record.main_data, vec![5, 0, 0]);
+        assert_eq!(
+            record.blocks,
+            vec![
+                BlockRef {
+                    id: 0,
+                    locator,
+                    fork: ForkType::Main,
+                    block_number: 7,
+                    will_init: false,
+                    image: Some(BlockImage {
+                        bytes: vec![0xAA, 0xBB, 0xCC, 0xDD],
+                        hole_offset: 16,
+                        hole_length: 16,
+                        apply: true,
+                        compression: ImageCompression::Pglz,
+                    }),
+                    data: vec![1, 2, 3],
+                },
+                BlockRef {
+                    id: 1,
+                    locator,
+                    fork: ForkType::Main,
+                    block_number: 9,
+                    will_init: false,
+                    image: None,
+                    data: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_full_page_image_without_main_data() {
+        // given
+        let mut block = vec![0, 0x10 | 1, 0, 0];
+        block.extend([8, 0, 0, 0, 0x02]); // 8 bytes image without hole
+        block.extend([0x7F, 6, 0, 0, 1, 0, 0, 0, 0xE7, 4, 0, 0]);
+        block.extend(2u32.to_le_bytes());
+        // the image starts with bytes which would pass for another block header
+        let bytes = wal_record(0, 0xA0, 1, 0, &[(&block, &[0, 0, 0, 1, 0, 0, 0, 0])], &[]);
+
+        // when
+        let record = WalRecord::parse(Lsn(0x0100_00A0), &bytes, 15).unwrap();
+
+        // then
+        assert_eq!(record.blocks.len(), 1);
+        assert_eq!(record.blocks[0].fork, ForkType::FreeSpaceMap);
+        assert_eq!(
+            record.blocks[0]
+                .image
+                .as_ref()
+                .map(|image| image.bytes.clone()),
+            Some(vec![0, 0, 0, 1, 0, 0, 0, 0])
+        );
+        assert!(record.main_data.is_empty());
+    }
+
+    #[test]
+    fn restores_page_image_with_hole() {
+        // given
+        let image = BlockImage {
+            bytes: vec![0x04, 1, 2, 0x06, 0x02],
+            hole_offset: 4,
+            hole_length: 8192 - 11,
+            apply: true,
+            compression: ImageCompression::Pglz,
+        };
+
+        // when
+        let page = image.restore().unwrap();
+
+        // then
+        assert_eq!(page.len(), 8192);
+        assert_eq!(page[..4], [1, 2, 1, 2]);
+        assert!(page[4..8185].iter().all(|byte| *byte == 0));
+        assert_eq!(page[8185..], [1, 2, 1, 2, 1, 2, 1]);
+    }
+
+    #[test]
+    fn rejects_record_with_invalid_crc() {
+        // given
+        let mut bytes = wal_record(0, 0x30, 0, 0, &[], &[1, 0, 0, 0]);
+        bytes[26] ^= 0xFF;
+
+        // when
+        let result = WalRecord::parse(Lsn(0x0100_0028), &bytes, 15);
+
+        // then
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "incorrect resource manager data checksum in record at 0/1000028"
+        );
+    }
+}
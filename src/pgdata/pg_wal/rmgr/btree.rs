@@ -0,0 +1,132 @@
+use anyhow::Result;
+
+use crate::{common::bytes::ReadBytes, pgdata::pg_wal::record::RelFileLocator};
+
+use super::unknown;
+
+const XLOG_BTREE_INSERT_LEAF: u8 = 0x00;
+const XLOG_BTREE_INSERT_UPPER: u8 = 0x10;
+const XLOG_BTREE_INSERT_META: u8 = 0x20;
+const XLOG_BTREE_SPLIT_L: u8 = 0x30;
+const XLOG_BTREE_SPLIT_R: u8 = 0x40;
+const XLOG_BTREE_INSERT_POST: u8 = 0x50;
+const XLOG_BTREE_DEDUP: u8 = 0x60;
+const XLOG_BTREE_DELETE: u8 = 0x70;
+const XLOG_BTREE_UNLINK_PAGE: u8 = 0x80;
+const XLOG_BTREE_UNLINK_PAGE_META: u8 = 0x90;
+const XLOG_BTREE_NEWROOT: u8 = 0xA0;
+const XLOG_BTREE_MARK_PAGE_HALFDEAD: u8 = 0xB0;
+const XLOG_BTREE_VACUUM: u8 = 0xC0;
+const XLOG_BTREE_REUSE_PAGE: u8 = 0xD0;
+const XLOG_BTREE_META_CLEANUP: u8 = 0xE0;
+
+/// Describes a btree record, the metapage of META_CLEANUP is registered with block 0 rather than
+/// with the main data
+pub fn describe(info: u8, data: &[u8], block_data: &[u8], pg_version: u32) -> Result<String> {
+    // snapshotConflictHorizon was called latestRemovedXid before PostgreSQL 16
+    let horizon = match pg_version {
+        ..=15 => "latestRemovedXid",
+        _ => "snapshotConflictHorizon",
+    };
+    Ok(match info {
+        XLOG_BTREE_INSERT_LEAF => format!("INSERT_LEAF off {}", data.u16_at(0)?),
+        XLOG_BTREE_INSERT_UPPER => format!("INSERT_UPPER off {}", data.u16_at(0)?),
+        XLOG_BTREE_INSERT_META => format!("INSERT_META off {}", data.u16_at(0)?),
+        XLOG_BTREE_INSERT_POST => format!("INSERT_POST off {}", data.u16_at(0)?),
+        op @ (XLOG_BTREE_SPLIT_L | XLOG_BTREE_SPLIT_R) => format!(
+            "{} level {}, firstrightoff {}, newitemoff {}, postingoff {}",
+            match op {
+                XLOG_BTREE_SPLIT_L => "SPLIT_L",
+                _ => "SPLIT_R",
+            },
+            data.u32_at(0)?,
+            data.u16_at(4)?,
+            data.u16_at(6)?,
+            data.u16_at(8)?
+        ),
+        XLOG_BTREE_DEDUP => format!("DEDUP nintervals {}", data.u16_at(0)?),
+        XLOG_BTREE_DELETE => match pg_version {
+            ..=13 => format!(
+                "DELETE latestRemovedXid {}; ndeleted {}",
+                data.u32_at(0)?,
+                data.i32_at(4)?
+            ),
+            _ => format!(
+                "DELETE {horizon} {}; ndeleted {}; nupdated {}",
+                data.u32_at(0)?,
+                data.u16_at(4)?,
+                data.u16_at(6)?
+            ),
+        },
+        op @ (XLOG_BTREE_UNLINK_PAGE | XLOG_BTREE_UNLINK_PAGE_META) => {
+            let name = match op {
+                XLOG_BTREE_UNLINK_PAGE => "UNLINK_PAGE",
+                _ => "UNLINK_PAGE_META",
+            };
+            match pg_version {
+                // before PostgreSQL 14 the deleted page was stamped with a 32-bit xid
+                ..=13 => format!(
+                    "{name} left {}; right {}; btpo_xact {}; leafleft {}; leafright {}; topparent {}",
+                    data.u32_at(0)?,
+                    data.u32_at(4)?,
+                    data.u32_at(20)?,
+                    data.u32_at(8)?,
+                    data.u32_at(12)?,
+                    data.u32_at(16)?
+                ),
+                _ => format!(
+                    "{name} left {}; right {}; level {}; safexid {}:{}; leafleft {}; leafright {}; leaftopparent {}",
+                    data.u32_at(0)?,
+                    data.u32_at(4)?,
+                    data.u32_at(8)?,
+                    data.u32_at(20)?,
+                    data.u32_at(16)?,
+                    data.u32_at(24)?,
+                    data.u32_at(28)?,
+                    data.u32_at(32)?
+                ),
+            }
+        }
+        XLOG_BTREE_NEWROOT => match pg_version {
+            ..=15 => format!("NEWROOT lev {}", data.u32_at(4)?),
+            _ => format!("NEWROOT level {}", data.u32_at(4)?),
+        },
+        XLOG_BTREE_MARK_PAGE_HALFDEAD => format!(
+            "MARK_PAGE_HALFDEAD topparent {}; leaf {}; left {}; right {}",
+            data.u32_at(16)?,
+            data.u32_at(4)?,
+            data.u32_at(8)?,
+            data.u32_at(12)?
+        ),
+        XLOG_BTREE_VACUUM => format!(
+            "VACUUM ndeleted {}; nupdated {}",
+            data.u16_at(0)?,
+            data.u16_at(2)?
+        ),
+        XLOG_BTREE_REUSE_PAGE => match pg_version {
+            ..=13 => format!(
+                "REUSE_PAGE rel {}; latestRemovedXid {}",
+                RelFileLocator::parse(data)?,
+                data.u32_at(16)?
+            ),
+            _ => format!(
+                "REUSE_PAGE rel {}; {horizon} {}:{}",
+                RelFileLocator::parse(data)?,
+                data.u32_at(20)?,
+                data.u32_at(16)?
+            ),
+        },
+        XLOG_BTREE_META_CLEANUP => match pg_version {
+            ..=13 => format!(
+                "META_CLEANUP oldest_btpo_xact {}; last_cleanup_num_heap_tuples {}",
+                block_data.u32_at(20)?,
+                block_data.f64_at(24)?
+            ),
+            _ => format!(
+                "META_CLEANUP last_cleanup_num_delpages {}",
+                block_data.u32_at(20)?
+            ),
+        },
+        _ => unknown(info),
+    })
+}
@@ -0,0 +1,277 @@
+use anyhow::Result;
+
+use crate::common::bytes::ReadBytes;
+
+use super::{join, unknown};
+use crate::pgdata::pg_wal::record::RelFileLocator;
+
+const XLOG_HEAP_OPMASK: u8 = 0x70;
+const XLOG_HEAP_INIT_PAGE: u8 = 0x80;
+
+const XLOG_HEAP_INSERT: u8 = 0x00;
+const XLOG_HEAP_DELETE: u8 = 0x10;
+const XLOG_HEAP_UPDATE: u8 = 0x20;
+const XLOG_HEAP_TRUNCATE: u8 = 0x30;
+const XLOG_HEAP_HOT_UPDATE: u8 = 0x40;
+const XLOG_HEAP_CONFIRM: u8 = 0x50;
+const XLOG_HEAP_LOCK: u8 = 0x60;
+const XLOG_HEAP_INPLACE: u8 = 0x70;
+
+const XLOG_HEAP2_REWRITE: u8 = 0x00;
+const XLOG_HEAP2_PRUNE: u8 = 0x10;
+const XLOG_HEAP2_VACUUM: u8 = 0x20;
+const XLOG_HEAP2_FREEZE_PAGE: u8 = 0x30;
+const XLOG_HEAP2_VISIBLE: u8 = 0x40;
+const XLOG_HEAP2_MULTI_INSERT: u8 = 0x50;
+const XLOG_HEAP2_LOCK_UPDATED: u8 = 0x60;
+const XLOG_HEAP2_NEW_CID: u8 = 0x70;
+
+const XLH_TRUNCATE_CASCADE: u8 = 1 << 0;
+const XLH_TRUNCATE_RESTART_SEQS: u8 = 1 << 1;
+
+#[rustfmt::skip]
+const INFOBITS: [(u8, &str); 5] = [
+    (0x01, "IS_MULTI"),
+    (0x02, "LOCK_ONLY"),
+    (0x04, "EXCL_LOCK"),
+    (0x08, "KEYSHR_LOCK"),
+    (0x10, "KEYS_UPDATED"),
+];
+
+pub fn describe_heap(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    let init = match info & XLOG_HEAP_INIT_PAGE {
+        0 => "",
+        _ => "+INIT",
+    };
+    if pg_version <= 15 {
+        return describe_heap_pg15(info, init, data);
+    }
+    Ok(match info & XLOG_HEAP_OPMASK {
+        XLOG_HEAP_INSERT => format!(
+            "INSERT{init} off {}, flags {:#04X}",
+            data.u16_at(0)?,
+            data.u8_at(2)?
+        ),
+        XLOG_HEAP_DELETE => format!(
+            "DELETE xmax {}, off {}, infobits [{}], flags {:#04X}",
+            data.u32_at(0)?,
+            data.u16_at(4)?,
+            infobits(data.u8_at(6)?),
+            data.u8_at(7)?
+        ),
+        op @ (XLOG_HEAP_UPDATE | XLOG_HEAP_HOT_UPDATE) => format!(
+            "{}{init} old_xmax {}, old_off {}, old_infobits [{}], flags {:#04X}, new_xmax {}, new_off {}",
+            match op {
+                XLOG_HEAP_UPDATE => "UPDATE",
+                _ => "HOT_UPDATE",
+            },
+            data.u32_at(0)?,
+            data.u16_at(4)?,
+            infobits(data.u8_at(6)?),
+            data.u8_at(7)?,
+            data.u32_at(8)?,
+            data.u16_at(12)?
+        ),
+        XLOG_HEAP_TRUNCATE => {
+            let flags = data.u8_at(8)?;
+            let relids = (0..data.u32_at(4)? as usize)
+                .map(|i| data.u32_at(12 + i * 4))
+                .collect::<Result<Vec<_>>>()?;
+            let mut description = String::from("TRUNCATE");
+            if flags & XLH_TRUNCATE_CASCADE != 0 {
+                description += " cascade";
+            }
+            if flags & XLH_TRUNCATE_RESTART_SEQS != 0 {
+                description += " restart_seqs";
+            }
+            format!("{description} nrelids {}, relids {}", relids.len(), join(relids))
+        }
+        XLOG_HEAP_CONFIRM => format!("HEAP_CONFIRM off {}", data.u16_at(0)?),
+        XLOG_HEAP_LOCK => format!(
+            "LOCK xmax {}, off {}, infobits [{}], flags {:#04X}",
+            data.u32_at(0)?,
+            data.u16_at(4)?,
+            infobits(data.u8_at(6)?),
+            data.u8_at(7)?
+        ),
+        XLOG_HEAP_INPLACE => format!("INPLACE off {}", data.u16_at(0)?),
+        _ => unknown(info),
+    })
+}
+
+/// Describes heap records as heapdesc.c does up to PostgreSQL 15, where the fields are separated
+/// by spaces only and the infobits are listed each followed by a space
+fn describe_heap_pg15(info: u8, init: &str, data: &[u8]) -> Result<String> {
+    Ok(match info & XLOG_HEAP_OPMASK {
+        XLOG_HEAP_INSERT => format!(
+            "INSERT{init} off {} flags {:#04X}",
+            data.u16_at(0)?,
+            data.u8_at(2)?
+        ),
+        XLOG_HEAP_DELETE => format!(
+            "DELETE off {} flags {:#04X} {}",
+            data.u16_at(4)?,
+            data.u8_at(7)?,
+            infobits_pg15(data.u8_at(6)?)
+        ),
+        op @ (XLOG_HEAP_UPDATE | XLOG_HEAP_HOT_UPDATE) => format!(
+            "{}{init} off {} xmax {} flags {:#04X} {}; new off {} xmax {}",
+            match op {
+                XLOG_HEAP_UPDATE => "UPDATE",
+                _ => "HOT_UPDATE",
+            },
+            data.u16_at(4)?,
+            data.u32_at(0)?,
+            data.u8_at(7)?,
+            infobits_pg15(data.u8_at(6)?),
+            data.u16_at(12)?,
+            data.u32_at(8)?
+        ),
+        XLOG_HEAP_TRUNCATE => {
+            let flags = data.u8_at(8)?;
+            let relids = (0..data.u32_at(4)? as usize)
+                .map(|i| data.u32_at(12 + i * 4))
+                .collect::<Result<Vec<_>>>()?;
+            let mut description = String::from("TRUNCATE ");
+            if flags & XLH_TRUNCATE_CASCADE != 0 {
+                description += "cascade ";
+            }
+            if flags & XLH_TRUNCATE_RESTART_SEQS != 0 {
+                description += "restart_seqs ";
+            }
+            format!(
+                "{description}nrelids {} relids {}",
+                relids.len(),
+                join(relids)
+            )
+        }
+        XLOG_HEAP_CONFIRM => format!("HEAP_CONFIRM off {}", data.u16_at(0)?),
+        XLOG_HEAP_LOCK => format!(
+            "LOCK off {}: xid {}: flags {:#04X} {}",
+            data.u16_at(4)?,
+            data.u32_at(0)?,
+            data.u8_at(7)?,
+            infobits_pg15(data.u8_at(6)?)
+        ),
+        XLOG_HEAP_INPLACE => format!("INPLACE off {}", data.u16_at(0)?),
+        _ => unknown(info),
+    }
+    .trim_end()
+    .to_string())
+}
+
+pub fn describe_heap2(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    let init = match info & XLOG_HEAP_INIT_PAGE {
+        0 => "",
+        _ => "+INIT",
+    };
+    Ok(match (info & XLOG_HEAP_OPMASK, pg_version) {
+        (XLOG_HEAP2_REWRITE, _) => "REWRITE".to_string(),
+        (XLOG_HEAP2_PRUNE, ..=13) => format!("CLEAN remxid {}", data.u32_at(0)?),
+        (XLOG_HEAP2_PRUNE, ..=15) => format!(
+            "PRUNE latestRemovedXid {} nredirected {} ndead {}",
+            data.u32_at(0)?,
+            data.u16_at(4)?,
+            data.u16_at(6)?
+        ),
+        (XLOG_HEAP2_PRUNE, 16) => format!(
+            "PRUNE snapshotConflictHorizon {}, nredirected {}, ndead {}",
+            data.u32_at(0)?,
+            data.u16_at(4)?,
+            data.u16_at(6)?
+        ),
+        (XLOG_HEAP2_VACUUM, ..=13) => format!(
+            "FREEZE_PAGE cutoff xid {} ntuples {}",
+            data.u32_at(0)?,
+            data.u16_at(4)?
+        ),
+        (XLOG_HEAP2_VACUUM, ..=16) => format!("VACUUM nunused {}", data.u16_at(0)?),
+        (XLOG_HEAP2_FREEZE_PAGE, ..=13) => format!("CLEANUP_INFO remxid {}", data.u32_at(12)?),
+        (XLOG_HEAP2_FREEZE_PAGE, ..=15) => format!(
+            "FREEZE_PAGE cutoff xid {} ntuples {}",
+            data.u32_at(0)?,
+            data.u16_at(4)?
+        ),
+        (XLOG_HEAP2_FREEZE_PAGE, 16) => format!(
+            "FREEZE_PAGE snapshotConflictHorizon {}, nplans {}",
+            data.u32_at(0)?,
+            data.u16_at(4)?
+        ),
+        // PostgreSQL 17 merged pruning, vacuuming and freezing into a single record
+        (op @ (XLOG_HEAP2_PRUNE | XLOG_HEAP2_VACUUM | XLOG_HEAP2_FREEZE_PAGE), _) => format!(
+            "{} flags {:#04X}",
+            match op {
+                XLOG_HEAP2_PRUNE => "PRUNE_ON_ACCESS",
+                XLOG_HEAP2_VACUUM => "PRUNE_VACUUM_SCAN",
+                _ => "PRUNE_VACUUM_CLEANUP",
+            },
+            data.u8_at(1)?
+        ),
+        (XLOG_HEAP2_VISIBLE, ..=15) => format!(
+            "VISIBLE cutoff xid {} flags {:#04X}",
+            data.u32_at(0)?,
+            data.u8_at(4)?
+        ),
+        (XLOG_HEAP2_VISIBLE, _) => format!(
+            "VISIBLE snapshotConflictHorizon {}, flags {:#04X}",
+            data.u32_at(0)?,
+            data.u8_at(4)?
+        ),
+        (XLOG_HEAP2_MULTI_INSERT, ..=15) => format!(
+            "MULTI_INSERT{init} {} tuples flags {:#04X}",
+            data.u16_at(2)?,
+            data.u8_at(0)?
+        ),
+        (XLOG_HEAP2_LOCK_UPDATED, ..=15) => format!(
+            "LOCK_UPDATED off {}: xmax {}: flags {:#04X} {}",
+            data.u16_at(4)?,
+            data.u32_at(0)?,
+            data.u8_at(7)?,
+            infobits_pg15(data.u8_at(6)?)
+        )
+        .trim_end()
+        .to_string(),
+        (XLOG_HEAP2_MULTI_INSERT, _) => format!(
+            "MULTI_INSERT{init} ntuples {}, flags {:#04X}",
+            data.u16_at(2)?,
+            data.u8_at(0)?
+        ),
+        (XLOG_HEAP2_LOCK_UPDATED, _) => format!(
+            "LOCK_UPDATED xmax {}, off {}, infobits [{}], flags {:#04X}",
+            data.u32_at(0)?,
+            data.u16_at(4)?,
+            infobits(data.u8_at(6)?),
+            data.u8_at(7)?
+        ),
+        (XLOG_HEAP2_NEW_CID, _) => format!(
+            "NEW_CID rel {}; tid {}/{}; cmin: {}, cmax: {}, combo: {}",
+            RelFileLocator::parse(data.bytes_at(16, 12)?)?,
+            (data.u16_at(28)? as u32) << 16 | data.u16_at(30)? as u32,
+            data.u16_at(32)?,
+            data.u32_at(4)?,
+            data.u32_at(8)?,
+            data.u32_at(12)?
+        ),
+        _ => unknown(info),
+    })
+}
+
+/// Names the bits of xl_heap_delete.infobits_set the way out_infobits does up to PostgreSQL 15,
+/// each name followed by a space
+fn infobits_pg15(infobits_set: u8) -> String {
+    INFOBITS
+        .iter()
+        .filter(|(bit, _)| infobits_set & bit != 0)
+        .map(|(_, name)| format!("{name} "))
+        .collect()
+}
+
+/// Names the bits of xl_heap_delete.infobits_set and its relatives
+fn infobits(infobits_set: u8) -> String {
+    join(
+        INFOBITS
+            .iter()
+            .filter(|(bit, _)| infobits_set & bit != 0)
+            .map(|(_, name)| name),
+    )
+}
@@ -0,0 +1,462 @@
+use anyhow::Result;
+
+use crate::{
+    common::bytes::ReadBytes,
+    pgdata::pg_wal::record::{RelFileLocator, WalRecord},
+};
+
+use super::{block_data, unknown, RmgrId};
+
+#[rustfmt::skip]
+const HASH_RECORDS: [(u8, &str); 13] = [
+    (0x00, "INIT_META_PAGE"),
+    (0x10, "INIT_BITMAP_PAGE"),
+    (0x20, "INSERT"),
+    (0x30, "ADD_OVFL_PAGE"),
+    (0x40, "SPLIT_ALLOCATE_PAGE"),
+    (0x50, "SPLIT_PAGE"),
+    (0x60, "SPLIT_COMPLETE"),
+    (0x70, "MOVE_PAGE_CONTENTS"),
+    (0x80, "SQUEEZE_PAGE"),
+    (0x90, "DELETE"),
+    (0xA0, "SPLIT_CLEANUP"),
+    (0xB0, "UPDATE_META_PAGE"),
+    (0xC0, "VACUUM_ONE_PAGE"),
+];
+
+#[rustfmt::skip]
+const GIN_RECORDS: [(u8, &str); 9] = [
+    (0x10, "CREATE_PTREE"),
+    (0x20, "INSERT"),
+    (0x30, "SPLIT"),
+    (0x40, "VACUUM_PAGE"),
+    (0x50, "DELETE_PAGE"),
+    (0x60, "UPDATE_META_PAGE"),
+    (0x70, "INSERT_LISTPAGE"),
+    (0x80, "DELETE_LISTPAGE"),
+    (0x90, "VACUUM_DATA_LEAF_PAGE"),
+];
+
+#[rustfmt::skip]
+const GIST_RECORDS: [(u8, &str); 6] = [
+    (0x00, "PAGE_UPDATE"),
+    (0x10, "DELETE"),
+    (0x20, "PAGE_REUSE"),
+    (0x30, "PAGE_SPLIT"),
+    (0x60, "PAGE_DELETE"),
+    (0x70, "ASSIGN_LSN"),
+];
+
+#[rustfmt::skip]
+const SPGIST_RECORDS: [(u8, &str); 8] = [
+    (0x10, "ADD_LEAF"),
+    (0x20, "MOVE_LEAFS"),
+    (0x30, "ADD_NODE"),
+    (0x40, "SPLIT_TUPLE"),
+    (0x50, "PICKSPLIT"),
+    (0x60, "VACUUM_LEAF"),
+    (0x70, "VACUUM_ROOT"),
+    (0x80, "VACUUM_REDIRECT"),
+];
+
+#[rustfmt::skip]
+const BRIN_RECORDS: [(u8, &str); 6] = [
+    (0x00, "CREATE_INDEX"),
+    (0x10, "INSERT"),
+    (0x20, "UPDATE"),
+    (0x30, "SAMEPAGE_UPDATE"),
+    (0x40, "REVMAP_EXTEND"),
+    (0x50, "DESUMMARIZE"),
+];
+
+const XLOG_BRIN_OPMASK: u8 = 0x70;
+const XLOG_BRIN_INIT_PAGE: u8 = 0x80;
+
+const XLH_SPLIT_META_UPDATE_MASKS: u8 = 1 << 0;
+const XLH_SPLIT_META_UPDATE_SPLITPOINT: u8 = 1 << 1;
+
+const GIN_INSERT_ISDATA: u16 = 0x01;
+const GIN_INSERT_ISLEAF: u16 = 0x02;
+const GIN_SPLIT_ROOT: u16 = 0x04;
+
+const GIN_SEGMENT_DELETE: u8 = 1;
+const GIN_SEGMENT_INSERT: u8 = 2;
+const GIN_SEGMENT_REPLACE: u8 = 3;
+const GIN_SEGMENT_ADDITEMS: u8 = 4;
+
+/// Size of GinMetaPageData which precedes ndeleted in ginxlogDeleteListPages
+const SIZE_OF_GIN_META_PAGE_DATA: usize = 56;
+
+/// Describes the records of index access methods the way their rmgrdesc routines of PostgreSQL 15
+/// do, some of them describe the data of block 0 rather than the main data
+pub fn describe(rmgr_id: RmgrId, record: &WalRecord) -> Result<String> {
+    let info = record.rmgr_info();
+    let (records, op, suffix): (&[(u8, &str)], u8, &str) = match rmgr_id {
+        RmgrId::Hash => (&HASH_RECORDS, info, ""),
+        RmgrId::Gin => (&GIN_RECORDS, info, ""),
+        RmgrId::Gist => (&GIST_RECORDS, info, ""),
+        RmgrId::SpGist => (&SPGIST_RECORDS, info, ""),
+        RmgrId::Brin => (
+            &BRIN_RECORDS,
+            info & XLOG_BRIN_OPMASK,
+            match info & XLOG_BRIN_INIT_PAGE {
+                0 => "",
+                _ => "+INIT",
+            },
+        ),
+        RmgrId::Generic => {
+            return Ok(format!("Generic {}", describe_generic(&record.main_data)?)
+                .trim_end()
+                .to_string())
+        }
+        _ => return Ok(unknown(info)),
+    };
+    let Some((_, name)) = records.iter().find(|(record_op, _)| *record_op == op) else {
+        return Ok(unknown(info));
+    };
+    let data = record.main_data.as_slice();
+    let description = match rmgr_id {
+        RmgrId::Hash => describe_hash(op, data)?,
+        RmgrId::Gin => describe_gin(op, data, record)?,
+        RmgrId::Gist => describe_gist(op, data)?,
+        RmgrId::SpGist => describe_spgist(op, data)?,
+        _ => describe_brin(op, data)?,
+    };
+    Ok(format!("{name}{suffix} {description}")
+        .trim_end()
+        .to_string())
+}
+
+fn describe_hash(op: u8, data: &[u8]) -> Result<String> {
+    let flag = |value: bool| if value { 'T' } else { 'F' };
+    Ok(match op {
+        0x00 => format!(
+            "num_tuples {}, fillfactor {}",
+            format_g(data.f64_at(0)?),
+            data.u16_at(12)?
+        ),
+        0x10 => format!("bmsize {}", data.u16_at(0)?),
+        0x20 => format!("off {}", data.u16_at(0)?),
+        0x30 => format!(
+            "bmsize {}, bmpage_found {}",
+            data.u16_at(0)?,
+            flag(data.u8_at(2)? != 0)
+        ),
+        0x40 => {
+            let flags = data.u8_at(8)?;
+            format!(
+                "new_bucket {}, meta_page_masks_updated {}, issplitpoint_changed {}",
+                data.u32_at(0)?,
+                flag(flags & XLH_SPLIT_META_UPDATE_MASKS != 0),
+                flag(flags & XLH_SPLIT_META_UPDATE_SPLITPOINT != 0)
+            )
+        }
+        0x60 => format!(
+            "old_bucket_flag {}, new_bucket_flag {}",
+            data.u16_at(0)?,
+            data.u16_at(2)?
+        ),
+        0x70 => format!(
+            "ntups {}, is_primary {}",
+            data.u16_at(0)?,
+            flag(data.u8_at(2)? != 0)
+        ),
+        0x80 => format!(
+            "prevblkno {}, nextblkno {}, ntups {}, is_primary {}",
+            data.u32_at(0)?,
+            data.u32_at(4)?,
+            data.u16_at(8)?,
+            flag(data.u8_at(10)? != 0)
+        ),
+        0x90 => format!(
+            "clear_dead_marking {}, is_primary {}",
+            flag(data.u8_at(0)? != 0),
+            flag(data.u8_at(1)? != 0)
+        ),
+        0xB0 => format!("ntuples {}", format_g(data.f64_at(0)?)),
+        0xC0 => format!(
+            "ntuples {}, latestRemovedXid {}",
+            data.i32_at(4)?,
+            data.u32_at(0)?
+        ),
+        _ => String::new(),
+    })
+}
+
+fn describe_gin(op: u8, data: &[u8], record: &WalRecord) -> Result<String> {
+    let flag = |value: bool| if value { 'T' } else { 'F' };
+    let image = || {
+        record
+            .block(0)
+            .and_then(|block| block.image.as_ref())
+            .map(|image| match image.apply {
+                true => " (full page image)",
+                false => " (full page image, for WAL verification)",
+            })
+    };
+    Ok(match op {
+        0x20 => {
+            let flags = data.u16_at(0)?;
+            let mut description = format!(
+                "isdata: {} isleaf: {}",
+                flag(flags & GIN_INSERT_ISDATA != 0),
+                flag(flags & GIN_INSERT_ISLEAF != 0)
+            );
+            if flags & GIN_INSERT_ISLEAF == 0 {
+                description += &format!(
+                    " children: {}/{}",
+                    block_id_at(data, 2)?,
+                    block_id_at(data, 6)?
+                );
+            }
+            let payload = block_data(record, 0);
+            match image() {
+                Some(image) => description += image,
+                None if flags & GIN_INSERT_ISDATA == 0 => {
+                    description += &format!(" isdelete: {}", flag(payload.u8_at(2)? != 0))
+                }
+                None if flags & GIN_INSERT_ISLEAF != 0 => {
+                    description += &describe_recompress_leaf(payload)?
+                }
+                None => {
+                    description += &format!(
+                        " pitem: {}-{}/{}",
+                        block_id_at(payload, 2)?,
+                        block_id_at(payload, 6)?,
+                        payload.u16_at(10)?
+                    )
+                }
+            }
+            description
+        }
+        0x30 => {
+            let flags = data.u16_at(24)?;
+            format!(
+                "isrootsplit: {} isdata: {} isleaf: {}",
+                flag(flags & GIN_SPLIT_ROOT != 0),
+                flag(flags & GIN_INSERT_ISDATA != 0),
+                flag(flags & GIN_INSERT_ISLEAF != 0)
+            )
+        }
+        0x80 => format!("ndeleted: {}", data.i32_at(SIZE_OF_GIN_META_PAGE_DATA)?),
+        0x90 => match image() {
+            Some(image) => image.to_string(),
+            None => describe_recompress_leaf(block_data(record, 0))?,
+        },
+        _ => String::new(),
+    })
+}
+
+/// Describes the segment actions of ginxlogRecompressDataLeaf as desc_recompress_leaf does
+fn describe_recompress_leaf(data: &[u8]) -> Result<String> {
+    let nactions = data.u16_at(0)?;
+    let mut description = format!(" {} segments:", nactions);
+    let mut offset = 2;
+    for _ in 0..nactions {
+        let segno = data.u8_at(offset)?;
+        let action = data.u8_at(offset + 1)?;
+        offset += 2;
+        description += &match action {
+            GIN_SEGMENT_ADDITEMS => {
+                let nitems = data.u16_at(offset)?;
+                offset += 2 + nitems as usize * 6;
+                format!(" {} (add {} items)", segno, nitems)
+            }
+            GIN_SEGMENT_DELETE => format!(" {} (delete)", segno),
+            GIN_SEGMENT_INSERT | GIN_SEGMENT_REPLACE => {
+                // a GinPostingList: the first item pointer, nbytes and the SHORTALIGNed bytes
+                offset += 8 + (data.u16_at(offset + 6)? as usize).next_multiple_of(2);
+                match action {
+                    GIN_SEGMENT_INSERT => format!(" {} (insert)", segno),
+                    _ => format!(" {} (replace)", segno),
+                }
+            }
+            _ => {
+                // unrecognized actions cannot be decoded further
+                description += &format!(" {} unknown action {} ???", segno, action);
+                break;
+            }
+        };
+    }
+    Ok(description)
+}
+
+fn describe_gist(op: u8, data: &[u8]) -> Result<String> {
+    Ok(match op {
+        0x10 => format!(
+            "delete: latestRemovedXid {}, nitems: {}",
+            data.u32_at(0)?,
+            data.u16_at(4)?
+        ),
+        0x20 => format!(
+            "rel {}; blk {}; latestRemovedXid {}:{}",
+            RelFileLocator::parse(data)?,
+            data.u32_at(12)?,
+            data.u32_at(20)?,
+            data.u32_at(16)?
+        ),
+        0x30 => format!("page_split: splits to {} pages", data.u16_at(18)?),
+        0x60 => format!(
+            "deleteXid {}:{}; downlink {}",
+            data.u32_at(4)?,
+            data.u32_at(0)?,
+            data.u16_at(8)?
+        ),
+        _ => String::new(),
+    })
+}
+
+fn describe_spgist(op: u8, data: &[u8]) -> Result<String> {
+    let marks = |marks: &[(bool, &str)]| -> String {
+        marks
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, mark)| format!(" ({mark})"))
+            .collect()
+    };
+    Ok(match op {
+        0x10 => format!(
+            "off: {}, headoff: {}, parentoff: {}, nodeI: {}{}",
+            data.u16_at(2)?,
+            data.u16_at(4)?,
+            data.u16_at(6)?,
+            data.u16_at(8)?,
+            marks(&[
+                (data.u8_at(0)? != 0, "newpage"),
+                (data.u8_at(1)? != 0, "nulls")
+            ])
+        ),
+        0x20 => format!(
+            "nmoves: {}, parentoff: {}, nodeI: {}{}",
+            data.u16_at(0)?,
+            data.u16_at(6)?,
+            data.u16_at(8)?,
+            marks(&[
+                (data.u8_at(2)? != 0, "newpage"),
+                (data.u8_at(3)? != 0, "replacedead"),
+                (data.u8_at(4)? != 0, "nulls"),
+            ])
+        ),
+        0x30 => format!(
+            "off: {}, newoff: {}, parentBlk: {}, parentoff: {}, nodeI: {}{}",
+            data.u16_at(0)?,
+            data.u16_at(2)?,
+            data.u8_at(5)? as i8,
+            data.u16_at(6)?,
+            data.u16_at(8)?,
+            marks(&[(data.u8_at(4)? != 0, "newpage")])
+        ),
+        0x40 => format!(
+            "prefixoff: {}, postfixoff: {}{}",
+            data.u16_at(0)?,
+            data.u16_at(2)?,
+            marks(&[
+                (data.u8_at(4)? != 0, "newpage"),
+                (data.u8_at(5)? != 0, "same")
+            ])
+        ),
+        0x50 => format!(
+            "ndelete: {}, ninsert: {}, inneroff: {}, parentoff: {}, nodeI: {}{}",
+            data.u16_at(2)?,
+            data.u16_at(4)?,
+            data.u16_at(8)?,
+            data.u16_at(14)?,
+            data.u16_at(16)?,
+            marks(&[
+                (data.u8_at(12)? != 0, "innerIsParent"),
+                (data.u8_at(11)? != 0, "nulls"),
+                (data.u8_at(0)? != 0, "isRootSplit"),
+            ])
+        ),
+        0x60 => format!(
+            "ndead: {}, nplaceholder: {}, nmove: {}, nchain: {}",
+            data.u16_at(0)?,
+            data.u16_at(2)?,
+            data.u16_at(4)?,
+            data.u16_at(6)?
+        ),
+        0x70 => format!("ndelete: {}", data.u16_at(0)?),
+        0x80 => format!(
+            "ntoplaceholder: {}, firstplaceholder: {}, newestredirectxid: {}",
+            data.u16_at(0)?,
+            data.u16_at(2)?,
+            data.u32_at(4)?
+        ),
+        _ => String::new(),
+    })
+}
+
+fn describe_brin(op: u8, data: &[u8]) -> Result<String> {
+    Ok(match op {
+        0x00 => format!("v{} pagesPerRange {}", data.u16_at(4)?, data.u32_at(0)?),
+        0x10 => format!(
+            "heapBlk {} pagesPerRange {} offnum {}",
+            data.u32_at(0)?,
+            data.u32_at(4)?,
+            data.u16_at(8)?
+        ),
+        0x20 => format!(
+            "heapBlk {} pagesPerRange {} old offnum {}, new offnum {}",
+            data.u32_at(4)?,
+            data.u32_at(8)?,
+            data.u16_at(0)?,
+            data.u16_at(12)?
+        ),
+        0x30 => format!("offnum {}", data.u16_at(0)?),
+        0x40 => format!("targetBlk {}", data.u32_at(0)?),
+        0x50 => format!(
+            "pagesPerRange {}, heapBlk {}, page offset {}",
+            data.u32_at(0)?,
+            data.u32_at(4)?,
+            data.u16_at(8)?
+        ),
+        _ => String::new(),
+    })
+}
+
+/// Describes the page deltas of a generic record as generic_desc does
+fn describe_generic(data: &[u8]) -> Result<String> {
+    let mut deltas = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let length = data.u16_at(offset + 2)?;
+        deltas.push(format!(
+            "offset {}, length {}",
+            data.u16_at(offset)?,
+            length
+        ));
+        offset += 4 + length as usize;
+    }
+    Ok(deltas.join("; "))
+}
+
+/// Reads a BlockIdData, a block number stored as two 16-bit halves
+fn block_id_at(data: &[u8], offset: usize) -> Result<u32> {
+    Ok((data.u16_at(offset)? as u32) << 16 | data.u16_at(offset + 2)? as u32)
+}
+
+/// Formats a double the way printf `%g` does with its default precision of 6 significant digits
+fn format_g(value: f64) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return format!("{}", value);
+    }
+    let scientific = format!("{:.5e}", value);
+    let (mantissa, exponent) = scientific.split_once('e').expect("exponent");
+    let exponent: i32 = exponent.parse().expect("exponent");
+    let trim = |digits: String| match digits.contains('.') {
+        true => digits
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string(),
+        false => digits,
+    };
+    match exponent {
+        -4..=5 => trim(format!("{:.*}", (5 - exponent) as usize, value)),
+        _ => format!(
+            "{}e{}{:02}",
+            trim(mantissa.to_string()),
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        ),
+    }
+}
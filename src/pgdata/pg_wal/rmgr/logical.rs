@@ -0,0 +1,50 @@
+use anyhow::Result;
+
+use crate::common::{bytes::ReadBytes, Lsn};
+
+use super::unknown;
+
+const XLOG_REPLORIGIN_SET: u8 = 0x00;
+const XLOG_REPLORIGIN_DROP: u8 = 0x10;
+
+const XLOG_LOGICAL_MESSAGE: u8 = 0x00;
+
+pub fn describe_replorigin(info: u8, data: &[u8]) -> Result<String> {
+    Ok(match info {
+        XLOG_REPLORIGIN_SET => format!(
+            "SET set {}; lsn {}; force: {}",
+            data.u16_at(8)?,
+            Lsn(data.u64_at(0)?),
+            data.u8_at(10)? != 0
+        ),
+        XLOG_REPLORIGIN_DROP => format!("DROP drop {}", data.u16_at(0)?),
+        _ => unknown(info),
+    })
+}
+
+pub fn describe_logicalmsg(info: u8, data: &[u8]) -> Result<String> {
+    Ok(match info {
+        XLOG_LOGICAL_MESSAGE => {
+            let prefix_size = data.u64_at(8)? as usize;
+            let message_size = data.u64_at(16)? as usize;
+            let prefix = data.bytes_at(24, prefix_size)?;
+            let len = prefix.iter().position(|b| *b == 0).unwrap_or(prefix.len());
+            let payload = data.bytes_at(24 + prefix_size, message_size)?;
+            format!(
+                "MESSAGE {}, prefix \"{}\"; payload ({} bytes): {}",
+                match data.u8_at(4)? {
+                    0 => "non-transactional",
+                    _ => "transactional",
+                },
+                String::from_utf8_lossy(&prefix[..len]),
+                message_size,
+                payload
+                    .iter()
+                    .map(|byte| format!("{byte:02X}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        }
+        _ => unknown(info),
+    })
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+
+use crate::common::bytes::ReadBytes;
+
+use super::unknown;
+
+const CLOG_ZEROPAGE: u8 = 0x00;
+const CLOG_TRUNCATE: u8 = 0x10;
+
+const XLOG_MULTIXACT_ZERO_OFF_PAGE: u8 = 0x00;
+const XLOG_MULTIXACT_ZERO_MEM_PAGE: u8 = 0x10;
+const XLOG_MULTIXACT_CREATE_ID: u8 = 0x20;
+const XLOG_MULTIXACT_TRUNCATE_ID: u8 = 0x30;
+
+const COMMIT_TS_ZEROPAGE: u8 = 0x00;
+const COMMIT_TS_TRUNCATE: u8 = 0x10;
+
+/// Names of MultiXactStatus values as mxstatus_to_string spells them
+const MULTIXACT_STATUSES: [&str; 6] = ["keysh", "sh", "fornokeyupd", "forupd", "nokeyupd", "upd"];
+
+pub fn describe_clog(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    Ok(match info {
+        CLOG_ZEROPAGE => format!("ZEROPAGE page {}", page_number(data, pg_version)?),
+        CLOG_TRUNCATE => format!(
+            "TRUNCATE page {}; oldestXact {}",
+            page_number(data, pg_version)?,
+            data.u32_at(page_number_size(pg_version))?
+        ),
+        _ => unknown(info),
+    })
+}
+
+pub fn describe_multixact(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    Ok(match info {
+        XLOG_MULTIXACT_ZERO_OFF_PAGE => {
+            format!("ZERO_OFF_PAGE {}", page_number(data, pg_version)?)
+        }
+        XLOG_MULTIXACT_ZERO_MEM_PAGE => {
+            format!("ZERO_MEM_PAGE {}", page_number(data, pg_version)?)
+        }
+        XLOG_MULTIXACT_CREATE_ID => {
+            let members = (0..data.i32_at(8)?.max(0) as usize)
+                .map(|i| {
+                    let status = data.u32_at(16 + i * 8)?;
+                    Ok(format!(
+                        "{} ({})",
+                        data.u32_at(12 + i * 8)?,
+                        MULTIXACT_STATUSES
+                            .get(status as usize)
+                            .map_or_else(|| status.to_string(), |name| name.to_string())
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            format!(
+                "CREATE_ID {} offset {} nmembers {}: {}",
+                data.u32_at(0)?,
+                data.u32_at(4)?,
+                members.len(),
+                members.join(" ")
+            )
+        }
+        XLOG_MULTIXACT_TRUNCATE_ID => format!(
+            "TRUNCATE_ID offsets [{}, {}), members [{}, {})",
+            data.u32_at(4)?,
+            data.u32_at(8)?,
+            data.u32_at(12)?,
+            data.u32_at(16)?
+        ),
+        _ => unknown(info),
+    })
+}
+
+pub fn describe_commit_ts(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    Ok(match info {
+        COMMIT_TS_ZEROPAGE => format!("ZEROPAGE {}", page_number(data, pg_version)?),
+        COMMIT_TS_TRUNCATE => format!(
+            "TRUNCATE pageno {}, oldestXid {}",
+            page_number(data, pg_version)?,
+            data.u32_at(page_number_size(pg_version))?
+        ),
+        _ => unknown(info),
+    })
+}
+
+/// SLRU page numbers are 64-bit since PostgreSQL 17
+fn page_number(data: &[u8], pg_version: u32) -> Result<i64> {
+    match pg_version {
+        ..=16 => data.i32_at(0).map(i64::from),
+        _ => data.i64_at(0),
+    }
+}
+
+fn page_number_size(pg_version: u32) -> usize {
+    match pg_version {
+        ..=16 => 4,
+        _ => 8,
+    }
+}
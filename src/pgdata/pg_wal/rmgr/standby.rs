@@ -0,0 +1,108 @@
+use anyhow::Result;
+
+use crate::common::bytes::ReadBytes;
+
+use super::{join, unknown};
+
+const XLOG_STANDBY_LOCK: u8 = 0x00;
+const XLOG_RUNNING_XACTS: u8 = 0x10;
+const XLOG_INVALIDATIONS: u8 = 0x20;
+
+/// Size of SharedInvalidationMessage
+const SIZE_OF_INVAL_MESSAGE: usize = 16;
+
+pub fn describe(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    Ok(match info {
+        XLOG_STANDBY_LOCK => {
+            let locks = (0..data.i32_at(0)?.max(0) as usize)
+                .map(|i| {
+                    let offset = 4 + i * 12;
+                    Ok(format!(
+                        "xid {} db {} rel {}",
+                        data.u32_at(offset)?,
+                        data.u32_at(offset + 4)?,
+                        data.u32_at(offset + 8)?
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            format!("LOCK {}", locks.join(" "))
+        }
+        XLOG_RUNNING_XACTS => {
+            let xcnt = data.i32_at(0)?.max(0) as usize;
+            let subxcnt = data.i32_at(4)?.max(0) as usize;
+            let mut description = format!(
+                "RUNNING_XACTS nextXid {} latestCompletedXid {} oldestRunningXid {}",
+                data.u32_at(12)?,
+                data.u32_at(20)?,
+                data.u32_at(16)?
+            );
+            let xids = (0..xcnt + subxcnt)
+                .map(|i| data.u32_at(24 + i * 4))
+                .collect::<Result<Vec<_>>>()?;
+            if xcnt > 0 {
+                description += &format!("; {} xacts: {}", xcnt, join(&xids[..xcnt]));
+            }
+            // subtransactions are listed since PostgreSQL 16
+            if pg_version <= 15 {
+                if data.u8_at(8)? != 0 {
+                    description += "; subxid ovf";
+                }
+                return Ok(description);
+            }
+            if data.u8_at(8)? != 0 {
+                description += "; subxid overflowed";
+            }
+            if subxcnt > 0 {
+                description += &format!("; {} subxacts: {}", subxcnt, join(&xids[xcnt..]));
+            }
+            description
+        }
+        XLOG_INVALIDATIONS => {
+            let messages = data.bytes_at(16, data.len().saturating_sub(16))?;
+            let relcache_init_file = match data.u8_at(8)? {
+                0 => None,
+                _ => Some((data.u32_at(0)?, data.u32_at(4)?)),
+            };
+            format!(
+                "INVALIDATIONS {}",
+                describe_invalidations(messages, data.i32_at(12)?, relcache_init_file)?
+            )
+            .trim_end()
+            .to_string()
+        }
+        _ => unknown(info),
+    })
+}
+
+/// Describes shared invalidation messages as standby_desc_invalidations does, preceded by the
+/// database and tablespace whose relcache init file is invalidated if any. Nothing is described
+/// without messages
+pub fn describe_invalidations(
+    messages: &[u8],
+    count: i32,
+    relcache_init_file: Option<(u32, u32)>,
+) -> Result<String> {
+    if count <= 0 {
+        return Ok(String::new());
+    }
+    let descriptions = (0..count.max(0) as usize)
+        .map(|i| {
+            let message = messages.bytes_at(i * SIZE_OF_INVAL_MESSAGE, SIZE_OF_INVAL_MESSAGE)?;
+            Ok(match message.u8_at(0)? as i8 {
+                id if id >= 0 => format!("catcache {}", id),
+                -1 => format!("catalog {}", message.u32_at(8)?),
+                -2 => format!("relcache {}", message.u32_at(8)?),
+                -3 => "smgr".to_string(),
+                -4 => format!("relmap db {}", message.u32_at(4)?),
+                -5 => format!("snapshot {}", message.u32_at(8)?),
+                id => format!("unrecognized id {}", id),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut description = String::new();
+    if let Some((db_id, ts_id)) = relcache_init_file {
+        description += &format!("; relcache init file inval dbid {db_id} tsid {ts_id}");
+    }
+    description += &format!("; inval msgs: {}", descriptions.join(" "));
+    Ok(description)
+}
@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+
+use crate::{
+    common::bytes::ReadBytes,
+    pgdata::{base::db_dir::ForkType, pg_wal::record::RelFileLocator},
+};
+
+use super::{relpath, unknown};
+
+const XLOG_SMGR_CREATE: u8 = 0x10;
+const XLOG_SMGR_TRUNCATE: u8 = 0x20;
+
+const XLOG_DBASE_CREATE_FILE_COPY: u8 = 0x00;
+const XLOG_DBASE_CREATE_WAL_LOG: u8 = 0x10;
+const XLOG_DBASE_DROP: u8 = 0x20;
+/// XLOG_DBASE_DROP before PostgreSQL 15 introduced the WAL_LOG strategy of CREATE DATABASE
+const XLOG_DBASE_DROP_OLD: u8 = 0x10;
+
+const XLOG_TBLSPC_CREATE: u8 = 0x00;
+const XLOG_TBLSPC_DROP: u8 = 0x10;
+
+const XLOG_RELMAP_UPDATE: u8 = 0x00;
+
+const XLOG_SEQ_LOG: u8 = 0x00;
+
+pub fn describe_smgr(info: u8, data: &[u8]) -> Result<String> {
+    Ok(match info {
+        XLOG_SMGR_CREATE => {
+            let locator = RelFileLocator::parse(data)?;
+            let fork_number = data.i32_at(12)?;
+            let fork = ForkType::from_number(fork_number as u8)
+                .with_context(|| format!("Invalid fork number {}", fork_number))?;
+            format!("CREATE {}", relpath(&locator, fork))
+        }
+        XLOG_SMGR_TRUNCATE => format!(
+            "TRUNCATE {} to {} blocks flags {}",
+            relpath(
+                &RelFileLocator::parse(data.bytes_at(4, 12)?)?,
+                ForkType::Main
+            ),
+            data.u32_at(0)?,
+            data.i32_at(16)?
+        ),
+        _ => unknown(info),
+    })
+}
+
+pub fn describe_dbase(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    let drop = |data: &[u8]| -> Result<String> {
+        let db_id = data.u32_at(0)?;
+        let dirs = (0..data.i32_at(4)?.max(0) as usize)
+            .map(|i| Ok(format!("{}/{}", data.u32_at(8 + i * 4)?, db_id)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(format!("DROP dir {}", dirs.join(" ")))
+    };
+    Ok(match (info, pg_version) {
+        (XLOG_DBASE_CREATE_FILE_COPY, _) => format!(
+            "{} copy dir {}/{} to {}/{}",
+            match pg_version {
+                ..=14 => "CREATE",
+                _ => "CREATE_FILE_COPY",
+            },
+            data.u32_at(12)?,
+            data.u32_at(8)?,
+            data.u32_at(4)?,
+            data.u32_at(0)?
+        ),
+        (XLOG_DBASE_DROP_OLD, ..=14) => drop(data)?,
+        (XLOG_DBASE_CREATE_WAL_LOG, _) => format!(
+            "CREATE_WAL_LOG create dir {}/{}",
+            data.u32_at(4)?,
+            data.u32_at(0)?
+        ),
+        (XLOG_DBASE_DROP, 15..) => drop(data)?,
+        _ => unknown(info),
+    })
+}
+
+pub fn describe_tblspc(info: u8, data: &[u8]) -> Result<String> {
+    Ok(match info {
+        XLOG_TBLSPC_CREATE => {
+            let path = &data[4.min(data.len())..];
+            let len = path.iter().position(|b| *b == 0).unwrap_or(path.len());
+            format!(
+                "CREATE {} \"{}\"",
+                data.u32_at(0)?,
+                String::from_utf8_lossy(&path[..len])
+            )
+        }
+        XLOG_TBLSPC_DROP => format!("DROP {}", data.u32_at(0)?),
+        _ => unknown(info),
+    })
+}
+
+pub fn describe_relmap(info: u8, data: &[u8]) -> Result<String> {
+    Ok(match info {
+        XLOG_RELMAP_UPDATE => format!(
+            "UPDATE database {} tablespace {} size {}",
+            data.u32_at(0)?,
+            data.u32_at(4)?,
+            data.i32_at(8)?
+        ),
+        _ => unknown(info),
+    })
+}
+
+pub fn describe_seq(info: u8, data: &[u8]) -> Result<String> {
+    Ok(match info {
+        XLOG_SEQ_LOG => format!("LOG rel {}", RelFileLocator::parse(data)?),
+        _ => unknown(info),
+    })
+}
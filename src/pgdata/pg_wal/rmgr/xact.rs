@@ -0,0 +1,267 @@
+use anyhow::Result;
+
+use crate::common::{bytes::ReadBytes, time::format_timestamptz, Lsn};
+
+use super::{join, relpath, standby::describe_invalidations, unknown};
+use crate::pgdata::{base::db_dir::ForkType, pg_wal::record::RelFileLocator};
+
+const XLOG_XACT_OPMASK: u8 = 0x70;
+const XLOG_XACT_COMMIT: u8 = 0x00;
+const XLOG_XACT_PREPARE: u8 = 0x10;
+const XLOG_XACT_ABORT: u8 = 0x20;
+const XLOG_XACT_COMMIT_PREPARED: u8 = 0x30;
+const XLOG_XACT_ABORT_PREPARED: u8 = 0x40;
+const XLOG_XACT_ASSIGNMENT: u8 = 0x50;
+const XLOG_XACT_INVALIDATIONS: u8 = 0x60;
+const XLOG_XACT_HAS_INFO: u8 = 0x80;
+
+const XACT_XINFO_HAS_DBINFO: u32 = 1 << 0;
+const XACT_XINFO_HAS_SUBXACTS: u32 = 1 << 1;
+const XACT_XINFO_HAS_RELFILENODES: u32 = 1 << 2;
+const XACT_XINFO_HAS_INVALS: u32 = 1 << 3;
+const XACT_XINFO_HAS_TWOPHASE: u32 = 1 << 4;
+const XACT_XINFO_HAS_ORIGIN: u32 = 1 << 5;
+const XACT_XINFO_HAS_GID: u32 = 1 << 7;
+const XACT_XINFO_HAS_DROPPED_STATS: u32 = 1 << 8;
+const XACT_COMPLETION_APPLY_FEEDBACK: u32 = 1 << 29;
+const XACT_COMPLETION_UPDATE_RELCACHE_FILE: u32 = 1 << 30;
+const XACT_COMPLETION_FORCE_SYNC_COMMIT: u32 = 1 << 31;
+
+pub fn describe(info: u8, data: &[u8], origin: Option<u16>, pg_version: u32) -> Result<String> {
+    let has_info = info & XLOG_XACT_HAS_INFO != 0;
+    Ok(match info & XLOG_XACT_OPMASK {
+        XLOG_XACT_COMMIT => format!("COMMIT {}", describe_end(data, has_info, true, origin)?),
+        XLOG_XACT_ABORT => format!("ABORT {}", describe_end(data, has_info, false, origin)?),
+        XLOG_XACT_COMMIT_PREPARED => format!(
+            "COMMIT_PREPARED {}",
+            describe_end(data, has_info, true, origin)?
+        ),
+        XLOG_XACT_ABORT_PREPARED => format!(
+            "ABORT_PREPARED {}",
+            describe_end(data, has_info, false, origin)?
+        ),
+        XLOG_XACT_PREPARE => format!("PREPARE {}", describe_prepare(data, origin, pg_version)?),
+        XLOG_XACT_ASSIGNMENT => {
+            let subxacts = (0..data.i32_at(4)?.max(0) as usize)
+                .map(|i| data.u32_at(8 + i * 4))
+                .collect::<Result<Vec<_>>>()?;
+            format!(
+                "ASSIGNMENT xtop {}: subxacts: {}",
+                data.u32_at(0)?,
+                join(subxacts)
+            )
+        }
+        XLOG_XACT_INVALIDATIONS if pg_version >= 14 => format!(
+            "INVALIDATION {}",
+            describe_invalidations(&data[4.min(data.len())..], data.i32_at(0)?, None)?
+        )
+        .trim_end()
+        .to_string(),
+        _ => unknown(info),
+    })
+}
+
+/// Describes xl_xact_prepare decoded as ParsePrepareRecord does. The header gained the numbers
+/// of statistics dropped on commit and on abort in PostgreSQL 15, the arrays following it are
+/// each MAXALIGNed
+fn describe_prepare(data: &[u8], origin: Option<u16>, pg_version: u32) -> Result<String> {
+    let has_stats = pg_version >= 15;
+    // offset of ninvalmsgs, the fields following the counts shift with the statistics
+    let counts_end = if has_stats { 48 } else { 40 };
+    let count = |offset: usize| Ok::<_, anyhow::Error>(data.i32_at(offset)?.max(0) as usize);
+    let (ncommitstats, nabortstats) = match has_stats {
+        true => (count(40)?, count(44)?),
+        false => (0, 0),
+    };
+    let ninvalmsgs = count(counts_end)?;
+    let initfileinval = data.u8_at(counts_end + 4)? != 0;
+    let gidlen = data.u16_at(counts_end + 6)? as usize;
+
+    let mut offset = counts_end + 24;
+    let gid = data.bytes_at(offset, gidlen)?;
+    let gid = &gid[..gid.iter().position(|b| *b == 0).unwrap_or(gid.len())];
+    offset += gidlen.next_multiple_of(8);
+    let subxacts = (0..count(28)?)
+        .map(|i| data.u32_at(offset + i * 4))
+        .collect::<Result<Vec<_>>>()?;
+    offset += (subxacts.len() * 4).next_multiple_of(8);
+    let mut rels = |count: usize| {
+        let rels = (0..count)
+            .map(|i| {
+                let locator = RelFileLocator::parse(data.bytes_at(offset + i * 12, 12)?)?;
+                Ok(relpath(&locator, ForkType::Main))
+            })
+            .collect::<Result<Vec<_>>>();
+        offset += (count * 12).next_multiple_of(8);
+        rels
+    };
+    let commit_rels = rels(count(32)?)?;
+    let abort_rels = rels(count(36)?)?;
+    let mut stats = |count: usize| {
+        let stats = (0..count)
+            .map(|i| {
+                let item = offset + i * 12;
+                Ok(format!(
+                    "{}/{}/{}",
+                    data.i32_at(item)?,
+                    data.u32_at(item + 4)?,
+                    data.u32_at(item + 8)?
+                ))
+            })
+            .collect::<Result<Vec<_>>>();
+        offset += (count * 12).next_multiple_of(8);
+        stats
+    };
+    let commit_stats = stats(ncommitstats)?;
+    let abort_stats = stats(nabortstats)?;
+
+    let mut description = format!(
+        "gid {}: {}",
+        String::from_utf8_lossy(gid),
+        format_timestamptz(data.i64_at(16)?)
+    );
+    if !commit_rels.is_empty() {
+        description += &format!("; rels(commit): {}", commit_rels.join(" "));
+    }
+    if !abort_rels.is_empty() {
+        description += &format!("; rels(abort): {}", abort_rels.join(" "));
+    }
+    if !commit_stats.is_empty() {
+        description += &format!("; commit dropped stats: {}", commit_stats.join(" "));
+    }
+    if !abort_stats.is_empty() {
+        description += &format!("; abort dropped stats: {}", abort_stats.join(" "));
+    }
+    if !subxacts.is_empty() {
+        description += &format!("; subxacts: {}", join(subxacts));
+    }
+    description += &describe_invalidations(
+        data.bytes_at(offset, ninvalmsgs * 16)?,
+        ninvalmsgs as i32,
+        Some((data.u32_at(12)?, 0)).filter(|_| initfileinval),
+    )?;
+    if let Some(origin) = origin {
+        description += &format!(
+            "; origin: node {}, lsn {}, at {}",
+            origin,
+            Lsn(data.u64_at(counts_end + 8)?),
+            format_timestamptz(data.i64_at(counts_end + 16)?)
+        );
+    }
+    Ok(description)
+}
+
+/// Describes xl_xact_commit or xl_xact_abort decoded as ParseCommitRecord and ParseAbortRecord do,
+/// both share the layout except that aborts carry no invalidation messages
+fn describe_end(data: &[u8], has_info: bool, commit: bool, origin: Option<u16>) -> Result<String> {
+    let xact_time = data.i64_at(0)?;
+    let mut offset = 8;
+    let xinfo = match has_info {
+        true => {
+            offset += 4;
+            data.u32_at(8)?
+        }
+        false => 0,
+    };
+
+    let mut db_info = (0, 0);
+    if xinfo & XACT_XINFO_HAS_DBINFO != 0 {
+        db_info = (data.u32_at(offset)?, data.u32_at(offset + 4)?);
+        offset += 8;
+    }
+    let mut subxacts = Vec::new();
+    if xinfo & XACT_XINFO_HAS_SUBXACTS != 0 {
+        let count = data.i32_at(offset)?.max(0) as usize;
+        for i in 0..count {
+            subxacts.push(data.u32_at(offset + 4 + i * 4)?);
+        }
+        offset += 4 + count * 4;
+    }
+    let mut rels = Vec::new();
+    if xinfo & XACT_XINFO_HAS_RELFILENODES != 0 {
+        let count = data.i32_at(offset)?.max(0) as usize;
+        for i in 0..count {
+            let locator = RelFileLocator::parse(data.bytes_at(offset + 4 + i * 12, 12)?)?;
+            rels.push(relpath(&locator, ForkType::Main));
+        }
+        offset += 4 + count * 12;
+    }
+    let mut dropped_stats = Vec::new();
+    if xinfo & XACT_XINFO_HAS_DROPPED_STATS != 0 {
+        let count = data.i32_at(offset)?.max(0) as usize;
+        for i in 0..count {
+            let item = offset + 4 + i * 12;
+            dropped_stats.push(format!(
+                "{}/{}/{}",
+                data.i32_at(item)?,
+                data.u32_at(item + 4)?,
+                data.u32_at(item + 8)?
+            ));
+        }
+        offset += 4 + count * 12;
+    }
+    let mut invals = None;
+    if commit && xinfo & XACT_XINFO_HAS_INVALS != 0 {
+        let count = data.i32_at(offset)?;
+        let len = count.max(0) as usize * 16;
+        let relcache_init_file =
+            Some(db_info).filter(|_| xinfo & XACT_COMPLETION_UPDATE_RELCACHE_FILE != 0);
+        invals = Some(describe_invalidations(
+            data.bytes_at(offset + 4, len)?,
+            count,
+            relcache_init_file,
+        )?);
+        offset += 4 + len;
+    }
+    let mut twophase_xid = None;
+    if xinfo & XACT_XINFO_HAS_TWOPHASE != 0 {
+        twophase_xid = Some(data.u32_at(offset)?);
+        offset += 4;
+        if xinfo & XACT_XINFO_HAS_GID != 0 {
+            let gid = &data[offset.min(data.len())..];
+            let len = gid.iter().position(|b| *b == 0).unwrap_or(gid.len());
+            offset += len + 1;
+        }
+    }
+
+    let mut description = String::new();
+    if let Some(xid) = twophase_xid {
+        description += &format!("{}: ", xid);
+    }
+    description += &format_timestamptz(xact_time);
+    if !rels.is_empty() {
+        description += &format!("; rels: {}", rels.join(" "));
+    }
+    if !subxacts.is_empty() {
+        description += &format!("; subxacts: {}", join(subxacts));
+    }
+    let origin = match xinfo & XACT_XINFO_HAS_ORIGIN {
+        0 => String::new(),
+        _ => format!(
+            "; origin: node {}, lsn {}, at {}",
+            origin.unwrap_or_default(),
+            Lsn(data.u64_at(offset)?),
+            format_timestamptz(data.i64_at(offset + 8)?)
+        ),
+    };
+    // aborts show the origin before the dropped statistics, commits after everything else
+    if !commit {
+        description += &origin;
+    }
+    if !dropped_stats.is_empty() {
+        description += &format!("; dropped stats: {}", dropped_stats.join(" "));
+    }
+    if let Some(invals) = invals {
+        description += &invals;
+    }
+    if xinfo & XACT_COMPLETION_APPLY_FEEDBACK != 0 {
+        description += "; apply_feedback";
+    }
+    if xinfo & XACT_COMPLETION_FORCE_SYNC_COMMIT != 0 {
+        description += "; sync";
+    }
+    if commit {
+        description += &origin;
+    }
+    Ok(description)
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+
+use crate::{
+    common::{bytes::ReadBytes, time::format_timestamptz, Lsn},
+    pgdata::global::{
+        pg_control::{CheckPoint, WalLevel},
+        NAMEDATALEN,
+    },
+};
+
+use super::unknown;
+
+const XLOG_CHECKPOINT_SHUTDOWN: u8 = 0x00;
+const XLOG_CHECKPOINT_ONLINE: u8 = 0x10;
+const XLOG_NOOP: u8 = 0x20;
+const XLOG_NEXTOID: u8 = 0x30;
+pub const XLOG_SWITCH: u8 = 0x40;
+const XLOG_BACKUP_END: u8 = 0x50;
+const XLOG_PARAMETER_CHANGE: u8 = 0x60;
+const XLOG_RESTORE_POINT: u8 = 0x70;
+const XLOG_FPW_CHANGE: u8 = 0x80;
+const XLOG_END_OF_RECOVERY: u8 = 0x90;
+const XLOG_FPI_FOR_HINT: u8 = 0xA0;
+const XLOG_FPI: u8 = 0xB0;
+const XLOG_OVERWRITE_CONTRECORD: u8 = 0xC0;
+const XLOG_CHECKPOINT_REDO: u8 = 0xD0;
+
+pub fn describe(info: u8, data: &[u8], pg_version: u32) -> Result<String> {
+    Ok(match info {
+        XLOG_CHECKPOINT_SHUTDOWN | XLOG_CHECKPOINT_ONLINE => {
+            let checkpoint = CheckPoint::parse(data)?;
+            format!(
+                "{} redo {}; tli {}; prev tli {}; fpw {}; xid {}:{}; oid {}; multi {}; offset {}; \
+                oldest xid {} in DB {}; oldest multi {} in DB {}; \
+                oldest/newest commit timestamp xid: {}/{}; oldest running xid {}; {}",
+                match info {
+                    XLOG_CHECKPOINT_SHUTDOWN => "CHECKPOINT_SHUTDOWN",
+                    _ => "CHECKPOINT_ONLINE",
+                },
+                checkpoint.redo,
+                checkpoint.this_timeline_id,
+                checkpoint.prev_timeline_id,
+                checkpoint.full_page_writes,
+                checkpoint.next_xid_epoch(),
+                checkpoint.next_xid_xid(),
+                checkpoint.next_oid,
+                checkpoint.next_multi,
+                checkpoint.next_multi_offset,
+                checkpoint.oldest_xid,
+                checkpoint.oldest_xid_db,
+                checkpoint.oldest_multi,
+                checkpoint.oldest_multi_db,
+                checkpoint.oldest_commit_ts_xid,
+                checkpoint.newest_commit_ts_xid,
+                checkpoint.oldest_active_xid,
+                match info {
+                    XLOG_CHECKPOINT_SHUTDOWN => "shutdown",
+                    _ => "online",
+                }
+            )
+        }
+        XLOG_NOOP => "NOOP".to_string(),
+        XLOG_NEXTOID => format!("NEXTOID {}", data.u32_at(0)?),
+        XLOG_SWITCH => "SWITCH".to_string(),
+        XLOG_BACKUP_END => format!("BACKUP_END {}", Lsn(data.u64_at(0)?)),
+        XLOG_PARAMETER_CHANGE => format!(
+            "PARAMETER_CHANGE max_connections={} max_worker_processes={} max_wal_senders={} \
+            max_prepared_xacts={} max_locks_per_xact={} wal_level={} wal_log_hints={} \
+            track_commit_timestamp={}",
+            data.i32_at(0)?,
+            data.i32_at(4)?,
+            data.i32_at(8)?,
+            data.i32_at(12)?,
+            data.i32_at(16)?,
+            WalLevel::from(data.i32_at(20)?),
+            on_off(data.u8_at(24)?),
+            on_off(data.u8_at(25)?)
+        ),
+        XLOG_RESTORE_POINT => {
+            let name = data.bytes_at(8, NAMEDATALEN)?;
+            let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+            format!("RESTORE_POINT {}", String::from_utf8_lossy(&name[..len]))
+        }
+        XLOG_FPW_CHANGE => format!("FPW_CHANGE {}", data.u8_at(0)? != 0),
+        XLOG_END_OF_RECOVERY => format!(
+            "END_OF_RECOVERY tli {}; prev tli {}; time {}",
+            data.u32_at(8)?,
+            data.u32_at(12)?,
+            format_timestamptz(data.i64_at(0)?)
+        ),
+        XLOG_FPI_FOR_HINT => "FPI_FOR_HINT".to_string(),
+        XLOG_FPI => "FPI".to_string(),
+        XLOG_OVERWRITE_CONTRECORD => format!(
+            "OVERWRITE_CONTRECORD lsn {}; time {}",
+            Lsn(data.u64_at(0)?),
+            format_timestamptz(data.i64_at(8)?)
+        ),
+        XLOG_CHECKPOINT_REDO if pg_version >= 17 => format!(
+            "CHECKPOINT_REDO wal_level {}",
+            WalLevel::from(data.i32_at(0)?)
+        ),
+        _ => unknown(info),
+    })
+}
+
+fn on_off(value: u8) -> &'static str {
+    match value {
+        0 => "off",
+        _ => "on",
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+
+use super::{
+    reader::WalRecords,
+    record::WalRecord,
+    rmgr::{self, RmgrId},
+    PGWal, WalSegmentName,
+};
+
+/// Bits of xl_info of transaction records which are flags rather than the record type
+const XLOG_XACT_HAS_INFO: u8 = 0x80;
+
+/// Number and volume of records, counted as XLogRecStoreStats does
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct RecordCounters {
+    pub count: u64,
+    /// Bytes of the records without their full-page images
+    pub record_bytes: u64,
+    pub fpi_bytes: u64,
+}
+
+impl RecordCounters {
+    pub fn combined_bytes(&self) -> u64 {
+        self.record_bytes + self.fpi_bytes
+    }
+
+    fn add(&mut self, record: &WalRecord) {
+        let fpi_len = record.fpi_len() as u64;
+        self.count += 1;
+        self.record_bytes += record.header.tot_len as u64 - fpi_len;
+        self.fpi_bytes += fpi_len;
+    }
+}
+
+/// Counters of a record type of a resource manager
+#[derive(Debug, PartialEq)]
+pub struct RecordTypeStats {
+    pub name: String,
+    pub counters: RecordCounters,
+}
+
+/// Counters of a resource manager and of its record types ordered by their xl_info
+#[derive(Debug, Default, PartialEq)]
+pub struct RmgrStats {
+    pub counters: RecordCounters,
+    pub record_types: BTreeMap<u8, RecordTypeStats>,
+}
+
+/// Aggregates records by resource manager and record type like `pg_waldump --stats=record`
+#[derive(Debug, Default, PartialEq)]
+pub struct WalStats {
+    /// Resource managers ordered by their RmgrId
+    pub rmgrs: BTreeMap<u8, RmgrStats>,
+    pub total: RecordCounters,
+}
+
+impl WalStats {
+    pub fn add(&mut self, records: &WalRecords) {
+        records
+            .records
+            .iter()
+            .for_each(|record| self.add_record(record, records.pg_version));
+    }
+
+    pub fn add_record(&mut self, record: &WalRecord, pg_version: u32) {
+        let rmid = record.header.rmid;
+        // commit and abort records of a kind are one type whether or not they carry xinfo
+        let info = match RmgrId::from(rmid) {
+            RmgrId::Transaction => record.rmgr_info() & !XLOG_XACT_HAS_INFO,
+            _ => record.rmgr_info(),
+        };
+
+        let rmgr_stats = self.rmgrs.entry(rmid).or_default();
+        rmgr_stats
+            .record_types
+            .entry(info)
+            .or_insert_with(|| RecordTypeStats {
+                name: rmgr::identify(record, pg_version),
+                counters: RecordCounters::default(),
+            })
+            .counters
+            .add(record);
+        rmgr_stats.counters.add(record);
+        self.total.add(record);
+    }
+}
+
+/// Aggregates the records of the segments from first to last inclusive, a record spanning two
+/// segments is counted in the one it starts in. The reasons the decoding of the segments stopped
+/// early are returned along, the last one usually tells where WAL ends
+pub fn segment_range_stats(
+    pg_wal: &impl PGWal,
+    first: &WalSegmentName,
+    last: &WalSegmentName,
+) -> Result<(WalStats, Vec<anyhow::Error>)> {
+    let wal_seg_size = pg_wal.wal_seg_size()?;
+    if first.timeline_id != last.timeline_id {
+        bail!(
+            "{} and {} belong to different timelines",
+            first.file_name(),
+            last.file_name()
+        );
+    }
+    let (first_no, last_no) = (
+        first.segment_no(wal_seg_size),
+        last.segment_no(wal_seg_size),
+    );
+    if first_no > last_no {
+        bail!("{} follows {}", first.file_name(), last.file_name());
+    }
+
+    let mut stats = WalStats::default();
+    let mut errors = Vec::new();
+    for segment_no in first_no..=last_no {
+        match pg_wal.records(&first.with_segment_no(segment_no, wal_seg_size)) {
+            Ok(records) => {
+                stats.add(&records);
+                errors.extend(records.error);
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+    Ok((stats, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        common::Lsn,
+        pgdata::pg_wal::record::{test_records::wal_record, WalRecord},
+    };
+
+    use super::{RecordCounters, WalStats};
+
+    fn record(rmid: u8, info: u8, blocks: &[(&[u8], &[u8])], main_data: &[u8]) -> WalRecord {
+        WalRecord::parse(
+            Lsn(0x0100_0028),
+            &wal_record(rmid, info, 740, 0, blocks, main_data),
+            15,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn aggregates_records_by_rmgr_and_record_type() {
+        // given
+        let mut fpi_block = vec![0, 0x10, 0, 0, 8, 0, 0, 0, 0x02];
+        fpi_block.extend([0x7F, 6, 0, 0, 5, 0, 0, 0, 0, 0x40, 0, 0]);
+        fpi_block.extend(0u32.to_le_bytes());
+        let records = [
+            record(10, 0x00, &[], &[3, 0, 0]),
+            record(10, 0x00, &[], &[4, 0, 0]),
+            record(0, 0xA0, &[(&fpi_block, &[0; 8])], &[]),
+            record(1, 0x00, &[], &0i64.to_le_bytes()),
+            record(1, 0x80, &[], &[0; 12]),
+        ];
+        let mut stats = WalStats::default();
+
+        // when
+        records
+            .iter()
+            .for_each(|record| stats.add_record(record, 15));
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            stats
+                .rmgrs
+                .iter()
+                .flat_map(|(rmid, rmgr)| rmgr.record_types.values().map(move |record_type| {
+                    (*rmid, record_type.name.as_str(), record_type.counters)
+                }))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, "FPI_FOR_HINT", RecordCounters { count: 1, record_bytes: 49, fpi_bytes: 8 }),
+                (1, "COMMIT", RecordCounters { count: 2, record_bytes: 34 + 38, fpi_bytes: 0 }),
+                (10, "INSERT", RecordCounters { count: 2, record_bytes: 29 + 29, fpi_bytes: 0 }),
+            ]
+        );
+        assert_eq!(
+            stats.rmgrs[&1].counters,
+            RecordCounters {
+                count: 2,
+                record_bytes: 72,
+                fpi_bytes: 0
+            }
+        );
+        assert_eq!(
+            stats.total,
+            RecordCounters {
+                count: 5,
+                record_bytes: 179,
+                fpi_bytes: 8
+            }
+        );
+        assert_eq!(stats.total.combined_bytes(), 187);
+    }
+}
@@ -1,8 +1,246 @@
+use std::{fmt::Display, path::Path};
+
+use anyhow::Result;
+
 use crate::common::fs::DirEntry;
 
-#[allow(dead_code)]
-pub trait PGXact {}
+use super::slru::{self, EntryLayout, SlruSegment};
+
+/// Commit status of a transaction takes 2 bits of a pg_xact page
+pub const CLOG_LAYOUT: EntryLayout = EntryLayout::bits(2);
+
+/// The first xid which is not one of the permanent ones below
+pub const FIRST_NORMAL_TRANSACTION_ID: u32 = 3;
+
+/// Represents PG_DATA/pg_xact directory, also known as CLOG
+pub trait PGXact {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Lists the segment files of pg_xact ordered by name
+    fn segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Looks the commit status of the transaction up, permanent xids are reported as
+    /// TransactionLogFetch does without reading pg_xact
+    fn status(&self, xid: u32) -> Result<XactStatus>;
+}
 
 pub fn dir_entry() -> DirEntry<'static> {
     DirEntry::dir("pg_xact")
 }
+
+/// Instantiates a default implementation of [PGXact]
+pub fn pg_xact(pgdata_path: &Path) -> impl PGXact {
+    default_impl::PGXact::new(slru::slru(&pgdata_path.join("pg_xact")))
+}
+
+/// Tells whether xid `a` is logically older than `b` as TransactionIdPrecedes does, normal xids
+/// are compared modulo 2^32 and the permanent ones precede all normal xids
+pub fn xid_precedes(a: u32, b: u32) -> bool {
+    if a < FIRST_NORMAL_TRANSACTION_ID || b < FIRST_NORMAL_TRANSACTION_ID {
+        return a < b;
+    }
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+/// Transaction status as stored in pg_xact, see XidStatus
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum XactStatus {
+    InProgress,
+    Committed,
+    Aborted,
+    /// Committed subtransaction whose top-level transaction is still in progress
+    SubCommitted,
+}
+
+impl XactStatus {
+    /// Decodes the 2 bits of a transaction
+    pub fn from_bits(bits: u8) -> XactStatus {
+        match bits & 0x03 {
+            0x00 => XactStatus::InProgress,
+            0x01 => XactStatus::Committed,
+            0x02 => XactStatus::Aborted,
+            _ => XactStatus::SubCommitted,
+        }
+    }
+
+    /// Status of the permanent xids which pg_xact does not hold
+    pub fn of_permanent_xid(xid: u32) -> Option<XactStatus> {
+        match xid {
+            // InvalidTransactionId
+            0 => Some(XactStatus::Aborted),
+            // BootstrapTransactionId and FrozenTransactionId
+            1 | 2 => Some(XactStatus::Committed),
+            _ => None,
+        }
+    }
+}
+
+impl Display for XactStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            XactStatus::InProgress => "IN_PROGRESS",
+            XactStatus::Committed => "COMMITTED",
+            XactStatus::Aborted => "ABORTED",
+            XactStatus::SubCommitted => "SUB_COMMITTED",
+        })
+    }
+}
+
+mod default_impl {
+    use std::path::Path;
+
+    use anyhow::Context;
+
+    use crate::pgdata::slru::{Slru, SlruSegment};
+
+    use super::{XactStatus, CLOG_LAYOUT};
+
+    pub struct PGXact<S: Slru> {
+        slru: S,
+    }
+
+    impl<S: Slru> PGXact<S> {
+        pub fn new(slru: S) -> Self {
+            PGXact { slru }
+        }
+    }
+
+    impl<S: Slru> super::PGXact for PGXact<S> {
+        fn path(&self) -> &Path {
+            self.slru.path()
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            self.slru.segments()
+        }
+
+        fn status(&self, xid: u32) -> anyhow::Result<XactStatus> {
+            if let Some(status) = XactStatus::of_permanent_xid(xid) {
+                return Ok(status);
+            }
+            let location = CLOG_LAYOUT.locate(xid);
+            let bytes = self
+                .slru
+                .read(&location, 1)
+                .with_context(|| format!("Looking up status of xid {xid}"))?;
+            Ok(XactStatus::from_bits(bytes[0] >> location.shift))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        use crate::pgdata::{
+            pg_xact::{PGXact as _, XactStatus},
+            slru::test_stubs::StubSlru,
+        };
+
+        use super::PGXact;
+
+        #[rstest]
+        #[case(2, Ok(XactStatus::Committed))]
+        #[case(740, Ok(XactStatus::Committed))]
+        #[case(741, Ok(XactStatus::Aborted))]
+        #[case(742, Ok(XactStatus::InProgress))]
+        #[case(743, Ok(XactStatus::SubCommitted))]
+        #[case(32768, Err("Looking up status of xid 32768: Segment \"/pgdata/pg_xact/0000\" holds 1 pages, page 1 is missing"))]
+        #[case(1_048_576, Err("Looking up status of xid 1048576: Segment \"/pgdata/pg_xact/0001\" holding page 32 is missing"))]
+        fn looks_up_xid_status(#[case] xid: u32, #[case] expected: Result<XactStatus, &str>) {
+            // given
+            let mut page = vec![0; 8192];
+            // 740 is the first xid of byte 185, 2 bits per xid from the least significant ones
+            page[185] = 0b11_00_10_01;
+            let pg_xact = PGXact::new(StubSlru {
+                path: Path::new("/pgdata/pg_xact").to_path_buf(),
+                segments: vec![("0000".to_string(), page)],
+            });
+
+            // when
+            let status = pg_xact.status(xid);
+
+            // then
+            assert_eq!(
+                status.map_err(|err| format!("{err:#}")),
+                expected.map_err(String::from)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::Path;
+
+    use anyhow::bail;
+
+    use crate::pgdata::slru::SlruSegment;
+
+    use super::{PGXact, XactStatus};
+
+    /// Knows the statuses of xids 740..=743, the others are missing from pg_xact
+    pub struct StubPGXact;
+    impl PGXact for StubPGXact {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/pg_xact")
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            Ok(vec![
+                SlruSegment {
+                    file_name: "0000".to_string(),
+                    size: 8192,
+                },
+                SlruSegment {
+                    file_name: "0001".to_string(),
+                    size: 2 * 8192,
+                },
+                SlruSegment {
+                    file_name: "pg_xact.tmp".to_string(),
+                    size: 0,
+                },
+            ])
+        }
+
+        fn status(&self, xid: u32) -> anyhow::Result<XactStatus> {
+            match xid {
+                740 => Ok(XactStatus::Committed),
+                741 => Ok(XactStatus::Aborted),
+                742 => Ok(XactStatus::InProgress),
+                743 => Ok(XactStatus::SubCommitted),
+                _ => match XactStatus::of_permanent_xid(xid) {
+                    Some(status) => Ok(status),
+                    None => bail!("Status of xid {xid} is missing"),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::xid_precedes;
+
+    #[rstest]
+    #[case(740, 741, true)]
+    #[case(741, 740, false)]
+    #[case(740, 740, false)]
+    #[case(u32::MAX, 5, true)]
+    #[case(5, u32::MAX, false)]
+    #[case(2, u32::MAX, true)]
+    #[case(5, 0x8000_0004, true)]
+    #[case(5, 0x8000_0006, false)]
+    fn compares_xids(#[case] a: u32, #[case] b: u32, #[case] expected: bool) {
+        // when
+        let precedes = xid_precedes(a, b);
+
+        // then
+        assert_eq!(precedes, expected);
+    }
+}
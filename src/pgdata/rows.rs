@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+
+use super::{
+    base::db_dir::{relation::RelationFiles, ForkType},
+    catalog::Class,
+    page::{ItemPointer, Page},
+    transactions::Transactions,
+};
+
+/// A live tuple of a table split into the values of its columns, dropped ones included
+#[derive(Debug, PartialEq)]
+pub struct Row {
+    pub ctid: ItemPointer,
+    pub values: Vec<Option<Vec<u8>>>,
+}
+
+/// Decodes the live tuples of the main fork in physical order, see [Page::live_tuples]. Pages
+/// are read as the rows are consumed, a page or a tuple which cannot be decoded yields an error
+pub fn rows<'a>(
+    files: &'a impl RelationFiles,
+    class: &'a Class,
+    transactions: &'a impl Transactions,
+) -> impl Iterator<Item = Result<Row>> + 'a {
+    let blocks = files
+        .relation()
+        .fork(ForkType::Main)
+        .map_or(0, |fork| fork.blocks()) as u32;
+    (0..blocks).flat_map(move |block| {
+        match files
+            .page(ForkType::Main, block)
+            .and_then(|page| page_rows(&page, block, class, transactions))
+        {
+            Ok(rows) => rows,
+            Err(err) => vec![Err(err.context(format!("Reading block {block}")))],
+        }
+    })
+}
+
+fn page_rows(
+    page: &Page,
+    block: u32,
+    class: &Class,
+    transactions: &impl Transactions,
+) -> Result<Vec<Result<Row>>> {
+    Ok(page
+        .live_tuples(block, transactions)?
+        .into_iter()
+        .map(|tuple| {
+            let (ctid, tuple) = tuple?;
+            let values = class
+                .values(&tuple)
+                .with_context(|| format!("Decoding tuple {ctid}"))?
+                .into_iter()
+                .map(|value| value.map(<[u8]>::to_vec))
+                .collect();
+            Ok(Row { ctid, values })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        common::PgOid,
+        pgdata::{
+            base::db_dir::{
+                relation::{test_stubs::StubRelationFiles, Relation},
+                ForkSegmentFile, ForkType,
+            },
+            catalog::test_stubs::catalog,
+            page::{
+                heap::HEAP_XMAX_COMMITTED,
+                test_pages::{heap_tuple, with_nulls, PageBuilder},
+                ItemPointer,
+            },
+            transactions::test_stubs::StubTransactions,
+        },
+    };
+
+    use super::{rows, Row};
+
+    #[test]
+    fn decodes_live_rows() {
+        // given
+        let catalog = catalog();
+        let orders = catalog.class(PgOid(16385)).unwrap();
+        let files = StubRelationFiles {
+            relation: Relation::group(vec![(
+                ForkSegmentFile::create(16385, ForkType::Main, 0),
+                3 * 8192,
+            )])
+            .remove(0),
+            pages: vec![
+                (
+                    ForkType::Main,
+                    0,
+                    PageBuilder::empty()
+                        .item(&heap_tuple(740, 0, 0x0802, 2, &[1, 0, 0, 0, 0x05, b'a']))
+                        .item(&heap_tuple(740, 741, HEAP_XMAX_COMMITTED, 1, &[2, 0, 0, 0]))
+                        .item(&with_nulls(heap_tuple(740, 0, 0, 2, &[3, 0, 0, 0]), &[1]))
+                        .build(),
+                ),
+                (ForkType::Main, 1, vec![0; 8192]),
+            ],
+        };
+
+        // when
+        let rows = rows(&files, orders, &StubTransactions)
+            .map(|row| row.map_err(|err| format!("{err:#}")))
+            .collect::<Vec<_>>();
+
+        // then
+        assert_eq!(
+            rows,
+            vec![
+                Ok(Row {
+                    ctid: ItemPointer {
+                        block_number: 0,
+                        offset_number: 1
+                    },
+                    values: vec![Some(vec![1, 0, 0, 0]), Some(vec![0x05, b'a'])]
+                }),
+                Ok(Row {
+                    ctid: ItemPointer {
+                        block_number: 0,
+                        offset_number: 3
+                    },
+                    values: vec![Some(vec![3, 0, 0, 0]), None]
+                }),
+                Err("Reading block 2: Block 2 is not stubbed".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use std::{ops::Range, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+
+use super::BLCKSZ;
+
+/// Pages of an SLRU segment file, the newest segment is usually shorter
+pub const SLRU_PAGES_PER_SEGMENT: u64 = 32;
+
+/// Segment numbers above this one do not fit a short segment file name
+const MAX_SHORT_SEGMENT_NUMBER: u64 = 0xFF_FFFF;
+
+/// Represents a directory of PG_DATA holding a simple LRU buffered log, e.g. pg_xact. The log is
+/// a sequence of pages split into segment files of [SLRU_PAGES_PER_SEGMENT] pages named after
+/// their number in hex
+pub trait Slru {
+    /// Returns the actual path of the directory represented by this trait
+    fn path(&self) -> &Path;
+
+    /// Lists the files of the directory ordered by name
+    fn segments(&self) -> Result<Vec<SlruSegment>>;
+
+    /// Reads `len` bytes of the page at the location, fails the same way for all SLRUs when the
+    /// segment is missing or too short to hold the page
+    fn read(&self, location: &EntryLocation, len: usize) -> Result<Vec<u8>>;
+}
+
+/// Instantiates a default implementation of [Slru] for the directory
+pub fn slru(path: &Path) -> impl Slru {
+    default_impl::Slru::new(path)
+}
+
+/// Layout of the fixed-size entries an SLRU keeps, e.g. 2 bits of commit status per xid in pg_xact.
+/// Entries do not span pages, the bits left at the end of a page are unused
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EntryLayout {
+    pub entry_bits: u32,
+}
+
+impl EntryLayout {
+    pub const fn bits(entry_bits: u32) -> EntryLayout {
+        EntryLayout { entry_bits }
+    }
+
+    pub const fn bytes(entry_bytes: u32) -> EntryLayout {
+        EntryLayout::bits(entry_bytes * 8)
+    }
+
+    pub const fn entries_per_page(&self) -> u32 {
+        BLCKSZ as u32 * 8 / self.entry_bits
+    }
+
+    /// Locates the entry of the given number, e.g. of an xid. Numbers wrap around after 2^32 as
+    /// xids do, the last page of the log is partially used unless entries per page divide 2^32
+    pub fn locate(&self, entry_number: u32) -> EntryLocation {
+        let entries_per_page = self.entries_per_page();
+        let bit = entry_number % entries_per_page * self.entry_bits;
+        EntryLocation {
+            page_number: (entry_number / entries_per_page) as u64,
+            byte: bit / 8,
+            shift: bit % 8,
+        }
+    }
+
+    /// Numbers of the entries the present pages of the segment hold, None for files which are
+    /// not segments
+    pub fn segment_entries(&self, segment: &SlruSegment) -> Option<Range<u64>> {
+        let entries_per_page = self.entries_per_page() as u64;
+        segment.segment_number().map(|segment_number| {
+            let first = segment_number * SLRU_PAGES_PER_SEGMENT * entries_per_page;
+            let end = (first + segment.pages() * entries_per_page).min(1 << 32);
+            first.min(end)..end
+        })
+    }
+}
+
+/// Position of an entry in an SLRU
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EntryLocation {
+    /// Page number counted from the beginning of the log
+    pub page_number: u64,
+    /// Byte of the page the entry starts at
+    pub byte: u32,
+    /// Shift of the entry bits in the byte, non-zero only for entries smaller than a byte
+    pub shift: u32,
+}
+
+impl EntryLocation {
+    pub fn segment_number(&self) -> u64 {
+        self.page_number / SLRU_PAGES_PER_SEGMENT
+    }
+
+    /// Name of the segment file, e.g. `0000`
+    pub fn segment_file_name(&self) -> String {
+        segment_file_names(self.segment_number())[0].clone()
+    }
+
+    /// Offset of the entry in the segment file
+    pub fn segment_offset(&self) -> u64 {
+        self.page_number % SLRU_PAGES_PER_SEGMENT * BLCKSZ + self.byte as u64
+    }
+}
+
+/// Names the segment file may have: the short one, e.g. `0A1F`, used by all SLRUs before
+/// PostgreSQL 17 and the long one, e.g. `000000000000A1F`, some SLRUs use since then
+pub fn segment_file_names(segment_number: u64) -> [String; 2] {
+    let long_name = format!("{segment_number:015X}");
+    match segment_number {
+        ..=MAX_SHORT_SEGMENT_NUMBER => [format!("{segment_number:04X}"), long_name],
+        _ => [long_name.clone(), long_name],
+    }
+}
+
+/// Iterates over the xids from first to last inclusive, wrapping around after 2^32 - 1 as
+/// transaction ids do
+pub fn wrapping_range(first: u32, last: u32) -> impl Iterator<Item = u32> {
+    (0..=last.wrapping_sub(first) as u64).map(move |i| first.wrapping_add(i as u32))
+}
+
+/// A file of an SLRU directory
+#[derive(Debug, PartialEq)]
+pub struct SlruSegment {
+    pub file_name: String,
+    pub size: u64,
+}
+
+impl SlruSegment {
+    /// Segment number parsed from the file name, None for files which are not segments
+    pub fn segment_number(&self) -> Option<u64> {
+        match self.file_name.len() {
+            4..=6 | 15 => u64::from_str_radix(&self.file_name, 16).ok(),
+            _ => None,
+        }
+    }
+
+    /// Number of complete pages the segment holds
+    pub fn pages(&self) -> u64 {
+        self.size / BLCKSZ
+    }
+
+    /// Tells whether the segment holds fewer than [SLRU_PAGES_PER_SEGMENT] pages or ends with
+    /// a partial page, which is expected only of the newest segment
+    pub fn is_short(&self) -> bool {
+        self.size != SLRU_PAGES_PER_SEGMENT * BLCKSZ
+    }
+}
+
+/// Checks the segment file of the given size holds `len` bytes at the location
+fn check_segment_size(
+    segment_path: &Path,
+    size: u64,
+    location: &EntryLocation,
+    len: usize,
+) -> Result<()> {
+    if location.segment_offset() + len as u64 > size {
+        bail!(
+            "Segment {:?} holds {} pages, page {} is missing",
+            segment_path,
+            size / BLCKSZ,
+            location.page_number
+        );
+    }
+    Ok(())
+}
+
+fn missing_segment(segment_path: &Path, location: &EntryLocation) -> anyhow::Error {
+    anyhow!(
+        "Segment {:?} holding page {} is missing",
+        segment_path,
+        location.page_number
+    )
+}
+
+mod default_impl {
+    use std::{
+        fs::File,
+        io::{Read, Seek, SeekFrom},
+        path::{Path, PathBuf},
+    };
+
+    use anyhow::{anyhow, Context};
+
+    use super::{
+        check_segment_size, missing_segment, segment_file_names, EntryLocation, SlruSegment,
+    };
+
+    pub struct Slru {
+        path: PathBuf,
+    }
+
+    impl Slru {
+        pub fn new(path: &Path) -> Self {
+            Slru {
+                path: path.to_path_buf(),
+            }
+        }
+    }
+
+    impl super::Slru for Slru {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            let mut segments = std::fs::read_dir(&self.path)
+                .with_context(|| format!("Reading {:?}", self.path))?
+                .map(|entry| {
+                    let entry = entry.map_err(|err| anyhow!(err))?;
+                    Ok(SlruSegment {
+                        file_name: entry.file_name().to_string_lossy().into_owned(),
+                        size: entry.metadata()?.len(),
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            segments.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+            Ok(segments)
+        }
+
+        fn read(&self, location: &EntryLocation, len: usize) -> anyhow::Result<Vec<u8>> {
+            let path = segment_file_names(location.segment_number())
+                .iter()
+                .map(|name| self.path.join(name))
+                .find(|path| path.exists())
+                .ok_or_else(|| {
+                    missing_segment(&self.path.join(location.segment_file_name()), location)
+                })?;
+
+            let mut file = File::open(&path).with_context(|| format!("Reading {:?}", path))?;
+            check_segment_size(&path, file.metadata()?.len(), location, len)?;
+            let mut bytes = vec![0; len];
+            file.seek(SeekFrom::Start(location.segment_offset()))
+                .and_then(|_| file.read_exact(&mut bytes))
+                .with_context(|| format!("Reading {:?}", path))?;
+            Ok(bytes)
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use std::path::{Path, PathBuf};
+
+    use super::{
+        check_segment_size, missing_segment, segment_file_names, EntryLocation, Slru, SlruSegment,
+    };
+
+    /// Keeps the segment files in memory
+    pub struct StubSlru {
+        pub path: PathBuf,
+        pub segments: Vec<(String, Vec<u8>)>,
+    }
+
+    impl Slru for StubSlru {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn segments(&self) -> anyhow::Result<Vec<SlruSegment>> {
+            Ok(self
+                .segments
+                .iter()
+                .map(|(file_name, bytes)| SlruSegment {
+                    file_name: file_name.clone(),
+                    size: bytes.len() as u64,
+                })
+                .collect())
+        }
+
+        fn read(&self, location: &EntryLocation, len: usize) -> anyhow::Result<Vec<u8>> {
+            let names = segment_file_names(location.segment_number());
+            let (name, bytes) = self
+                .segments
+                .iter()
+                .find(|(name, _)| names.contains(name))
+                .ok_or_else(|| {
+                    missing_segment(&self.path.join(location.segment_file_name()), location)
+                })?;
+            check_segment_size(&self.path.join(name), bytes.len() as u64, location, len)?;
+            let offset = location.segment_offset() as usize;
+            Ok(bytes[offset..offset + len].to_vec())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{
+        segment_file_names, test_stubs::StubSlru, wrapping_range, EntryLayout, EntryLocation, Slru,
+        SlruSegment,
+    };
+
+    fn location(page_number: u64, byte: u32, shift: u32) -> EntryLocation {
+        EntryLocation {
+            page_number,
+            byte,
+            shift,
+        }
+    }
+
+    #[rstest]
+    #[case(EntryLayout::bits(2), 12345, location(0, 3086, 2), "0000", 3086)]
+    #[case(EntryLayout::bits(2), 32768, location(1, 0, 0), "0000", 8192)]
+    #[case(EntryLayout::bits(2), 1_048_581, location(32, 1, 2), "0001", 1)]
+    #[case(EntryLayout::bits(2), u32::MAX, location(131_071, 8191, 6), "0FFF", 31 * 8192 + 8191)]
+    #[case(EntryLayout::bytes(4), 2049, location(1, 4, 0), "0000", 8196)]
+    #[case(EntryLayout::bytes(10), 820, location(1, 10, 0), "0000", 8202)]
+    #[case(
+        EntryLayout::bytes(10),
+        u32::MAX,
+        location(5_244_160, 2550, 0),
+        "28028",
+        2550
+    )]
+    fn locates_entry(
+        #[case] layout: EntryLayout,
+        #[case] entry_number: u32,
+        #[case] expected: EntryLocation,
+        #[case] file_name: &str,
+        #[case] offset: u64,
+    ) {
+        // when
+        let location = layout.locate(entry_number);
+
+        // then
+        assert_eq!(location, expected);
+        assert_eq!(location.segment_file_name(), file_name);
+        assert_eq!(location.segment_offset(), offset);
+    }
+
+    #[rstest]
+    #[case("0000", 8192, Some(0..32768))]
+    #[case("0002", 3 * 8192, Some(2_097_152..2_097_152 + 3 * 32768))]
+    #[case("0FFF", 32 * 8192, Some(4_293_918_720..1 << 32))]
+    #[case("00000000000000A", 8192, Some(10_485_760..10_518_528))]
+    #[case("pg_xact.tmp", 0, None)]
+    fn computes_segment_entries(
+        #[case] file_name: &str,
+        #[case] size: u64,
+        #[case] expected: Option<std::ops::Range<u64>>,
+    ) {
+        // given
+        let segment = SlruSegment {
+            file_name: file_name.to_string(),
+            size,
+        };
+
+        // when
+        let entries = EntryLayout::bits(2).segment_entries(&segment);
+
+        // then
+        assert_eq!(entries, expected);
+    }
+
+    #[rstest]
+    #[case(0x1F, ["001F", "00000000000001F"])]
+    #[case(0x1234567, ["000000001234567", "000000001234567"])]
+    fn names_segments(#[case] segment_number: u64, #[case] expected: [&str; 2]) {
+        // when
+        let names = segment_file_names(segment_number);
+
+        // then
+        assert_eq!(names, expected);
+    }
+
+    #[rstest]
+    #[case(740, 743, vec![740, 741, 742, 743])]
+    #[case(u32::MAX - 1, 1, vec![u32::MAX - 1, u32::MAX, 0, 1])]
+    #[case(5, 5, vec![5])]
+    fn iterates_over_wrapping_range(
+        #[case] first: u32,
+        #[case] last: u32,
+        #[case] expected: Vec<u32>,
+    ) {
+        // when
+        let xids = wrapping_range(first, last).collect::<Vec<_>>();
+
+        // then
+        assert_eq!(xids, expected);
+    }
+
+    #[rstest]
+    #[case(location(1, 2, 0), Ok(vec![7, 8]))]
+    #[case(location(33, 0, 0), Ok(vec![9, 0]))]
+    #[case(
+        location(2, 0, 0),
+        Err("Segment \"/pgdata/pg_xact/0000\" holds 2 pages, page 2 is missing")
+    )]
+    #[case(
+        location(64, 0, 0),
+        Err("Segment \"/pgdata/pg_xact/0002\" holding page 64 is missing")
+    )]
+    fn reads_entries(#[case] location: EntryLocation, #[case] expected: Result<Vec<u8>, &str>) {
+        // given
+        let mut segment = vec![0; 2 * 8192];
+        segment[8194..8196].copy_from_slice(&[7, 8]);
+        let slru = StubSlru {
+            path: Path::new("/pgdata/pg_xact").to_path_buf(),
+            segments: vec![
+                ("0000".to_string(), segment),
+                (
+                    "000000000000001".to_string(),
+                    [vec![0; 8192], vec![9, 0]].concat(),
+                ),
+            ],
+        };
+
+        // when
+        let bytes = slru.read(&location, 2);
+
+        // then
+        assert_eq!(
+            bytes.map_err(|err| err.to_string()),
+            expected.map_err(String::from)
+        );
+    }
+}
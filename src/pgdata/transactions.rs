@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use super::{
+    pg_commit_ts::{self, PGCommitTS},
+    pg_multiexact::{self, PGMultixact},
+    pg_subtrans::{self, PGSubtrans},
+    pg_xact::{self, PGXact},
+};
+
+/// Transaction metadata of PG_DATA kept apart from the tuples, needed to explain their
+/// visibility: commit statuses and timestamps, subtransaction parents and multixact members
+pub trait Transactions {
+    /// Represents PG_DATA/pg_xact directory
+    fn pg_xact(&self) -> &impl PGXact;
+
+    /// Represents PG_DATA/pg_subtrans directory
+    fn pg_subtrans(&self) -> &impl PGSubtrans;
+
+    /// Represents PG_DATA/pg_multixact directory
+    fn pg_multixact(&self) -> &impl PGMultixact;
+
+    /// Represents PG_DATA/pg_commit_ts directory
+    fn pg_commit_ts(&self) -> &impl PGCommitTS;
+}
+
+/// Instantiates a default implementation of [Transactions]
+pub fn transactions(pgdata_path: &Path) -> impl Transactions {
+    default_impl::Transactions {
+        pg_xact: pg_xact::pg_xact(pgdata_path),
+        pg_subtrans: pg_subtrans::pg_subtrans(pgdata_path),
+        pg_multixact: pg_multiexact::pg_multixact(pgdata_path),
+        pg_commit_ts: pg_commit_ts::pg_commit_ts(pgdata_path),
+    }
+}
+
+mod default_impl {
+    use crate::pgdata::{
+        pg_commit_ts::PGCommitTS, pg_multiexact::PGMultixact, pg_subtrans::PGSubtrans,
+        pg_xact::PGXact,
+    };
+
+    pub struct Transactions<X: PGXact, S: PGSubtrans, M: PGMultixact, C: PGCommitTS> {
+        pub pg_xact: X,
+        pub pg_subtrans: S,
+        pub pg_multixact: M,
+        pub pg_commit_ts: C,
+    }
+
+    impl<X: PGXact, S: PGSubtrans, M: PGMultixact, C: PGCommitTS> super::Transactions
+        for Transactions<X, S, M, C>
+    {
+        fn pg_xact(&self) -> &impl PGXact {
+            &self.pg_xact
+        }
+
+        fn pg_subtrans(&self) -> &impl PGSubtrans {
+            &self.pg_subtrans
+        }
+
+        fn pg_multixact(&self) -> &impl PGMultixact {
+            &self.pg_multixact
+        }
+
+        fn pg_commit_ts(&self) -> &impl PGCommitTS {
+            &self.pg_commit_ts
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_stubs {
+    use crate::pgdata::{
+        pg_commit_ts::{test_stubs::StubPGCommitTS, PGCommitTS},
+        pg_multiexact::{test_stubs::StubPGMultixact, PGMultixact},
+        pg_subtrans::{test_stubs::StubPGSubtrans, PGSubtrans},
+        pg_xact::{test_stubs::StubPGXact, PGXact},
+    };
+
+    use super::Transactions;
+
+    pub struct StubTransactions;
+    impl Transactions for StubTransactions {
+        fn pg_xact(&self) -> &impl PGXact {
+            &StubPGXact
+        }
+
+        fn pg_subtrans(&self) -> &impl PGSubtrans {
+            &StubPGSubtrans
+        }
+
+        fn pg_multixact(&self) -> &impl PGMultixact {
+            &StubPGMultixact
+        }
+
+        fn pg_commit_ts(&self) -> &impl PGCommitTS {
+            &StubPGCommitTS
+        }
+    }
+}
@@ -16,6 +16,7 @@ pub mod colors {
 
     pub const BLUE: Option<Color> = Some(Color::Blue);
     pub const BRIGHT_BLUE: Option<Color> = Some(Color::BrightBlue);
+    pub const CYAN: Option<Color> = Some(Color::Cyan);
     pub const GRAY: Option<Color> = Some(crate::GRAY);
     pub const GREEN: Option<Color> = Some(Color::Green);
     pub const RED: Option<Color> = Some(Color::Red);
@@ -21,6 +21,9 @@ pub struct TermSize {
 }
 
 impl TermSize {
+    /// Size assumed when the output is not a terminal, e.g. redirected to a file
+    pub const DEFAULT: TermSize = TermSize { rows: 24, cols: 80 };
+
     pub fn new(size: &termsize::Size) -> TermSize {
         TermSize {
             rows: size.rows as usize,
@@ -29,6 +32,21 @@ impl TermSize {
     }
 }
 
+/// Splits text into lines of at most `width` chars breaking it at whitespaces,
+/// words longer than `width` occupy a line of their own
+pub fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    text.split_whitespace()
+        .for_each(|word| match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        });
+    lines
+}
+
 pub trait Viewer {
     fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>>;
 
@@ -39,7 +57,10 @@ pub trait Viewer {
 mod tests {
     use anyhow::anyhow;
 
-    use super::{find_viewer, TermSize, Viewer};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::{find_viewer, wrap_words, TermSize, Viewer};
 
     const TERM_SIZE: TermSize = TermSize { rows: 20, cols: 80 };
 
@@ -82,6 +103,19 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case("", 10, &[])]
+    #[case("aaa bbb ccc", 7, &["aaa bbb", "ccc"])]
+    #[case("aaa  bbb", 20, &["aaa bbb"])]
+    #[case("aaaaaaaaaa b", 5, &["aaaaaaaaaa", "b"])]
+    fn wraps_words(#[case] text: &str, #[case] width: usize, #[case] expected: &[&str]) {
+        // when
+        let lines = wrap_words(text, width);
+
+        // then
+        assert_eq!(lines, expected);
+    }
+
     struct MockViewer {
         collected_args: Vec<String>,
     }
@@ -96,9 +130,9 @@ mod tests {
             }))
         }
 
-        fn handle<'a>(
+        fn handle(
             &self,
-            term_size: &'a TermSize,
+            term_size: &TermSize,
             write: Box<&mut dyn std::io::prelude::Write>,
         ) -> anyhow::Result<()> {
             write!(write, "{} / {:?}", self.collected_args.join(" "), term_size)
@@ -112,9 +146,9 @@ mod tests {
             Err(anyhow!("{param} is not supported"))
         }
 
-        fn handle<'a>(
+        fn handle(
             &self,
-            _term_size: &'a TermSize,
+            _term_size: &TermSize,
             _write: Box<&mut dyn std::io::prelude::Write>,
         ) -> anyhow::Result<()> {
             todo!()
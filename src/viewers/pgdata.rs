@@ -9,11 +9,24 @@ use crate::{
     GRAY,
 };
 
-use self::base::BaseViewer;
+use self::{
+    base::BaseViewer, global::GlobalViewer, pg_commit_ts::PGCommitTSViewer,
+    pg_multixact::PGMultixactViewer, pg_subtrans::PGSubtransViewer, pg_wal::PGWalViewer,
+    pg_xact::PGXactViewer,
+};
 
 use super::{TermSize, Viewer};
 
 mod base;
+mod filenode_map;
+mod global;
+mod page;
+mod pg_commit_ts;
+mod pg_multixact;
+mod pg_subtrans;
+mod pg_wal;
+mod pg_xact;
+mod slru;
 
 pub struct RootViewer<T: PGData> {
     // TODO: create factory and make private
@@ -25,7 +38,23 @@ impl<T: PGData> Viewer for RootViewer<T> {
         match param {
             "base" => Ok(Box::new(BaseViewer {
                 base: self.pgdata.items().base(),
+                transactions: self.pgdata.items().transactions(),
             })),
+            "global" => Ok(Box::new(GlobalViewer::new(self.pgdata.items().global()))),
+            "pg_wal" => Ok(Box::new(PGWalViewer::new(
+                self.pgdata.items().pg_wal(),
+                self.pgdata.items().transactions(),
+            ))),
+            "pg_commit_ts" => Ok(Box::new(PGCommitTSViewer::new(
+                self.pgdata.items().pg_commit_ts(),
+            ))),
+            "pg_multixact" => Ok(Box::new(PGMultixactViewer::new(
+                self.pgdata.items().transactions(),
+            ))),
+            "pg_subtrans" => Ok(Box::new(PGSubtransViewer::new(
+                self.pgdata.items().transactions(),
+            ))),
+            "pg_xact" => Ok(Box::new(PGXactViewer::new(self.pgdata.items().pg_xact()))),
             "a" => Ok(Box::new(AViewer {})),
             "b" => Ok(Box::new(BViewer {})),
             val => Ok(Box::new(ArbViewer {
@@ -169,7 +198,7 @@ impl PgDataItemView<'_> {
         })
     }
 
-    fn chunks(str: &str, size: usize) -> StrChunks {
+    fn chunks(str: &str, size: usize) -> StrChunks<'_> {
         StrChunks {
             chunk_size: size,
             str,
@@ -275,7 +304,7 @@ mod tests {
         F: Fn() -> anyhow::Result<Vec<PGDataItem<'static>>>,
     {
         fn path(&self) -> &Path {
-            &self.path
+            self.path
         }
 
         fn list_items(
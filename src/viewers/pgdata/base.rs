@@ -5,7 +5,10 @@ use crate::{
         fs::{render_file_type, DirEntry},
         PgOid,
     },
-    pgdata::base::{Base, BaseDirItem},
+    pgdata::{
+        base::{Base, BaseDirItem},
+        transactions::Transactions,
+    },
     viewers::{TermSize, Viewer},
 };
 
@@ -18,16 +21,18 @@ use self::db_dir::DbDirViewer;
 
 mod db_dir;
 
-pub struct BaseViewer<T: Base> {
+pub struct BaseViewer<T: Base, X: Transactions> {
     pub base: T,
+    /// Passed along to the page viewer to annotate the heap tuples
+    pub transactions: X,
 }
 
-impl<T: Base> Viewer for BaseViewer<T> {
+impl<T: Base, X: Transactions + 'static> Viewer for BaseViewer<T, X> {
     fn get_next(self: Box<Self>, _param: &str) -> anyhow::Result<Box<dyn Viewer>> {
         let base_dir = PgOid::try_parse(_param)
             .context("Expected database oid")
             .and_then(|oid| self.base.db_dir(oid))?;
-        Ok(Box::new(DbDirViewer::new(base_dir)))
+        Ok(Box::new(DbDirViewer::new(base_dir, self.transactions)))
     }
 
     fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
@@ -62,12 +67,17 @@ fn format_base_dir_item(
             // dir name is a string representation of oid
             // oid is an unsigned 32-bit integer with a range of values [0; 4,294,967,295]
             // and string representation maximum length of 10 chars
-            write!(
-                target,
-                "D {:>10} {}",
-                dir.dir_name().bright_blue(),
-                dir.db_name()
-            )
+            match dir.db_name() {
+                Some(db_name) => {
+                    write!(target, "D {:>10} {}", dir.dir_name().bright_blue(), db_name)
+                }
+                None => write!(
+                    target,
+                    "D {:>10} {}",
+                    dir.dir_name().bright_blue(),
+                    "unknown".color(GRAY)
+                ),
+            }
         }
         BaseDirItem::UnknownEntry(DirEntry { name, entry_type }) => {
             write!(
@@ -94,6 +104,7 @@ mod tests {
     use crate::pgdata::base::db_dir::test_stubs::StubDbDir;
     use crate::pgdata::base::db_dir::DbDir;
     use crate::pgdata::base::{Base, BaseDirItem};
+    use crate::pgdata::transactions::test_stubs::StubTransactions;
     use crate::viewers::{TermSize, Viewer};
     use crate::{
         test_utils::colors::{BRIGHT_BLUE, GRAY, NONE, RED, YELLOW},
@@ -109,7 +120,8 @@ mod tests {
             items: || {
                 vec![
                     BaseDirItem::database_dir(2, "database_name_1"),
-                    BaseDirItem::database_dir(std::u32::MAX, "database_name_2"),
+                    BaseDirItem::database_dir(u32::MAX, "database_name_2"),
+                    BaseDirItem::unnamed_database_dir(16384),
                     BaseDirItem::unknown_file("some_file"),
                     BaseDirItem::unknown_dir("some_dir"),
                     BaseDirItem::Error(anyhow!("unexpected error")),
@@ -117,7 +129,10 @@ mod tests {
             },
         };
 
-        let base_viewer = BaseViewer { base };
+        let base_viewer = BaseViewer {
+            base,
+            transactions: StubTransactions,
+        };
 
         let term_size = TermSize {
             rows: 100,
@@ -139,6 +154,7 @@ mod tests {
                 line("Each directory stores data for each database in the cluster and is named after the database's OID in |pg_database", &[NONE, GRAY]),
                 line("D |         2| database_name_1", &[NONE, BRIGHT_BLUE, NONE]),
                 line("D |4294967295| database_name_2", &[NONE, BRIGHT_BLUE, NONE]),
+                line("D |     16384| |unknown", &[NONE, BRIGHT_BLUE, NONE, GRAY]),
                 line("F |some_file", &[NONE, GRAY]),
                 line("D |some_dir", &[NONE, GRAY]),
                 line("E |unexpected error", &[NONE, RED]),
@@ -167,8 +183,8 @@ mod tests {
             &self,
         ) -> anyhow::Result<
             impl IntoIterator<
-                Item = crate::pgdata::base::BaseDirItem,
-                IntoIter = impl Iterator<Item = crate::pgdata::base::BaseDirItem>,
+                Item = crate::pgdata::base::BaseDirItem<'_>,
+                IntoIter = impl Iterator<Item = crate::pgdata::base::BaseDirItem<'_>>,
             >,
         > {
             Ok((self.items)().into_iter())
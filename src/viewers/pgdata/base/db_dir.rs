@@ -1,30 +1,533 @@
 use std::io::prelude::Write;
 
+use anyhow::{anyhow, bail, Context};
+use colored::Colorize;
+
 use crate::{
-    pgdata::base::db_dir::DbDir,
-    viewers::{TermSize, Viewer},
+    common::{
+        fs::{render_file_type, DirEntry},
+        size::pretty_size,
+    },
+    pgdata::{
+        base::db_dir::{
+            relation::{Fork, Relation},
+            DbDir, DbDirItem, ForkSegmentFile, ForkType,
+        },
+        catalog::{Catalog, Class, RelKind},
+        filenode_map::FILENODE_MAP_FILE_NAME,
+        transactions::Transactions,
+    },
+    viewers::{pgdata::filenode_map::FileNodeMapViewer, TermSize, Viewer},
+    GRAY,
 };
 
-#[allow(dead_code)]
-pub struct DbDirViewer<T: DbDir> {
+use self::relation::RelationViewer;
+
+mod free_space_map;
+mod relation;
+mod rows;
+mod visibility_map;
+
+pub struct DbDirViewer<T: DbDir, X: Transactions> {
     base_dir: T,
+    transactions: X,
 }
 
-impl<T> DbDirViewer<T>
+impl<T, X> DbDirViewer<T, X>
 where
     T: DbDir,
+    X: Transactions,
 {
-    pub fn new(base_dir: T) -> Self {
-        DbDirViewer { base_dir }
+    pub fn new(base_dir: T, transactions: X) -> Self {
+        DbDirViewer {
+            base_dir,
+            transactions,
+        }
     }
 }
 
-impl<T: DbDir> Viewer for DbDirViewer<T> {
+impl<T: DbDir + 'static, X: Transactions + 'static> Viewer for DbDirViewer<T, X> {
     fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
-        anyhow::bail!("${param} not supported")
+        if param == FILENODE_MAP_FILE_NAME {
+            return Ok(Box::new(FileNodeMapViewer::new(
+                self.base_dir.path().join(FILENODE_MAP_FILE_NAME),
+                self.base_dir.filenode_map()?,
+            )));
+        }
+        let fork_segment_file = ForkSegmentFile::try_parse(param).with_context(|| {
+            format!("Expected relfilenode, e.g. 16384 or 16384_vm, got {param}")
+        })?;
+        if fork_segment_file.segment_id() != 0 {
+            bail!("Segments are not browsed separately, pass the block number of the fork instead");
+        }
+        let files = self.base_dir.relation(fork_segment_file.rel_file_node())?;
+        let base_dir = self.base_dir;
+        Ok(Box::new(RelationViewer::new(
+            files,
+            *fork_segment_file.fork_type(),
+            self.transactions,
+            Box::new(move || base_dir.catalog()),
+        )))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let path = self.base_dir.path();
+        write!(
+            write,
+            "{}",
+            path.parent()
+                .expect("base path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(
+            write,
+            "{}",
+            format!(
+                "/{}",
+                path.file_name().expect("db dir name").to_string_lossy()
+            )
+            .yellow()
+        )?;
+
+        let catalog = match self.base_dir.catalog() {
+            Ok(catalog) => Some(catalog),
+            Err(err) => {
+                let err =
+                    err.context("Relations are not named, the system catalogs are unreadable");
+                write!(write, "\nE {}", format!("{err:#}").red())?;
+                None
+            }
+        };
+        let items = self.base_dir.items()?;
+        items.into_iter().try_for_each(|item| {
+            writeln!(write)?;
+            format_db_dir_item(item, catalog.as_ref(), &mut write)
+        })?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+fn format_db_dir_item(
+    db_dir_item: DbDirItem<'_>,
+    catalog: Option<&Catalog>,
+    target: &mut dyn Write,
+) -> anyhow::Result<()> {
+    match db_dir_item {
+        DbDirItem::Relation(relation) => format_relation(&relation, catalog, target),
+        DbDirItem::FileNodeMapFile => write!(
+            target,
+            "F {} Mapping of system catalog OIDs to their relfilenodes",
+            "pg_filenode.map".bright_blue(),
+        ),
+        DbDirItem::PgVersionFile => write!(
+            target,
+            "F {} Major version number of PostgreSQL",
+            "PG_VERSION".bright_blue(),
+        ),
+        DbDirItem::UnknownEntry(DirEntry { name, entry_type }) => write!(
+            target,
+            "{} {}",
+            render_file_type(&entry_type),
+            name.to_string_lossy().color(GRAY)
+        ),
+        DbDirItem::Error(err) => write!(target, "E {}", err.0.to_string().red()),
+    }
+    .map_err(|err| anyhow!(err))
+}
+
+fn format_relation(
+    relation: &Relation,
+    catalog: Option<&Catalog>,
+    target: &mut dyn Write,
+) -> std::io::Result<()> {
+    // relfilenode is an oid and takes up to 10 chars,
+    // pretty size takes up to 11 chars, e.g. "10239 bytes"
+    let forks = relation
+        .forks()
+        .map(|(fork_type, fork)| describe_fork(fork_type, fork))
+        .collect::<Vec<_>>()
+        .join(", ");
+    write!(
+        target,
+        "R {:>10} {:>11}",
+        relation.rel_file_node().file_name().bright_blue(),
+        pretty_size(relation.size()),
+    )?;
+    if let Some(catalog) = catalog {
+        match catalog.class_by_relfilenode(relation.relfilenode()) {
+            None => write!(target, " {}", "orphan, not in pg_class".red())?,
+            Some(class) => write!(
+                target,
+                " {} {}",
+                catalog.qualified_name(class),
+                format!("({})", describe_class(catalog, class)).color(GRAY)
+            )?,
+        }
+    }
+    write!(target, " {}", forks)?;
+
+    let notes = [
+        relation
+            .rel_file_node()
+            .backend_id()
+            .map(|backend_id| format!("temporary relation of backend {backend_id}")),
+        relation
+            .is_unlogged()
+            .then(|| "unlogged relation".to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if !notes.is_empty() {
+        write!(target, " {}", notes.join(", ").color(GRAY))?;
+    }
+
+    let missing_segments = relation.missing_segments();
+    if !missing_segments.is_empty() {
+        let missing_files = missing_segments
+            .into_iter()
+            .map(|(fork_type, segment_id)| {
+                ForkSegmentFile::new(relation.rel_file_node(), fork_type, segment_id).file_name()
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            target,
+            " {}",
+            format!("missing segments: {missing_files}").red()
+        )?;
+    }
+    Ok(())
+}
+
+/// Describes the kind of the relation, the table of an index or a TOAST table and the owner
+fn describe_class(catalog: &Catalog, class: &Class) -> String {
+    let kind = match (class.kind, catalog.parent(class)) {
+        (RelKind::Index | RelKind::PartitionedIndex, Some(table)) => {
+            format!("{} on {}", class.kind, catalog.qualified_name(table))
+        }
+        (RelKind::ToastTable, Some(table)) => {
+            format!("{} of {}", class.kind, catalog.qualified_name(table))
+        }
+        (kind, _) => kind.to_string(),
+    };
+    format!("{kind}, owner {}", catalog.owner_name(class))
+}
+
+fn describe_fork(fork_type: &ForkType, fork: &Fork) -> String {
+    let fork_name = match fork_type {
+        ForkType::Main => "main",
+        ForkType::FreeSpaceMap => "fsm",
+        ForkType::VisibilityMap => "vm",
+        ForkType::Init => "init",
+    };
+    let blocks = match fork.blocks() {
+        1 => "1 block".to_string(),
+        blocks => format!("{blocks} blocks"),
+    };
+    match fork.segments().count() {
+        1 => format!("{fork_name} {blocks}"),
+        segments => format!("{fork_name} {blocks} in {segments} segments"),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::{anyhow, bail};
+    use pretty_assertions::assert_eq;
+
+    use crate::common::fs::DirEntry;
+    use crate::pgdata::base::db_dir::relation::{
+        test_stubs::StubRelationFiles, Relation, RelationFiles,
+    };
+    use crate::pgdata::base::db_dir::{DbDir, DbDirItem, ForkSegmentFile, ForkType, RelFileNode};
+    use crate::pgdata::catalog::{test_stubs::catalog, Catalog};
+    use crate::pgdata::filenode_map::{test_files::filenode_map, FileNodeMap};
+    use crate::pgdata::transactions::test_stubs::StubTransactions;
+    use crate::viewers::{TermSize, Viewer};
+    use crate::{
+        test_utils::colors::{BRIGHT_BLUE, GRAY, NONE, RED, YELLOW},
+        test_utils::line,
+    };
+
+    use super::DbDirViewer;
+
+    #[test]
+    fn db_dir_handler_renders_db_dir_contents() {
+        // given
+        let db_dir = DbDirStub {
+            items: || {
+                vec![
+                    DbDirItem::PgVersionFile,
+                    DbDirItem::FileNodeMapFile,
+                    DbDirItem::Relation(
+                        Relation::group(vec![
+                            (ForkSegmentFile::create(1249, ForkType::Main, 0), 475136),
+                            (
+                                ForkSegmentFile::create(1249, ForkType::FreeSpaceMap, 0),
+                                24576,
+                            ),
+                            (
+                                ForkSegmentFile::create(1249, ForkType::VisibilityMap, 0),
+                                8192,
+                            ),
+                        ])
+                        .remove(0),
+                    ),
+                    DbDirItem::Relation(
+                        Relation::group(vec![
+                            (
+                                ForkSegmentFile::create(16385, ForkType::Main, 0),
+                                1073741824,
+                            ),
+                            (
+                                ForkSegmentFile::create(16385, ForkType::Main, 1),
+                                1073741824,
+                            ),
+                            (ForkSegmentFile::create(16385, ForkType::Main, 3), 8192),
+                        ])
+                        .remove(0),
+                    ),
+                    DbDirItem::Relation(
+                        Relation::group(vec![
+                            (ForkSegmentFile::create(16406, ForkType::Main, 0), 8192),
+                            (ForkSegmentFile::create(16406, ForkType::Init, 0), 0),
+                        ])
+                        .remove(0),
+                    ),
+                    DbDirItem::Relation(
+                        Relation::group(vec![(
+                            ForkSegmentFile::temp(3, 16410, ForkType::Main, 0),
+                            8192,
+                        )])
+                        .remove(0),
+                    ),
+                    DbDirItem::Relation(
+                        Relation::group(vec![(
+                            ForkSegmentFile::create(16500, ForkType::Main, 0),
+                            16384,
+                        )])
+                        .remove(0),
+                    ),
+                    DbDirItem::UnknownEntry(DirEntry::file("some_file")),
+                    DbDirItem::UnknownEntry(DirEntry::dir("some_dir")),
+                    DbDirItem::from_anyhow_error(anyhow!("unexpected error")),
+                ]
+            },
+            catalog: || Ok(catalog()),
+        };
+
+        let db_dir_viewer = DbDirViewer::new(db_dir, StubTransactions);
+
+        let term_size = TermSize {
+            rows: 100,
+            cols: 30,
+        };
+
+        let mut buf = Vec::new();
+
+        // when
+        db_dir_viewer
+            .handle(&term_size, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base|/5", &[GRAY, YELLOW]),
+                line("F |PG_VERSION| Major version number of PostgreSQL", &[NONE, BRIGHT_BLUE, NONE]),
+                line("F |pg_filenode.map| Mapping of system catalog OIDs to their relfilenodes", &[NONE, BRIGHT_BLUE, NONE]),
+                line("R |      1249|      496 kB pg_catalog.pg_attribute |(table, owner postgres)| main 58 blocks, fsm 3 blocks, vm 1 block", &[NONE, BRIGHT_BLUE, NONE, GRAY, NONE]),
+                line("R |     16385|     2048 MB public.orders |(table, owner alice)| main 262145 blocks in 3 segments |missing segments: 16385.2", &[NONE, BRIGHT_BLUE, NONE, GRAY, NONE, RED]),
+                line("R |     16406|  8192 bytes public.sessions |(table, owner alice)| main 1 block, init 0 blocks |unlogged relation", &[NONE, BRIGHT_BLUE, NONE, GRAY, NONE, GRAY]),
+                line("R |  t3_16410|  8192 bytes pg_temp_3.scratch |(table, owner alice)| main 1 block |temporary relation of backend 3", &[NONE, BRIGHT_BLUE, NONE, GRAY, NONE, GRAY]),
+                line("R |     16500|       16 kB |orphan, not in pg_class| main 2 blocks", &[NONE, BRIGHT_BLUE, NONE, RED, NONE]),
+                line("F |some_file", &[NONE, GRAY]),
+                line("D |some_dir", &[NONE, GRAY]),
+                line("E |unexpected error", &[NONE, RED]),
+                line("", &[])
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn db_dir_handler_reports_unreadable_catalog() {
+        // given
+        let db_dir_viewer = DbDirViewer::new(
+            DbDirStub {
+                items: || {
+                    vec![DbDirItem::Relation(
+                        Relation::group(vec![(
+                            ForkSegmentFile::create(16385, ForkType::Main, 0),
+                            8192,
+                        )])
+                        .remove(0),
+                    )]
+                },
+                catalog: || bail!("Reading pg_class: 1259 is truncated"),
+            },
+            StubTransactions,
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        db_dir_viewer
+            .handle(&TermSize { rows: 10, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base|/5", &[GRAY, YELLOW]),
+                line("E |Relations are not named, the system catalogs are unreadable: Reading pg_class: 1259 is truncated", &[NONE, RED]),
+                line("R |     16385|  8192 bytes main 1 block", &[NONE, BRIGHT_BLUE, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn db_dir_viewer_opens_relation_fork() {
+        // given
+        let db_dir_viewer = Box::new(DbDirViewer::new(
+            DbDirStub {
+                items: Vec::new,
+                catalog: || Ok(catalog()),
+            },
+            StubTransactions,
+        ));
+
+        let mut buf = Vec::new();
+
+        // when
+        db_dir_viewer
+            .get_next("t3_16410_vm")
+            .unwrap()
+            .handle(&TermSize { rows: 10, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output.lines().next(),
+            Some(line("/pgdata/base/5|/t3_16410_vm", &[GRAY, YELLOW]).as_str())
+        );
+    }
+
+    #[test]
+    fn db_dir_viewer_opens_filenode_map() {
+        // given
+        let db_dir_viewer = Box::new(DbDirViewer::new(
+            DbDirStub {
+                items: Vec::new,
+                catalog: || Ok(catalog()),
+            },
+            StubTransactions,
+        ));
+
+        let mut buf = Vec::new();
+
+        // when
+        db_dir_viewer
+            .get_next("pg_filenode.map")
+            .unwrap()
+            .handle(&TermSize { rows: 10, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output.lines().last(),
+            Some(
+                line(
+                    "      1259 |       1259| pg_class",
+                    &[NONE, BRIGHT_BLUE, NONE]
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn db_dir_viewer_rejects_segment_file() {
+        // given
+        let db_dir_viewer = Box::new(DbDirViewer::new(
+            DbDirStub {
+                items: Vec::new,
+                catalog: || Ok(catalog()),
+            },
+            StubTransactions,
+        ));
+
+        // when
+        let result = db_dir_viewer.get_next("16384.1");
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some(
+                "Segments are not browsed separately, pass the block number of the fork instead"
+                    .to_string()
+            )
+        );
+    }
+
+    struct DbDirStub<F>
+    where
+        F: Fn() -> Vec<DbDirItem<'static>>,
+    {
+        items: F,
+        catalog: fn() -> anyhow::Result<Catalog>,
+    }
+
+    impl<F> DbDir for DbDirStub<F>
+    where
+        F: Fn() -> Vec<DbDirItem<'static>>,
+    {
+        fn path(&self) -> &Path {
+            Path::new("/pgdata/base/5")
+        }
+
+        fn items(
+            &self,
+        ) -> anyhow::Result<
+            impl IntoIterator<Item = DbDirItem<'_>, IntoIter = impl Iterator<Item = DbDirItem<'_>>>,
+        > {
+            Ok((self.items)().into_iter())
+        }
+
+        fn relation<'a>(
+            &self,
+            rel_file_node: RelFileNode,
+        ) -> anyhow::Result<impl RelationFiles + 'a> {
+            Ok(StubRelationFiles {
+                relation: Relation::of(rel_file_node),
+                pages: vec![],
+            })
+        }
+
+        fn filenode_map(&self) -> anyhow::Result<FileNodeMap> {
+            FileNodeMap::parse(&filenode_map(&[(1259, 1259)]))
+        }
+
+        fn pg_version(&self) -> anyhow::Result<u32> {
+            Ok(15)
+        }
 
-    fn handle(&self, _term_size: &TermSize, _write: Box<&mut dyn Write>) -> anyhow::Result<()> {
-        anyhow::bail!("DbDirViewer is not implemented")
+        fn catalog(&self) -> anyhow::Result<Catalog> {
+            (self.catalog)()
+        }
     }
 }
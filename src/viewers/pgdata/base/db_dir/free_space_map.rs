@@ -0,0 +1,139 @@
+use std::io::Write;
+
+use colored::Colorize;
+
+use crate::{
+    common::size::pretty_size,
+    pgdata::{
+        base::db_dir::{relation::RelationFiles, ForkType},
+        page::free_space_map::{
+            avail_to_category, category_to_avail, heap_page_avail, FreeSpaceMap, FSM_CAT_STEP,
+            SLOTS_PER_FSM_PAGE,
+        },
+    },
+    GRAY,
+};
+
+/// Decodes the FSM pages of the relation and compares the free space category recorded for
+/// each heap block with the category of the actual free space of the main fork pages. Both are
+/// shown as the least free space of the category so that they are comparable
+pub fn format_free_space_map(
+    files: &impl RelationFiles,
+    target: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let relation = files.relation();
+    let fork_blocks = |fork_type| relation.fork(fork_type).map_or(0, |fork| fork.blocks()) as u32;
+    let map = (0..fork_blocks(ForkType::FreeSpaceMap))
+        .map(|block| files.page(ForkType::FreeSpaceMap, block))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .and_then(|pages| FreeSpaceMap::new(&pages));
+    let map = match map {
+        Ok(map) => map,
+        Err(err) => return write!(target, "\nE {}", format!("{err:#}").red()).map_err(Into::into),
+    };
+
+    let heap_blocks = fork_blocks(ForkType::Main);
+    write!(
+        target,
+        "\n{}",
+        format!(
+            "Free space map of {} heap blocks, categories of {} bytes, {} blocks per leaf page",
+            heap_blocks, FSM_CAT_STEP, SLOTS_PER_FSM_PAGE
+        )
+        .color(GRAY)
+    )?;
+    write!(
+        target,
+        "\n{}",
+        format!(
+            "  {:<7} {:<7} {:<7} {:<9} {}",
+            "block", "level", "page", "next slot", "root"
+        )
+        .color(GRAY)
+    )?;
+    map.pages()
+        .enumerate()
+        .try_for_each(|(block, (address, page))| {
+            write!(
+                target,
+                "\n  {:<7} {:<7} {:<7} {:<9} {}",
+                block,
+                address.level,
+                address.logical_page,
+                page.next_slot,
+                page.root()
+            )
+        })?;
+
+    let mut errors = Vec::new();
+    let actual_avail = (0..heap_blocks)
+        .map(|block| {
+            files
+                .page(ForkType::Main, block)
+                .and_then(|page| heap_page_avail(&page))
+                .map_err(|err| errors.push(format!("block {block}: {err:#}")))
+                .ok()
+        })
+        .collect::<Vec<_>>();
+    let runs = map.compare(&actual_avail);
+    let recorded_free = (0..heap_blocks)
+        .map(|block| category_to_avail(map.category(block)) as u64)
+        .sum::<u64>();
+    let actual_free = actual_avail
+        .iter()
+        .flatten()
+        .map(|avail| category_to_avail(avail_to_category(*avail)) as u64)
+        .sum::<u64>();
+    let stale_blocks = runs
+        .iter()
+        .filter(|run| run.is_stale())
+        .map(|run| run.blocks.end() - run.blocks.start() + 1)
+        .sum::<u32>();
+    write!(
+        target,
+        "\nfree space by category: recorded {}, actual {}, stale {} of {} blocks",
+        pretty_size(recorded_free),
+        pretty_size(actual_free),
+        stale_blocks,
+        heap_blocks
+    )?;
+    write!(
+        target,
+        "\n{}",
+        format!("  {:<15} {:<9} {}", "heap blocks", "recorded", "actual").color(GRAY)
+    )?;
+    runs.iter().try_for_each(|run| {
+        let blocks = match run.blocks.start() == run.blocks.end() {
+            true => run.blocks.start().to_string(),
+            false => format!("{}..{}", run.blocks.start(), run.blocks.end()),
+        };
+        let recorded = format!("{:<9}", category_to_avail(run.recorded));
+        match run.actual {
+            None => write!(
+                target,
+                "\n  {:<15} {} {}",
+                blocks,
+                recorded,
+                "?".color(GRAY)
+            ),
+            Some(actual) if run.is_stale() => write!(
+                target,
+                "\n  {:<15} {} {}",
+                blocks,
+                recorded.red(),
+                category_to_avail(actual).to_string().red()
+            ),
+            Some(actual) => write!(
+                target,
+                "\n  {:<15} {} {}",
+                blocks,
+                recorded,
+                category_to_avail(actual)
+            ),
+        }
+    })?;
+    errors
+        .into_iter()
+        .try_for_each(|error| write!(target, "\nE {}", error.red()))?;
+    Ok(())
+}
@@ -0,0 +1,455 @@
+use std::io::Write;
+
+use anyhow::{anyhow, bail, Context};
+use colored::Colorize;
+
+use crate::{
+    common::size::pretty_size,
+    pgdata::{
+        base::db_dir::{relation::RelationFiles, ForkSegmentFile, ForkType},
+        catalog::{Catalog, RelKind},
+        transactions::Transactions,
+    },
+    viewers::{pgdata::page::PageViewer, TermSize, Viewer},
+    GRAY,
+};
+
+use super::{
+    describe_fork, free_space_map::format_free_space_map, rows::RowsViewer,
+    visibility_map::format_visibility_map,
+};
+
+/// Param decoding the rows of a table instead of a single block
+pub const ROWS_PARAM: &str = "rows";
+
+/// Decodes the system catalogs of the database, called only when the rows are decoded
+pub type CatalogLoader = Box<dyn FnOnce() -> anyhow::Result<Catalog>>;
+
+/// Shows a single fork of a relation, its blocks are reachable by number and the rows of a
+/// table by [ROWS_PARAM]
+pub struct RelationViewer<T: RelationFiles, X: Transactions> {
+    files: T,
+    fork_type: ForkType,
+    transactions: X,
+    catalog: CatalogLoader,
+}
+
+impl<T: RelationFiles, X: Transactions> RelationViewer<T, X> {
+    pub fn new(files: T, fork_type: ForkType, transactions: X, catalog: CatalogLoader) -> Self {
+        RelationViewer {
+            files,
+            fork_type,
+            transactions,
+            catalog,
+        }
+    }
+}
+
+impl<T: RelationFiles + 'static, X: Transactions + 'static> Viewer for RelationViewer<T, X> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        if param == ROWS_PARAM {
+            if self.fork_type != ForkType::Main {
+                bail!("Rows are stored in the main fork, pass the relfilenode without a suffix");
+            }
+            let catalog = (self.catalog)()
+                .context("Rows are not decoded, the system catalogs are unreadable")?;
+            let relfilenode = self.files.relation().rel_file_node().oid();
+            let class = catalog
+                .class_by_relfilenode(relfilenode)
+                .with_context(|| format!("Relfilenode {} is not in pg_class", relfilenode.0))?;
+            if !matches!(
+                class.kind,
+                RelKind::Table
+                    | RelKind::ToastTable
+                    | RelKind::MaterializedView
+                    | RelKind::Sequence
+            ) {
+                bail!(
+                    "{} is {} {}, rows are decoded for tables only",
+                    catalog.qualified_name(class),
+                    if class.kind == RelKind::Index {
+                        "an"
+                    } else {
+                        "a"
+                    },
+                    class.kind
+                );
+            }
+            let relid = class.oid;
+            return Ok(Box::new(RowsViewer::new(
+                self.files,
+                self.transactions,
+                catalog,
+                relid,
+                1,
+            )));
+        }
+        let block_number = param
+            .parse::<u32>()
+            .with_context(|| format!("Expected block number or {ROWS_PARAM}, got {param}"))?;
+        let page = self.files.page(self.fork_type, block_number)?;
+        let location = self
+            .files
+            .fork_path(self.fork_type)
+            .to_string_lossy()
+            .into_owned();
+        Ok(Box::new(PageViewer::new(
+            location,
+            self.fork_type,
+            block_number,
+            page,
+            self.transactions,
+        )))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let path = self.files.fork_path(self.fork_type);
+        write!(
+            write,
+            "{}",
+            path.parent()
+                .expect("db dir path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(
+            write,
+            "{}",
+            format!(
+                "/{}",
+                path.file_name().expect("fork file name").to_string_lossy()
+            )
+            .yellow()
+        )?;
+
+        let relation = self.files.relation();
+        match relation.fork(self.fork_type) {
+            None => write!(write, "\n{}", "Fork has no files".red())?,
+            Some(fork) => {
+                write!(
+                    write,
+                    "\n{}, {}",
+                    describe_fork(&self.fork_type, fork),
+                    pretty_size(fork.size())
+                )?;
+                fork.segments().try_for_each(|(segment_id, size)| {
+                    let segment =
+                        ForkSegmentFile::new(relation.rel_file_node(), self.fork_type, segment_id);
+                    write!(
+                        write,
+                        "\nF {} {}",
+                        segment.file_name().bright_blue(),
+                        pretty_size(size)
+                    )
+                })?;
+                if fork.blocks() > 0 {
+                    write!(
+                        write,
+                        "\n{}",
+                        format!(
+                            "Pass a block number 0..{} to decode the page",
+                            fork.blocks() - 1
+                        )
+                        .color(GRAY)
+                    )?;
+                }
+                match self.fork_type {
+                    ForkType::VisibilityMap => format_visibility_map(&self.files, &mut write)?,
+                    ForkType::FreeSpaceMap => format_free_space_map(&self.files, &mut write)?,
+                    _ => {}
+                }
+            }
+        }
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::base::db_dir::{
+            relation::{test_stubs::StubRelationFiles, Relation},
+            ForkSegmentFile, ForkType,
+        },
+        pgdata::catalog::test_stubs::catalog,
+        pgdata::page::test_pages::{heap_tuple, PageBuilder},
+        pgdata::transactions::test_stubs::StubTransactions,
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::RelationViewer;
+
+    const TERM_SIZE: TermSize = TermSize {
+        rows: 100,
+        cols: 80,
+    };
+
+    fn relation() -> Relation {
+        Relation::group(vec![
+            (
+                ForkSegmentFile::create(16384, ForkType::Main, 0),
+                1073741824,
+            ),
+            (ForkSegmentFile::create(16384, ForkType::Main, 1), 16384),
+        ])
+        .remove(0)
+    }
+
+    #[test]
+    fn relation_handler_renders_fork_segments() {
+        // given
+        let relation_viewer = RelationViewer::new(
+            StubRelationFiles {
+                relation: relation(),
+                pages: vec![],
+            },
+            ForkType::Main,
+            StubTransactions,
+            Box::new(|| Err(anyhow!("Catalog is not stubbed"))),
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        relation_viewer
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5|/16384", &[GRAY, YELLOW]),
+                line("main 131074 blocks in 2 segments, 1024 MB", &[NONE]),
+                line("F |16384| 1024 MB", &[NONE, BRIGHT_BLUE, NONE]),
+                line("F |16384.1| 16 kB", &[NONE, BRIGHT_BLUE, NONE]),
+                line("Pass a block number 0..131073 to decode the page", &[GRAY]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn relation_handler_reports_missing_fork() {
+        // given
+        let relation_viewer = RelationViewer::new(
+            StubRelationFiles {
+                relation: relation(),
+                pages: vec![],
+            },
+            ForkType::VisibilityMap,
+            StubTransactions,
+            Box::new(|| Err(anyhow!("Catalog is not stubbed"))),
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        relation_viewer
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5|/16384_vm", &[GRAY, YELLOW]),
+                line("Fork has no files", &[RED]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn relation_handler_decodes_visibility_map() {
+        // given
+        let mut vm_page = vec![0; 8192];
+        vm_page[24..26].copy_from_slice(&[0b00_01_11_11, 0b01]);
+        let all_visible_page = PageBuilder::empty().flags(0x0004).build();
+        let relation_viewer = RelationViewer::new(
+            StubRelationFiles {
+                relation: Relation::group(vec![
+                    (ForkSegmentFile::create(16384, ForkType::Main, 0), 5 * 8192),
+                    (
+                        ForkSegmentFile::create(16384, ForkType::VisibilityMap, 0),
+                        8192,
+                    ),
+                ])
+                .remove(0),
+                pages: vec![
+                    (ForkType::VisibilityMap, 0, vm_page),
+                    (ForkType::Main, 0, all_visible_page.clone()),
+                    (ForkType::Main, 1, all_visible_page.clone()),
+                    (ForkType::Main, 2, PageBuilder::empty().build()),
+                    (ForkType::Main, 3, all_visible_page),
+                ],
+            },
+            ForkType::VisibilityMap,
+            StubTransactions,
+            Box::new(|| Err(anyhow!("Catalog is not stubbed"))),
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        relation_viewer
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5|/16384_vm", &[GRAY, YELLOW]),
+                line("vm 1 block, 8192 bytes", &[NONE]),
+                line("F |16384_vm| 8192 bytes", &[NONE, BRIGHT_BLUE, NONE]),
+                line("Pass a block number 0..0 to decode the page", &[GRAY]),
+                line("Visibility map of 5 heap blocks, 2 bits per block, 32672 blocks per page", &[GRAY]),
+                line("all-visible 4 of 5, all-frozen 2 of 5", &[NONE]),
+                line("  heap blocks     bits", &[GRAY]),
+                line("  0..1            all-visible all-frozen", &[NONE]),
+                line("  2               all-visible", &[NONE]),
+                line("  3               |none", &[NONE, GRAY]),
+                line("  4               all-visible", &[NONE]),
+                line("E |block 4: Block 4 is not stubbed", &[NONE, RED]),
+                line("E |block 2: visibility map bit is set but PD_ALL_VISIBLE is not", &[NONE, RED]),
+                line("E |block 3: PD_ALL_VISIBLE is set but visibility map bit is not", &[NONE, RED]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn relation_handler_decodes_free_space_map() {
+        // given
+        let mut leaf_page = vec![0; 8192];
+        leaf_page[24..28].copy_from_slice(&1i32.to_le_bytes());
+        leaf_page[28 + 4095..28 + 4098].copy_from_slice(&[254, 3, 0]);
+        let relation_viewer = RelationViewer::new(
+            StubRelationFiles {
+                relation: Relation::group(vec![
+                    (ForkSegmentFile::create(16384, ForkType::Main, 0), 3 * 8192),
+                    (
+                        ForkSegmentFile::create(16384, ForkType::FreeSpaceMap, 0),
+                        3 * 8192,
+                    ),
+                ])
+                .remove(0),
+                pages: vec![
+                    (ForkType::FreeSpaceMap, 0, vec![0; 8192]),
+                    (ForkType::FreeSpaceMap, 1, vec![0; 8192]),
+                    (ForkType::FreeSpaceMap, 2, leaf_page),
+                    (
+                        ForkType::Main,
+                        0,
+                        PageBuilder::empty()
+                            .item(&heap_tuple(740, 0, 0, 0, &[]))
+                            .build(),
+                    ),
+                    (ForkType::Main, 1, PageBuilder::empty().build()),
+                ],
+            },
+            ForkType::FreeSpaceMap,
+            StubTransactions,
+            Box::new(|| Err(anyhow!("Catalog is not stubbed"))),
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        relation_viewer
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5|/16384_fsm", &[GRAY, YELLOW]),
+                line("fsm 3 blocks, 24 kB", &[NONE]),
+                line("F |16384_fsm| 24 kB", &[NONE, BRIGHT_BLUE, NONE]),
+                line("Pass a block number 0..2 to decode the page", &[GRAY]),
+                line("Free space map of 3 heap blocks, categories of 32 bytes, 4069 blocks per leaf page", &[GRAY]),
+                line("  block   level   page    next slot root", &[GRAY]),
+                line("  0       2       0       0         0", &[NONE]),
+                line("  1       1       0       0         0", &[NONE]),
+                line("  2       0       0       1         0", &[NONE]),
+                line("free space by category: recorded 8224 bytes, actual 16 kB, stale 1 of 3 blocks", &[NONE]),
+                line("  heap blocks     recorded  actual", &[GRAY]),
+                line("  0               8128      8128", &[NONE]),
+                line("  1               |96       | |8160", &[NONE, RED, NONE, RED]),
+                line("  2               0         |?", &[NONE, GRAY]),
+                line("E |block 2: Block 2 is not stubbed", &[NONE, RED]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn relation_viewer_rejects_non_numeric_block() {
+        // given
+        let relation_viewer = Box::new(RelationViewer::new(
+            StubRelationFiles {
+                relation: relation(),
+                pages: vec![],
+            },
+            ForkType::Main,
+            StubTransactions,
+            Box::new(|| Err(anyhow!("Catalog is not stubbed"))),
+        ));
+
+        // when
+        let result = relation_viewer.get_next("first");
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some("Expected block number or rows, got first".to_string())
+        );
+    }
+
+    #[test]
+    fn relation_viewer_rejects_rows_of_index() {
+        // given
+        let relation_viewer = Box::new(RelationViewer::new(
+            StubRelationFiles {
+                relation: Relation::group(vec![(
+                    ForkSegmentFile::create(16391, ForkType::Main, 0),
+                    8192,
+                )])
+                .remove(0),
+                pages: vec![],
+            },
+            ForkType::Main,
+            StubTransactions,
+            Box::new(|| Ok(catalog())),
+        ));
+
+        // when
+        let result = relation_viewer.get_next("rows");
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()),
+            Some("public.orders_pkey is an index, rows are decoded for tables only".to_string())
+        );
+    }
+}
@@ -0,0 +1,479 @@
+use std::io::Write;
+
+use anyhow::{anyhow, bail, Context};
+use colored::Colorize;
+
+use crate::{
+    common::PgOid,
+    pgdata::{
+        base::db_dir::{relation::RelationFiles, ForkType},
+        catalog::Catalog,
+        datum::{format_datum, format_datum_json, jsonb::quote_json},
+        rows::rows,
+        transactions::Transactions,
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+/// Lines of the output other than the rows and the errors of rows: the location, the relation
+/// name, the column names, the hint at the next page and the prompt following the output
+const NON_ROW_LINES: usize = 5;
+
+/// Columns are not narrowed below this width to fit the terminal
+const MIN_COL_WIDTH: usize = 4;
+
+const NULL: &str = "null";
+
+/// Param exporting all the rows as JSON instead of a page of them
+pub const JSON_PARAM: &str = "json";
+
+/// Shows the live rows of a table decoded with the columns pg_attribute describes, as many
+/// rows as fit the terminal on a page. All of them are exported as JSON by [JSON_PARAM]
+pub struct RowsViewer<T: RelationFiles, X: Transactions> {
+    files: T,
+    transactions: X,
+    catalog: Catalog,
+    relid: PgOid,
+    /// 1-based number of the page of rows
+    page: usize,
+}
+
+impl<T: RelationFiles, X: Transactions> RowsViewer<T, X> {
+    pub fn new(files: T, transactions: X, catalog: Catalog, relid: PgOid, page: usize) -> Self {
+        RowsViewer {
+            files,
+            transactions,
+            catalog,
+            relid,
+            page,
+        }
+    }
+}
+
+impl<T: RelationFiles + 'static, X: Transactions + 'static> Viewer for RowsViewer<T, X> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        if param == JSON_PARAM {
+            return Ok(Box::new(RowsJsonViewer {
+                files: self.files,
+                transactions: self.transactions,
+                catalog: self.catalog,
+                relid: self.relid,
+            }));
+        }
+        let page = param
+            .parse::<usize>()
+            .ok()
+            .filter(|page| *page > 0)
+            .with_context(|| {
+                format!("Expected page number starting at 1 or {JSON_PARAM}, got {param}")
+            })?;
+        Ok(Box::new(RowsViewer { page, ..*self }))
+    }
+
+    fn handle(&self, term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let class = self.catalog.class(self.relid).expect("class of the rows");
+        write!(
+            write,
+            "{} {}",
+            self.files
+                .fork_path(ForkType::Main)
+                .to_string_lossy()
+                .color(GRAY),
+            format!("rows, page {}", self.page).yellow()
+        )?;
+        let columns = class
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| !column.dropped)
+            .collect::<Vec<_>>();
+        write!(
+            write,
+            "\n{} {}",
+            self.catalog.qualified_name(class),
+            format!("({}, {} columns)", class.kind, columns.len()).color(GRAY)
+        )?;
+
+        // rows which cannot be decoded take a line of the page each
+        let page_rows = term_size.rows.saturating_sub(NON_ROW_LINES).max(1);
+        let mut page = rows(&self.files, class, &self.transactions)
+            .skip((self.page - 1) * page_rows)
+            .take(page_rows + 1)
+            .collect::<Vec<_>>();
+        let has_next_page = page.len() > page_rows;
+        page.truncate(page_rows);
+        let mut errors = Vec::new();
+        let rows = page
+            .into_iter()
+            .filter_map(|row| row.map_err(|err| errors.push(format!("{err:#}"))).ok())
+            .map(|row| {
+                let cells = columns
+                    .iter()
+                    .map(|(attnum, column)| {
+                        row.values[*attnum].as_deref().map(|value| {
+                            format_datum(&self.catalog, column.type_oid, column.layout, value)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                (row.ctid.to_string(), cells)
+            })
+            .collect::<Vec<_>>();
+
+        if rows.is_empty() && errors.is_empty() {
+            write!(write, "\n{}", "No rows on the page".color(GRAY))?;
+        } else if !rows.is_empty() {
+            let names = columns
+                .iter()
+                .map(|(_, column)| column.name.as_str())
+                .collect::<Vec<_>>();
+            let ctid_width = rows
+                .iter()
+                .map(|(ctid, _)| ctid.len())
+                .fold("ctid".len(), usize::max);
+            let widths = fit_widths(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(col, name)| {
+                        rows.iter()
+                            .map(|(_, cells)| cells[col].as_deref().unwrap_or(NULL))
+                            .map(|cell| escape(cell).chars().count())
+                            .fold(name.chars().count(), usize::max)
+                    })
+                    .collect(),
+                term_size.cols.saturating_sub(2 + ctid_width),
+            );
+            // the last column is not padded to keep the lines free of trailing spaces
+            let fit_col = |col: usize, value: &str| match col + 1 == widths.len() {
+                true => fit(value, widths[col]).trim_end().to_string(),
+                false => fit(value, widths[col]),
+            };
+            let header = names
+                .iter()
+                .enumerate()
+                .map(|(col, name)| fit_col(col, name))
+                .collect::<Vec<_>>();
+            write!(
+                write,
+                "\n{}",
+                format!("  {:<ctid_width$}  {}", "ctid", header.join("  ")).color(GRAY)
+            )?;
+            rows.iter().try_for_each(|(ctid, cells)| {
+                let cells = cells
+                    .iter()
+                    .enumerate()
+                    .map(|(col, cell)| match cell {
+                        Some(cell) => fit_col(col, cell),
+                        None => fit_col(col, NULL).color(GRAY).to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                let line = format!("  {:<ctid_width$}  {}", ctid, cells.join("  "));
+                write!(write, "\n{}", line.trim_end())
+            })?;
+        }
+        if has_next_page {
+            write!(
+                write,
+                "\n{}",
+                format!("Pass page number {} to see the next rows", self.page + 1).color(GRAY)
+            )?;
+        }
+        errors
+            .into_iter()
+            .try_for_each(|error| write!(write, "\nE {}", error.red()))?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Exports the live rows of a table as JSON lines, an object per row with the ctid and the
+/// values of the columns. Rows which cannot be decoded are reported by objects with an error
+pub struct RowsJsonViewer<T: RelationFiles, X: Transactions> {
+    files: T,
+    transactions: X,
+    catalog: Catalog,
+    relid: PgOid,
+}
+
+impl<T: RelationFiles + 'static, X: Transactions + 'static> Viewer for RowsJsonViewer<T, X> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let class = self.catalog.class(self.relid).expect("class of the rows");
+        rows(&self.files, class, &self.transactions).try_for_each(|row| {
+            let row = match row {
+                Ok(row) => row,
+                Err(err) => {
+                    return writeln!(write, "{{\"error\": {}}}", quote_json(&format!("{err:#}")))
+                }
+            };
+            let values = class
+                .columns
+                .iter()
+                .zip(&row.values)
+                .filter(|(column, _)| !column.dropped)
+                .map(|(column, value)| {
+                    let value = match value {
+                        Some(value) => {
+                            format_datum_json(&self.catalog, column.type_oid, column.layout, value)
+                        }
+                        None => NULL.to_string(),
+                    };
+                    format!(", {}: {}", quote_json(&column.name), value)
+                })
+                .collect::<String>();
+            writeln!(
+                write,
+                "{{\"ctid\": {}{}}}",
+                quote_json(&row.ctid.to_string()),
+                values
+            )
+        })?;
+        Ok(())
+    }
+}
+
+/// Narrows the widest columns until all of them, separated by 2 spaces, fit the available
+/// width or none can be narrowed any further
+fn fit_widths(mut widths: Vec<usize>, available: usize) -> Vec<usize> {
+    while widths.iter().map(|width| width + 2).sum::<usize>() > available {
+        match widths.iter_mut().max() {
+            Some(widest) if *widest > MIN_COL_WIDTH => *widest -= 1,
+            _ => break,
+        }
+    }
+    widths
+}
+
+/// Escapes the value to keep it on a single line, then pads or truncates it to the width
+fn fit(value: &str, width: usize) -> String {
+    let value = escape(value);
+    match value.chars().count() {
+        len if len <= width => format!("{value}{}", " ".repeat(width - len)),
+        _ => format!(
+            "{}…",
+            value
+                .chars()
+                .take(width.saturating_sub(1))
+                .collect::<String>()
+        ),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .chars()
+        .map(|char| match char {
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            char if char.is_control() => char.escape_default().to_string(),
+            char => char.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        common::PgOid,
+        pgdata::{
+            base::db_dir::{
+                relation::{test_stubs::StubRelationFiles, Relation},
+                ForkSegmentFile, ForkType,
+            },
+            catalog::test_stubs::catalog,
+            page::test_pages::{heap_tuple, with_nulls, PageBuilder},
+            transactions::test_stubs::StubTransactions,
+        },
+        test_utils::{
+            colors::{GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::RowsViewer;
+
+    fn rows_viewer() -> Box<RowsViewer<StubRelationFiles, StubTransactions>> {
+        let order = |id: u8, note: &str| {
+            let mut data = vec![id, 0, 0, 0, (note.len() as u8 + 1) << 1 | 1];
+            data.extend_from_slice(note.as_bytes());
+            heap_tuple(740, 0, 0x0802, 2, &data)
+        };
+        Box::new(RowsViewer::new(
+            StubRelationFiles {
+                relation: Relation::group(vec![(
+                    ForkSegmentFile::create(16385, ForkType::Main, 0),
+                    2 * 8192,
+                )])
+                .remove(0),
+                pages: vec![(
+                    ForkType::Main,
+                    0,
+                    PageBuilder::empty()
+                        .item(&order(1, "first"))
+                        .item(&with_nulls(heap_tuple(740, 0, 0, 2, &[2, 0, 0, 0]), &[1]))
+                        .item(&order(3, "a note\ntoo long for the terminal"))
+                        .item(&order(4, ""))
+                        .build(),
+                )],
+            },
+            StubTransactions,
+            catalog(),
+            PgOid(16385),
+            1,
+        ))
+    }
+
+    #[test]
+    fn rows_handler_fits_rows_to_terminal() {
+        // given
+        let rows_viewer = rows_viewer();
+        let mut buf = Vec::new();
+
+        // when
+        rows_viewer
+            .handle(&TermSize { rows: 8, cols: 30 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5/16385| |rows, page 1", &[GRAY, NONE, YELLOW]),
+                line("public.orders |(table, 2 columns)", &[NONE, GRAY]),
+                line("  ctid   id  note", &[GRAY]),
+                line("  (0,1)  1   first", &[NONE]),
+                line("  (0,2)  2   |null", &[NONE, GRAY]),
+                line("  (0,3)  3   a note\\ntoo long…", &[NONE]),
+                line("Pass page number 2 to see the next rows", &[GRAY]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn rows_viewer_shows_next_page() {
+        // given
+        let rows_viewer = rows_viewer().get_next("2").unwrap();
+        let mut buf = Vec::new();
+
+        // when
+        rows_viewer
+            .handle(&TermSize { rows: 8, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5/16385| |rows, page 2", &[GRAY, NONE, YELLOW]),
+                line("public.orders |(table, 2 columns)", &[NONE, GRAY]),
+                line("  ctid   id  note", &[GRAY]),
+                line("  (0,4)  4", &[NONE]),
+                line("E |Reading block 1: Block 1 is not stubbed", &[NONE, RED]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn rows_viewer_shows_errors_of_its_page_only() {
+        // given
+        let row = |id: u8| heap_tuple(740, 0, 0x0802, 2, &[id, 0, 0, 0, 0x05, b'x']);
+        let rows_viewer = Box::new(RowsViewer::new(
+            StubRelationFiles {
+                relation: Relation::group(vec![(
+                    ForkSegmentFile::create(16385, ForkType::Main, 0),
+                    2 * 8192,
+                )])
+                .remove(0),
+                pages: vec![(
+                    ForkType::Main,
+                    1,
+                    PageBuilder::empty()
+                        .item(&row(1))
+                        .item(&row(2))
+                        .item(&row(3))
+                        .build(),
+                )],
+            },
+            StubTransactions,
+            catalog(),
+            PgOid(16385),
+            1,
+        ));
+        let mut pages = Vec::new();
+
+        // when
+        rows_viewer
+            .handle(&TermSize { rows: 7, cols: 80 }, Box::new(&mut pages))
+            .unwrap();
+        rows_viewer
+            .get_next("2")
+            .unwrap()
+            .handle(&TermSize { rows: 7, cols: 80 }, Box::new(&mut pages))
+            .unwrap();
+        let output = String::from_utf8_lossy(&pages).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5/16385| |rows, page 1", &[GRAY, NONE, YELLOW]),
+                line("public.orders |(table, 2 columns)", &[NONE, GRAY]),
+                line("  ctid   id  note", &[GRAY]),
+                line("  (1,1)  1   x", &[NONE]),
+                line("Pass page number 2 to see the next rows", &[GRAY]),
+                line("E |Reading block 0: Block 0 is not stubbed", &[NONE, RED]),
+                line("/pgdata/base/5/16385| |rows, page 2", &[GRAY, NONE, YELLOW]),
+                line("public.orders |(table, 2 columns)", &[NONE, GRAY]),
+                line("  ctid   id  note", &[GRAY]),
+                line("  (1,2)  2   x", &[NONE]),
+                line("  (1,3)  3   x", &[NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn rows_json_viewer_exports_all_rows() {
+        // given
+        let rows_json_viewer = rows_viewer().get_next("json").unwrap();
+        let mut buf = Vec::new();
+
+        // when
+        rows_json_viewer
+            .handle(&TermSize { rows: 8, cols: 30 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                r#"{"ctid": "(0,1)", "id": 1, "note": "first"}"#,
+                r#"{"ctid": "(0,2)", "id": 2, "note": null}"#,
+                r#"{"ctid": "(0,3)", "id": 3, "note": "a note\ntoo long for the terminal"}"#,
+                r#"{"ctid": "(0,4)", "id": 4, "note": ""}"#,
+                r#"{"error": "Reading block 1: Block 1 is not stubbed"}"#,
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::Write;
+
+use colored::Colorize;
+
+use crate::{
+    pgdata::{
+        base::db_dir::{relation::RelationFiles, ForkType},
+        page::visibility_map::{
+            VisibilityMap, BITS_PER_HEAPBLOCK, HEAPBLOCKS_PER_PAGE, VISIBILITYMAP_ALL_FROZEN,
+            VISIBILITYMAP_ALL_VISIBLE,
+        },
+    },
+    GRAY,
+};
+
+/// Decodes the visibility map of the relation into runs of heap blocks with the same bits and
+/// cross-checks the bits with PD_ALL_VISIBLE of the main fork pages
+pub fn format_visibility_map(
+    files: &impl RelationFiles,
+    target: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let relation = files.relation();
+    let fork_blocks = |fork_type| relation.fork(fork_type).map_or(0, |fork| fork.blocks()) as u32;
+    let pages = (0..fork_blocks(ForkType::VisibilityMap))
+        .map(|block| files.page(ForkType::VisibilityMap, block))
+        .collect::<anyhow::Result<Vec<_>>>();
+    let map = match pages {
+        Ok(pages) => VisibilityMap::new(&pages),
+        Err(err) => return write!(target, "\nE {}", format!("{err:#}").red()).map_err(Into::into),
+    };
+
+    let heap_blocks = fork_blocks(ForkType::Main);
+    write!(
+        target,
+        "\n{}",
+        format!(
+            "Visibility map of {} heap blocks, {} bits per block, {} blocks per page",
+            heap_blocks, BITS_PER_HEAPBLOCK, HEAPBLOCKS_PER_PAGE
+        )
+        .color(GRAY)
+    )?;
+    let counts = map.counts(heap_blocks);
+    write!(
+        target,
+        "\nall-visible {} of {heap_blocks}, all-frozen {} of {heap_blocks}",
+        counts.all_visible, counts.all_frozen
+    )?;
+    write!(
+        target,
+        "\n{}",
+        format!("  {:<15} {}", "heap blocks", "bits").color(GRAY)
+    )?;
+    map.runs(heap_blocks)
+        .into_iter()
+        .try_for_each(|(blocks, bits)| {
+            let blocks = match blocks.start() == blocks.end() {
+                true => blocks.start().to_string(),
+                false => format!("{}..{}", blocks.start(), blocks.end()),
+            };
+            let bits = [
+                (VISIBILITYMAP_ALL_VISIBLE, "all-visible"),
+                (VISIBILITYMAP_ALL_FROZEN, "all-frozen"),
+            ]
+            .into_iter()
+            .filter(|(flag, _)| bits & flag != 0)
+            .map(|(_, name)| name)
+            .collect::<Vec<_>>();
+            match bits.is_empty() {
+                true => write!(target, "\n  {:<15} {}", blocks, "none".color(GRAY)),
+                false => write!(target, "\n  {:<15} {}", blocks, bits.join(" ")),
+            }
+        })?;
+
+    let mut errors = Vec::new();
+    let pages_all_visible = (0..heap_blocks)
+        .map(|block| {
+            let all_visible =
+                files
+                    .page(ForkType::Main, block)
+                    .and_then(|page| match page.is_new() {
+                        true => Ok(false),
+                        false => page.header().map(|header| header.is_all_visible()),
+                    });
+            all_visible
+                .map_err(|err| errors.push(format!("block {block}: {err:#}")))
+                .ok()
+        })
+        .collect::<Vec<_>>();
+    errors
+        .into_iter()
+        .chain(
+            map.check(&pages_all_visible)
+                .iter()
+                .map(|mismatch| mismatch.to_string()),
+        )
+        .try_for_each(|error| write!(target, "\nE {}", error.red()))?;
+    Ok(())
+}
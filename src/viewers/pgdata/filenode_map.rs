@@ -0,0 +1,165 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+
+use crate::{
+    pgdata::filenode_map::{mapped_catalog_name, FileNodeMap, RELMAPPER_FILEMAGIC},
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+/// Renders the relation mapper file, i.e. relfilenodes of the mapped catalogs
+pub struct FileNodeMapViewer {
+    path: PathBuf,
+    map: FileNodeMap,
+}
+
+impl FileNodeMapViewer {
+    pub fn new(path: PathBuf, map: FileNodeMap) -> Self {
+        FileNodeMapViewer { path, map }
+    }
+}
+
+impl Viewer for FileNodeMapViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.path
+                .parent()
+                .expect("directory path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(
+            write,
+            "{}",
+            format!(
+                "/{}",
+                self.path.file_name().expect("file name").to_string_lossy()
+            )
+            .yellow()
+        )?;
+
+        write!(
+            write,
+            "\n{} {:#x}, {} {}, {} {:#010x} ",
+            "magic".color(GRAY),
+            RELMAPPER_FILEMAGIC,
+            "mappings".color(GRAY),
+            self.map.mappings.len(),
+            "crc".color(GRAY),
+            self.map.crc
+        )?;
+        match self.map.is_crc_valid() {
+            true => write!(write, "{}", "valid".green())?,
+            false => write!(
+                write,
+                "{}",
+                format!("mismatch, computed {:#010x}", self.map.computed_crc).red()
+            )?,
+        }
+
+        write!(
+            write,
+            "\n{}",
+            format!("{:>10} {:>11} catalog", "oid", "relfilenode").color(GRAY)
+        )?;
+        self.map.mappings.iter().try_for_each(|mapping| {
+            write!(
+                write,
+                "\n{:>10} {:>11} {}",
+                mapping.oid.0,
+                mapping.relfilenode.0.to_string().bright_blue(),
+                mapped_catalog_name(mapping.oid).unwrap_or_default()
+            )
+        })?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::filenode_map::{test_files::filenode_map, FileNodeMap},
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, GREEN, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::FileNodeMapViewer;
+
+    const TERM_SIZE: TermSize = TermSize { rows: 20, cols: 80 };
+
+    #[test]
+    fn filenode_map_handler_renders_mappings() {
+        // given
+        let bytes = filenode_map(&[(1259, 1259), (1249, 16401), (9999, 9999)]);
+        let viewer = FileNodeMapViewer::new(
+            PathBuf::from("/pgdata/base/5/pg_filenode.map"),
+            FileNodeMap::parse(&bytes).unwrap(),
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer.handle(&TERM_SIZE, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        let crc = format!("{:#010x}", FileNodeMap::parse(&bytes).unwrap().crc);
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5|/pg_filenode.map", &[GRAY, YELLOW]),
+                line(&format!("magic| 0x592717, |mappings| 3, |crc| {crc} |valid"), &[GRAY, NONE, GRAY, NONE, GRAY, NONE, GREEN]),
+                line("       oid relfilenode catalog", &[GRAY]),
+                line("      1259 |       1259| pg_class", &[NONE, BRIGHT_BLUE, NONE]),
+                line("      1249 |      16401| pg_attribute", &[NONE, BRIGHT_BLUE, NONE]),
+                line("      9999 |       9999| ", &[NONE, BRIGHT_BLUE, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn filenode_map_handler_reports_crc_mismatch() {
+        // given
+        let mut bytes = filenode_map(&[]);
+        bytes[504..508].copy_from_slice(&[1, 0, 0, 0]);
+        let map = FileNodeMap::parse(&bytes).unwrap();
+        let computed_crc = format!("{:#010x}", map.computed_crc);
+        let viewer = FileNodeMapViewer::new(PathBuf::from("/pgdata/global/pg_filenode.map"), map);
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer.handle(&TERM_SIZE, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output.lines().nth(1),
+            Some(
+                line(
+                    &format!("magic| 0x592717, |mappings| 0, |crc| 0x00000001 |mismatch, computed {computed_crc}"),
+                    &[GRAY, NONE, GRAY, NONE, GRAY, NONE, RED]
+                )
+                .as_str()
+            )
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::io::Write;
+
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+
+use crate::{
+    pgdata::{
+        filenode_map::FILENODE_MAP_FILE_NAME,
+        global::{pg_control::PG_CONTROL_FILE_NAME, Global},
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+use self::pg_control::ControlFileViewer;
+
+use super::filenode_map::FileNodeMapViewer;
+
+mod pg_control;
+
+pub struct GlobalViewer<T: Global> {
+    global: T,
+}
+
+impl<T: Global> GlobalViewer<T> {
+    pub fn new(global: T) -> Self {
+        GlobalViewer { global }
+    }
+}
+
+impl<T: Global> Viewer for GlobalViewer<T> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        match param {
+            FILENODE_MAP_FILE_NAME => Ok(Box::new(FileNodeMapViewer::new(
+                self.global.path().join(FILENODE_MAP_FILE_NAME),
+                self.global.filenode_map()?,
+            ))),
+            PG_CONTROL_FILE_NAME => Ok(Box::new(ControlFileViewer::new(
+                self.global.path().join(PG_CONTROL_FILE_NAME),
+                self.global.control_file()?,
+            ))),
+            _ => bail!("{param} not supported"),
+        }
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let path = self.global.path();
+        write!(
+            write,
+            "{}",
+            path.parent()
+                .expect("pgdata path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(write, "{}", "/global".yellow())?;
+        write!(
+            write,
+            "\nCluster-wide tables, such as {}",
+            "pg_database".color(GRAY)
+        )?;
+        write!(
+            write,
+            "\nF {} Cluster-wide control data",
+            PG_CONTROL_FILE_NAME.bright_blue()
+        )?;
+        write!(
+            write,
+            "\nF {} Mapping of shared catalog OIDs to their relfilenodes",
+            FILENODE_MAP_FILE_NAME.bright_blue()
+        )?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::global::test_stubs::StubGlobal,
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, NONE, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::GlobalViewer;
+
+    #[test]
+    fn global_handler_renders_known_files() {
+        // given
+        let viewer = GlobalViewer::new(StubGlobal {});
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .handle(&TermSize { rows: 20, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata|/global", &[GRAY, YELLOW]),
+                line("Cluster-wide tables, such as |pg_database", &[NONE, GRAY]),
+                line("F |pg_control| Cluster-wide control data", &[NONE, BRIGHT_BLUE, NONE]),
+                line("F |pg_filenode.map| Mapping of shared catalog OIDs to their relfilenodes", &[NONE, BRIGHT_BLUE, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+}
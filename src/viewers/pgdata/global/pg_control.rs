@@ -0,0 +1,254 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+
+use crate::{
+    common::time::format_unix_time,
+    pgdata::{
+        global::pg_control::{ControlFile, FLOATFORMAT_VALUE},
+        pg_wal::wal_file_name,
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+/// Renders pg_control the same way pg_controldata does. Times are shown in UTC, as pg_controldata
+/// shows them when run with `TZ=UTC`, rather than in the local time zone
+pub struct ControlFileViewer {
+    path: PathBuf,
+    control_file: ControlFile,
+}
+
+impl ControlFileViewer {
+    pub fn new(path: PathBuf, control_file: ControlFile) -> Self {
+        ControlFileViewer { path, control_file }
+    }
+}
+
+impl Viewer for ControlFileViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.path
+                .parent()
+                .expect("global path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(write, "{}", "/pg_control".yellow())?;
+
+        let control_file = &self.control_file;
+        if !control_file.is_crc_valid() {
+            write!(
+                write,
+                "\n{}",
+                format!(
+                    "WARNING: Calculated CRC checksum {:#010x} does not match value stored in file {:#010x}",
+                    control_file.computed_crc, control_file.crc
+                )
+                .red()
+            )?;
+        }
+        if control_file.float_format != FLOATFORMAT_VALUE {
+            write!(
+                write,
+                "\n{}",
+                "WARNING: possible byte ordering mismatch, the values below are likely invalid"
+                    .red()
+            )?;
+        }
+
+        fields(control_file).iter().try_for_each(|(label, value)| {
+            write!(write, "\n{}{}", format!("{:<38}", label).color(GRAY), value)
+        })?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+#[rustfmt::skip]
+fn fields(control_file: &ControlFile) -> Vec<(&'static str, String)> {
+    let checkpoint = &control_file.checkpoint_copy;
+    let on_off = |value: bool| if value { "on" } else { "off" }.to_string();
+    let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+    vec![
+        ("pg_control version number:", control_file.pg_control_version.to_string()),
+        ("Catalog version number:", control_file.catalog_version_no.to_string()),
+        ("Database system identifier:", control_file.system_identifier.to_string()),
+        ("Database cluster state:", control_file.state.to_string()),
+        ("pg_control last modified:", format_unix_time(control_file.time)),
+        ("Latest checkpoint location:", control_file.checkpoint.to_string()),
+        ("Latest checkpoint's REDO location:", checkpoint.redo.to_string()),
+        ("Latest checkpoint's REDO WAL file:", wal_file_name(checkpoint.this_timeline_id, checkpoint.redo, control_file.xlog_seg_size)),
+        ("Latest checkpoint's TimeLineID:", checkpoint.this_timeline_id.to_string()),
+        ("Latest checkpoint's PrevTimeLineID:", checkpoint.prev_timeline_id.to_string()),
+        ("Latest checkpoint's full_page_writes:", on_off(checkpoint.full_page_writes)),
+        ("Latest checkpoint's NextXID:", format!("{}:{}", checkpoint.next_xid_epoch(), checkpoint.next_xid_xid())),
+        ("Latest checkpoint's NextOID:", checkpoint.next_oid.to_string()),
+        ("Latest checkpoint's NextMultiXactId:", checkpoint.next_multi.to_string()),
+        ("Latest checkpoint's NextMultiOffset:", checkpoint.next_multi_offset.to_string()),
+        ("Latest checkpoint's oldestXID:", checkpoint.oldest_xid.to_string()),
+        ("Latest checkpoint's oldestXID's DB:", checkpoint.oldest_xid_db.to_string()),
+        ("Latest checkpoint's oldestActiveXID:", checkpoint.oldest_active_xid.to_string()),
+        ("Latest checkpoint's oldestMultiXid:", checkpoint.oldest_multi.to_string()),
+        ("Latest checkpoint's oldestMulti's DB:", checkpoint.oldest_multi_db.to_string()),
+        ("Latest checkpoint's oldestCommitTsXid:", checkpoint.oldest_commit_ts_xid.to_string()),
+        ("Latest checkpoint's newestCommitTsXid:", checkpoint.newest_commit_ts_xid.to_string()),
+        ("Time of latest checkpoint:", format_unix_time(checkpoint.time)),
+        ("Fake LSN counter for unlogged rels:", control_file.unlogged_lsn.to_string()),
+        ("Minimum recovery ending location:", control_file.min_recovery_point.to_string()),
+        ("Min recovery ending loc's timeline:", control_file.min_recovery_point_tli.to_string()),
+        ("Backup start location:", control_file.backup_start_point.to_string()),
+        ("Backup end location:", control_file.backup_end_point.to_string()),
+        ("End-of-backup record required:", yes_no(control_file.backup_end_required)),
+        ("wal_level setting:", control_file.wal_level.to_string()),
+        ("wal_log_hints setting:", on_off(control_file.wal_log_hints)),
+        ("max_connections setting:", control_file.max_connections.to_string()),
+        ("max_worker_processes setting:", control_file.max_worker_processes.to_string()),
+        ("max_wal_senders setting:", control_file.max_wal_senders.to_string()),
+        ("max_prepared_xacts setting:", control_file.max_prepared_xacts.to_string()),
+        ("max_locks_per_xact setting:", control_file.max_locks_per_xact.to_string()),
+        ("track_commit_timestamp setting:", on_off(control_file.track_commit_timestamp)),
+        ("Maximum data alignment:", control_file.max_align.to_string()),
+        ("Database block size:", control_file.blcksz.to_string()),
+        ("Blocks per segment of large relation:", control_file.relseg_size.to_string()),
+        ("WAL block size:", control_file.xlog_blcksz.to_string()),
+        ("Bytes per WAL segment:", control_file.xlog_seg_size.to_string()),
+        ("Maximum length of identifiers:", control_file.name_data_len.to_string()),
+        ("Maximum columns in an index:", control_file.index_max_keys.to_string()),
+        ("Maximum size of a TOAST chunk:", control_file.toast_max_chunk_size.to_string()),
+        ("Size of a large-object chunk:", control_file.loblksize.to_string()),
+        ("Date/time type storage:", "64-bit integers".to_string()),
+        ("Float8 argument passing:", if control_file.float8_by_val { "by value" } else { "by reference" }.to_string()),
+        ("Data page checksum version:", control_file.data_checksum_version.to_string()),
+        ("Mock authentication nonce:", control_file.mock_authentication_nonce.iter().map(|byte| format!("{byte:02x}")).collect()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::global::pg_control::{test_files::pg_control, ControlFile},
+        test_utils::{
+            colors::{GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::ControlFileViewer;
+
+    const TERM_SIZE: TermSize = TermSize { rows: 20, cols: 80 };
+
+    fn render(bytes: &[u8]) -> String {
+        let viewer = ControlFileViewer::new(
+            PathBuf::from("/pgdata/global/pg_control"),
+            ControlFile::parse(bytes).unwrap(),
+        );
+        let mut buf = Vec::new();
+        viewer.handle(&TERM_SIZE, Box::new(&mut buf)).unwrap();
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    #[test]
+    fn control_file_handler_renders_control_data() {
+        // given
+        let bytes = pg_control();
+
+        // when
+        let output = render(&bytes);
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/global|/pg_control", &[GRAY, YELLOW]),
+                line("pg_control version number:            |1300", &[GRAY, NONE]),
+                line("Catalog version number:               |202209061", &[GRAY, NONE]),
+                line("Database system identifier:           |7697690184480792812", &[GRAY, NONE]),
+                line("Database cluster state:               |shut down", &[GRAY, NONE]),
+                line("pg_control last modified:             |Sat Oct 17 17:29:45 2026", &[GRAY, NONE]),
+                line("Latest checkpoint location:           |0/1B7B110", &[GRAY, NONE]),
+                line("Latest checkpoint's REDO location:    |0/1B7B110", &[GRAY, NONE]),
+                line("Latest checkpoint's REDO WAL file:    |000000010000000000000001", &[GRAY, NONE]),
+                line("Latest checkpoint's TimeLineID:       |1", &[GRAY, NONE]),
+                line("Latest checkpoint's PrevTimeLineID:   |1", &[GRAY, NONE]),
+                line("Latest checkpoint's full_page_writes: |on", &[GRAY, NONE]),
+                line("Latest checkpoint's NextXID:          |0:740", &[GRAY, NONE]),
+                line("Latest checkpoint's NextOID:          |16420", &[GRAY, NONE]),
+                line("Latest checkpoint's NextMultiXactId:  |1", &[GRAY, NONE]),
+                line("Latest checkpoint's NextMultiOffset:  |0", &[GRAY, NONE]),
+                line("Latest checkpoint's oldestXID:        |716", &[GRAY, NONE]),
+                line("Latest checkpoint's oldestXID's DB:   |1", &[GRAY, NONE]),
+                line("Latest checkpoint's oldestActiveXID:  |0", &[GRAY, NONE]),
+                line("Latest checkpoint's oldestMultiXid:   |1", &[GRAY, NONE]),
+                line("Latest checkpoint's oldestMulti's DB: |1", &[GRAY, NONE]),
+                line("Latest checkpoint's oldestCommitTsXid:|724", &[GRAY, NONE]),
+                line("Latest checkpoint's newestCommitTsXid:|739", &[GRAY, NONE]),
+                line("Time of latest checkpoint:            |Sat Oct 17 17:29:45 2026", &[GRAY, NONE]),
+                line("Fake LSN counter for unlogged rels:   |0/3E8", &[GRAY, NONE]),
+                line("Minimum recovery ending location:     |0/0", &[GRAY, NONE]),
+                line("Min recovery ending loc's timeline:   |0", &[GRAY, NONE]),
+                line("Backup start location:                |0/0", &[GRAY, NONE]),
+                line("Backup end location:                  |0/0", &[GRAY, NONE]),
+                line("End-of-backup record required:        |no", &[GRAY, NONE]),
+                line("wal_level setting:                    |replica", &[GRAY, NONE]),
+                line("wal_log_hints setting:                |off", &[GRAY, NONE]),
+                line("max_connections setting:              |100", &[GRAY, NONE]),
+                line("max_worker_processes setting:         |8", &[GRAY, NONE]),
+                line("max_wal_senders setting:              |10", &[GRAY, NONE]),
+                line("max_prepared_xacts setting:           |0", &[GRAY, NONE]),
+                line("max_locks_per_xact setting:           |64", &[GRAY, NONE]),
+                line("track_commit_timestamp setting:       |on", &[GRAY, NONE]),
+                line("Maximum data alignment:               |8", &[GRAY, NONE]),
+                line("Database block size:                  |8192", &[GRAY, NONE]),
+                line("Blocks per segment of large relation: |131072", &[GRAY, NONE]),
+                line("WAL block size:                       |8192", &[GRAY, NONE]),
+                line("Bytes per WAL segment:                |16777216", &[GRAY, NONE]),
+                line("Maximum length of identifiers:        |64", &[GRAY, NONE]),
+                line("Maximum columns in an index:          |32", &[GRAY, NONE]),
+                line("Maximum size of a TOAST chunk:        |1996", &[GRAY, NONE]),
+                line("Size of a large-object chunk:         |2048", &[GRAY, NONE]),
+                line("Date/time type storage:               |64-bit integers", &[GRAY, NONE]),
+                line("Float8 argument passing:              |by value", &[GRAY, NONE]),
+                line("Data page checksum version:           |1", &[GRAY, NONE]),
+                line(&format!("Mock authentication nonce:            |{}", "ab".repeat(32)), &[GRAY, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn control_file_handler_warns_about_crc_mismatch() {
+        // given
+        let mut bytes = pg_control();
+        bytes[288..292].copy_from_slice(&[1, 0, 0, 0]);
+        let computed_crc = ControlFile::parse(&bytes).unwrap().computed_crc;
+
+        // when
+        let output = render(&bytes);
+
+        // then
+        assert_eq!(
+            output.lines().nth(1),
+            Some(
+                line(
+                    &format!("WARNING: Calculated CRC checksum {computed_crc:#010x} does not match value stored in file 0x00000001"),
+                    &[RED]
+                )
+                .as_str()
+            )
+        );
+    }
+}
@@ -0,0 +1,618 @@
+use std::io::Write;
+
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+
+use crate::{
+    common::time::format_timestamptz_compact,
+    pgdata::{
+        base::db_dir::ForkType,
+        page::{
+            heap::{HeapTuple, HeapTupleHeader, HEAP_XMAX_IS_MULTI},
+            LinePointer, LinePointerState, Page, PageHeader,
+        },
+        pg_commit_ts::PGCommitTS,
+        pg_multiexact::PGMultixact,
+        pg_subtrans::parent_chain,
+        pg_xact::{PGXact, XactStatus},
+        transactions::Transactions,
+        BLCKSZ,
+    },
+    viewers::{wrap_words, TermSize, Viewer},
+    GRAY,
+};
+
+/// Width of the line pointer and tuple columns preceding the flags column
+const FLAGS_COL_OFFSET: usize = 77;
+
+/// Narrowest flags column worth laying out next to the other columns
+const MIN_FLAGS_COL_WIDTH: usize = 20;
+
+/// Exports the page as a raw block file instead of decoding it
+const RAW_PARAM: &str = "raw";
+
+/// Leading bytes of an item shown for pages which do not hold heap tuples
+const ITEM_SUMMARY_BYTES: usize = 16;
+
+/// Renders a decoded page: its header, line pointers and heap tuple headers annotated with
+/// the commit status of their xmin and xmax. Items of index, free space map and visibility map
+/// pages are not heap tuples, their leading bytes are shown instead
+pub struct PageViewer<X: Transactions> {
+    location: String,
+    fork_type: ForkType,
+    block_number: u32,
+    page: Page,
+    transactions: X,
+}
+
+impl<X: Transactions> PageViewer<X> {
+    pub fn new(
+        location: String,
+        fork_type: ForkType,
+        block_number: u32,
+        page: Page,
+        transactions: X,
+    ) -> Self {
+        PageViewer {
+            location,
+            fork_type,
+            block_number,
+            page,
+            transactions,
+        }
+    }
+}
+
+impl<X: Transactions> Viewer for PageViewer<X> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        match param {
+            RAW_PARAM => Ok(Box::new(RawPageViewer { page: self.page })),
+            _ => bail!("{param} not supported"),
+        }
+    }
+
+    fn handle(&self, term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{} {}",
+            self.location.color(GRAY),
+            format!("block {}", self.block_number).yellow()
+        )?;
+
+        if self.page.is_new() {
+            writeln!(write, "\n{}", "Page is new (not initialized)".color(GRAY))?;
+            return Ok(());
+        }
+
+        let header = self.page.header()?;
+        format_header(&header, &mut write)?;
+
+        // heap pages have no special space, index pages keep their opaque data there
+        if !matches!(self.fork_type, ForkType::Main | ForkType::Init)
+            || header.special as u64 != BLCKSZ
+        {
+            writeln!(
+                write,
+                "\n{}",
+                format!("{:>5} {:<11} {:>5} {:>5} item", "lp", "state", "off", "len").color(GRAY)
+            )?;
+            return self
+                .page
+                .line_pointers()?
+                .iter()
+                .try_for_each(|line_pointer| {
+                    format_line_pointer(&self.page, line_pointer, &mut write, |item, target| {
+                        write!(target, "{}", format_item_summary(item).color(GRAY))
+                            .map_err(|err| anyhow!(err))
+                    })
+                });
+        }
+
+        writeln!(
+            write,
+            "\n{}",
+            format!(
+                "{:>5} {:<11} {:>5} {:>5} {:>10} {:>10} {:>5} {:<12} {:>5} {:>4} flags",
+                "lp", "state", "off", "len", "xmin", "xmax", "cid", "ctid", "natts", "hoff"
+            )
+            .color(GRAY)
+        )?;
+        self.page
+            .line_pointers()?
+            .iter()
+            .try_for_each(|line_pointer| {
+                format_line_pointer(&self.page, line_pointer, &mut write, |item, target| {
+                    match HeapTuple::parse(item) {
+                        Ok(tuple) => {
+                            format_heap_tuple(&tuple, &self.transactions, term_size, target)
+                        }
+                        Err(err) => {
+                            write!(target, "{}", err.to_string().red()).map_err(|err| anyhow!(err))
+                        }
+                    }
+                })
+            })
+    }
+}
+
+/// Writes the page as is, to be redirected to a file, e.g. `base 5 16384 0 raw > 16384.0`
+pub struct RawPageViewer {
+    page: Page,
+}
+
+impl Viewer for RawPageViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write
+            .write_all(self.page.bytes())
+            .map_err(|err| anyhow!(err))
+    }
+}
+
+fn format_header(header: &PageHeader, target: &mut dyn Write) -> anyhow::Result<()> {
+    let flags = match header.flag_names().join(" ") {
+        names if names.is_empty() => format!("{:#06x}", header.flags),
+        names => format!("{:#06x} {}", header.flags, names),
+    };
+    let fields = [
+        ("pd_lsn", header.lsn.to_string()),
+        ("pd_checksum", format!("{:#06x}", header.checksum)),
+        ("pd_flags", flags),
+        ("pd_lower", header.lower.to_string()),
+        ("pd_upper", header.upper.to_string()),
+        ("pd_special", header.special.to_string()),
+        ("pagesize", header.page_size.to_string()),
+        ("version", header.layout_version.to_string()),
+        ("pd_prune_xid", header.prune_xid.to_string()),
+        ("free space", format!("{} bytes", header.free_space())),
+    ];
+    fields
+        .iter()
+        .try_for_each(|(name, value)| write!(target, "\n{:<12} {}", name.color(GRAY), value))?;
+    Ok(())
+}
+
+/// Writes the state, the offset and the length of the line pointer, the item of a normal one is
+/// rendered by `format_item`
+fn format_line_pointer(
+    page: &Page,
+    line_pointer: &LinePointer,
+    target: &mut dyn Write,
+    format_item: impl Fn(&[u8], &mut dyn Write) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let LinePointer {
+        number,
+        offset,
+        state,
+        length,
+    } = line_pointer;
+    let state_col = format!("{:<11}", state.to_string());
+    write!(target, "{:>5} ", number)?;
+    match state {
+        LinePointerState::Unused => write!(target, "{}", state_col.trim_end().color(GRAY))?,
+        LinePointerState::Dead => write!(target, "{}", state_col.trim_end().red())?,
+        LinePointerState::Redirect => write!(target, "{} -> {offset}", state_col.cyan())?,
+        LinePointerState::Normal => {
+            write!(target, "{} {:>5} {:>5} ", state_col, offset, length)?;
+            match page.item(line_pointer) {
+                Ok(item) => format_item(item, target)?,
+                Err(err) => write!(target, "{}", err.to_string().red())?,
+            }
+        }
+    }
+    writeln!(target).map_err(|err| anyhow!(err))
+}
+
+/// Shows the leading bytes of an item in hex, e.g. `0100000005610000 (24 bytes)`
+fn format_item_summary(item: &[u8]) -> String {
+    let hex = item
+        .iter()
+        .take(ITEM_SUMMARY_BYTES)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    match item.len() > ITEM_SUMMARY_BYTES {
+        true => format!("{hex}.. ({} bytes)", item.len()),
+        false => hex,
+    }
+}
+
+fn format_heap_tuple(
+    tuple: &HeapTuple,
+    transactions: &impl Transactions,
+    term_size: &TermSize,
+    target: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let header = &tuple.header;
+    let cid = match (header.cid(), header.xvac()) {
+        (Some(cid), _) => cid.to_string(),
+        (None, Some(xvac)) => format!("xvac {xvac}"),
+        (None, None) => String::new(),
+    };
+    write!(
+        target,
+        "{:>10} {:>10} {:>5} {:<12} {:>5} {:>4} ",
+        header.xmin,
+        header.xmax,
+        cid,
+        header.ctid.to_string(),
+        header.natts(),
+        header.hoff
+    )?;
+
+    let null_bitmap = header
+        .null_bitmap_string()
+        .map(|bits| format!("t_bits={bits}"));
+    let flags = xact_statuses(header, transactions)
+        .into_iter()
+        .chain(null_bitmap)
+        .chain(header.infomask_flags().into_iter().map(String::from))
+        .chain(header.infomask2_flags().into_iter().map(String::from))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // flags are wrapped to fit the terminal if there is enough room next to the other columns,
+    // otherwise they go to the following lines
+    let (flags_offset, flags_width) = match term_size.cols.saturating_sub(FLAGS_COL_OFFSET) {
+        width if width >= MIN_FLAGS_COL_WIDTH => (FLAGS_COL_OFFSET, width),
+        _ => (6, term_size.cols.saturating_sub(6).max(MIN_FLAGS_COL_WIDTH)),
+    };
+    wrap_words(&flags, flags_width)
+        .iter()
+        .enumerate()
+        .try_for_each(|(i, line)| {
+            if i > 0 || flags_offset != FLAGS_COL_OFFSET {
+                write!(target, "\n{: <1$}", "", flags_offset)?;
+            }
+            write!(target, "{}", line.color(GRAY))
+        })?;
+    Ok(())
+}
+
+/// Looks the commit status of xmin and of xmax up in pg_xact, xmax is skipped when it is not set.
+/// The status of a committed subtransaction is followed by its parents up to the top-level
+/// transaction and the status of the latter, e.g. `SUB_COMMITTED->741->740:COMMITTED`. The commit
+/// time from pg_commit_ts follows when one is recorded, e.g. `COMMITTED@2026-10-17T17:29:45Z`. A
+/// multixact xmax is replaced by its members with their lock modes, e.g. `MULTI[740:ForShare]`
+fn xact_statuses(header: &HeapTupleHeader, transactions: &impl Transactions) -> Vec<String> {
+    let xact_status = |xid: u32| match transactions.pg_xact().status(xid) {
+        Ok(XactStatus::SubCommitted) => format!(
+            "{}->{}",
+            XactStatus::SubCommitted,
+            describe_parents(xid, transactions).unwrap_or_else(|| "?".to_string())
+        ),
+        Ok(status) => status.to_string(),
+        Err(_) => "?".to_string(),
+    };
+    let status = |xid: u32| match transactions.pg_commit_ts().commit_ts(xid) {
+        Ok(Some(commit_ts)) => format!(
+            "{}@{}",
+            xact_status(xid),
+            format_timestamptz_compact(commit_ts.time)
+        ),
+        _ => xact_status(xid),
+    };
+    let mut statuses = vec![format!("xmin={}", status(header.xmin))];
+    if header.infomask & HEAP_XMAX_IS_MULTI != 0 {
+        statuses.push(format!(
+            "xmax={}",
+            describe_members(header.xmax, transactions)
+        ));
+    } else if header.xmax != 0 {
+        statuses.push(format!("xmax={}", status(header.xmax)));
+    }
+    statuses
+}
+
+/// Lists the members of a multixact with their lock modes
+fn describe_members(multi: u32, transactions: &impl Transactions) -> String {
+    let members = transactions.pg_multixact().members(multi).map_or_else(
+        |_| "?".to_string(),
+        |members| {
+            members
+                .iter()
+                .map(|member| format!("{}:{}", member.xid, member.status))
+                .collect::<Vec<_>>()
+                .join(",")
+        },
+    );
+    format!("MULTI[{members}]")
+}
+
+/// Lists the parents of a subtransaction and the status of its top-level transaction, None when
+/// pg_subtrans does not know the parent, e.g. after a restart
+fn describe_parents(xid: u32, transactions: &impl Transactions) -> Option<String> {
+    let chain = parent_chain(transactions.pg_subtrans(), xid).ok()?;
+    let top_level_status = transactions
+        .pg_xact()
+        .status(*chain.last()?)
+        .map_or_else(|_| "?".to_string(), |status| status.to_string());
+    Some(format!(
+        "{}:{}",
+        chain
+            .iter()
+            .map(|xid| xid.to_string())
+            .collect::<Vec<_>>()
+            .join("->"),
+        top_level_status
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{
+        pgdata::base::db_dir::ForkType,
+        pgdata::page::{
+            test_pages::{heap_tuple, PageBuilder},
+            Page,
+        },
+        pgdata::transactions::test_stubs::StubTransactions,
+        test_utils::{
+            colors::{CYAN, GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::PageViewer;
+
+    fn page() -> Page {
+        let mut tuple = heap_tuple(740, 0, 0x0001 | 0x0100 | 0x0800, 3, &[]);
+        tuple[23] = 0b101;
+        tuple.extend_from_slice(&[0; 8]);
+        tuple[22] = 32;
+        Page::new(
+            PageBuilder::empty()
+                .flags(0x0004)
+                .item(&tuple)
+                .line_pointer(1, 2, 0)
+                .line_pointer(0, 3, 0)
+                .line_pointer(0, 0, 0)
+                .build(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn page_handler_renders_page() {
+        // given
+        let page_viewer = PageViewer::new(
+            "/pgdata/base/5/16384".to_string(),
+            ForkType::Main,
+            0,
+            page(),
+            StubTransactions,
+        );
+
+        let term_size = TermSize {
+            rows: 100,
+            cols: 160,
+        };
+
+        let mut buf = Vec::new();
+
+        // when
+        page_viewer.handle(&term_size, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5/16384| |block 0", &[GRAY, NONE, YELLOW]),
+                line("pd_lsn      | 0/17BB028", &[GRAY, NONE]),
+                line("pd_checksum | 0x0000", &[GRAY, NONE]),
+                line("pd_flags    | 0x0004 PD_ALL_VISIBLE", &[GRAY, NONE]),
+                line("pd_lower    | 40", &[GRAY, NONE]),
+                line("pd_upper    | 8160", &[GRAY, NONE]),
+                line("pd_special  | 8192", &[GRAY, NONE]),
+                line("pagesize    | 8192", &[GRAY, NONE]),
+                line("version     | 4", &[GRAY, NONE]),
+                line("pd_prune_xid| 0", &[GRAY, NONE]),
+                line("free space  | 8120 bytes", &[GRAY, NONE]),
+                line("   lp state         off   len       xmin       xmax   cid ctid         natts hoff flags", &[GRAY]),
+                line("    1 LP_NORMAL    8160    32        740          0     0 (1,1)            3   32 |xmin=COMMITTED@2026-10-17T17:29:45.123456Z t_bits=101 HEAP_HASNULL", &[NONE, GRAY]),
+                line("                                                                             |HEAP_XMIN_COMMITTED HEAP_XMAX_INVALID", &[NONE, GRAY]),
+                line("    2 |LP_REDIRECT| -> 1", &[NONE, CYAN, NONE]),
+                line("    3 |LP_DEAD", &[NONE, RED]),
+                line("    4 |LP_UNUSED", &[NONE, GRAY]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn page_handler_wraps_flags_on_narrow_terminal() {
+        // given
+        let page_viewer = PageViewer::new(
+            "/pgdata/base/5/16384".to_string(),
+            ForkType::Main,
+            0,
+            page(),
+            StubTransactions,
+        );
+
+        let term_size = TermSize {
+            rows: 100,
+            cols: 80,
+        };
+
+        let mut buf = Vec::new();
+
+        // when
+        page_viewer.handle(&term_size, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output.lines().skip(12).take(4).collect::<Vec<_>>(),
+            vec![
+                "    1 LP_NORMAL    8160    32        740          0     0 (1,1)            3   32 ",
+                &line("      |xmin=COMMITTED@2026-10-17T17:29:45.123456Z t_bits=101 HEAP_HASNULL", &[NONE, GRAY]),
+                &line("      |HEAP_XMIN_COMMITTED HEAP_XMAX_INVALID", &[NONE, GRAY]),
+                &line("    2 |LP_REDIRECT| -> 1", &[NONE, CYAN, NONE]),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_handler_annotates_xact_statuses() {
+        // given
+        let page = Page::new(
+            PageBuilder::empty()
+                .item(&heap_tuple(742, 743, 0, 0, &[]))
+                .item(&heap_tuple(5000, 7, 0x1000, 0, &[]))
+                .item(&heap_tuple(740, 8, 0x1000, 0, &[]))
+                .build(),
+        )
+        .unwrap();
+        let page_viewer = PageViewer::new(
+            "/pgdata/base/5/16384".to_string(),
+            ForkType::Main,
+            0,
+            page,
+            StubTransactions,
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        page_viewer
+            .handle(
+                &TermSize {
+                    rows: 100,
+                    cols: 160,
+                },
+                Box::new(&mut buf),
+            )
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output.lines().skip(12).collect::<Vec<_>>(),
+            vec![
+                &line("    1 LP_NORMAL    8168    24        742        743     0 (1,1)            0   24 |xmin=IN_PROGRESS xmax=SUB_COMMITTED->741->740:COMMITTED@2026-10-17T17:29:45.123456Z", &[NONE, GRAY]),
+                &line("    2 LP_NORMAL    8144    24       5000          7     0 (1,1)            0   24 |xmin=? xmax=MULTI[740:ForKeyShare,742:NoKeyUpdate] HEAP_XMAX_IS_MULTI", &[NONE, GRAY]),
+                &line("    3 LP_NORMAL    8120    24        740          8     0 (1,1)            0   24 |xmin=COMMITTED@2026-10-17T17:29:45.123456Z xmax=MULTI[?] HEAP_XMAX_IS_MULTI", &[NONE, GRAY]),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::index_page(ForkType::Main, 16)]
+    #[case::visibility_map_page(ForkType::VisibilityMap, 0)]
+    fn page_handler_summarizes_items_of_non_heap_pages(
+        #[case] fork_type: ForkType,
+        #[case] special: usize,
+    ) {
+        // given
+        let page = Page::new(
+            PageBuilder::empty()
+                .special(special)
+                .item(&[1, 0, 2, 0, 0x10, 0, 0x10, 0, 7, 0, 0, 0, 0, 0, 0, 0])
+                .item(&(0..24).collect::<Vec<u8>>())
+                .line_pointer(0, 3, 0)
+                .build(),
+        )
+        .unwrap();
+        let page_viewer = PageViewer::new(
+            "/pgdata/base/5/16390".to_string(),
+            fork_type,
+            1,
+            page,
+            StubTransactions,
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        page_viewer
+            .handle(
+                &TermSize {
+                    rows: 100,
+                    cols: 160,
+                },
+                Box::new(&mut buf),
+            )
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        let offset = |item: usize| 8192 - special - 16 - 24 * (item - 1);
+        #[rustfmt::skip]
+        assert_eq!(
+            output.lines().skip(11).collect::<Vec<_>>(),
+            vec![
+                &line("   lp state         off   len item", &[GRAY]),
+                &line(&format!("    1 LP_NORMAL    {:>4}    16 |01000200100010000700000000000000", offset(1)), &[NONE, GRAY]),
+                &line(&format!("    2 LP_NORMAL    {:>4}    24 |000102030405060708090a0b0c0d0e0f.. (24 bytes)", offset(2)), &[NONE, GRAY]),
+                &line("    3 |LP_DEAD", &[NONE, RED]),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_handler_exports_raw_page() {
+        // given
+        let page_viewer = Box::new(PageViewer::new(
+            "/pgdata/base/5/16384".to_string(),
+            ForkType::Main,
+            0,
+            page(),
+            StubTransactions,
+        ));
+
+        let mut buf = Vec::new();
+
+        // when
+        page_viewer
+            .get_next("raw")
+            .unwrap()
+            .handle(&TermSize { rows: 10, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+
+        // then
+        assert_eq!(buf, page().bytes());
+    }
+
+    #[test]
+    fn page_handler_renders_new_page() {
+        // given
+        let page_viewer = PageViewer::new(
+            "/pgdata/base/5/16384".to_string(),
+            ForkType::Main,
+            3,
+            Page::new(vec![0; 8192]).unwrap(),
+            StubTransactions,
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        page_viewer
+            .handle(&TermSize { rows: 10, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/base/5/16384| |block 3", &[GRAY, NONE, YELLOW]),
+                line("Page is new (not initialized)", &[GRAY]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+}
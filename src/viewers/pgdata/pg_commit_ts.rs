@@ -0,0 +1,318 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use colored::Colorize;
+
+use crate::{
+    common::time::format_timestamptz,
+    pgdata::{
+        pg_commit_ts::{CommitTimestamp, PGCommitTS, COMMIT_TS_LAYOUT},
+        pg_xact::FIRST_NORMAL_TRANSACTION_ID,
+        slru::{wrapping_range, SLRU_PAGES_PER_SEGMENT},
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+use super::slru::format_segments;
+
+/// Most xids a range lists, so that a mistyped bound does not read the whole of pg_commit_ts
+const MAX_RANGE_XIDS: u64 = 10_000;
+
+/// Lists the segments of pg_commit_ts with the xids they hold, the commit timestamp is looked up
+/// by xid or for an xid range, e.g. `740..760`
+pub struct PGCommitTSViewer<T: PGCommitTS> {
+    pg_commit_ts: T,
+}
+
+impl<T: PGCommitTS> PGCommitTSViewer<T> {
+    pub fn new(pg_commit_ts: T) -> Self {
+        PGCommitTSViewer { pg_commit_ts }
+    }
+}
+
+impl<T: PGCommitTS> Viewer for PGCommitTSViewer<T> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        let xid = |param: &str| {
+            param
+                .parse::<u32>()
+                .with_context(|| format!("Expected transaction id, got {param}"))
+        };
+        let pg_commit_ts_path = self.pg_commit_ts.path().to_path_buf();
+        let Some((first, last)) = param.split_once("..") else {
+            let xid = xid(param)?;
+            return Ok(Box::new(CommitTimestampViewer {
+                pg_commit_ts_path,
+                xid,
+                commit_ts: self.pg_commit_ts.commit_ts(xid),
+            }));
+        };
+        let (first, last) = (xid(first)?, xid(last)?);
+        let len = last.wrapping_sub(first) as u64 + 1;
+        if len > MAX_RANGE_XIDS {
+            bail!("Range {first}..{last} holds {len} xids, at most {MAX_RANGE_XIDS} are listed");
+        }
+        Ok(Box::new(CommitTimestampRangeViewer {
+            pg_commit_ts_path,
+            first,
+            last,
+            commit_timestamps: wrapping_range(first, last)
+                .map(|xid| (xid, self.pg_commit_ts.commit_ts(xid)))
+                .collect(),
+        }))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.pg_commit_ts
+                .path()
+                .parent()
+                .expect("pgdata path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(write, "{}", "/pg_commit_ts".yellow())?;
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "Commit timestamp and replication origin of transactions, 10 bytes per xid, {} xids per page, {} pages per segment",
+                COMMIT_TS_LAYOUT.entries_per_page(),
+                SLRU_PAGES_PER_SEGMENT
+            )
+            .color(GRAY)
+        )?;
+
+        format_segments(
+            &self.pg_commit_ts.segments()?,
+            &COMMIT_TS_LAYOUT,
+            "xids",
+            &mut write,
+        )?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Shows the commit timestamp of a transaction and where pg_commit_ts keeps it
+pub struct CommitTimestampViewer {
+    pg_commit_ts_path: PathBuf,
+    xid: u32,
+    commit_ts: anyhow::Result<Option<CommitTimestamp>>,
+}
+
+impl Viewer for CommitTimestampViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.pg_commit_ts_path.to_string_lossy().color(GRAY)
+        )?;
+        write!(write, "{}", format!("/{}", self.xid).yellow())?;
+
+        let location = COMMIT_TS_LAYOUT.locate(self.xid);
+        let stored_at = match self.xid {
+            xid if xid < FIRST_NORMAL_TRANSACTION_ID => "permanent xid, not stored".to_string(),
+            _ => format!(
+                "{} page {} byte {}",
+                location.segment_file_name(),
+                location.page_number,
+                location.byte
+            ),
+        };
+        write!(write, "\n{:<8} {}", "location".color(GRAY), stored_at)?;
+        match &self.commit_ts {
+            Ok(Some(commit_ts)) => {
+                write!(
+                    write,
+                    "\n{:<8} {}",
+                    "time".color(GRAY),
+                    format_timestamptz(commit_ts.time)
+                )?;
+                write!(write, "\n{:<8} {}", "origin".color(GRAY), commit_ts.origin)?;
+            }
+            Ok(None) => write!(
+                write,
+                "\n{:<8} {}",
+                "time".color(GRAY),
+                "not recorded: in progress, aborted, or committed while tracking was off"
+                    .color(GRAY)
+            )?,
+            Err(err) => write!(write, "\nE {}", format!("{err:#}").red())?,
+        }
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Lists the commit timestamps of the transactions in an xid range
+pub struct CommitTimestampRangeViewer {
+    pg_commit_ts_path: PathBuf,
+    first: u32,
+    last: u32,
+    commit_timestamps: Vec<(u32, anyhow::Result<Option<CommitTimestamp>>)>,
+}
+
+impl Viewer for CommitTimestampRangeViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.pg_commit_ts_path.to_string_lossy().color(GRAY)
+        )?;
+        write!(
+            write,
+            "{}",
+            format!("/{}..{}", self.first, self.last).yellow()
+        )?;
+        write!(
+            write,
+            "\n{}",
+            format!("  {:>10} {:<30} {:>6}", "xid", "time", "origin").color(GRAY)
+        )?;
+        self.commit_timestamps
+            .iter()
+            .try_for_each(|(xid, commit_ts)| match commit_ts {
+                Ok(Some(commit_ts)) => write!(
+                    write,
+                    "\n  {:>10} {:<30} {:>6}",
+                    xid,
+                    format_timestamptz(commit_ts.time),
+                    commit_ts.origin
+                ),
+                Ok(None) => write!(write, "\n  {:>10} {}", xid, "not recorded".color(GRAY)),
+                Err(err) => write!(write, "\n  {:>10} {}", xid, format!("{err:#}").red()),
+            })?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{
+        pgdata::pg_commit_ts::test_stubs::StubPGCommitTS,
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::PGCommitTSViewer;
+
+    const TERM_SIZE: TermSize = TermSize { rows: 20, cols: 80 };
+
+    #[test]
+    fn pg_commit_ts_handler_lists_segments() {
+        // given
+        let viewer = PGCommitTSViewer::new(StubPGCommitTS);
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer.handle(&TERM_SIZE, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata|/pg_commit_ts", &[GRAY, YELLOW]),
+                line("Commit timestamp and replication origin of transactions, 10 bytes per xid, 819 xids per page, 32 pages per segment", &[GRAY]),
+                line("  file       size pages xids", &[GRAY]),
+                line("F |0000| 8192 bytes     1 0..818", &[NONE, BRIGHT_BLUE, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[rstest]
+    #[case("743", &[
+        line("/pgdata/pg_commit_ts|/743", &[GRAY, YELLOW]),
+        line("location| 0000 page 0 byte 7430", &[GRAY, NONE]),
+        line("time    | 2026-10-17 17:29:45.123456 UTC", &[GRAY, NONE]),
+        line("origin  | 1", &[GRAY, NONE]),
+    ])]
+    #[case("741", &[
+        line("/pgdata/pg_commit_ts|/741", &[GRAY, YELLOW]),
+        line("location| 0000 page 0 byte 7410", &[GRAY, NONE]),
+        line("time    | |not recorded: in progress, aborted, or committed while tracking was off", &[GRAY, NONE, GRAY]),
+    ])]
+    #[case("2", &[
+        line("/pgdata/pg_commit_ts|/2", &[GRAY, YELLOW]),
+        line("location| permanent xid, not stored", &[GRAY, NONE]),
+        line("time    | |not recorded: in progress, aborted, or committed while tracking was off", &[GRAY, NONE, GRAY]),
+    ])]
+    #[case("5000", &[
+        line("/pgdata/pg_commit_ts|/5000", &[GRAY, YELLOW]),
+        line("location| 0000 page 6 byte 860", &[GRAY, NONE]),
+        line("E |Commit timestamp of xid 5000 is missing", &[NONE, RED]),
+    ])]
+    #[case("741..744", &[
+        line("/pgdata/pg_commit_ts|/741..744", &[GRAY, YELLOW]),
+        line("         xid time                           origin", &[GRAY]),
+        line("         741 |not recorded", &[NONE, GRAY]),
+        line("         742 |not recorded", &[NONE, GRAY]),
+        line("         743 2026-10-17 17:29:45.123456 UTC      1", &[NONE]),
+        line("         744 |Commit timestamp of xid 744 is missing", &[NONE, RED]),
+    ])]
+    #[case("4294967295..0", &[
+        line("/pgdata/pg_commit_ts|/4294967295..0", &[GRAY, YELLOW]),
+        line("         xid time                           origin", &[GRAY]),
+        line("  4294967295 |Commit timestamp of xid 4294967295 is missing", &[NONE, RED]),
+        line("           0 |not recorded", &[NONE, GRAY]),
+    ])]
+    fn pg_commit_ts_handler_shows_commit_timestamps(
+        #[case] param: &str,
+        #[case] expected: &[String],
+    ) {
+        // given
+        let viewer = Box::new(PGCommitTSViewer::new(StubPGCommitTS));
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .get_next(param)
+            .unwrap()
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(output, [expected, &[line("", &[])]].concat().join("\n"));
+    }
+
+    #[rstest]
+    #[case("740..", "Expected transaction id, got ")]
+    #[case(
+        "10..10010",
+        "Range 10..10010 holds 10001 xids, at most 10000 are listed"
+    )]
+    fn pg_commit_ts_handler_rejects_bad_range(#[case] param: &str, #[case] expected: &str) {
+        // given
+        let viewer = Box::new(PGCommitTSViewer::new(StubPGCommitTS));
+
+        // when
+        let result = viewer.get_next(param);
+
+        // then
+        assert_eq!(
+            result.err().map(|err| err.to_string()).as_deref(),
+            Some(expected)
+        );
+    }
+}
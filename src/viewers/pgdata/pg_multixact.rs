@@ -0,0 +1,273 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use colored::Colorize;
+
+use crate::{
+    pgdata::{
+        pg_multiexact::{
+            locate_member, MultiXactMember, PGMultixact, MEMBERS_DIR_NAME, MEMBERS_PER_GROUP,
+            MEMBER_GROUP_LAYOUT, OFFSETS_DIR_NAME, OFFSETS_LAYOUT,
+        },
+        pg_xact::{PGXact, XactStatus},
+        slru::SLRU_PAGES_PER_SEGMENT,
+        transactions::Transactions,
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+use super::{pg_xact::render_status, slru::format_segments};
+
+/// Lists the segments of pg_multixact offsets and members, the members of a multixact are looked
+/// up by its id
+pub struct PGMultixactViewer<T: Transactions> {
+    transactions: T,
+}
+
+impl<T: Transactions> PGMultixactViewer<T> {
+    pub fn new(transactions: T) -> Self {
+        PGMultixactViewer { transactions }
+    }
+}
+
+impl<T: Transactions> Viewer for PGMultixactViewer<T> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        let multi = param
+            .parse::<u32>()
+            .with_context(|| format!("Expected multixact id, got {param}"))?;
+        let members = self
+            .transactions
+            .pg_multixact()
+            .members(multi)
+            .map(|members| {
+                members
+                    .into_iter()
+                    .map(|member| (member, self.transactions.pg_xact().status(member.xid)))
+                    .collect()
+            });
+        Ok(Box::new(MultixactMembersViewer {
+            pg_multixact_path: self.transactions.pg_multixact().path().to_path_buf(),
+            multi,
+            members,
+        }))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let pg_multixact = self.transactions.pg_multixact();
+        write!(
+            write,
+            "{}",
+            pg_multixact
+                .path()
+                .parent()
+                .expect("pgdata path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(write, "{}", "/pg_multixact".yellow())?;
+
+        write!(write, "\n{}", format!("/{OFFSETS_DIR_NAME}").yellow())?;
+        write!(
+            write,
+            " {}",
+            format!(
+                "Offset of the first member of multixacts, {} multixacts per page, {} pages per segment",
+                OFFSETS_LAYOUT.entries_per_page(),
+                SLRU_PAGES_PER_SEGMENT
+            )
+            .color(GRAY)
+        )?;
+        format_segments(
+            &pg_multixact.offsets_segments()?,
+            &OFFSETS_LAYOUT,
+            "multixacts",
+            &mut write,
+        )?;
+
+        write!(write, "\n{}", format!("/{MEMBERS_DIR_NAME}").yellow())?;
+        write!(
+            write,
+            " {}",
+            format!(
+                "Lock modes and xids of members in groups of {}, {} groups per page, {} pages per segment",
+                MEMBERS_PER_GROUP,
+                MEMBER_GROUP_LAYOUT.entries_per_page(),
+                SLRU_PAGES_PER_SEGMENT
+            )
+            .color(GRAY)
+        )?;
+        format_segments(
+            &pg_multixact.members_segments()?,
+            &MEMBER_GROUP_LAYOUT,
+            "groups",
+            &mut write,
+        )?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Shows the members of a multixact with their lock modes and commit statuses
+pub struct MultixactMembersViewer {
+    pg_multixact_path: PathBuf,
+    multi: u32,
+    members: anyhow::Result<Vec<(MultiXactMember, anyhow::Result<XactStatus>)>>,
+}
+
+impl Viewer for MultixactMembersViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.pg_multixact_path.to_string_lossy().color(GRAY)
+        )?;
+        write!(write, "{}", format!("/{}", self.multi).yellow())?;
+
+        let location = OFFSETS_LAYOUT.locate(self.multi);
+        write!(
+            write,
+            "\n{:<8} {} page {} byte {}",
+            "offsets".color(GRAY),
+            location.segment_file_name(),
+            location.page_number,
+            location.byte
+        )?;
+        let members = match &self.members {
+            Ok(members) => members,
+            Err(err) => {
+                write!(write, "\nE {}", format!("{err:#}").red())?;
+                return writeln!(write).map(|_| ()).map_err(|err| anyhow!(err));
+            }
+        };
+        if let Some((first, _)) = members.first() {
+            let (location, slot) = locate_member(first.offset);
+            write!(
+                write,
+                "\n{:<8} {} page {} byte {} slot {}",
+                "members".color(GRAY),
+                location.segment_file_name(),
+                location.page_number,
+                location.byte,
+                slot
+            )?;
+        }
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "  {:>10} {:>10} {:<14} {}",
+                "offset", "xid", "lock mode", "status"
+            )
+            .color(GRAY)
+        )?;
+        members.iter().try_for_each(|(member, status)| {
+            write!(
+                write,
+                "\n  {:>10} {:>10} {:<14} {}",
+                member.offset,
+                member.xid,
+                member.status.to_string(),
+                match status {
+                    Ok(status) => render_status(*status),
+                    Err(err) => format!("{err:#}").red(),
+                }
+            )
+        })?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{
+        pgdata::transactions::test_stubs::StubTransactions,
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, GREEN, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::PGMultixactViewer;
+
+    const TERM_SIZE: TermSize = TermSize { rows: 20, cols: 80 };
+
+    #[test]
+    fn pg_multixact_handler_lists_segments() {
+        // given
+        let viewer = PGMultixactViewer::new(StubTransactions);
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer.handle(&TERM_SIZE, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata|/pg_multixact", &[GRAY, YELLOW]),
+                line("/offsets| |Offset of the first member of multixacts, 2048 multixacts per page, 32 pages per segment", &[YELLOW, NONE, GRAY]),
+                line("  file       size pages multixacts", &[GRAY]),
+                line("F |0000| 8192 bytes     1 0..2047", &[NONE, BRIGHT_BLUE, NONE]),
+                line("/members| |Lock modes and xids of members in groups of 4, 409 groups per page, 32 pages per segment", &[YELLOW, NONE, GRAY]),
+                line("  file       size pages groups", &[GRAY]),
+                line("F |0000| 8192 bytes     1 0..408 |short, 1 of 32 pages", &[NONE, BRIGHT_BLUE, NONE, RED]),
+                line("F |0001| 8192 bytes     1 13088..13496", &[NONE, BRIGHT_BLUE, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[rstest]
+    #[case("7", &[
+        line("offsets | 0000 page 0 byte 28", &[GRAY, NONE]),
+        line("members | 0000 page 0 byte 60 slot 0", &[GRAY, NONE]),
+        line("      offset        xid lock mode      status", &[GRAY]),
+        line("          12        740 ForKeyShare    |COMMITTED", &[NONE, GREEN]),
+        line("          13        742 NoKeyUpdate    |IN_PROGRESS", &[NONE, YELLOW]),
+    ])]
+    #[case("12", &[
+        line("offsets | 0000 page 0 byte 48", &[GRAY, NONE]),
+        line("E |Members of multixact 12 are missing", &[NONE, RED]),
+    ])]
+    fn pg_multixact_handler_shows_members(#[case] multi: &str, #[case] expected: &[String]) {
+        // given
+        let viewer = Box::new(PGMultixactViewer::new(StubTransactions));
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .get_next(multi)
+            .unwrap()
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output,
+            [
+                &[line(
+                    &format!("/pgdata/pg_multixact|/{multi}"),
+                    &[GRAY, YELLOW]
+                )],
+                expected,
+                &[line("", &[])],
+            ]
+            .concat()
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use colored::Colorize;
+
+use crate::{
+    pgdata::{
+        pg_subtrans::{parent_chain, PGSubtrans, SUBTRANS_LAYOUT},
+        pg_xact::{PGXact, XactStatus},
+        slru::SLRU_PAGES_PER_SEGMENT,
+        transactions::Transactions,
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+use super::{pg_xact::render_status, slru::format_segments};
+
+/// Lists the segments of pg_subtrans with the xids they hold, the parents of a subtransaction are
+/// looked up by xid
+pub struct PGSubtransViewer<T: Transactions> {
+    transactions: T,
+}
+
+impl<T: Transactions> PGSubtransViewer<T> {
+    pub fn new(transactions: T) -> Self {
+        PGSubtransViewer { transactions }
+    }
+}
+
+impl<T: Transactions> Viewer for PGSubtransViewer<T> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        let xid = param
+            .parse::<u32>()
+            .with_context(|| format!("Expected transaction id, got {param}"))?;
+        let chain = parent_chain(self.transactions.pg_subtrans(), xid);
+        let top_level_xid = match &chain {
+            Ok(chain) => chain.last().copied().unwrap_or(xid),
+            Err(_) => xid,
+        };
+        Ok(Box::new(ParentChainViewer {
+            pg_subtrans_path: self.transactions.pg_subtrans().path().to_path_buf(),
+            xid,
+            chain,
+            top_level_status: self.transactions.pg_xact().status(top_level_xid),
+        }))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let pg_subtrans = self.transactions.pg_subtrans();
+        write!(
+            write,
+            "{}",
+            pg_subtrans
+                .path()
+                .parent()
+                .expect("pgdata path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(write, "{}", "/pg_subtrans".yellow())?;
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "Parent xid of subtransactions, {} xids per page, {} pages per segment, zeroed on startup",
+                SUBTRANS_LAYOUT.entries_per_page(),
+                SLRU_PAGES_PER_SEGMENT
+            )
+            .color(GRAY)
+        )?;
+        format_segments(
+            &pg_subtrans.segments()?,
+            &SUBTRANS_LAYOUT,
+            "xids",
+            &mut write,
+        )?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Shows the parents of a subtransaction up to its top-level transaction and the status of the latter
+pub struct ParentChainViewer {
+    pg_subtrans_path: PathBuf,
+    xid: u32,
+    chain: anyhow::Result<Vec<u32>>,
+    top_level_status: anyhow::Result<XactStatus>,
+}
+
+impl Viewer for ParentChainViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.pg_subtrans_path.to_string_lossy().color(GRAY)
+        )?;
+        write!(write, "{}", format!("/{}", self.xid).yellow())?;
+
+        let location = SUBTRANS_LAYOUT.locate(self.xid);
+        write!(
+            write,
+            "\n{:<8} {} page {} byte {}",
+            "location".color(GRAY),
+            location.segment_file_name(),
+            location.page_number,
+            location.byte
+        )?;
+        let chain = match &self.chain {
+            Ok(chain) => chain,
+            Err(err) => {
+                write!(write, "\nE {}", format!("{err:#}").red())?;
+                return writeln!(write).map(|_| ()).map_err(|err| anyhow!(err));
+            }
+        };
+        write!(
+            write,
+            "\n{:<8} {}",
+            "chain".color(GRAY),
+            std::iter::once(&self.xid)
+                .chain(chain)
+                .map(|xid| xid.to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        )?;
+        if chain.is_empty() {
+            write!(
+                write,
+                " {}",
+                "top-level transaction, or started before the last restart".color(GRAY)
+            )?;
+        }
+        match &self.top_level_status {
+            Ok(status) => write!(
+                write,
+                "\n{:<8} {}",
+                "status".color(GRAY),
+                render_status(*status)
+            )?,
+            Err(err) => write!(write, "\nE {}", format!("{err:#}").red())?,
+        }
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{
+        pgdata::transactions::test_stubs::StubTransactions,
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, GREEN, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::PGSubtransViewer;
+
+    const TERM_SIZE: TermSize = TermSize { rows: 20, cols: 80 };
+
+    #[test]
+    fn pg_subtrans_handler_lists_segments() {
+        // given
+        let viewer = PGSubtransViewer::new(StubTransactions);
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer.handle(&TERM_SIZE, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata|/pg_subtrans", &[GRAY, YELLOW]),
+                line("Parent xid of subtransactions, 2048 xids per page, 32 pages per segment, zeroed on startup", &[GRAY]),
+                line("  file       size pages xids", &[GRAY]),
+                line("F |0000| 8192 bytes     1 0..2047", &[NONE, BRIGHT_BLUE, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[rstest]
+    #[case("743", &[
+        line("location| 0000 page 0 byte 2972", &[GRAY, NONE]),
+        line("chain   | 743 -> 741 -> 740", &[GRAY, NONE]),
+        line("status  | |COMMITTED", &[GRAY, NONE, GREEN]),
+    ])]
+    #[case("740", &[
+        line("location| 0000 page 0 byte 2960", &[GRAY, NONE]),
+        line("chain   | 740 |top-level transaction, or started before the last restart", &[GRAY, NONE, GRAY]),
+        line("status  | |COMMITTED", &[GRAY, NONE, GREEN]),
+    ])]
+    #[case("5000", &[
+        line("location| 0000 page 2 byte 3616", &[GRAY, NONE]),
+        line("E |Parent of xid 5000 is missing", &[NONE, RED]),
+    ])]
+    fn pg_subtrans_handler_shows_parent_chain(#[case] xid: &str, #[case] expected: &[String]) {
+        // given
+        let viewer = Box::new(PGSubtransViewer::new(StubTransactions));
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .get_next(xid)
+            .unwrap()
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output,
+            [
+                &[line(
+                    &format!("/pgdata/pg_subtrans|/{xid}"),
+                    &[GRAY, YELLOW]
+                )],
+                expected,
+                &[line("", &[])],
+            ]
+            .concat()
+            .join("\n")
+        );
+    }
+}
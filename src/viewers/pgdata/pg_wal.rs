@@ -0,0 +1,333 @@
+use std::{io::Write, path::Path};
+
+use anyhow::{anyhow, Context};
+use colored::{ColoredString, Colorize};
+
+use crate::{
+    common::{fs::render_file_type, size::pretty_size, Lsn},
+    pgdata::pg_wal::{
+        self, block_history::RelationFork, stats::segment_range_stats, ArchiveStatus, PGWal,
+        WalFile, WalFileKind, WalItem, WalSegmentName, ARCHIVE_STATUS_DIR_NAME,
+    },
+    pgdata::transactions::Transactions,
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+use self::{
+    block_history::RelationHistoryViewer, wal_segment::WalSegmentViewer, wal_stats::WalStatsViewer,
+};
+
+mod block_history;
+mod wal_segment;
+mod wal_stats;
+
+/// Shows the statistics of all segments of the newest timeline, a range of segments is given as
+/// `<first>..<last>`
+const STATS_PARAM: &str = "stats";
+
+/// An absolute path leads to a directory of archived segments which is browsed like pg_wal,
+/// e.g. `pg_wal /mnt/wal_archive stats`
+const ARCHIVE_PATH_PREFIX: &str = "/";
+
+/// Directories of PG_DATA holding relation files, a fork path given relative to PG_DATA leads to
+/// the history of its blocks, e.g. `base/5/16384`
+const RELATION_DIRS: [&str; 3] = ["base/", "global/", "pg_tblspc/"];
+
+/// Lists WAL segments and the other files of pg_wal or of an archive with their archive status
+pub struct PGWalViewer<T: PGWal, X: Transactions> {
+    pg_wal: T,
+    /// Passed along to the page viewer of full-page images to annotate the heap tuples
+    transactions: X,
+}
+
+impl<T: PGWal, X: Transactions> PGWalViewer<T, X> {
+    pub fn new(pg_wal: T, transactions: X) -> Self {
+        PGWalViewer {
+            pg_wal,
+            transactions,
+        }
+    }
+}
+
+impl<T: PGWal + 'static, X: Transactions + 'static> Viewer for PGWalViewer<T, X> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        let wal_seg_size = self.pg_wal.wal_seg_size()?;
+        let segment = |file_name: &str| match WalFileKind::try_parse(file_name, wal_seg_size) {
+            Some(WalFileKind::Segment(segment)) => Ok(segment),
+            _ => Err(anyhow!("Expected WAL segment file name, got {file_name}")),
+        };
+        if param.starts_with(ARCHIVE_PATH_PREFIX) {
+            return Ok(Box::new(PGWalViewer::new(
+                pg_wal::archive(Path::new(param)),
+                self.transactions,
+            )));
+        }
+        if RELATION_DIRS.iter().any(|dir| param.starts_with(dir)) {
+            let fork = RelationFork::try_parse(param)?;
+            return Ok(Box::new(RelationHistoryViewer::new(
+                self.pg_wal,
+                self.transactions,
+                param.trim_end_matches('/').to_string(),
+                fork,
+            )));
+        }
+        let (first, last) = match param.split_once("..") {
+            Some((first, last)) => (segment(first)?, segment(last)?),
+            None if param == STATS_PARAM => newest_timeline_segments(&self.pg_wal.items()?)
+                .context("There are no WAL segments")?,
+            None => {
+                return Ok(Box::new(WalSegmentViewer::new(
+                    self.pg_wal.path().to_path_buf(),
+                    param.to_string(),
+                    self.pg_wal.records(&segment(param)?)?,
+                )))
+            }
+        };
+        let (stats, errors) = segment_range_stats(&self.pg_wal, &first, &last)?;
+        Ok(Box::new(WalStatsViewer::new(
+            self.pg_wal.path().to_path_buf(),
+            format!("{}..{}", first.file_name(), last.file_name()),
+            last.segment_no(wal_seg_size) - first.segment_no(wal_seg_size) + 1,
+            stats,
+            errors,
+        )))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        let path = self.pg_wal.path();
+        write!(
+            write,
+            "{}",
+            path.parent()
+                .unwrap_or(Path::new(""))
+                .to_string_lossy()
+                .trim_end_matches('/')
+                .color(GRAY)
+        )?;
+        write!(
+            write,
+            "{}",
+            format!(
+                "/{}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            )
+            .yellow()
+        )?;
+
+        let wal_seg_size = self.pg_wal.wal_seg_size()?;
+        write!(
+            write,
+            "\n{} {}",
+            "segment size".color(GRAY),
+            pretty_size(wal_seg_size as u64)
+        )?;
+
+        let items = self.pg_wal.items()?;
+        let name_col_width = items
+            .iter()
+            .filter_map(|item| match item {
+                WalItem::File(file) => Some(file.file_name.len()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "  {:<name_col_width$} {:>8} {:>8} {:>8} {:<6} LSN",
+                "file", "timeline", "log", "segment", "status"
+            )
+            .color(GRAY)
+        )?;
+
+        items.into_iter().try_for_each(|item| {
+            writeln!(write)?;
+            match item {
+                WalItem::File(file) => {
+                    let status = render_archive_status(file.archive_status);
+                    let name = file.file_name.bright_blue();
+                    match file.kind {
+                        WalFileKind::Segment(segment) | WalFileKind::Partial(segment) => write!(
+                            write,
+                            "F {name:<name_col_width$} {:>8} {:>8} {:>8} {status:<6} {}..{}",
+                            segment.timeline_id,
+                            segment.log,
+                            segment.seg,
+                            segment.start_lsn(wal_seg_size),
+                            segment.end_lsn(wal_seg_size)
+                        ),
+                        WalFileKind::History(timeline_id) => write!(
+                            write,
+                            "F {name:<name_col_width$} {:<26} {status:<6}",
+                            format!("history of timeline {timeline_id}")
+                        ),
+                        WalFileKind::Backup(segment, offset) => write!(
+                            write,
+                            "F {name:<name_col_width$} {:<26} {status:<6} {}",
+                            "backup history",
+                            Lsn(segment.start_lsn(wal_seg_size).0 + offset as u64)
+                        ),
+                    }
+                }
+                WalItem::Gap { first, last } if first == last => write!(
+                    write,
+                    "E {}",
+                    format!("Missing segment {}", first.file_name()).red()
+                ),
+                WalItem::Gap { first, last } => write!(
+                    write,
+                    "E {}",
+                    format!(
+                        "Missing segments {}..{}",
+                        first.file_name(),
+                        last.file_name()
+                    )
+                    .red()
+                ),
+                WalItem::OrphanArchiveStatus(file_name, status) => write!(
+                    write,
+                    "E {}",
+                    format!(
+                        "{ARCHIVE_STATUS_DIR_NAME}/{file_name}.{} refers to a missing WAL file",
+                        match status {
+                            ArchiveStatus::Ready => "ready",
+                            ArchiveStatus::Done => "done",
+                        }
+                    )
+                    .red()
+                ),
+                WalItem::Unknown(entry) => write!(
+                    write,
+                    "{} {}",
+                    render_file_type(&entry.entry_type),
+                    entry.name.to_string_lossy().color(GRAY)
+                ),
+            }
+        })?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Returns the first and the last segment of the newest timeline present in pg_wal
+fn newest_timeline_segments(items: &[WalItem]) -> Option<(WalSegmentName, WalSegmentName)> {
+    let segments = items.iter().filter_map(|item| match item {
+        WalItem::File(WalFile {
+            kind: WalFileKind::Segment(segment),
+            ..
+        }) => Some(*segment),
+        _ => None,
+    });
+    let timeline_id = segments.clone().map(|segment| segment.timeline_id).max()?;
+    let segments = segments.filter(|segment| segment.timeline_id == timeline_id);
+    Some((segments.clone().min()?, segments.max()?))
+}
+
+fn render_archive_status(archive_status: Option<ArchiveStatus>) -> ColoredString {
+    match archive_status {
+        Some(ArchiveStatus::Ready) => "ready".yellow(),
+        Some(ArchiveStatus::Done) => "done".green(),
+        None => "".normal(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::{
+            pg_wal::{
+                reader::test_files::WalBuilder, record::test_records::wal_record,
+                test_stubs::StubPGWal,
+            },
+            transactions::test_stubs::StubTransactions,
+        },
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, GREEN, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::PGWalViewer;
+
+    #[test]
+    fn pg_wal_handler_renders_wal_files() {
+        // given
+        let viewer = PGWalViewer::new(StubPGWal {}, StubTransactions);
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .handle(&TermSize { rows: 20, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata|/pg_wal", &[GRAY, YELLOW]),
+                line("segment size| 16 MB", &[GRAY, NONE]),
+                line("  file                                     timeline      log  segment status LSN", &[GRAY]),
+                line("F |000000010000000000000001                |        1        0        1 |done  | 0/1000000..0/2000000", &[NONE, BRIGHT_BLUE, NONE, GREEN, NONE]),
+                line("F |000000010000000000000002                |        1        0        2 |ready | 0/2000000..0/3000000", &[NONE, BRIGHT_BLUE, NONE, YELLOW, NONE]),
+                line("F |000000010000000000000002.00000028.backup| backup history                    0/2000028", &[NONE, BRIGHT_BLUE, NONE]),
+                line("E |Missing segments 000000010000000000000003..000000010000000000000004", &[NONE, RED]),
+                line("F |000000010000000000000005.partial        |        1        0        5        0/5000000..0/6000000", &[NONE, BRIGHT_BLUE, NONE]),
+                line("F |00000002.history                        | history of timeline 2      |ready |", &[NONE, BRIGHT_BLUE, NONE, YELLOW, NONE]),
+                line("F |000000020000000000000005                |        2        0        5        0/5000000..0/6000000", &[NONE, BRIGHT_BLUE, NONE]),
+                line("E |archive_status/000000010000000000000000.done refers to a missing WAL file", &[NONE, RED]),
+                line("F |xlogtemp.42", &[NONE, GRAY]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn pg_wal_handler_opens_archive_directories() {
+        // given
+        let archive_name = format!("pg-browser-wal-archive-{}", std::process::id());
+        let path = std::env::temp_dir().join(&archive_name);
+        let header = format!(
+            "{}|/{archive_name}",
+            std::env::temp_dir().to_string_lossy().trim_end_matches('/')
+        );
+        create_dir_all(&path).unwrap();
+        let mut wal = WalBuilder::new(1 << 20, 1, 1);
+        wal.record(&wal_record(1, 0x00, 740, 0, &[], &0i64.to_le_bytes()));
+        write(path.join("000000010000000000000001"), wal.segment(0)).unwrap();
+        let viewer = Box::new(PGWalViewer::new(StubPGWal {}, StubTransactions));
+
+        let mut buf = Vec::new();
+
+        // when
+        let result = viewer
+            .get_next(&path.to_string_lossy())
+            .and_then(|viewer| viewer.handle(&TermSize { rows: 20, cols: 80 }, Box::new(&mut buf)));
+        remove_dir_all(&path).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        result.unwrap();
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line(&header, &[GRAY, YELLOW]),
+                line("segment size| 1024 kB", &[GRAY, NONE]),
+                line("  file                     timeline      log  segment status LSN", &[GRAY]),
+                line("F |000000010000000000000001|        1        0        1        0/100000..0/200000", &[NONE, BRIGHT_BLUE, NONE]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, Context};
+use colored::Colorize;
+
+use crate::{
+    common::Lsn,
+    pgdata::page::Page,
+    pgdata::pg_wal::{
+        block_history::{block_history, BlockChange, RelationFork},
+        rmgr::RmgrId,
+        PGWal,
+    },
+    pgdata::transactions::Transactions,
+    viewers::{pgdata::page::PageViewer, TermSize, Viewer},
+    GRAY,
+};
+
+use super::wal_segment::write_description;
+
+/// Width of the LSN, xid, rmgr and image columns preceding the description column
+const DESC_COL_OFFSET: usize = 40;
+
+/// A relation fork whose blocks are searched for in WAL by number
+pub struct RelationHistoryViewer<T: PGWal, X: Transactions> {
+    pg_wal: T,
+    transactions: X,
+    relpath: String,
+    fork: RelationFork,
+}
+
+impl<T: PGWal, X: Transactions> RelationHistoryViewer<T, X> {
+    pub fn new(pg_wal: T, transactions: X, relpath: String, fork: RelationFork) -> Self {
+        RelationHistoryViewer {
+            pg_wal,
+            transactions,
+            relpath,
+            fork,
+        }
+    }
+}
+
+impl<T: PGWal + 'static, X: Transactions + 'static> Viewer for RelationHistoryViewer<T, X> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        let block_number = param
+            .parse::<u32>()
+            .with_context(|| format!("Expected block number, got {param}"))?;
+        let (changes, errors) = block_history(&self.pg_wal, &self.fork, block_number)?;
+        Ok(Box::new(BlockHistoryViewer {
+            pg_wal_path: self.pg_wal.path().to_path_buf(),
+            transactions: self.transactions,
+            relpath: self.relpath,
+            fork: self.fork,
+            block_number,
+            changes,
+            errors,
+        }))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.pg_wal.path().to_string_lossy().color(GRAY)
+        )?;
+        write!(write, "{}", format!("/{}", self.relpath).yellow())?;
+        write!(write, "\n{} {}", "rel".color(GRAY), self.fork.locator)?;
+        if let Some(fork) = describe_fork(&self.fork) {
+            write!(write, " {}", fork)?;
+        }
+        write!(
+            write,
+            "\n{}",
+            "Pass a block number to list the WAL records referencing it".color(GRAY)
+        )?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Lists the records referencing a block of a relation fork, oldest first
+pub struct BlockHistoryViewer<X: Transactions> {
+    pg_wal_path: PathBuf,
+    transactions: X,
+    relpath: String,
+    fork: RelationFork,
+    block_number: u32,
+    changes: Vec<BlockChange>,
+    errors: Vec<anyhow::Error>,
+}
+
+impl<X: Transactions + 'static> Viewer for BlockHistoryViewer<X> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        let lsn = Lsn::try_parse(param)
+            .with_context(|| format!("Expected LSN of a record, e.g. 0/1000028, got {param}"))?;
+        let change = self
+            .changes
+            .iter()
+            .find(|change| change.record.lsn == lsn)
+            .with_context(|| format!("No record at {lsn} references the block"))?;
+        let image = change
+            .image(&self.fork, self.block_number)
+            .with_context(|| format!("Record at {lsn} carries no image of the block"))?;
+        let page = Page::new(image.restore()?)?;
+        let location = format!(
+            "{}/{} FPI at {}",
+            self.pg_wal_path.to_string_lossy(),
+            self.relpath,
+            lsn
+        );
+        Ok(Box::new(PageViewer::new(
+            location,
+            self.fork.fork,
+            self.block_number,
+            page,
+            self.transactions,
+        )))
+    }
+
+    fn handle(&self, term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(write, "{}", self.pg_wal_path.to_string_lossy().color(GRAY))?;
+        write!(
+            write,
+            "{}",
+            format!("/{}/{}", self.relpath, self.block_number).yellow()
+        )?;
+        write!(write, "\n{} {}", "rel".color(GRAY), self.fork.locator)?;
+        if let Some(fork) = describe_fork(&self.fork) {
+            write!(write, " {}", fork)?;
+        }
+        write!(
+            write,
+            " {} {}, {} {}",
+            "blk".color(GRAY),
+            self.block_number,
+            "records".color(GRAY),
+            self.changes.len()
+        )?;
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "{:>12} {:>10} {:<11} {:<3} desc",
+                "LSN", "xid", "rmgr", "FPI"
+            )
+            .color(GRAY)
+        )?;
+
+        self.changes.iter().try_for_each(|change| {
+            let record = &change.record;
+            write!(
+                write,
+                "\n{:>12} {:>10} {:<11} {:<3} ",
+                record.lsn.to_string(),
+                record.header.xid,
+                RmgrId::from(record.header.rmid).to_string(),
+                match change.image(&self.fork, self.block_number) {
+                    Some(_) => "FPW".yellow(),
+                    None => "".normal(),
+                }
+            )?;
+            write_description(
+                record,
+                change.pg_version,
+                DESC_COL_OFFSET,
+                term_size,
+                &mut **write,
+            )
+        })?;
+
+        self.errors
+            .iter()
+            .try_for_each(|err| write!(write, "\nE {}", format!("{err:#}").red()))?;
+        if self
+            .changes
+            .iter()
+            .any(|change| change.image(&self.fork, self.block_number).is_some())
+        {
+            write!(
+                write,
+                "\n{}",
+                "Pass the LSN of a record marked FPW to decode its page image".color(GRAY)
+            )?;
+        }
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+fn describe_fork(fork: &RelationFork) -> Option<String> {
+    match fork.fork {
+        crate::pgdata::base::db_dir::ForkType::Main => None,
+        fork_type => Some(format!("fork {}", fork_type.name())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::{
+            pg_wal::{block_history::RelationFork, test_stubs::StubPGWal},
+            transactions::test_stubs::StubTransactions,
+        },
+        test_utils::{
+            colors::{GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::RelationHistoryViewer;
+
+    #[test]
+    fn block_history_handler_renders_records_referencing_block() {
+        // given
+        let viewer = Box::new(RelationHistoryViewer::new(
+            StubPGWal,
+            StubTransactions,
+            "base/5/16384".to_string(),
+            RelationFork::try_parse("base/5/16384").unwrap(),
+        ));
+        let viewer = viewer.get_next("7").unwrap();
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .handle(&TermSize { rows: 20, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/pg_wal|/base/5/16384/7", &[GRAY, YELLOW]),
+                line("rel| 1663/5/16384 |blk| 7, |records| 1", &[GRAY, NONE, GRAY, NONE, GRAY, NONE]),
+                line("         LSN        xid rmgr        FPI desc", &[GRAY]),
+                line("   0/1000048        740 Heap            INSERT off 3 flags 0x08, blkref #0: rel", &[NONE]),
+                line("                                        1663/5/16384 blk 7", &[NONE]),
+                line("E |invalid record length at 0/10000A8: expected at least 24, got 0", &[NONE, RED]),
+                line("E |Reading WAL segment 000000010000000000000002: 000000010000000000000002 is missing", &[NONE, RED]),
+                line("E |Reading WAL segment 000000020000000000000005: 000000020000000000000005 is missing", &[NONE, RED]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+}
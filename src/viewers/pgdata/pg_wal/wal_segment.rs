@@ -0,0 +1,207 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+
+use crate::{
+    pgdata::{
+        base::db_dir::ForkType,
+        pg_wal::{
+            reader::WalRecords,
+            record::{BlockRef, WalRecord},
+            rmgr::{self, RmgrId},
+        },
+    },
+    viewers::{wrap_words, TermSize, Viewer},
+    GRAY,
+};
+
+/// Width of the LSN, prev, xid, rmgr and length columns preceding the description column
+const DESC_COL_OFFSET: usize = 56;
+
+/// Narrowest description column worth laying out next to the other columns
+const MIN_DESC_COL_WIDTH: usize = 20;
+
+/// Lists the records of a WAL segment one per line as pg_waldump does
+pub struct WalSegmentViewer {
+    pg_wal_path: PathBuf,
+    file_name: String,
+    records: WalRecords,
+}
+
+impl WalSegmentViewer {
+    pub fn new(pg_wal_path: PathBuf, file_name: String, records: WalRecords) -> Self {
+        WalSegmentViewer {
+            pg_wal_path,
+            file_name,
+            records,
+        }
+    }
+}
+
+impl Viewer for WalSegmentViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(write, "{}", self.pg_wal_path.to_string_lossy().color(GRAY))?;
+        write!(write, "{}", format!("/{}", self.file_name).yellow())?;
+        write!(
+            write,
+            "\n{} {}, {} {}",
+            "PostgreSQL".color(GRAY),
+            self.records.pg_version,
+            "records".color(GRAY),
+            self.records.records.len()
+        )?;
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "{:>12} {:>12} {:>10} {:<11} {:>6} desc",
+                "LSN", "prev", "xid", "rmgr", "len"
+            )
+            .color(GRAY)
+        )?;
+
+        self.records.records.iter().try_for_each(|record| {
+            write!(
+                write,
+                "\n{:>12} {:>12} {:>10} {:<11} {:>6} ",
+                record.lsn.to_string(),
+                record.header.prev.to_string(),
+                record.header.xid,
+                RmgrId::from(record.header.rmid).to_string(),
+                record.header.tot_len
+            )?;
+            write_description(
+                record,
+                self.records.pg_version,
+                DESC_COL_OFFSET,
+                term_size,
+                &mut **write,
+            )
+        })?;
+
+        if let Some(err) = &self.records.error {
+            write!(write, "\nE {}", format!("{err:#}").red())?;
+        }
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Writes the description of the record with its block references. It is wrapped to fit the
+/// terminal if there is enough room next to the columns taking `desc_col_offset` chars,
+/// otherwise it goes to the following lines
+pub(super) fn write_description(
+    record: &WalRecord,
+    pg_version: u32,
+    desc_col_offset: usize,
+    term_size: &TermSize,
+    write: &mut dyn Write,
+) -> std::io::Result<()> {
+    let (desc_offset, desc_width) = match term_size.cols.saturating_sub(desc_col_offset) {
+        width if width >= MIN_DESC_COL_WIDTH => (desc_col_offset, width),
+        _ => (6, term_size.cols.saturating_sub(6).max(MIN_DESC_COL_WIDTH)),
+    };
+    match rmgr::describe(record, pg_version) {
+        Ok(description) => wrap_words(&describe_blocks(description, record), desc_width)
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, line)| {
+                if i > 0 || desc_offset != desc_col_offset {
+                    write!(write, "\n{: <1$}", "", desc_offset)?;
+                }
+                write!(write, "{}", line)
+            }),
+        Err(err) => write!(write, "{}", err.to_string().red()),
+    }
+}
+
+/// Appends the block references to the record description the way pg_waldump lists them
+fn describe_blocks(description: String, record: &WalRecord) -> String {
+    record
+        .blocks
+        .iter()
+        .fold(description, |description, block| {
+            format!("{description}, {}", describe_block(block))
+        })
+}
+
+fn describe_block(block: &BlockRef) -> String {
+    let mut description = format!("blkref #{}: rel {}", block.id, block.locator);
+    if block.fork != ForkType::Main {
+        description += &format!(" fork {}", block.fork.name());
+    }
+    description += &format!(" blk {}", block.block_number);
+    if block.image.is_some() {
+        description += " FPW";
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::pg_wal::{test_stubs::StubPGWal, PGWal, WalSegmentName},
+        test_utils::{
+            colors::{GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::WalSegmentViewer;
+
+    #[test]
+    fn wal_segment_handler_renders_records() {
+        // given
+        let segment = WalSegmentName {
+            timeline_id: 1,
+            log: 0,
+            seg: 1,
+        };
+        let viewer = WalSegmentViewer::new(
+            Path::new("/pgdata/pg_wal").to_path_buf(),
+            segment.file_name(),
+            StubPGWal.records(&segment).unwrap(),
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .handle(
+                &TermSize {
+                    rows: 20,
+                    cols: 100,
+                },
+                Box::new(&mut buf),
+            )
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/pg_wal|/000000010000000000000001", &[GRAY, YELLOW]),
+                line("PostgreSQL| 15, |records| 3", &[GRAY, NONE, GRAY, NONE]),
+                line("         LSN         prev        xid rmgr           len desc", &[GRAY]),
+                line("   0/1000028          0/0          0 XLOG            30 NEXTOID 24576", &[NONE]),
+                line("   0/1000048    0/1000028        740 Heap            52 INSERT off 3 flags 0x08, blkref #0: rel", &[NONE]),
+                line("                                                        1663/5/16384 blk 7", &[NONE]),
+                line("   0/1000080    0/1000048        740 Transaction     34 COMMIT 2000-01-01 00:00:00.000000 UTC", &[NONE]),
+                line("E |invalid record length at 0/10000A8: expected at least 24, got 0", &[NONE, RED]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+}
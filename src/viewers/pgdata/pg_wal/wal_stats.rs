@@ -0,0 +1,194 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+
+use crate::{
+    pgdata::pg_wal::{
+        rmgr::RmgrId,
+        stats::{RecordCounters, WalStats},
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+/// Shows the volume of WAL per resource manager and record type as `pg_waldump --stats=record`
+pub struct WalStatsViewer {
+    pg_wal_path: PathBuf,
+    range: String,
+    segments: u64,
+    stats: WalStats,
+    errors: Vec<anyhow::Error>,
+}
+
+impl WalStatsViewer {
+    pub fn new(
+        pg_wal_path: PathBuf,
+        range: String,
+        segments: u64,
+        stats: WalStats,
+        errors: Vec<anyhow::Error>,
+    ) -> Self {
+        WalStatsViewer {
+            pg_wal_path,
+            range,
+            segments,
+            stats,
+            errors,
+        }
+    }
+}
+
+impl Viewer for WalStatsViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(write, "{}", self.pg_wal_path.to_string_lossy().color(GRAY))?;
+        write!(write, "{}", format!("/{}", self.range).yellow())?;
+        write!(
+            write,
+            "\n{} {}, {} {}",
+            "segments".color(GRAY),
+            self.segments,
+            "records".color(GRAY),
+            self.stats.total.count
+        )?;
+
+        let rows = self
+            .stats
+            .rmgrs
+            .iter()
+            .flat_map(|(rmid, rmgr)| {
+                std::iter::once((RmgrId::from(*rmid).to_string(), rmgr.counters)).chain(
+                    rmgr.record_types.values().map(|record_type| {
+                        (format!("  {}", record_type.name), record_type.counters)
+                    }),
+                )
+            })
+            .collect::<Vec<_>>();
+        let name_col_width = rows
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Type".len());
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "{:<name_col_width$} {:>10} {:>8} {:>14} {:>8} {:>14} {:>8} {:>14} {:>8}",
+                "Type", "N", "(%)", "Record size", "(%)", "FPI size", "(%)", "Combined size", "(%)"
+            )
+            .color(GRAY)
+        )?;
+
+        let total = &self.stats.total;
+        rows.iter().try_for_each(|(name, counters)| {
+            write!(
+                write,
+                "\n{name:<name_col_width$} {}",
+                render_counters(counters, total)
+            )
+        })?;
+        write!(
+            write,
+            "\n{} {}",
+            format!("{:<name_col_width$}", "Total").color(GRAY),
+            render_counters(total, total)
+        )?;
+
+        self.errors
+            .iter()
+            .try_for_each(|err| write!(write, "\nE {}", format!("{err:#}").red()))?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+fn render_counters(counters: &RecordCounters, total: &RecordCounters) -> String {
+    format!(
+        "{:>10} {:>8} {:>14} {:>8} {:>14} {:>8} {:>14} {:>8}",
+        counters.count,
+        percentage(counters.count, total.count),
+        counters.record_bytes,
+        percentage(counters.record_bytes, total.record_bytes),
+        counters.fpi_bytes,
+        percentage(counters.fpi_bytes, total.fpi_bytes),
+        counters.combined_bytes(),
+        percentage(counters.combined_bytes(), total.combined_bytes())
+    )
+}
+
+fn percentage(value: u64, total: u64) -> String {
+    match total {
+        0 => format!("({:>6.2})", 0.0),
+        _ => format!("({:>6.2})", value as f64 * 100.0 / total as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pgdata::pg_wal::{stats::segment_range_stats, test_stubs::StubPGWal, WalSegmentName},
+        test_utils::{
+            colors::{GRAY, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::WalStatsViewer;
+
+    #[test]
+    fn wal_stats_handler_renders_stats() {
+        // given
+        let segment = |seg| WalSegmentName {
+            timeline_id: 1,
+            log: 0,
+            seg,
+        };
+        let (stats, errors) = segment_range_stats(&StubPGWal, &segment(1), &segment(2)).unwrap();
+        let viewer = WalStatsViewer::new(
+            Path::new("/pgdata/pg_wal").to_path_buf(),
+            "000000010000000000000001..000000010000000000000002".to_string(),
+            2,
+            stats,
+            errors,
+        );
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .handle(&TermSize { rows: 20, cols: 80 }, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata/pg_wal|/000000010000000000000001..000000010000000000000002", &[GRAY, YELLOW]),
+                line("segments| 2, |records| 3", &[GRAY, NONE, GRAY, NONE]),
+                line("Type                 N      (%)    Record size      (%)       FPI size      (%)  Combined size      (%)", &[GRAY]),
+                line("XLOG                 1 ( 33.33)             30 ( 25.86)              0 (  0.00)             30 ( 25.86)", &[NONE]),
+                line("  NEXTOID            1 ( 33.33)             30 ( 25.86)              0 (  0.00)             30 ( 25.86)", &[NONE]),
+                line("Transaction          1 ( 33.33)             34 ( 29.31)              0 (  0.00)             34 ( 29.31)", &[NONE]),
+                line("  COMMIT             1 ( 33.33)             34 ( 29.31)              0 (  0.00)             34 ( 29.31)", &[NONE]),
+                line("Heap                 1 ( 33.33)             52 ( 44.83)              0 (  0.00)             52 ( 44.83)", &[NONE]),
+                line("  INSERT             1 ( 33.33)             52 ( 44.83)              0 (  0.00)             52 ( 44.83)", &[NONE]),
+                line("Total|                3 (100.00)            116 (100.00)              0 (  0.00)            116 (100.00)", &[GRAY, NONE]),
+                line("E |invalid record length at 0/10000A8: expected at least 24, got 0", &[NONE, RED]),
+                line("E |Reading WAL segment 000000010000000000000002: 000000010000000000000002 is missing", &[NONE, RED]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use colored::{ColoredString, Colorize};
+
+use crate::{
+    pgdata::{
+        pg_xact::{PGXact, XactStatus, CLOG_LAYOUT, FIRST_NORMAL_TRANSACTION_ID},
+        slru::SLRU_PAGES_PER_SEGMENT,
+    },
+    viewers::{TermSize, Viewer},
+    GRAY,
+};
+
+use super::slru::format_segments;
+
+/// Lists the segments of pg_xact with the xids they hold, a status is looked up by xid
+pub struct PGXactViewer<T: PGXact> {
+    pg_xact: T,
+}
+
+impl<T: PGXact> PGXactViewer<T> {
+    pub fn new(pg_xact: T) -> Self {
+        PGXactViewer { pg_xact }
+    }
+}
+
+impl<T: PGXact> Viewer for PGXactViewer<T> {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        let xid = param
+            .parse::<u32>()
+            .with_context(|| format!("Expected transaction id, got {param}"))?;
+        Ok(Box::new(XactStatusViewer {
+            pg_xact_path: self.pg_xact.path().to_path_buf(),
+            xid,
+            status: self.pg_xact.status(xid),
+        }))
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(
+            write,
+            "{}",
+            self.pg_xact
+                .path()
+                .parent()
+                .expect("pgdata path")
+                .to_string_lossy()
+                .color(GRAY)
+        )?;
+        write!(write, "{}", "/pg_xact".yellow())?;
+        write!(
+            write,
+            "\n{}",
+            format!(
+                "Commit status of transactions, 2 bits per xid, {} xids per page, {} pages per segment",
+                CLOG_LAYOUT.entries_per_page(),
+                SLRU_PAGES_PER_SEGMENT
+            )
+            .color(GRAY)
+        )?;
+
+        format_segments(&self.pg_xact.segments()?, &CLOG_LAYOUT, "xids", &mut write)?;
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+/// Shows the status of a transaction and where pg_xact keeps it
+pub struct XactStatusViewer {
+    pg_xact_path: PathBuf,
+    xid: u32,
+    status: anyhow::Result<XactStatus>,
+}
+
+impl Viewer for XactStatusViewer {
+    fn get_next(self: Box<Self>, param: &str) -> anyhow::Result<Box<dyn Viewer>> {
+        bail!("{param} not supported")
+    }
+
+    fn handle(&self, _term_size: &TermSize, mut write: Box<&mut dyn Write>) -> anyhow::Result<()> {
+        write!(write, "{}", self.pg_xact_path.to_string_lossy().color(GRAY))?;
+        write!(write, "{}", format!("/{}", self.xid).yellow())?;
+
+        let location = CLOG_LAYOUT.locate(self.xid);
+        let stored_at = match self.xid {
+            xid if xid < FIRST_NORMAL_TRANSACTION_ID => "permanent xid, not stored".to_string(),
+            _ => format!(
+                "{} page {} byte {} bits {}..{}",
+                location.segment_file_name(),
+                location.page_number,
+                location.byte,
+                location.shift,
+                location.shift + 1
+            ),
+        };
+        write!(write, "\n{:<8} {}", "location".color(GRAY), stored_at)?;
+        match &self.status {
+            Ok(status) => write!(
+                write,
+                "\n{:<8} {}",
+                "status".color(GRAY),
+                render_status(*status)
+            )?,
+            Err(err) => write!(write, "\nE {}", format!("{err:#}").red())?,
+        }
+        writeln!(write).map(|_| ()).map_err(|err| anyhow!(err))
+    }
+}
+
+pub fn render_status(status: XactStatus) -> ColoredString {
+    let name = status.to_string();
+    match status {
+        XactStatus::InProgress => name.yellow(),
+        XactStatus::Committed => name.green(),
+        XactStatus::Aborted => name.red(),
+        XactStatus::SubCommitted => name.cyan(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use crate::{
+        pgdata::pg_xact::test_stubs::StubPGXact,
+        test_utils::{
+            colors::{BRIGHT_BLUE, GRAY, GREEN, NONE, RED, YELLOW},
+            line,
+        },
+        viewers::{TermSize, Viewer},
+    };
+
+    use super::PGXactViewer;
+
+    const TERM_SIZE: TermSize = TermSize { rows: 20, cols: 80 };
+
+    #[test]
+    fn pg_xact_handler_lists_segments() {
+        // given
+        let viewer = PGXactViewer::new(StubPGXact);
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer.handle(&TERM_SIZE, Box::new(&mut buf)).unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        #[rustfmt::skip]
+        assert_eq!(
+            output,
+            [
+                line("/pgdata|/pg_xact", &[GRAY, YELLOW]),
+                line("Commit status of transactions, 2 bits per xid, 32768 xids per page, 32 pages per segment", &[GRAY]),
+                line("  file       size pages xids", &[GRAY]),
+                line("F |0000| 8192 bytes     1 0..32767 |short, 1 of 32 pages", &[NONE, BRIGHT_BLUE, NONE, RED]),
+                line("F |0001|      16 kB     2 1048576..1114111", &[NONE, BRIGHT_BLUE, NONE]),
+                line("F |pg_xact.tmp", &[NONE, GRAY]),
+                line("", &[]),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[rstest]
+    #[case("740", &[
+        line("location| 0000 page 0 byte 185 bits 0..1", &[GRAY, NONE]),
+        line("status  | |COMMITTED", &[GRAY, NONE, GREEN]),
+    ])]
+    #[case("2", &[
+        line("location| permanent xid, not stored", &[GRAY, NONE]),
+        line("status  | |COMMITTED", &[GRAY, NONE, GREEN]),
+    ])]
+    #[case("40000", &[
+        line("location| 0000 page 1 byte 1808 bits 0..1", &[GRAY, NONE]),
+        line("E |Status of xid 40000 is missing", &[NONE, RED]),
+    ])]
+    fn pg_xact_handler_shows_xid_status(#[case] xid: &str, #[case] expected: &[String]) {
+        // given
+        let viewer = Box::new(PGXactViewer::new(StubPGXact));
+
+        let mut buf = Vec::new();
+
+        // when
+        viewer
+            .get_next(xid)
+            .unwrap()
+            .handle(&TERM_SIZE, Box::new(&mut buf))
+            .unwrap();
+        let output = String::from_utf8_lossy(&buf).into_owned();
+
+        // then
+        assert_eq!(
+            output,
+            [
+                &[line(&format!("/pgdata/pg_xact|/{xid}"), &[GRAY, YELLOW])],
+                expected,
+                &[line("", &[])],
+            ]
+            .concat()
+            .join("\n")
+        );
+    }
+}
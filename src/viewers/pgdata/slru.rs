@@ -0,0 +1,64 @@
+use std::io::Write;
+
+use colored::Colorize;
+
+use crate::{
+    common::size::pretty_size,
+    pgdata::slru::{EntryLayout, SlruSegment, SLRU_PAGES_PER_SEGMENT},
+    GRAY,
+};
+
+/// Lists the files of an SLRU directory with the numbers of the entries, e.g. xids, their pages
+/// hold. A short segment is expected to be the newest one only, the others are reported
+pub fn format_segments(
+    segments: &[SlruSegment],
+    layout: &EntryLayout,
+    entries_name: &str,
+    target: &mut dyn Write,
+) -> anyhow::Result<()> {
+    write!(
+        target,
+        "\n{}",
+        format!(
+            "  {:<4} {:>10} {:>5} {}",
+            "file", "size", "pages", entries_name
+        )
+        .color(GRAY)
+    )?;
+    let newest = segments
+        .iter()
+        .filter_map(|segment| segment.segment_number())
+        .max();
+    segments.iter().try_for_each(|segment| {
+        let (Some(segment_number), Some(entries)) =
+            (segment.segment_number(), layout.segment_entries(segment))
+        else {
+            return write!(target, "\nF {}", segment.file_name.color(GRAY));
+        };
+        write!(
+            target,
+            "\nF {:<4} {:>10} {:>5} {}",
+            segment.file_name.bright_blue(),
+            pretty_size(segment.size),
+            segment.pages(),
+            match entries.is_empty() {
+                true => String::new(),
+                false => format!("{}..{}", entries.start, entries.end - 1),
+            }
+        )?;
+        if segment.is_short() && Some(segment_number) != newest {
+            write!(
+                target,
+                " {}",
+                format!(
+                    "short, {} of {} pages",
+                    segment.pages(),
+                    SLRU_PAGES_PER_SEGMENT
+                )
+                .red()
+            )?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}